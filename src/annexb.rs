@@ -0,0 +1,82 @@
+//! Conversion of length-prefixed AVC samples to Annex B byte streams.
+//!
+//! MP4 stores each NAL unit behind a big-endian length whose size comes from
+//! the `avcC` `lengthSizeMinusOne` field; raw H.264 decoders expect the units
+//! separated by `00 00 00 01` start codes instead, with SPS/PPS in-band.
+
+use crate::registry::{AvcConfig, NalUnitRange};
+
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+const NAL_TYPE_IDR: u8 = 5;
+
+/// Convert one sample from length-prefixed NAL units to Annex B.
+///
+/// `length_size` is the prefix size in bytes (`lengthSizeMinusOne + 1`, i.e.
+/// 1, 2 or 4). A NAL unit whose length runs past the end of `sample` is
+/// truncated to the bytes that are there and ends the conversion; other
+/// lengths produce an empty result.
+pub fn to_annexb(sample: &[u8], length_size: u8) -> Vec<u8> {
+    let mut out = Vec::with_capacity(sample.len() + 16);
+    for nal in nal_units(sample, length_size) {
+        out.extend_from_slice(&START_CODE);
+        out.extend_from_slice(nal);
+    }
+    out
+}
+
+/// Like [`to_annexb`], but when the sample contains an IDR slice its NAL units
+/// are preceded by the SPS and PPS from `config`.
+///
+/// `avcc` is the `avcC` payload that `config` was parsed from (the parameter
+/// sets are stored as ranges into it). Use this for the first IDR sample of a
+/// stream so a decoder can start from it.
+pub fn to_annexb_with_parameter_sets(
+    sample: &[u8],
+    length_size: u8,
+    config: &AvcConfig,
+    avcc: &[u8],
+) -> Vec<u8> {
+    let has_idr = nal_units(sample, length_size)
+        .any(|nal| nal.first().is_some_and(|h| h & 0x1F == NAL_TYPE_IDR));
+    if !has_idr {
+        return to_annexb(sample, length_size);
+    }
+
+    let mut out = Vec::new();
+    for range in config.sps.iter().chain(&config.pps) {
+        if let Some(nal) = param_set(avcc, range) {
+            out.extend_from_slice(&START_CODE);
+            out.extend_from_slice(nal);
+        }
+    }
+    out.extend_from_slice(&to_annexb(sample, length_size));
+    out
+}
+
+fn param_set<'a>(avcc: &'a [u8], range: &NalUnitRange) -> Option<&'a [u8]> {
+    let start = range.offset as usize;
+    avcc.get(start..start + range.size as usize)
+}
+
+/// The non-empty NAL unit payloads of a length-prefixed sample, with the last
+/// one cut short if its length overruns the buffer.
+fn nal_units(sample: &[u8], length_size: u8) -> impl Iterator<Item = &[u8]> {
+    let length_size = match length_size {
+        1..=4 => length_size as usize,
+        _ => sample.len() + 1,
+    };
+    let mut pos = 0usize;
+    std::iter::from_fn(move || {
+        if pos + length_size > sample.len() {
+            return None;
+        }
+        let len = sample[pos..pos + length_size]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        let start = pos + length_size;
+        let end = start.saturating_add(len).min(sample.len());
+        pos = if end - start < len { sample.len() } else { end };
+        Some(&sample[start..end])
+    })
+    .filter(|nal| !nal.is_empty())
+}
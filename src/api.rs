@@ -1,12 +1,20 @@
 use crate::{
-    boxes::{BoxRef, NodeKind},
+    boxes::{BoxHeader, BoxRef, FourCC, NodeKind},
+    options::ParseOptions,
     parser::read_box_header,
-    registry::{BoxValue, Registry, default_registry},
+    registry::{
+        BoxValue, DecodeContext, KeysData, MetaData, MetaKind, Registry, StructuredData,
+        default_registry,
+    },
     util::{hex_dump, read_slice},
+    warning::Warning,
 };
-use byteorder::ReadBytesExt;
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
+use std::ops::ControlFlow;
+use std::path::{Component, Path, PathBuf};
 
 /// A JSON-serializable representation of a single MP4 box.
 ///
@@ -67,60 +75,654 @@ pub struct Box {
 /// # Ok::<(), anyhow::Error>(())
 /// ```
 pub fn get_boxes<R: Read + Seek>(r: &mut R, size: u64, decode: bool) -> anyhow::Result<Vec<Box>> {
-    // let mut f = File::open(&path)?;
-    // let file_len = f.metadata()?.len();
-
     // parse top-level boxes
-    let mut boxes = Vec::new();
-    while r.stream_position()? < size {
+    let boxes = crate::parser::parse_children(r, size)?;
+
+    // build JSON tree
+    let reg = default_registry();
+    let opts = ParseOptions {
+        decode,
+        ..Default::default()
+    };
+    let mut ctx = BuildCtx::new(&reg, &opts);
+    let json_boxes = boxes
+        .iter()
+        .map(|b| build_box(r, b, None, &mut ctx))
+        .collect();
+
+    Ok(json_boxes)
+}
+
+/// Parse MP4 data already in memory, e.g. headers fetched over HTTP, like
+/// [`get_boxes`] does for a reader.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::analyze_bytes;
+///
+/// let data = std::fs::read("video.mp4")?;
+/// let boxes = analyze_bytes(&data, true)?;
+/// println!("{} top-level boxes", boxes.len());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn analyze_bytes(data: &[u8], decode: bool) -> anyhow::Result<Vec<Box>> {
+    get_boxes(&mut std::io::Cursor::new(data), data.len() as u64, decode)
+}
+
+/// Parse the file at `path` and return its box tree.
+///
+/// Unlike [`get_boxes`], a damaged file does not fail the whole call: when a
+/// box header cannot be read the scan of its parent stops there, the boxes
+/// before it are kept, and the problem is recorded in [`Analysis::errors`].
+/// Clamped sizes and decode problems inside boxes are reported in
+/// [`Analysis::warnings`].
+///
+/// # Example
+/// ```no_run
+/// use mp4box::analyze_file;
+///
+/// let analysis = analyze_file("video.mp4", true)?;
+/// println!("{} top-level boxes", analysis.boxes.len());
+/// for e in &analysis.errors {
+///     eprintln!("{}", e);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn analyze_file(path: impl AsRef<Path>, decode: bool) -> anyhow::Result<Analysis> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    analyze_reader(&mut file, size, decode)
+}
+
+/// Like [`analyze_file`], returning every non-fatal problem in one list: the
+/// box-level warnings and errors of [`Analysis`] plus the sample table
+/// warnings of each track (see [`crate::TrackSamples::warnings`]), such as
+/// `stsc` entries pointing past the chunk offset table or defaults assumed
+/// for missing boxes.
+///
+/// Warnings are ordered by file offset. A file whose sample tables cannot
+/// be read at all still returns its boxes, with one warning saying why.
+pub fn analyze_file_with_warnings(
+    path: impl AsRef<Path>,
+    decode: bool,
+) -> anyhow::Result<(Vec<Box>, Vec<Warning>)> {
+    let analysis = analyze_file(&path, decode)?;
+    let mut warnings = analysis.warnings;
+    warnings.extend(analysis.errors);
+    match crate::track_samples_from_path(&path) {
+        Ok(tracks) => warnings.extend(tracks.into_iter().flat_map(|t| t.warnings)),
+        Err(e) => warnings.push(Warning::new(
+            0,
+            "",
+            format!("cannot read sample tables: {:#}", e),
+        )),
+    }
+    warnings.sort_by_key(|w| w.offset);
+    Ok((analysis.boxes, warnings))
+}
+
+/// Like [`analyze_file`], for any seekable source of `size` bytes (a
+/// `Cursor` over downloaded bytes, a buffered stream, a decompressing
+/// wrapper, ...). Parsing and decoding share the one reader, which is read
+/// from offset 0.
+pub fn analyze_reader<R: Read + Seek>(
+    r: &mut R,
+    size: u64,
+    decode: bool,
+) -> anyhow::Result<Analysis> {
+    let opts = ParseOptions {
+        decode,
+        ..Default::default()
+    };
+    analyze_reader_opts(r, size, &opts)
+}
+
+/// Whether the file at `path` contains a `moov` box that fits inside the file.
+///
+/// Recorders that write `moov` last leave it missing or cut short until the
+/// recording is finalized, so this tells whether a growing file is done. Only
+/// top-level headers are read. Unreadable files report `false`.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::has_complete_moov;
+///
+/// if !has_complete_moov("recording.mp4") {
+///     println!("still being written");
+/// }
+/// ```
+pub fn has_complete_moov(path: impl AsRef<Path>) -> bool {
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    let Ok(len) = file.metadata().map(|m| m.len()) else {
+        return false;
+    };
+
+    let mut complete = false;
+    let walked = walk_top_level(&mut file, len, |h, end| {
+        // A size-0 box runs to the end of the file: for moov that is a size
+        // placeholder the writer has not patched yet, for anything else there
+        // is nothing after it.
+        if h.size == 0 {
+            return ControlFlow::Break(());
+        }
+        if &h.typ.0 == b"moov" {
+            complete = end <= len;
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    });
+    walked.is_ok() && complete
+}
+
+/// Call `f` with the header of each top-level box of `r` (`len` bytes) and
+/// where the box ends, until it returns [`ControlFlow::Break`] or fewer than
+/// 8 bytes are left.
+///
+/// Only headers are read. Ends are not clamped to the file; a size-0 box
+/// ends at `len`.
+fn walk_top_level<R: Read + Seek>(
+    r: &mut R,
+    len: u64,
+    mut f: impl FnMut(&BoxHeader, u64) -> ControlFlow<()>,
+) -> anyhow::Result<()> {
+    let mut pos = 0u64;
+    // Ends saturate, so a huge box stops the walk instead of wrapping around.
+    while len.saturating_sub(pos) >= 8 {
+        r.seek(SeekFrom::Start(pos))?;
         let h = read_box_header(r)?;
-        let box_end = if h.size == 0 { size } else { h.start + h.size };
-
-        let kind = if crate::known_boxes::KnownBox::from(h.typ).is_container() {
-            r.seek(SeekFrom::Start(h.start + h.header_size))?;
-            NodeKind::Container(crate::parser::parse_children(r, box_end)?)
-        } else if crate::known_boxes::KnownBox::from(h.typ).is_full_box() {
-            r.seek(SeekFrom::Start(h.start + h.header_size))?;
-            let version = r.read_u8()?;
-            let mut fl = [0u8; 3];
-            r.read_exact(&mut fl)?;
-            let flags = ((fl[0] as u32) << 16) | ((fl[1] as u32) << 8) | (fl[2] as u32);
-            let data_offset = r.stream_position()?;
-            let data_len = box_end.saturating_sub(data_offset);
-            NodeKind::FullBox {
-                version,
-                flags,
-                data_offset,
-                data_len,
-            }
+        let end = if h.size == 0 {
+            len
         } else {
-            let data_offset = h.start + h.header_size;
-            let data_len = box_end.saturating_sub(data_offset);
-            if &h.typ.0 == b"uuid" {
-                NodeKind::Unknown {
-                    data_offset,
-                    data_len,
-                }
-            } else {
-                NodeKind::Leaf {
-                    data_offset,
-                    data_len,
-                }
-            }
+            h.start.saturating_add(h.size)
         };
+        if f(&h, end).is_break() {
+            break;
+        }
+        pos = end;
+    }
+    Ok(())
+}
+
+/// The initialization segment of the file at `path`: its `ftyp` (if any)
+/// followed by its `moov`, as used by DASH/CMAF players.
+///
+/// Other top-level boxes are left out, so a `moov` placed after `mdat` or
+/// after fragments is moved up next to the `ftyp`. Fails when the file has no
+/// complete `moov`.
+pub fn extract_init_segment(path: impl AsRef<Path>) -> anyhow::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    let mut ftyp = None;
+    let mut moov = None;
+    walk_top_level(&mut file, len, |h, end| {
+        match &h.typ.0 {
+            b"ftyp" if ftyp.is_none() => ftyp = Some((h.start, end)),
+            b"moov" => {
+                moov = Some((h.start, end));
+                return ControlFlow::Break(());
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    })?;
 
-        r.seek(SeekFrom::Start(box_end))?;
-        boxes.push(BoxRef { hdr: h, kind });
+    let Some((moov_start, moov_end)) = moov else {
+        anyhow::bail!("file has no moov box");
+    };
+    if moov_end > len {
+        anyhow::bail!(
+            "moov ends at {} but the file is only {} bytes",
+            moov_end,
+            len
+        );
     }
 
-    // build JSON tree
-    let reg = default_registry();
-    let json_boxes = boxes
+    let mut out = Vec::new();
+    for (start, end) in ftyp.into_iter().chain([(moov_start, moov_end)]) {
+        out.extend_from_slice(&read_slice(&mut file, start, end.min(len) - start)?);
+    }
+    Ok(out)
+}
+
+/// Parse the file at `path` with explicit [`ParseOptions`].
+///
+/// Anomalies are collected in [`Analysis::warnings`] and unreadable headers in
+/// [`Analysis::errors`], as for [`analyze_file`]; with `opts.strict` the first
+/// of them is returned as an error instead.
+pub fn analyze_file_opts(path: impl AsRef<Path>, opts: &ParseOptions) -> anyhow::Result<Analysis> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    analyze_reader_opts(&mut file, size, opts)
+}
+
+/// Like [`analyze_file_opts`], for any `Read + Seek` source of `size` bytes.
+pub fn analyze_reader_opts<R: Read + Seek>(
+    r: &mut R,
+    size: u64,
+    opts: &ParseOptions,
+) -> anyhow::Result<Analysis> {
+    analyze_reader_with_registry(r, size, opts, &default_registry())
+}
+
+/// Like [`analyze_reader_opts`], decoding with `reg` instead of the default
+/// registry (see [`crate::RegistryBuilder`]). Boxes without a decoder in `reg`
+/// are left undecoded.
+pub fn analyze_reader_with_registry<R: Read + Seek>(
+    r: &mut R,
+    size: u64,
+    opts: &ParseOptions,
+    reg: &Registry,
+) -> anyhow::Result<Analysis> {
+    r.seek(SeekFrom::Start(0))?;
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+    let refs = crate::parser::parse_children_checked(r, size, opts, &mut warnings, &mut errors)?;
+
+    let mut ctx = BuildCtx::new(reg, opts);
+    let boxes = refs
         .iter()
-        .map(|b| build_box(r, b, decode, &reg))
+        .map(|b| build_box(r, b, None, &mut ctx))
         .collect();
+    warnings.append(&mut ctx.warnings);
 
-    Ok(json_boxes)
+    if opts.strict
+        && let Some(first) = errors.first().or(warnings.first())
+    {
+        anyhow::bail!("strict parse failed: {}", first);
+    }
+
+    Ok(Analysis {
+        boxes,
+        references: Vec::new(),
+        warnings,
+        errors,
+    })
+}
+
+/// Parse the file at `path` like [`analyze_file_opts`], and also report what
+/// kind of file it is (see [`FormatInfo`]).
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{ParseOptions, analyze_full};
+///
+/// let full = analyze_full("video.mp4", &ParseOptions::default())?;
+/// println!("{} ({:?})", full.format.detected_format, full.format.major_brand);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn analyze_full(path: impl AsRef<Path>, opts: &ParseOptions) -> anyhow::Result<FullAnalysis> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let analysis = analyze_reader_opts(&mut file, size, opts)?;
+    let format = format_info(&mut file, &analysis.boxes)?;
+    Ok(FullAnalysis { format, analysis })
+}
+
+/// Result of [`analyze_full`]: an [`Analysis`] plus the file's identity.
+#[derive(Serialize)]
+pub struct FullAnalysis {
+    /// Brands and detected container format
+    pub format: FormatInfo,
+    /// Box tree, warnings and errors, as from [`analyze_file_opts`]
+    #[serde(flatten)]
+    pub analysis: Analysis,
+}
+
+/// File identity taken from the `ftyp` box and the top-level layout.
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatInfo {
+    /// `ftyp` major brand (None when the file has no `ftyp`)
+    pub major_brand: Option<String>,
+    /// `ftyp` minor version
+    pub minor_version: Option<u32>,
+    /// `ftyp` compatible brands, in file order
+    pub compatible_brands: Vec<String>,
+    /// Whether the file uses movie fragments (`moov/mvex` or a top-level `moof`)
+    pub is_fragmented: bool,
+    /// "MP4", "QuickTime", "3GP", "3G2", "M4A", "M4V", "HEIF", "AVIF", "CMAF"
+    /// or "unknown", from the major brand, else the first recognized
+    /// compatible brand
+    pub detected_format: String,
+}
+
+fn format_info<R: Read + Seek>(r: &mut R, boxes: &[Box]) -> anyhow::Result<FormatInfo> {
+    let mut major_brand = None;
+    let mut minor_version = None;
+    let mut compatible_brands = Vec::new();
+    if let Some(ftyp) = boxes.iter().find(|b| b.typ == "ftyp")
+        && let (Some(off), Some(len)) = (ftyp.payload_offset, ftyp.payload_size)
+        && len >= 8
+    {
+        let payload = read_slice(r, off, len)?;
+        let brand = |b: &[u8]| String::from_utf8_lossy(b).to_string();
+        major_brand = Some(brand(&payload[0..4]));
+        minor_version = Some(u32::from_be_bytes([
+            payload[4], payload[5], payload[6], payload[7],
+        ]));
+        compatible_brands = payload[8..].chunks_exact(4).map(brand).collect();
+    }
+
+    let is_fragmented = boxes.iter().any(|b| {
+        b.typ == "moof"
+            || (b.typ == "moov"
+                && b.children
+                    .as_ref()
+                    .is_some_and(|kids| kids.iter().any(|c| c.typ == "mvex")))
+    });
+
+    let detected_format = major_brand
+        .iter()
+        .chain(&compatible_brands)
+        .find_map(|b| brand_format(b))
+        .unwrap_or(match major_brand {
+            // QuickTime files predating ftyp start straight with moov/mdat/wide.
+            None if boxes.iter().any(|b| b.typ == "moov") => "QuickTime",
+            None => "unknown",
+            Some(_) => "MP4",
+        })
+        .to_string();
+
+    Ok(FormatInfo {
+        major_brand,
+        minor_version,
+        compatible_brands,
+        is_fragmented,
+        detected_format,
+    })
+}
+
+fn brand_format(brand: &str) -> Option<&'static str> {
+    Some(match brand {
+        "qt  " => "QuickTime",
+        "M4A " | "M4B " | "M4P " => "M4A",
+        "M4V " | "M4VH" | "M4VP" => "M4V",
+        "avif" | "avis" => "AVIF",
+        "heic" | "heix" | "heim" | "heis" | "mif1" | "msf1" => "HEIF",
+        b if b.starts_with("3gp") => "3GP",
+        b if b.starts_with("3g2") => "3G2",
+        b if crate::cmaf::CMAF_BRANDS.contains(&b) => "CMAF",
+        "isom" | "iso2" | "iso4" | "iso5" | "iso6" | "mp41" | "mp42" | "avc1" | "dash" => "MP4",
+        _ => return None,
+    })
+}
+
+/// Container family of a file, as classified by [`detect_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FileKind {
+    /// Generic ISO base media / MP4 (including M4V)
+    Mp4,
+    /// Audio-only MP4 (M4A, M4B, ...)
+    M4a,
+    /// 3GPP / 3GPP2
+    ThreeGp,
+    /// HEIF / HEIC / AVIF still image or image sequence
+    Heif,
+    /// QuickTime movie
+    QuickTime,
+    /// Fragmented MP4 (DASH / CMAF segments)
+    Fragmented,
+    /// No recognizable brand or layout
+    Unknown,
+}
+
+/// Classify the file at `path` by its `ftyp` brands and box layout, ignoring
+/// the file extension.
+///
+/// Brands decide first (QuickTime, 3GP, HEIF/AVIF, M4A); otherwise a file
+/// using movie fragments is [`FileKind::Fragmented`], and one whose tracks
+/// all have a `soun` handler is [`FileKind::M4a`].
+pub fn detect_kind(path: impl AsRef<Path>) -> anyhow::Result<FileKind> {
+    let full = analyze_full(path, &ParseOptions::default())?;
+    let format = &full.format;
+    let boxes = &full.analysis.boxes;
+
+    let kind = match format.detected_format.as_str() {
+        "QuickTime" => FileKind::QuickTime,
+        "3GP" | "3G2" => FileKind::ThreeGp,
+        "HEIF" | "AVIF" => FileKind::Heif,
+        "M4A" => FileKind::M4a,
+        _ if format.is_fragmented || boxes.iter().any(|b| b.typ == "styp") => FileKind::Fragmented,
+        "unknown" => FileKind::Unknown,
+        _ => {
+            let handlers: Vec<&str> = boxes
+                .iter()
+                .filter(|b| b.typ == "moov")
+                .flat_map(|moov| moov.children.iter().flatten())
+                .filter(|trak| trak.typ == "trak")
+                .flat_map(|trak| trak.children.iter().flatten())
+                .filter(|mdia| mdia.typ == "mdia")
+                .flat_map(|mdia| mdia.children.iter().flatten())
+                .filter_map(|hdlr| match &hdlr.structured_data {
+                    Some(StructuredData::HandlerReference(h)) => Some(h.handler_type.as_str()),
+                    _ => None,
+                })
+                .collect();
+            if !handlers.is_empty() && handlers.iter().all(|h| *h == "soun") {
+                FileKind::M4a
+            } else {
+                FileKind::Mp4
+            }
+        }
+    };
+    Ok(kind)
+}
+
+/// Result of a file analysis: the decoded box tree plus anything found along the way.
+#[derive(Serialize)]
+pub struct Analysis {
+    /// Top-level boxes of the analyzed file
+    pub boxes: Vec<Box>,
+    /// External files pulled in through data references, if requested
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<ReferencedFile>,
+    /// Non-fatal problems found during analysis
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+    /// Unreadable box headers that stopped the scan of their parent early;
+    /// `boxes` holds everything read before them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Warning>,
+}
+
+/// An external media file named by a `dref` entry.
+#[derive(Serialize)]
+pub struct ReferencedFile {
+    /// Location string as written in the `url ` / `urn ` entry
+    pub location: String,
+    /// Where the location was resolved to on disk
+    pub path: PathBuf,
+    /// Top-level boxes of the referenced file
+    pub boxes: Vec<Box>,
+}
+
+/// Analyze a reference movie and inline the files its data references point to.
+///
+/// Every `dref` entry that is not self-contained is resolved against `base_dir`
+/// (a `file://` prefix is stripped) and the target's top-level boxes are added to
+/// [`Analysis::references`]. Targets that cannot be opened are reported as warnings.
+///
+/// Locations come from the file being analyzed, so only relative paths that
+/// stay inside `base_dir` are read; absolute paths and `..` components are
+/// reported as warnings instead.
+pub fn analyze_file_with_refs(
+    path: impl AsRef<Path>,
+    base_dir: impl AsRef<Path>,
+) -> anyhow::Result<Analysis> {
+    let Analysis {
+        boxes,
+        mut warnings,
+        errors,
+        ..
+    } = analyze_file(path, true)?;
+
+    let mut locations: Vec<(u64, String)> = Vec::new();
+    collect_external_refs(&boxes, &mut locations);
+
+    let mut references = Vec::new();
+    for (dref_offset, location) in locations {
+        if references
+            .iter()
+            .any(|r: &ReferencedFile| r.location == location)
+        {
+            continue;
+        }
+
+        let relative = Path::new(location.strip_prefix("file://").unwrap_or(&location));
+        let escapes = relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            warnings.push(Warning::new(
+                dref_offset,
+                "dref",
+                format!(
+                    "not reading referenced file '{}': only paths inside the base directory are followed",
+                    location
+                ),
+            ));
+            continue;
+        }
+
+        let target = base_dir.as_ref().join(relative);
+        match analyze_file(&target, true) {
+            Ok(analysis) => {
+                for e in &analysis.errors {
+                    warnings.push(Warning::new(
+                        dref_offset,
+                        "dref",
+                        format!("referenced file '{}': {}", target.display(), e),
+                    ));
+                }
+                references.push(ReferencedFile {
+                    location,
+                    path: target,
+                    boxes: analysis.boxes,
+                });
+            }
+            Err(e) => warnings.push(Warning::new(
+                dref_offset,
+                "dref",
+                format!("cannot open referenced file '{}': {}", target.display(), e),
+            )),
+        }
+    }
+
+    Ok(Analysis {
+        boxes,
+        references,
+        warnings,
+        errors,
+    })
+}
+
+/// Count boxes by type across the whole tree, nested boxes included.
+///
+/// Returned as a `BTreeMap` so iteration is sorted by box type and identical
+/// across runs, which keeps snapshot tests and UI listings stable.
+///
+/// ```no_run
+/// use mp4box::{analyze_file, box_type_counts};
+///
+/// let analysis = analyze_file("video.mp4", false)?;
+/// for (typ, count) in box_type_counts(&analysis.boxes) {
+///     println!("{typ}: {count}");
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn box_type_counts(boxes: &[Box]) -> BTreeMap<String, usize> {
+    fn walk(boxes: &[Box], counts: &mut BTreeMap<String, usize>) {
+        for b in boxes {
+            *counts.entry(b.typ.clone()).or_default() += 1;
+            if let Some(children) = &b.children {
+                walk(children, counts);
+            }
+        }
+    }
+
+    let mut counts = BTreeMap::new();
+    walk(boxes, &mut counts);
+    counts
+}
+
+/// Every box reached by following `path` (one box type per level) down from
+/// `root`, in tree order.
+///
+/// A `*` segment matches any box type, so `["moov", "*", "tkhd"]` finds the
+/// `tkhd` of every track. An empty path matches nothing.
+///
+/// ```no_run
+/// use mp4box::{analyze_file, find_path};
+///
+/// let analysis = analyze_file("video.mp4", true)?;
+/// let stsds = find_path(
+///     &analysis.boxes,
+///     &["moov", "trak", "mdia", "minf", "stbl", "stsd"],
+/// );
+/// println!("{} sample descriptions", stsds.len());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn find_path<'a>(root: &'a [Box], path: &[&str]) -> Vec<&'a Box> {
+    let matches = |b: &Box, segment: &str| segment == "*" || b.typ == segment;
+
+    let Some((first, rest)) = path.split_first() else {
+        return Vec::new();
+    };
+    let mut found: Vec<&Box> = root.iter().filter(|b| matches(b, first)).collect();
+    for segment in rest {
+        found = found
+            .into_iter()
+            .flat_map(|b| b.children.iter().flatten())
+            .filter(|b| matches(b, segment))
+            .collect();
+    }
+    found
+}
+
+/// Presentation duration of the movie in seconds, from boxes decoded by
+/// [`get_boxes`] or [`analyze_file`].
+///
+/// Fragmented files often leave the `mvhd` duration at 0 and carry the total
+/// in `moov/mvex/mehd` instead; a non-zero `mehd` duration wins. Both are in
+/// the `mvhd` timescale. `None` without a decoded `mvhd` or when its
+/// timescale is 0.
+pub fn movie_duration(boxes: &[Box]) -> Option<f64> {
+    let mvhd = find_path(boxes, &["moov", "mvhd"])
+        .into_iter()
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::MovieHeader(mvhd)) => Some(mvhd),
+            _ => None,
+        })?;
+    if mvhd.timescale == 0 {
+        return None;
+    }
+    let fragment_duration = find_path(boxes, &["moov", "mvex", "mehd"])
+        .into_iter()
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::MovieExtendsHeader(mehd)) => Some(mehd.fragment_duration),
+            _ => None,
+        })
+        .filter(|&d| d > 0);
+    Some(fragment_duration.unwrap_or(mvhd.duration) as f64 / mvhd.timescale as f64)
+}
+
+fn collect_external_refs(boxes: &[Box], out: &mut Vec<(u64, String)>) {
+    for b in boxes {
+        if let Some(StructuredData::DataReference(dref)) = &b.structured_data {
+            for entry in &dref.entries {
+                if !entry.self_contained
+                    && let Some(location) = &entry.location
+                {
+                    out.push((b.offset, location.clone()));
+                }
+            }
+        }
+        if let Some(children) = &b.children {
+            collect_external_refs(children, out);
+        }
+    }
 }
 
 fn payload_region(b: &BoxRef) -> Option<(crate::boxes::BoxKey, u64, u64)> {
@@ -136,19 +738,21 @@ fn payload_region(b: &BoxRef) -> Option<(crate::boxes::BoxKey, u64, u64)> {
             data_len,
             ..
         } => Some((key, *data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
-                return None;
-            }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
+        // Leaf geometry is already resolved against the parent end (size 0 = "to end").
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        }
+        | NodeKind::Unknown {
+            data_offset,
+            data_len,
+        } => {
+            if *data_len == 0 {
                 return None;
             }
-            Some((key, off, len))
+            Some((key, *data_offset, *data_len))
         }
-        NodeKind::Container(_) => None,
+        NodeKind::Container(_) | NodeKind::Unexpanded => None,
     }
 }
 
@@ -159,34 +763,130 @@ fn payload_geometry(b: &BoxRef) -> Option<(u64, u64)> {
             data_len,
             ..
         } => Some((*data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
+        // Leaf geometry is already resolved against the parent end (size 0 = "to end").
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        }
+        | NodeKind::Unknown {
+            data_offset,
+            data_len,
+        } => {
+            if *data_len == 0 {
                 return None;
             }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
-                return None;
+            Some((*data_offset, *data_len))
+        }
+        NodeKind::Container(_) | NodeKind::Unexpanded => None,
+    }
+}
+
+/// Shared state while turning a parsed [`BoxRef`] tree into [`Box`]es.
+struct BuildCtx<'a> {
+    reg: &'a Registry,
+    opts: &'a ParseOptions,
+    /// Anomalies found while decoding
+    warnings: Vec<Warning>,
+    /// Track of the most recent `tkhd` / `tfhd`, i.e. the one being built
+    current_track: Option<u32>,
+    /// `tenc` per-sample IV size by track, for decoding `senc`
+    iv_sizes: BTreeMap<u32, u8>,
+    /// `mdhd` timescale by track, for decoding `tfdt`
+    timescales: BTreeMap<u32, u32>,
+    /// `hdlr` handler type by track, for decoding `stsd`
+    handlers: BTreeMap<u32, FourCC>,
+}
+
+impl<'a> BuildCtx<'a> {
+    fn new(reg: &'a Registry, opts: &'a ParseOptions) -> Self {
+        Self {
+            reg,
+            opts,
+            warnings: Vec::new(),
+            current_track: None,
+            iv_sizes: BTreeMap::new(),
+            timescales: BTreeMap::new(),
+            handlers: BTreeMap::new(),
+        }
+    }
+
+    fn decode_context(&self, parent: Option<&str>) -> DecodeContext {
+        // A tenc seen outside any track (or a lone one) still applies.
+        let per_sample_iv_size = match self.current_track.and_then(|id| self.iv_sizes.get(&id)) {
+            Some(&size) => Some(size),
+            None if self.iv_sizes.len() == 1 => self.iv_sizes.values().next().copied(),
+            None => None,
+        };
+        let timescale = self
+            .current_track
+            .and_then(|id| self.timescales.get(&id))
+            .copied();
+        let handler_type = self
+            .current_track
+            .and_then(|id| self.handlers.get(&id))
+            .copied();
+        DecodeContext {
+            per_sample_iv_size,
+            timescale,
+            handler_type,
+            parent: parent.and_then(|p| <[u8; 4]>::try_from(p.as_bytes()).ok().map(FourCC)),
+        }
+    }
+
+    /// Remember what later boxes of the same track need to be decoded.
+    fn record(&mut self, data: &StructuredData) {
+        match data {
+            StructuredData::TrackHeader(tkhd) => self.current_track = Some(tkhd.track_id),
+            StructuredData::TrackFragmentHeader(tfhd) => self.current_track = Some(tfhd.track_id),
+            StructuredData::MediaHeader(mdhd) => {
+                if let Some(id) = self.current_track {
+                    self.timescales.insert(id, mdhd.timescale);
+                }
+            }
+            // The first hdlr of a track is mdia's; later ones belong to meta boxes.
+            StructuredData::HandlerReference(hdlr) => {
+                if let (Some(id), Ok(typ)) = (
+                    self.current_track,
+                    <[u8; 4]>::try_from(hdlr.handler_type.as_bytes()),
+                ) {
+                    self.handlers.entry(id).or_insert(FourCC(typ));
+                }
             }
-            Some((off, len))
+            StructuredData::TrackEncryption(tenc) => {
+                self.iv_sizes.insert(
+                    self.current_track.unwrap_or(0),
+                    tenc.default_per_sample_iv_size,
+                );
+            }
+            _ => {}
         }
-        NodeKind::Container(_) => None,
     }
 }
 
 fn decode_value<R: Read + Seek>(
     r: &mut R,
     b: &BoxRef,
-    reg: &Registry,
+    parent: Option<&str>,
+    ctx: &mut BuildCtx,
 ) -> (Option<String>, Option<crate::registry::StructuredData>) {
     let (key, off, len) = match payload_region(b) {
         Some(region) => region,
         None => return (None, None),
     };
-    if len == 0 {
+    // A full box with nothing after version/flags (e.g. nmhd) still decodes.
+    let full_box = matches!(b.kind, crate::boxes::NodeKind::FullBox { .. });
+    if len == 0 && !full_box {
         return (None, None);
     }
+    if ctx.opts.max_decode_bytes.is_some_and(|max| len > max) {
+        return (Some(format!("[not decoded: {} byte payload]", len)), None);
+    }
+    let key = match key {
+        crate::boxes::BoxKey::FourCC(cc) => {
+            crate::boxes::BoxKey::FourCC(ctx.opts.canonical_type(cc))
+        }
+        other => other,
+    };
 
     if r.seek(SeekFrom::Start(off)).is_err() {
         return (None, None);
@@ -199,28 +899,105 @@ fn decode_value<R: Read + Seek>(
         _ => (None, None),
     };
 
-    if let Some(res) = reg.decode(&key, &mut limited, &b.hdr, version, flags) {
+    let dctx = ctx.decode_context(parent);
+    let mut result = ctx
+        .reg
+        .decode_with_context(&key, &mut limited, &b.hdr, version, flags, &dctx);
+    if result.is_none()
+        && ctx.opts.uuid_fourcc_fallback
+        && let crate::boxes::BoxKey::Uuid(u) = key
+    {
+        let fallback = crate::boxes::BoxKey::FourCC(FourCC([u[0], u[1], u[2], u[3]]));
+        result = ctx
+            .reg
+            .decode(&fallback, &mut limited, &b.hdr, version, flags);
+    }
+
+    if let Some(res) = result {
         match res {
             Ok(BoxValue::Text(s)) => (Some(s), None),
             Ok(BoxValue::Bytes(bytes)) => (Some(format!("{} bytes", bytes.len())), None),
             Ok(BoxValue::Structured(data)) => {
-                let debug_str = format!("structured: {:?}", data);
-                (Some(debug_str), Some(data))
+                check_structured(b, &data, ctx);
+                ctx.record(&data);
+                let text = data
+                    .summary()
+                    .unwrap_or_else(|| format!("structured: {:?}", data));
+                (Some(text), Some(data))
             }
-            Err(e) => (Some(format!("[decode error: {}]", e)), None),
+            Err(e) => {
+                ctx.warnings.push(Warning::new(
+                    b.hdr.start,
+                    b.hdr.typ.to_string(),
+                    format!("decode error: {}", e),
+                ));
+                (Some(format!("[decode error: {}]", e)), None)
+            }
+        }
+    } else if ctx.opts.hexdump_preview > 0 {
+        let n = len.min(ctx.opts.hexdump_preview as u64);
+        match read_slice(r, off, n) {
+            Ok(bytes) => (Some(hex_dump(&bytes, off).trim_end().to_string()), None),
+            Err(_) => (None, None),
         }
     } else {
         (None, None)
     }
 }
 
-fn build_box<R: Read + Seek>(r: &mut R, b: &BoxRef, decode: bool, reg: &Registry) -> Box {
+/// Consistency checks on a decoded box that only warn; the decoded data is kept.
+fn check_structured(b: &BoxRef, data: &StructuredData, ctx: &mut BuildCtx) {
+    if let StructuredData::DataReference(dref) = data
+        && dref.entry_count as usize != dref.entries.len()
+    {
+        // Entries are sized boxes, so all present ones were decoded regardless.
+        ctx.warnings.push(Warning::new(
+            b.hdr.start,
+            "dref",
+            format!(
+                "declares {} entries but contains {}",
+                dref.entry_count,
+                dref.entries.len()
+            ),
+        ));
+    }
+}
+
+/// Conformance checks that need several sample tables at once.
+fn check_stbl(stbl_children: &[Box], ctx: &mut BuildCtx) {
+    // Negative composition offsets need cslg so players can shift the
+    // timeline; several misbehave without it.
+    let negative_ctts = stbl_children.iter().find(|c| {
+        matches!(
+            &c.structured_data,
+            Some(StructuredData::CompositionTimeToSample(ctts))
+                if ctts.version == 1 && ctts.entries.iter().any(|e| e.sample_offset < 0)
+        )
+    });
+    if let Some(ctts) = negative_ctts
+        && !stbl_children.iter().any(|c| c.typ == "cslg")
+    {
+        ctx.warnings.push(Warning::new(
+            ctts.offset,
+            "ctts",
+            "version 1 ctts has negative offsets but the track has no cslg",
+        ));
+    }
+}
+
+fn build_box<R: Read + Seek>(
+    r: &mut R,
+    b: &BoxRef,
+    parent: Option<&str>,
+    ctx: &mut BuildCtx,
+) -> Box {
+    let decode = ctx.opts.decode;
     let hdr = &b.hdr;
     let uuid_str = hdr
         .uuid
         .map(|u| u.iter().map(|b| format!("{:02x}", b)).collect::<String>());
 
-    let kb = crate::known_boxes::KnownBox::from(hdr.typ);
+    let kb = crate::known_boxes::KnownBox::from(ctx.opts.canonical_type(hdr.typ));
     let full_name = kb.full_name().to_string();
 
     // basic geometry
@@ -229,24 +1006,48 @@ fn build_box<R: Read + Seek>(r: &mut R, b: &BoxRef, decode: bool, reg: &Registry
         .map(|(off, len)| (Some(off), Some(len)))
         .unwrap_or((None, None));
 
-    let (version, flags, kind_str, children) = match &b.kind {
+    let (version, flags, kind_str, mut children) = match &b.kind {
         NodeKind::FullBox { version, flags, .. } => {
             (Some(*version), Some(*flags), "full".to_string(), None)
         }
         NodeKind::Leaf { .. } => (None, None, "leaf".to_string(), None),
         NodeKind::Unknown { .. } => (None, None, "unknown".to_string(), None),
+        NodeKind::Unexpanded => (None, None, "container".to_string(), None),
         NodeKind::Container(kids) => {
-            let child_nodes = kids.iter().map(|c| build_box(r, c, decode, reg)).collect();
+            let typ = hdr.typ.to_string();
+            let child_nodes: Vec<Box> = kids
+                .iter()
+                .map(|c| build_box(r, c, Some(&typ), ctx))
+                .collect();
             (None, None, "container".to_string(), Some(child_nodes))
         }
     };
 
-    let (decoded, structured_data) = if decode {
-        decode_value(r, b, reg)
+    let (mut decoded, mut structured_data) = if decode {
+        decode_value(r, b, parent, ctx)
     } else {
         (None, None)
     };
 
+    if decode
+        && &hdr.typ.0 == b"meta"
+        && let Some(kids) = children.as_mut()
+    {
+        let meta = interpret_meta(kids, parent);
+        decoded = Some(format!(
+            "{:?} metadata (handler '{}')",
+            meta.kind, meta.handler_type
+        ));
+        structured_data = Some(StructuredData::Meta(meta));
+    }
+
+    if decode
+        && &hdr.typ.0 == b"stbl"
+        && let Some(kids) = children.as_ref()
+    {
+        check_stbl(kids, ctx);
+    }
+
     Box {
         offset: hdr.start,
         size: hdr.size,
@@ -266,6 +1067,63 @@ fn build_box<R: Read + Seek>(r: &mut R, b: &BoxRef, decode: bool, reg: &Registry
     }
 }
 
+/// Classify a `meta` box and interpret its children accordingly.
+///
+/// `moov/udta/meta` carries iTunes-style items (handler `mdir`) or QuickTime
+/// keyed items (`mdta`), while a file-level `meta` is usually HEIF (`pict`).
+/// The handler decides; the parent is only used when there is no `hdlr`.
+fn interpret_meta(meta_children: &mut [Box], parent: Option<&str>) -> MetaData {
+    let handler_type = meta_children
+        .iter()
+        .find_map(|c| match &c.structured_data {
+            Some(StructuredData::HandlerReference(h)) => Some(h.handler_type.clone()),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let kind = match (handler_type.as_str(), parent) {
+        ("mdir", _) => MetaKind::ITunes,
+        ("mdta", _) => MetaKind::QuickTimeKeys,
+        ("pict", _) => MetaKind::Heif,
+        ("", Some("udta")) => MetaKind::ITunes,
+        ("", None) => MetaKind::Heif,
+        _ => MetaKind::Other,
+    };
+
+    if kind == MetaKind::QuickTimeKeys {
+        resolve_metadata_keys(meta_children);
+    }
+
+    MetaData { handler_type, kind }
+}
+
+/// Name keyed `ilst` items after the entries of the sibling `keys` box.
+fn resolve_metadata_keys(meta_children: &mut [Box]) {
+    let keys = meta_children
+        .iter()
+        .find_map(|c| match &c.structured_data {
+            Some(StructuredData::MetadataKeys(k)) => Some(k.clone()),
+            _ => None,
+        })
+        .unwrap_or(KeysData {
+            version: 0,
+            flags: 0,
+            keys: Vec::new(),
+        });
+
+    for child in meta_children.iter_mut() {
+        if let Some(data) = &mut child.structured_data
+            && let StructuredData::Metadata(ilst) = data
+        {
+            ilst.resolve_keys(&keys);
+            child.decoded = Some(
+                data.summary()
+                    .unwrap_or_else(|| format!("structured: {:?}", data)),
+            );
+        }
+    }
+}
+
 /// Result of a hex dump operation containing the formatted hex output.
 #[derive(Serialize)]
 pub struct HexDump {
@@ -310,10 +1168,6 @@ pub fn hex_range<R: Read + Seek>(
 ) -> anyhow::Result<HexDump> {
     use std::cmp::min;
 
-    // let path = path.as_ref().to_path_buf();
-    // let mut f = File::open(&path)?;
-    // let file_len = f.metadata()?.len();
-
     // How many bytes are actually available from this offset to EOF.
     let available = size.saturating_sub(offset);
 
@@ -1,11 +1,9 @@
 use clap::{ArgAction, Parser};
 use mp4box::{
-    BoxHeader,
     boxes::{BoxKey, BoxRef, FourCC, NodeKind},
-    known_boxes::KnownBox,
-    parser::{parse_children, read_box_header},
-    registry::{BoxValue, Registry, default_registry},
-    util::{hex_dump, read_slice},
+    parser::parse_children,
+    registry::{BoxValue, DecodeContext, Registry, default_registry},
+    util::{hex_dump, human_size, read_slice},
 };
 use serde::Serialize;
 use std::io::{Read, Seek, SeekFrom};
@@ -47,63 +45,15 @@ fn main() -> anyhow::Result<()> {
     let mut f = File::open(&args.path)?;
 
     let file_len = f.metadata()?.len();
-    let top = {
-        // Top-level loop
-        let mut kids = Vec::new();
-        while f.stream_position()? < file_len {
-            let h = read_box_header(&mut f)?;
-            let box_end = if h.size == 0 {
-                file_len
-            } else {
-                h.start + h.size
-            };
-
-            let kind = if is_container(&h) {
-                f.seek(SeekFrom::Start(h.start + h.header_size))?;
-                NodeKind::Container(parse_children(&mut f, box_end)?)
-            } else if is_full_box(&h) {
-                f.seek(SeekFrom::Start(h.start + h.header_size))?;
-                use byteorder::ReadBytesExt;
-                let version = f.read_u8()?;
-                let mut fl = [0u8; 3];
-                f.read_exact(&mut fl)?;
-                let flags = ((fl[0] as u32) << 16) | ((fl[1] as u32) << 8) | (fl[2] as u32);
-                let data_offset = f.stream_position()?;
-                let data_len = box_end.saturating_sub(data_offset);
-                NodeKind::FullBox {
-                    version,
-                    flags,
-                    data_offset,
-                    data_len,
-                }
-            } else {
-                let data_offset = h.start + h.header_size;
-                let data_len = box_end.saturating_sub(data_offset);
-                if &h.typ.0 == b"uuid" {
-                    NodeKind::Unknown {
-                        data_offset,
-                        data_len,
-                    }
-                } else {
-                    NodeKind::Leaf {
-                        data_offset,
-                        data_len,
-                    }
-                }
-            };
-            f.seek(SeekFrom::Start(box_end))?;
-            kids.push(BoxRef { hdr: h, kind });
-        }
-        kids
-    };
+    let top = parse_children(&mut f, file_len)?;
 
     let reg = default_registry();
 
-    // Target roots for printing/JSON
-    let targets: Vec<&BoxRef> = if let Some(path) = &args.filter {
-        select_by_path(&top, path)
+    // Target roots for printing/JSON, and the type they sit in
+    let (targets, parent): (Vec<&BoxRef>, Option<FourCC>) = if let Some(path) = &args.filter {
+        (select_by_path(&top, path), path_parent(path))
     } else {
-        top.iter().collect()
+        (top.iter().collect(), None)
     };
 
     // JSON mode: output JSON and exit (no tree or raw to keep output clean)
@@ -111,7 +61,7 @@ fn main() -> anyhow::Result<()> {
         let mut json_file = File::open(&args.path)?; // fresh handle for decoding
         let json_boxes: Vec<JsonBox> = targets
             .iter()
-            .map(|b| build_json_for_box(&mut json_file, b, args.decode, &reg))
+            .map(|b| build_json_for_box(&mut json_file, b, parent, args.decode, &reg))
             .collect();
         println!("{}", serde_json::to_string_pretty(&json_boxes)?);
         return Ok(());
@@ -119,7 +69,7 @@ fn main() -> anyhow::Result<()> {
 
     // Text tree
     for b in &targets {
-        print_box(&mut f, b, 0, args.max_depth, args.decode, &reg)?;
+        print_box(&mut f, b, parent, 0, args.max_depth, args.decode, &reg)?;
     }
 
     // Optional raw dump (unfiltered: still walks the whole tree)
@@ -135,6 +85,7 @@ fn main() -> anyhow::Result<()> {
 fn print_box(
     f: &mut File,
     b: &BoxRef,
+    parent: Option<FourCC>,
     depth: usize,
     max_depth: usize,
     decode: bool,
@@ -145,38 +96,41 @@ fn print_box(
     match &b.kind {
         NodeKind::FullBox { version, flags, .. } => {
             println!(
-                "{indent}{:>6} {:>10} {} (ver={}, flags=0x{:06x})",
+                "{indent}{:>6} {:>10} {:>10} {} (ver={}, flags=0x{:06x})",
                 format!("{:#x}", hdr.start),
                 hdr.size,
+                human_size(hdr.size),
                 display_type(hdr),
                 version,
                 flags
             );
             if decode {
-                maybe_decode(f, b, reg)?;
+                maybe_decode(f, b, parent, reg)?;
             }
         }
         NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
             println!(
-                "{indent}{:>6} {:>10} {}",
+                "{indent}{:>6} {:>10} {:>10} {}",
                 format!("{:#x}", hdr.start),
                 hdr.size,
+                human_size(hdr.size),
                 display_type(hdr)
             );
             if decode {
-                maybe_decode(f, b, reg)?;
+                maybe_decode(f, b, parent, reg)?;
             }
         }
-        NodeKind::Container(children) => {
+        NodeKind::Container(_) | NodeKind::Unexpanded => {
             println!(
-                "{indent}{:>6} {:>10} {} (container)",
+                "{indent}{:>6} {:>10} {:>10} {} (container)",
                 format!("{:#x}", hdr.start),
                 hdr.size,
+                human_size(hdr.size),
                 display_type(hdr)
             );
             if depth < max_depth {
-                for c in children {
-                    print_box(f, c, depth + 1, max_depth, decode, reg)?;
+                for c in b.children() {
+                    print_box(f, c, Some(hdr.typ), depth + 1, max_depth, decode, reg)?;
                 }
             }
         }
@@ -208,23 +162,30 @@ fn payload_region(b: &BoxRef) -> Option<(BoxKey, u64, u64)> {
             data_len,
             ..
         } => Some((key, *data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
-                return None;
-            }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
+        // Leaf geometry is already resolved against the parent end (size 0 = "to end").
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        }
+        | NodeKind::Unknown {
+            data_offset,
+            data_len,
+        } => {
+            if *data_len == 0 {
                 return None;
             }
-            Some((key, off, len))
+            Some((key, *data_offset, *data_len))
         }
-        NodeKind::Container(_) => None,
+        NodeKind::Container(_) | NodeKind::Unexpanded => None,
     }
 }
 
-fn decode_value(f: &mut File, b: &BoxRef, reg: &Registry) -> Option<String> {
+fn decode_value(
+    f: &mut File,
+    b: &BoxRef,
+    parent: Option<FourCC>,
+    reg: &Registry,
+) -> Option<String> {
     let (key, off, len) = payload_region(b)?;
     if len == 0 {
         return None;
@@ -241,7 +202,11 @@ fn decode_value(f: &mut File, b: &BoxRef, reg: &Registry) -> Option<String> {
         _ => (None, None),
     };
 
-    if let Some(res) = reg.decode(&key, &mut limited, &b.hdr, version, flags) {
+    let ctx = DecodeContext {
+        parent,
+        ..DecodeContext::default()
+    };
+    if let Some(res) = reg.decode_with_context(&key, &mut limited, &b.hdr, version, flags, &ctx) {
         match res {
             Ok(BoxValue::Text(s)) => Some(s),
             Ok(BoxValue::Bytes(bytes)) => Some(format!("{} bytes", bytes.len())),
@@ -253,8 +218,13 @@ fn decode_value(f: &mut File, b: &BoxRef, reg: &Registry) -> Option<String> {
     }
 }
 
-fn maybe_decode(f: &mut File, b: &BoxRef, reg: &Registry) -> anyhow::Result<()> {
-    if let Some(s) = decode_value(f, b, reg) {
+fn maybe_decode(
+    f: &mut File,
+    b: &BoxRef,
+    parent: Option<FourCC>,
+    reg: &Registry,
+) -> anyhow::Result<()> {
+    if let Some(s) = decode_value(f, b, parent, reg) {
         println!("        -> {}", s);
     }
     Ok(())
@@ -323,7 +293,7 @@ fn select_boxes(list: &[BoxRef], sel: &str, out: &mut Vec<(u64, u64, mp4box::box
                 } => {
                     out.push((*data_offset, *data_len, b.hdr.clone()));
                 }
-                NodeKind::Container(_) => {
+                NodeKind::Container(_) | NodeKind::Unexpanded => {
                     let hdr: &BoxHeader = &b.hdr;
                     if hdr.size != 0 && hdr.size > hdr.header_size {
                         let off = hdr.start + hdr.header_size;
@@ -334,14 +304,20 @@ fn select_boxes(list: &[BoxRef], sel: &str, out: &mut Vec<(u64, u64, mp4box::box
             }
         }
 
-        if let NodeKind::Container(kids) = &b.kind {
-            select_boxes(kids, sel, out);
-        }
+        select_boxes(b.children(), sel, out);
     }
 }
 
 // ---------- Filter path: moov.trak[0].mdia.minf.stbl ----------
 
+/// Type of the box the targets of a `--filter` path sit in, e.g. `trak`
+/// for `moov.trak[0].tref`.
+fn path_parent(path: &str) -> Option<FourCC> {
+    let segs: Vec<&str> = path.split('.').collect();
+    let seg = segs.len().checked_sub(2).map(|i| segs[i])?;
+    FourCC::from_str(parse_segment(seg).0).ok()
+}
+
 fn select_by_path<'a>(roots: &'a [BoxRef], path: &str) -> Vec<&'a BoxRef> {
     let mut current: Vec<&'a BoxRef> = roots.iter().collect();
 
@@ -366,16 +342,17 @@ fn select_by_path<'a>(roots: &'a [BoxRef], path: &str) -> Vec<&'a BoxRef> {
         } else {
             // match in children of current set
             for b in &current {
-                if let NodeKind::Container(kids) = &b.kind {
-                    let mut matches: Vec<&BoxRef> =
-                        kids.iter().filter(|c| c.hdr.typ == fourcc).collect();
-                    if let Some(i) = idx {
-                        if i < matches.len() {
-                            next.push(matches[i]);
-                        }
-                    } else {
-                        next.append(&mut matches);
+                let mut matches: Vec<&BoxRef> = b
+                    .children()
+                    .iter()
+                    .filter(|c| c.hdr.typ == fourcc)
+                    .collect();
+                if let Some(i) = idx {
+                    if i < matches.len() {
+                        next.push(matches[i]);
                     }
+                } else {
+                    next.append(&mut matches);
                 }
             }
         }
@@ -430,23 +407,31 @@ fn payload_geometry(b: &BoxRef) -> Option<(u64, u64)> {
             data_len,
             ..
         } => Some((*data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
-                return None;
-            }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
+        // Leaf geometry is already resolved against the parent end (size 0 = "to end").
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        }
+        | NodeKind::Unknown {
+            data_offset,
+            data_len,
+        } => {
+            if *data_len == 0 {
                 return None;
             }
-            Some((off, len))
+            Some((*data_offset, *data_len))
         }
-        NodeKind::Container(_) => None,
+        NodeKind::Container(_) | NodeKind::Unexpanded => None,
     }
 }
 
-fn build_json_for_box(f: &mut File, b: &BoxRef, decode: bool, reg: &Registry) -> JsonBox {
+fn build_json_for_box(
+    f: &mut File,
+    b: &BoxRef,
+    parent: Option<FourCC>,
+    decode: bool,
+    reg: &Registry,
+) -> JsonBox {
     let hdr = &b.hdr;
     let uuid_str = hdr
         .uuid
@@ -466,17 +451,18 @@ fn build_json_for_box(f: &mut File, b: &BoxRef, decode: bool, reg: &Registry) ->
         }
         NodeKind::Leaf { .. } => (None, None, "leaf".to_string(), None),
         NodeKind::Unknown { .. } => (None, None, "unknown".to_string(), None),
+        NodeKind::Unexpanded => (None, None, "container".to_string(), None),
         NodeKind::Container(kids) => {
             let child_nodes = kids
                 .iter()
-                .map(|c| build_json_for_box(f, c, decode, reg))
+                .map(|c| build_json_for_box(f, c, Some(hdr.typ), decode, reg))
                 .collect();
             (None, None, "container".to_string(), Some(child_nodes))
         }
     };
 
     let decoded = if decode {
-        decode_value(f, b, reg)
+        decode_value(f, b, parent, reg)
     } else {
         None
     };
@@ -498,11 +484,3 @@ fn build_json_for_box(f: &mut File, b: &BoxRef, decode: bool, reg: &Registry) ->
         children,
     }
 }
-
-fn is_container(h: &BoxHeader) -> bool {
-    KnownBox::from(h.typ).is_container()
-}
-
-fn is_full_box(h: &BoxHeader) -> bool {
-    KnownBox::from(h.typ).is_full_box()
-}
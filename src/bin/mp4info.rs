@@ -103,21 +103,10 @@ fn main() -> anyhow::Result<()> {
 }
 
 fn parse_ftyp(b: &Box, info: &mut MediaInfo) {
-    let decoded = match &b.decoded {
-        Some(s) => s,
-        None => return,
-    };
-
-    // Example decoded string:
-    // "major=isom minor=512 compatible=[\"isom\", \"iso2\", \"avc1\", \"mp41\"]"
-    if let Some(major) = parse_string_field(decoded, "major=") {
-        info.major_brand = Some(major);
-    }
-    if let Some(minor) = parse_u32_field(decoded, "minor=") {
-        info.minor_version = Some(minor);
-    }
-    if let Some(compat) = parse_compatible_brands(decoded) {
-        info.compatible_brands = compat;
+    if let Some(mp4box::registry::StructuredData::FileType(ftyp)) = &b.structured_data {
+        info.major_brand = Some(ftyp.major_brand.clone());
+        info.minor_version = Some(ftyp.minor_version);
+        info.compatible_brands = ftyp.compatible_brands.clone();
     }
 }
 
@@ -129,18 +118,12 @@ fn parse_moov(b: &Box, info: &mut MediaInfo) {
 
     // mvhd for overall movie duration
     if let Some(mvhd) = children.iter().find(|c| c.typ == "mvhd")
-        && let Some(decoded) = &mvhd.decoded
+        && let Some(mp4box::registry::StructuredData::MovieHeader(mvhd_data)) =
+            &mvhd.structured_data
     {
-        // Example: "timescale=600000 duration=65536"
-        if let Some(ts) = parse_u32_field(decoded, "timescale=") {
-            info.movie_timescale = Some(ts);
-        }
-        if let Some(dur) = parse_u64_field(decoded, "duration=") {
-            info.movie_duration_ticks = Some(dur);
-            if let Some(ts) = info.movie_timescale {
-                info.movie_duration_seconds = Some(dur as f64 / ts as f64);
-            }
-        }
+        info.movie_timescale = Some(mvhd_data.timescale);
+        info.movie_duration_ticks = Some(mvhd_data.duration);
+        info.movie_duration_seconds = Some(mvhd_data.duration_seconds);
     }
 
     // trak boxes for per-track timing
@@ -162,8 +145,11 @@ fn parse_trak(trak: &Box, index: usize, info: &mut MediaInfo) {
         language: None,
     };
 
-    // tkhd at the trak level: possible width/height
-    if let Some(tkhd) = find_child(trak, "tkhd")
+    // Display size: tapt/clef or tkhd, falling back to tkhd text
+    if let Some((w, h)) = display_dimensions(trak) {
+        ti.width = Some(w);
+        ti.height = Some(h);
+    } else if let Some(tkhd) = find_child(trak, "tkhd")
         && let Some(decoded) = &tkhd.decoded
     {
         // For “normal” tkhd decoders you’ll get something like:
@@ -261,11 +247,15 @@ fn parse_trak(trak: &Box, index: usize, info: &mut MediaInfo) {
             }
         }
 
-        // width / height (for video)
-        if let Some(w) = parse_u32_field(decoded, "width=") {
+        // width / height (for video), unless the track header already gave
+        // a display size
+        if ti.width.is_none()
+            && let (Some(w), Some(h)) = (
+                parse_u32_field(decoded, "width="),
+                parse_u32_field(decoded, "height="),
+            )
+        {
             ti.width = Some(w);
-        }
-        if let Some(h) = parse_u32_field(decoded, "height=") {
             ti.height = Some(h);
         }
     }
@@ -273,6 +263,25 @@ fn parse_trak(trak: &Box, index: usize, info: &mut MediaInfo) {
     info.tracks.push(ti);
 }
 
+/// Display size of a track. QuickTime's `tapt/clef` (clean aperture) is what
+/// players show, so it wins over the tkhd width/height.
+fn display_dimensions(trak: &Box) -> Option<(u32, u32)> {
+    use mp4box::registry::StructuredData;
+
+    if let Some(clef) = find_child(trak, "tapt").and_then(|t| find_child(t, "clef"))
+        && let Some(StructuredData::TrackAperture(ap)) = &clef.structured_data
+    {
+        return Some((ap.width.round() as u32, ap.height.round() as u32));
+    }
+
+    match find_child(trak, "tkhd").and_then(|t| t.structured_data.as_ref()) {
+        Some(StructuredData::TrackHeader(tkhd)) if tkhd.width > 0.0 && tkhd.height > 0.0 => {
+            Some((tkhd.width.round() as u32, tkhd.height.round() as u32))
+        }
+        _ => None,
+    }
+}
+
 fn find_child<'a>(parent: &'a Box, typ: &str) -> Option<&'a Box> {
     parent
         .children
@@ -314,25 +323,6 @@ fn parse_string_field(s: &str, key: &str) -> Option<String> {
     }
 }
 
-fn parse_compatible_brands(s: &str) -> Option<Vec<String>> {
-    // e.g. compatible=["isom", "iso2", "avc1", "mp41"]
-    let idx = s.find("compatible=[")?;
-    let rest = &s[idx + "compatible=[".len()..];
-    let end = rest.find(']')?;
-    let inside = &rest[..end];
-    if inside.trim().is_empty() {
-        return Some(Vec::new());
-    }
-    let mut out = Vec::new();
-    for part in inside.split(',') {
-        let trimmed = part.trim().trim_matches('"');
-        if !trimmed.is_empty() {
-            out.push(trimmed.to_string());
-        }
-    }
-    Some(out)
-}
-
 // ---- human-readable output -----------------------------------------
 
 fn print_human(info: &MediaInfo) {
@@ -418,18 +408,4 @@ mod tests {
         let s = r#"language="und""#;
         assert_eq!(parse_string_field(s, "language="), Some("und".to_string()));
     }
-
-    #[test]
-    fn parse_compatible_brands_parses_list() {
-        let s = r#"major=isom minor=512 compatible=["isom", "iso2", "avc1", "mp41"]"#;
-        let brands = parse_compatible_brands(s).unwrap();
-        assert_eq!(brands, vec!["isom", "iso2", "avc1", "mp41"]);
-    }
-
-    #[test]
-    fn parse_compatible_brands_empty() {
-        let s = r#"compatible=[]"#;
-        let brands = parse_compatible_brands(s).unwrap();
-        assert!(brands.is_empty());
-    }
 }
@@ -334,8 +334,9 @@ fn build_samples(table_data: &SampleTableData, timescale: u32) -> Result<Vec<Sam
             pts,
             start_time: dts as f64 / timescale as f64,
             duration,
-            rendered_offset: 0,            // From ctts if present
-            file_offset: i as u64 * 50000, // Rough estimate - would come from STCO
+            effective_duration: duration,
+            rendered_offset: 0,                  // From ctts if present
+            file_offset: Some(i as u64 * 50000), // Rough estimate - would come from STCO
             size: if !table_data.sample_sizes.is_empty() {
                 if i < table_data.sample_sizes.len() as u32 {
                     table_data.sample_sizes[i as usize]
@@ -347,6 +348,7 @@ fn build_samples(table_data: &SampleTableData, timescale: u32) -> Result<Vec<Sam
                 if i == 0 { 50000 } else { 5000 } // First sample larger (keyframe)
             },
             is_sync: i % 30 == 0, // Every 30th sample is keyframe (more realistic)
+            flags: None,
         };
         samples.push(sample);
     }
@@ -529,6 +531,7 @@ fn print_text(tracks: &[TrackInfo], args: &Args) -> Result<()> {
             {
                 break;
             }
+            let file_offset = s.file_offset.map_or("-".to_string(), |o| o.to_string());
 
             if args.timing {
                 println!(
@@ -539,7 +542,7 @@ fn print_text(tracks: &[TrackInfo], args: &Args) -> Result<()> {
                     s.start_time,
                     s.duration,
                     s.size,
-                    s.file_offset,
+                    file_offset,
                     if s.is_sync { "*" } else { "" },
                 );
             } else {
@@ -549,7 +552,7 @@ fn print_text(tracks: &[TrackInfo], args: &Args) -> Result<()> {
                     s.start_time,
                     s.duration,
                     s.size,
-                    s.file_offset,
+                    file_offset,
                     if s.is_sync { "*" } else { "" },
                 );
             }
@@ -48,7 +48,7 @@ impl FromStr for FourCC {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BoxHeader {
     pub size: u64,   // total size including header, or 0=to parent end
     pub typ: FourCC, // 4CC or b"uuid"
@@ -57,9 +57,77 @@ pub struct BoxHeader {
     pub start: u64,       // file offset of header start
 }
 
+impl BoxHeader {
+    /// Header for a `typ` box with `payload_len` bytes of payload, at offset 0.
+    ///
+    /// Uses the 8-byte form, or a 64-bit largesize when the box doesn't fit in
+    /// 32 bits.
+    pub fn new(typ: FourCC, payload_len: u64) -> Self {
+        Self::with_header(typ, None, 8, payload_len)
+    }
+
+    /// Header for a full box: `payload_len` excludes the version/flags word,
+    /// which the parser counts as payload rather than header.
+    pub fn new_full(typ: FourCC, payload_len: u64) -> Self {
+        Self::new(typ, payload_len + 4)
+    }
+
+    /// Header for a `uuid` box with the given usertype.
+    pub fn new_uuid(uuid: [u8; 16], payload_len: u64) -> Self {
+        Self::with_header(FourCC(*b"uuid"), Some(uuid), 24, payload_len)
+    }
+
+    fn with_header(
+        typ: FourCC,
+        uuid: Option<[u8; 16]>,
+        header_size: u64,
+        payload_len: u64,
+    ) -> Self {
+        let mut header_size = header_size;
+        if header_size + payload_len > u32::MAX as u64 {
+            header_size += 8;
+        }
+        Self {
+            size: header_size + payload_len,
+            typ,
+            uuid,
+            header_size,
+            start: 0,
+        }
+    }
+
+    /// Whether the size is stored as a 64-bit largesize after a 32-bit size
+    /// field of 1. `header_size` then includes the extra 8 bytes.
+    pub fn is_extended_size(&self) -> bool {
+        self.header_size == 16 || self.header_size == 32
+    }
+
+    /// Serialize the header as it appears in a file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let large = self.is_extended_size();
+        let mut out = Vec::with_capacity(self.header_size as usize);
+        if large {
+            out.extend_from_slice(&1u32.to_be_bytes());
+        } else {
+            out.extend_from_slice(&(self.size as u32).to_be_bytes());
+        }
+        out.extend_from_slice(&self.typ.0);
+        if large {
+            out.extend_from_slice(&self.size.to_be_bytes());
+        }
+        if let Some(uuid) = self.uuid {
+            out.extend_from_slice(&uuid);
+        }
+        out
+    }
+}
+
 #[derive(Debug)]
 pub enum NodeKind {
     Container(Vec<BoxRef>),
+    /// A container whose children have not been read, as returned by
+    /// [`crate::parse_top_level_only`]; see [`crate::expand_container`]
+    Unexpanded,
     FullBox {
         version: u8,
         flags: u32,
@@ -82,6 +150,16 @@ pub struct BoxRef {
     pub kind: NodeKind,
 }
 
+impl BoxRef {
+    /// Child boxes, or an empty slice for anything that isn't a container.
+    pub fn children(&self) -> &[BoxRef] {
+        match &self.kind {
+            NodeKind::Container(kids) => kids,
+            _ => &[],
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BoxKey {
     FourCC(FourCC),
@@ -0,0 +1,114 @@
+//! CMAF (ISO/IEC 23000-19) brand detection and structural conformance checks.
+
+use crate::util::read_slice;
+use serde::Serialize;
+use std::io::{Read, Seek};
+
+/// Brands that signal CMAF conformance in `ftyp` / `styp`.
+pub const CMAF_BRANDS: &[&str] = &["cmfc", "cmf2", "cmfs", "cmff", "cmfl"];
+
+/// Result of checking a file or segment against the CMAF structural constraints.
+#[derive(Debug, Clone, Serialize)]
+pub struct CmafReport {
+    /// CMAF brands found in `ftyp` and/or `styp` (deduplicated, in order of appearance)
+    pub brands: Vec<String>,
+    /// Whether any CMAF brand is present
+    pub is_cmaf: bool,
+    /// Constraint violations, one human-readable message each
+    pub findings: Vec<String>,
+    /// `true` when the file claims CMAF and no violations were found
+    pub compliant: bool,
+}
+
+/// Check CMAF brands and structure for a parsed box tree.
+///
+/// `boxes` is the top-level box list from [`crate::get_boxes`]; the reader is
+/// used to read the `ftyp`/`styp` brand lists. The checks performed are:
+///
+/// - a segment without `moov` must start with `styp`
+/// - a header with `moov` must be preceded by `ftyp` and contain `mvex`
+/// - every `moof` carries exactly one `traf` and is followed by an `mdat`
+/// - no `moof` appears before `moov`
+pub fn cmaf_report<R: Read + Seek>(r: &mut R, boxes: &[crate::Box]) -> anyhow::Result<CmafReport> {
+    let mut brands: Vec<String> = Vec::new();
+    for b in boxes.iter().filter(|b| b.typ == "ftyp" || b.typ == "styp") {
+        let (Some(off), Some(len)) = (b.payload_offset, b.payload_size) else {
+            continue;
+        };
+        let payload = read_slice(r, off, len)?;
+        for brand in brand_list(&payload) {
+            if CMAF_BRANDS.contains(&brand.as_str()) && !brands.contains(&brand) {
+                brands.push(brand);
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    let moov_pos = boxes.iter().position(|b| b.typ == "moov");
+
+    match moov_pos {
+        Some(pos) => {
+            if !boxes[..pos].iter().any(|b| b.typ == "ftyp") {
+                findings.push("moov is not preceded by ftyp".to_string());
+            }
+            let has_mvex = boxes[pos]
+                .children
+                .as_ref()
+                .is_some_and(|kids| kids.iter().any(|c| c.typ == "mvex"));
+            if !has_mvex {
+                findings.push("moov has no mvex (track is not fragmented)".to_string());
+            }
+        }
+        None => {
+            if boxes.first().map(|b| b.typ.as_str()) != Some("styp") {
+                findings.push("segment does not start with styp".to_string());
+            }
+        }
+    }
+
+    for (i, b) in boxes.iter().enumerate().filter(|(_, b)| b.typ == "moof") {
+        if moov_pos.is_some_and(|pos| i < pos) {
+            findings.push(format!("moof at {:#x} appears before moov", b.offset));
+        }
+
+        let traf_count = b
+            .children
+            .as_ref()
+            .map_or(0, |kids| kids.iter().filter(|c| c.typ == "traf").count());
+        if traf_count != 1 {
+            findings.push(format!(
+                "moof at {:#x} contains {} traf boxes (expected exactly 1)",
+                b.offset, traf_count
+            ));
+        }
+
+        if boxes.get(i + 1).map(|n| n.typ.as_str()) != Some("mdat") {
+            findings.push(format!(
+                "moof at {:#x} is not immediately followed by mdat",
+                b.offset
+            ));
+        }
+    }
+
+    let is_cmaf = !brands.is_empty();
+    Ok(CmafReport {
+        compliant: is_cmaf && findings.is_empty(),
+        brands,
+        is_cmaf,
+        findings,
+    })
+}
+
+// ftyp/styp payload: major_brand, minor_version, compatible_brands[]
+fn brand_list(payload: &[u8]) -> Vec<String> {
+    let mut out = Vec::new();
+    if payload.len() >= 4 {
+        out.push(String::from_utf8_lossy(&payload[0..4]).to_string());
+    }
+    if payload.len() > 8 {
+        for chunk in payload[8..].chunks_exact(4) {
+            out.push(String::from_utf8_lossy(chunk).to_string());
+        }
+    }
+    out
+}
@@ -0,0 +1,58 @@
+//! One-call dumps of a file's box tree in the formats tools usually want.
+
+use crate::tree::{RenderOpts, render_tree};
+use std::fs::File;
+use std::path::Path;
+
+/// Output format for [`dump`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// The box tree as compact JSON
+    Json,
+    /// The box tree as indented JSON
+    PrettyJson,
+    /// One JSON object per box and line, without `children` and with a
+    /// `path` field (`"moov/trak/mdia"`), for grepping with `jq`
+    Ndjson,
+    /// The indented tree from [`render_tree`]
+    Text,
+}
+
+/// Parse the file at `path` and render its box tree as `format`.
+///
+/// `decode` decodes known boxes as [`crate::get_boxes`] does.
+pub fn dump(path: impl AsRef<Path>, format: DumpFormat, decode: bool) -> anyhow::Result<String> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let boxes = crate::get_boxes(&mut file, size, decode)?;
+
+    Ok(match format {
+        DumpFormat::Json => serde_json::to_string(&boxes)?,
+        DumpFormat::PrettyJson => serde_json::to_string_pretty(&boxes)?,
+        DumpFormat::Ndjson => {
+            let mut out = String::new();
+            write_ndjson(&boxes, "", &mut out)?;
+            out
+        }
+        DumpFormat::Text => render_tree(&boxes, RenderOpts::default()),
+    })
+}
+
+fn write_ndjson(boxes: &[crate::Box], parent: &str, out: &mut String) -> anyhow::Result<()> {
+    for b in boxes {
+        let path = if parent.is_empty() {
+            b.typ.clone()
+        } else {
+            format!("{}/{}", parent, b.typ)
+        };
+        let mut value = serde_json::to_value(b)?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.remove("children");
+            obj.insert("path".to_string(), path.clone().into());
+        }
+        out.push_str(&serde_json::to_string(&value)?);
+        out.push('\n');
+        write_ndjson(b.children.as_deref().unwrap_or_default(), &path, out)?;
+    }
+    Ok(())
+}
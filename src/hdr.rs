@@ -0,0 +1,136 @@
+//! HDR signalling summary (currently Dolby Vision).
+//!
+//! Dolby Vision streams come in two shapes: single-layer (e.g. profiles 5, 8 and 9)
+//! and dual-layer (profile 7), where the enhancement layer (EL) is either carried in
+//! the same track or in a separate track that references the base layer (BL) via a
+//! `tref/vdep` box.
+
+use crate::registry::{DolbyVisionConfig, StructuredData};
+use serde::Serialize;
+
+/// HDR signalling found on a single track.
+#[derive(Debug, Clone, Serialize)]
+pub struct HdrSummary {
+    pub track_id: u32,
+    /// Sample entry codec of the first stsd entry (e.g. "dvh1", "hvc1")
+    pub codec: Option<String>,
+    /// Dolby Vision configuration record, if the track carries one
+    pub dolby_vision: Option<DolbyVisionConfig>,
+    /// Whether the Dolby Vision stream uses a base + enhancement layer
+    pub is_dual_layer: bool,
+    /// For an enhancement-layer track: the base-layer track it depends on (from `tref/vdep`)
+    pub base_layer_track_id: Option<u32>,
+    /// For a base-layer track: the enhancement-layer track that references it
+    pub enhancement_layer_track_id: Option<u32>,
+}
+
+/// Summarize HDR signalling for every track in a decoded box tree.
+///
+/// Only tracks carrying a Dolby Vision configuration, or taking part in a
+/// `vdep` dependency, are reported. The box tree must have been produced with
+/// `decode = true`.
+///
+/// # Example
+///
+/// ```no_run
+/// use mp4box::{get_boxes, hdr::hdr_summary};
+/// use std::fs::File;
+///
+/// let mut file = File::open("dolby_vision.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for track in hdr_summary(&boxes) {
+///     println!("track {} dual-layer={}", track.track_id, track.is_dual_layer);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn hdr_summary(boxes: &[crate::Box]) -> Vec<HdrSummary> {
+    let mut tracks = Vec::new();
+
+    for moov in boxes.iter().filter(|b| b.typ == "moov") {
+        for trak in children(moov).iter().filter(|b| b.typ == "trak") {
+            tracks.push(scan_track(trak));
+        }
+    }
+
+    // Resolve vdep references: the referencing track is the enhancement layer.
+    let links: Vec<(u32, u32)> = tracks
+        .iter()
+        .filter_map(|t| t.vdep.map(|bl| (t.summary.track_id, bl)))
+        .collect();
+
+    for t in &mut tracks {
+        for &(el, bl) in &links {
+            if t.summary.track_id == el {
+                t.summary.base_layer_track_id = Some(bl);
+                t.summary.is_dual_layer = true;
+            }
+            if t.summary.track_id == bl {
+                t.summary.enhancement_layer_track_id = Some(el);
+                t.summary.is_dual_layer = true;
+            }
+        }
+    }
+
+    tracks
+        .into_iter()
+        .map(|t| t.summary)
+        .filter(|s| s.dolby_vision.is_some() || s.is_dual_layer)
+        .collect()
+}
+
+struct ScannedTrack {
+    summary: HdrSummary,
+    vdep: Option<u32>,
+}
+
+fn scan_track(trak: &crate::Box) -> ScannedTrack {
+    let mut summary = HdrSummary {
+        track_id: 0,
+        codec: None,
+        dolby_vision: None,
+        is_dual_layer: false,
+        base_layer_track_id: None,
+        enhancement_layer_track_id: None,
+    };
+    let mut vdep = None;
+
+    if let Some(StructuredData::TrackHeader(tkhd)) =
+        child(trak, "tkhd").and_then(|b| b.structured_data.as_ref())
+    {
+        summary.track_id = tkhd.track_id;
+    }
+
+    if let Some(tref) = child(trak, "tref")
+        && let Some(StructuredData::TrackReference(r)) =
+            child(tref, "vdep").and_then(|b| b.structured_data.as_ref())
+    {
+        vdep = r.track_ids.first().copied();
+    }
+
+    let stsd = child(trak, "mdia")
+        .and_then(|b| child(b, "minf"))
+        .and_then(|b| child(b, "stbl"))
+        .and_then(|b| child(b, "stsd"));
+    if let Some(StructuredData::SampleDescription(stsd)) =
+        stsd.and_then(|b| b.structured_data.as_ref())
+        && let Some(entry) = stsd.entries.first()
+    {
+        summary.codec = Some(entry.codec.clone());
+        summary.dolby_vision = entry.dolby_vision.clone();
+        summary.is_dual_layer = entry
+            .dolby_vision
+            .as_ref()
+            .is_some_and(|dv| dv.is_dual_layer());
+    }
+
+    ScannedTrack { summary, vdep }
+}
+
+fn children(b: &crate::Box) -> &[crate::Box] {
+    b.children.as_deref().unwrap_or(&[])
+}
+
+fn child<'a>(b: &'a crate::Box, typ: &str) -> Option<&'a crate::Box> {
+    children(b).iter().find(|c| c.typ == typ)
+}
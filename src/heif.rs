@@ -0,0 +1,217 @@
+//! HEIF item access.
+//!
+//! HEIF files describe their content as items in the file-level `meta` box:
+//! `iinf` names each item and its type, and `iloc` says where its bytes are,
+//! either at file offsets or inside the `meta`'s own `idat`. Besides the image
+//! itself, HEIC photos usually carry Exif and XMP metadata as items, and tiled
+//! photos store the image as a `grid` item derived (`dimg`) from its tiles.
+
+use crate::registry::{IinfData, IlocData, ImageGridData, IrefData, ItemInfoEntry, StructuredData};
+use crate::util::read_slice;
+use anyhow::Context;
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom};
+
+/// A metadata item (Exif or XMP) read out of a HEIF file.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeifMetadataItem {
+    pub item_id: u32,
+    /// "Exif" or "mime"
+    pub item_type: String,
+    /// Content type of `mime` items, e.g. "application/rdf+xml" for XMP
+    pub content_type: Option<String>,
+    /// Item payload. For Exif items the leading TIFF header offset is skipped,
+    /// so this starts at the TIFF header ("II*\0" / "MM\0*").
+    pub data: Vec<u8>,
+    /// The payload as text for XML content types
+    pub text: Option<String>,
+}
+
+/// Read the payload of item `item_id` from the file-level `meta`.
+///
+/// `boxes` must come from [`crate::get_boxes`] with `decode = true` on the same
+/// reader. Items built from several extents are concatenated.
+pub fn item_data<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+    item_id: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let meta = file_meta(boxes).context("no file-level meta box")?;
+    let iloc = find_iloc(meta).context("meta has no decodable iloc")?;
+    let location = iloc
+        .items
+        .iter()
+        .find(|i| i.item_id == item_id)
+        .with_context(|| format!("item {} has no iloc entry", item_id))?;
+
+    let (source_start, source_end) = match location.construction_method {
+        0 => (0, r.seek(SeekFrom::End(0))?),
+        1 => {
+            let idat = children(meta)
+                .iter()
+                .find(|b| b.typ == "idat")
+                .context("iloc refers to idat but meta has none")?;
+            let start = idat
+                .payload_offset
+                .unwrap_or(idat.offset + idat.header_size);
+            let end = start
+                .checked_add(idat.payload_size.unwrap_or(0))
+                .with_context(|| overrun(item_id))?;
+            (start, end)
+        }
+        other => anyhow::bail!(
+            "item {}: construction method {} is not supported",
+            item_id,
+            other
+        ),
+    };
+
+    let mut data = Vec::new();
+    for extent in &location.extents {
+        // Offsets come straight from iloc, so they may not even fit in 64 bits.
+        let start = source_start
+            .checked_add(location.base_offset)
+            .and_then(|s| s.checked_add(extent.offset))
+            .with_context(|| overrun(item_id))?;
+        let len = if extent.length == 0 {
+            source_end.saturating_sub(start)
+        } else {
+            extent.length
+        };
+        if start.checked_add(len).is_none_or(|end| end > source_end) {
+            anyhow::bail!(overrun(item_id));
+        }
+        data.extend_from_slice(&read_slice(r, start, len)?);
+    }
+    Ok(data)
+}
+
+fn overrun(item_id: u32) -> String {
+    format!("item {}: extent runs past the end of its source", item_id)
+}
+
+/// Read every Exif and XMP (`mime` with an XML content type) item.
+pub fn metadata_items<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+) -> anyhow::Result<Vec<HeifMetadataItem>> {
+    let Some(iinf) = file_meta(boxes).and_then(find_iinf) else {
+        return Ok(Vec::new());
+    };
+
+    let mut items = Vec::new();
+    for entry in iinf.entries.iter().filter(|e| is_metadata(e)) {
+        let mut data = item_data(r, boxes, entry.item_id)?;
+        let item_type = entry.item_type.clone().unwrap_or_else(|| "mime".into());
+
+        if item_type == "Exif" && data.len() >= 4 {
+            let tiff_offset = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
+            data.drain(..(4 + tiff_offset).min(data.len()));
+        }
+        let text = if entry
+            .content_type
+            .as_deref()
+            .is_some_and(|t| t.contains("xml"))
+        {
+            Some(String::from_utf8_lossy(&data).to_string())
+        } else {
+            None
+        };
+
+        items.push(HeifMetadataItem {
+            item_id: entry.item_id,
+            item_type,
+            content_type: entry.content_type.clone(),
+            data,
+            text,
+        });
+    }
+    Ok(items)
+}
+
+/// Decode the payload of a derived-image item.
+///
+/// Returns [`StructuredData::ImageGrid`] for `grid` items and `None` for item
+/// types whose payload is not decoded (coded images, Exif, ...).
+pub fn decode_item<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+    item_id: u32,
+) -> anyhow::Result<Option<StructuredData>> {
+    let item_type = file_meta(boxes)
+        .and_then(find_iinf)
+        .and_then(|iinf| iinf.entries.iter().find(|e| e.item_id == item_id))
+        .and_then(|e| e.item_type.as_deref());
+    match item_type {
+        Some("grid") => {
+            let data = item_data(r, boxes, item_id)?;
+            Ok(Some(StructuredData::ImageGrid(ImageGridData::parse(
+                &data,
+            )?)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The items `item_id` points to with `reference_type` references, in order.
+///
+/// For a grid and `"dimg"` these are its tiles in row-major order; for a
+/// thumbnail and `"thmb"` the image it is a thumbnail of.
+pub fn referenced_items(boxes: &[crate::Box], item_id: u32, reference_type: &str) -> Vec<u32> {
+    file_meta(boxes)
+        .and_then(find_iref)
+        .map(|iref| {
+            iref.references
+                .iter()
+                .filter(|r| r.from_item_id == item_id && r.reference_type == reference_type)
+                .flat_map(|r| r.to_item_ids.iter().copied())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn is_metadata(entry: &ItemInfoEntry) -> bool {
+    match entry.item_type.as_deref() {
+        Some("Exif") => true,
+        Some("mime") | None => entry
+            .content_type
+            .as_deref()
+            .is_some_and(|t| t.contains("xml")),
+        _ => false,
+    }
+}
+
+fn file_meta(boxes: &[crate::Box]) -> Option<&crate::Box> {
+    boxes.iter().find(|b| b.typ == "meta")
+}
+
+fn find_iinf(meta: &crate::Box) -> Option<&IinfData> {
+    children(meta)
+        .iter()
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::ItemInfo(iinf)) => Some(iinf),
+            _ => None,
+        })
+}
+
+fn find_iloc(meta: &crate::Box) -> Option<&IlocData> {
+    children(meta)
+        .iter()
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::ItemLocation(iloc)) => Some(iloc),
+            _ => None,
+        })
+}
+
+fn find_iref(meta: &crate::Box) -> Option<&IrefData> {
+    children(meta)
+        .iter()
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::ItemReference(iref)) => Some(iref),
+            _ => None,
+        })
+}
+
+fn children(b: &crate::Box) -> &[crate::Box] {
+    b.children.as_deref().unwrap_or(&[])
+}
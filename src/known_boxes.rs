@@ -54,6 +54,8 @@ pub enum KnownBox {
 
     // dinf children
     Dref,
+    Url,
+    Urn,
 
     // stbl children
     Stsd,
@@ -86,6 +88,8 @@ pub enum KnownBox {
 
     // meta / HEIF-ish
     Iloc,
+    Idat,
+    Mime,
     Iinf,
     Infe,
     Iref,
@@ -99,7 +103,12 @@ pub enum KnownBox {
     Colr,
     Hvcc,
     Avcc,
+    Dvcc,
+    Dvvc,
     Pitm,
+    Keys,
+    Ilst,
+    Xmp,
 
     // Encryption / CENC
     Sinf,
@@ -136,11 +145,16 @@ pub enum KnownBox {
 
     // Misc / QT-ish / common extras
     Pasp,
+    Chan,
+    Esds,
     Cslg,
     Cprt,
     Gama,
     Fiel,
     Tapt,
+    Clef,
+    Prof,
+    Enof,
 
     // Raw UUID/vendor
     Uuid,
@@ -194,6 +208,8 @@ impl From<FourCC> for KnownBox {
             b"stbl" => KnownBox::Stbl,
 
             b"dref" => KnownBox::Dref,
+            b"url " => KnownBox::Url,
+            b"urn " => KnownBox::Urn,
 
             b"stsd" => KnownBox::Stsd,
             b"stts" => KnownBox::Stts,
@@ -223,6 +239,8 @@ impl From<FourCC> for KnownBox {
             b"tfra" => KnownBox::Tfra,
 
             b"iloc" => KnownBox::Iloc,
+            b"idat" => KnownBox::Idat,
+            b"mime" => KnownBox::Mime,
             b"iinf" => KnownBox::Iinf,
             b"infe" => KnownBox::Infe,
             b"iref" => KnownBox::Iref,
@@ -236,6 +254,11 @@ impl From<FourCC> for KnownBox {
             b"colr" => KnownBox::Colr,
             b"hvcC" => KnownBox::Hvcc,
             b"avcC" => KnownBox::Avcc,
+            b"dvcC" => KnownBox::Dvcc,
+            b"dvvC" => KnownBox::Dvvc,
+            b"keys" => KnownBox::Keys,
+            b"ilst" => KnownBox::Ilst,
+            b"XMP_" => KnownBox::Xmp,
             b"pitm" => KnownBox::Pitm,
 
             b"sinf" => KnownBox::Sinf,
@@ -269,11 +292,16 @@ impl From<FourCC> for KnownBox {
             b"flac" => KnownBox::Flac,
 
             b"pasp" => KnownBox::Pasp,
+            b"chan" => KnownBox::Chan,
+            b"esds" => KnownBox::Esds,
             b"cslg" => KnownBox::Cslg,
             b"cprt" => KnownBox::Cprt,
             b"gama" => KnownBox::Gama,
             b"fiel" => KnownBox::Fiel,
             b"tapt" => KnownBox::Tapt,
+            b"clef" => KnownBox::Clef,
+            b"prof" => KnownBox::Prof,
+            b"enof" => KnownBox::Enof,
 
             b"uuid" => KnownBox::Uuid,
 
@@ -291,8 +319,11 @@ impl KnownBox {
                 | KnownBox::Trak
                 | KnownBox::Mdia
                 | KnownBox::Minf
+                | KnownBox::Dinf
+                | KnownBox::Tapt
                 | KnownBox::Stbl
                 | KnownBox::Edts
+                | KnownBox::Tref
                 | KnownBox::Udta
                 | KnownBox::Meta
                 | KnownBox::Moof
@@ -301,18 +332,23 @@ impl KnownBox {
                 | KnownBox::Meco
                 | KnownBox::Traf
                 | KnownBox::Sinf
+                | KnownBox::Schi
                 | KnownBox::Iprp
-                | KnownBox::Iref
                 | KnownBox::Ipco
                 | KnownBox::Ipma
         )
     }
 
     /// Returns `true` if this box type is a FullBox (has version/flags).
+    ///
+    /// A box can be both a container and a FullBox (e.g. `meta`); its children
+    /// then start after the version/flags word.
     pub fn is_full_box(&self) -> bool {
         matches!(
             self,
             KnownBox::Mvhd
+                | KnownBox::Meta
+                | KnownBox::Keys
                 | KnownBox::Tkhd
                 | KnownBox::Mdhd
                 | KnownBox::Hdlr
@@ -345,12 +381,21 @@ impl KnownBox {
                 | KnownBox::Tfdt
                 | KnownBox::Trun
                 | KnownBox::Tfra
+                | KnownBox::Mfro
                 | KnownBox::Iloc
+                | KnownBox::Mime
                 | KnownBox::Iinf
                 | KnownBox::Infe
+                | KnownBox::Iref
                 | KnownBox::Pitm
                 | KnownBox::Pssh
-                | KnownBox::Schi
+                | KnownBox::Tenc
+                | KnownBox::Senc
+                | KnownBox::Clef
+                | KnownBox::Prof
+                | KnownBox::Enof
+                | KnownBox::Chan
+                | KnownBox::Esds
                 | KnownBox::Saio
                 | KnownBox::Saiz
         )
@@ -397,6 +442,8 @@ impl KnownBox {
             KnownBox::Dinf => "Data Information Box",
             KnownBox::Stbl => "Sample Table Box",
             KnownBox::Dref => "Data Reference Box",
+            KnownBox::Url => "Data Entry URL Box",
+            KnownBox::Urn => "Data Entry URN Box",
             KnownBox::Stsd => "Sample Description Box",
             KnownBox::Stts => "Decoding Time-to-Sample Box",
             KnownBox::Ctts => "Composition Time-to-Sample Box",
@@ -423,6 +470,8 @@ impl KnownBox {
             KnownBox::Trun => "Track Fragment Run Box",
             KnownBox::Tfra => "Track Fragment Random Access Box",
             KnownBox::Iloc => "Item Location Box",
+            KnownBox::Idat => "Item Data Box",
+            KnownBox::Mime => "MIME Box",
             KnownBox::Iinf => "Item Information Box",
             KnownBox::Infe => "Item Info Entry Box",
             KnownBox::Iref => "Item Reference Box",
@@ -436,6 +485,11 @@ impl KnownBox {
             KnownBox::Colr => "Colour Information Box",
             KnownBox::Hvcc => "HEVC Decoder Configuration Box",
             KnownBox::Avcc => "AVC Decoder Configuration Box",
+            KnownBox::Dvcc => "Dolby Vision Configuration Box",
+            KnownBox::Dvvc => "Dolby Vision Configuration Box (profiles > 7)",
+            KnownBox::Keys => "Metadata Item Keys Box",
+            KnownBox::Ilst => "Metadata Item List Box",
+            KnownBox::Xmp => "XMP Metadata Box",
             KnownBox::Pitm => "Primary Item Box",
             KnownBox::Sinf => "Protection Scheme Information Box",
             KnownBox::Schm => "Scheme Type Box",
@@ -465,11 +519,16 @@ impl KnownBox {
             KnownBox::Alac => "Apple Lossless Sample Entry",
             KnownBox::Flac => "FLAC Audio Sample Entry",
             KnownBox::Pasp => "Pixel Aspect Ratio Box",
+            KnownBox::Chan => "Audio Channel Layout Box",
+            KnownBox::Esds => "Elementary Stream Descriptor Box",
             KnownBox::Cslg => "Composition Shift Least Greatest Box",
             KnownBox::Cprt => "Copyright Box",
             KnownBox::Gama => "Gamma Box",
             KnownBox::Fiel => "Field Handling Box",
             KnownBox::Tapt => "Track Aperture Mode Dimensions Box",
+            KnownBox::Clef => "Track Clean Aperture Dimensions Box",
+            KnownBox::Prof => "Track Production Aperture Dimensions Box",
+            KnownBox::Enof => "Track Encoded Pixels Dimensions Box",
             KnownBox::Uuid => "UUID Box",
             KnownBox::Unknown(_) => "Unknown Box",
         }
@@ -77,21 +77,49 @@
 //!
 //! For more examples, see the `mp4dump` and `mp4info` binaries in this repository.
 
+pub mod annexb;
 pub mod api;
 pub mod boxes;
+pub mod cmaf;
+pub mod dump;
+pub mod hdr;
+pub mod heif;
 pub mod known_boxes;
+pub mod options;
 pub mod parser;
 pub mod registry;
 pub mod samples;
+pub mod space;
+pub mod tree;
 pub mod util;
+pub mod warning;
 
+pub use annexb::{to_annexb, to_annexb_with_parameter_sets};
 pub use boxes::{BoxHeader, BoxKey, BoxRef, FourCC, NodeKind};
-pub use parser::{parse_children, read_box_header};
+pub use dump::{DumpFormat, dump};
+pub use parser::{
+    expand_container, parse_children, parse_top_level_only, read_box_header, visit_boxes,
+};
 pub use registry::{
-    BoxValue, Co64Data, CttsData, CttsEntry, HdlrData, MdhdData, Registry, SampleEntry, StcoData,
-    StructuredData, StscData, StscEntry, StsdData, StssData, StszData, SttsData, SttsEntry,
+    BoxValue, Co64Data, CttsData, CttsEntry, HdlrData, Matrix, MdhdData, Registry, RegistryBuilder,
+    SampleEntry, StcoData, StructuredData, StscData, StscEntry, StsdData, StssData, StszData,
+    SttsData, SttsEntry,
 };
 
 // High-level API
-pub use api::{Box, HexDump, get_boxes, hex_range};
-pub use samples::{SampleInfo, TrackSamples, track_samples_from_path, track_samples_from_reader};
+pub use api::{
+    Analysis, Box, FileKind, FormatInfo, FullAnalysis, HexDump, ReferencedFile, analyze_bytes,
+    analyze_file, analyze_file_opts, analyze_file_with_refs, analyze_file_with_warnings,
+    analyze_full, analyze_reader, analyze_reader_opts, analyze_reader_with_registry,
+    box_type_counts, detect_kind, extract_init_segment, find_path, get_boxes, has_complete_moov,
+    hex_range, movie_duration,
+};
+pub use options::ParseOptions;
+pub use samples::{
+    BitrateStats, ChunkEntry, GaplessInfo, GopStats, SampleFlags, SampleInfo, TrackSamples,
+    TrackSummary, concat_tracks, edit_lists, extract_chunk_table, read_all_samples,
+    read_sample_bytes, total_sample_count, track_samples_from_path, track_samples_from_reader,
+    tracks_to_json,
+};
+pub use tree::{RenderOpts, render_tree};
+pub use warning::Warning;
@@ -0,0 +1,77 @@
+//! Options controlling how strictly a file is parsed and how much is decoded.
+
+use crate::boxes::FourCC;
+use crate::known_boxes::KnownBox;
+
+/// Parsing profile for [`crate::analyze_file_opts`].
+///
+/// In lenient mode (the default) anomalies such as bad box sizes, boxes that
+/// overrun their parent, or payloads that fail to decode are recorded as
+/// warnings and parsing continues. In strict mode the first anomaly is an error.
+///
+/// ```no_run
+/// use mp4box::{ParseOptions, analyze_file_opts};
+///
+/// let opts = ParseOptions { strict: true, ..Default::default() };
+/// let analysis = analyze_file_opts("video.mp4", &opts)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Decode known boxes with the default registry
+    pub decode: bool,
+    /// Treat every anomaly as an error instead of a warning
+    pub strict: bool,
+    /// Maximum container nesting depth; deeper containers are not descended into
+    pub max_depth: Option<usize>,
+    /// Payloads larger than this are left undecoded
+    pub max_decode_bytes: Option<u64>,
+    /// Clamp boxes that run past their parent (or EOF) instead of failing.
+    /// When `false`, truncation is an error even in lenient mode.
+    pub lenient_truncation: bool,
+    /// Recognise box types regardless of case (e.g. `MOOV` as `moov`)
+    pub case_insensitive: bool,
+    /// Apply edit lists (`elst`) when computing sample presentation times
+    pub apply_edits: bool,
+    /// For `uuid` boxes with no registered uuid decoder, try the FourCC decoder
+    /// named by the first four bytes of the usertype (some vendors misuse uuid)
+    pub uuid_fourcc_fallback: bool,
+    /// For boxes with no registered decoder, put a hexdump of the first this
+    /// many payload bytes in `decoded` (0 = leave them undecoded)
+    pub hexdump_preview: usize,
+    /// After a malformed box header (bad size or non-printable type), scan
+    /// forward for the next plausible box in the same parent and resume
+    /// there instead of giving up on the rest of the parent
+    pub resync: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            decode: true,
+            strict: false,
+            max_depth: None,
+            max_decode_bytes: None,
+            lenient_truncation: true,
+            case_insensitive: false,
+            apply_edits: false,
+            uuid_fourcc_fallback: false,
+            hexdump_preview: 0,
+            resync: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// The box type to interpret `typ` as: itself, or its lowercase form when
+    /// `case_insensitive` is set and only the lowercase form is a known box.
+    pub fn canonical_type(&self, typ: FourCC) -> FourCC {
+        if self.case_insensitive && matches!(KnownBox::from(typ), KnownBox::Unknown(_)) {
+            let lower = FourCC(typ.0.map(|c| c.to_ascii_lowercase()));
+            if !matches!(KnownBox::from(lower), KnownBox::Unknown(_)) {
+                return lower;
+            }
+        }
+        typ
+    }
+}
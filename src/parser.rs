@@ -1,7 +1,12 @@
 use crate::boxes::{BoxHeader, BoxRef, FourCC, NodeKind};
 use crate::known_boxes::KnownBox;
+use crate::options::ParseOptions;
+use crate::warning::Warning;
 use byteorder::{BigEndian, ReadBytesExt};
+use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
+use std::ops::ControlFlow;
+use std::path::Path;
 
 #[derive(thiserror::Error, Debug)]
 pub enum ParseError {
@@ -9,6 +14,9 @@ pub enum ParseError {
     Io(#[from] std::io::Error),
     #[error("invalid box size")]
     InvalidSize,
+    /// A box runs past its parent and `ParseOptions::lenient_truncation` is off
+    #[error("{0}")]
+    Truncated(Warning),
 }
 
 pub type Result<T> = std::result::Result<T, ParseError>;
@@ -52,58 +60,510 @@ pub fn read_box_header<R: Read + Seek>(r: &mut R) -> Result<BoxHeader> {
 }
 
 pub fn parse_children<R: Read + Seek>(r: &mut R, parent_end: u64) -> Result<Vec<BoxRef>> {
+    let opts = ParseOptions::default();
+    parse_level(r, parent_end, 0, &opts, &mut Anomalies::fail(), true)
+}
+
+/// Parse only the top-level boxes of the file at `path`.
+///
+/// Containers are returned as [`NodeKind::Unexpanded`]; use [`expand_container`]
+/// to read the children of the one you're interested in. Memory use is
+/// proportional to the number of top-level boxes, not the size of the file.
+pub fn parse_top_level_only(path: impl AsRef<Path>) -> Result<Vec<BoxRef>> {
+    let mut f = File::open(path)?;
+    let end = f.metadata()?.len();
+    let opts = ParseOptions::default();
+    parse_level(&mut f, end, 0, &opts, &mut Anomalies::fail(), false)
+}
+
+/// Read the direct children of a container returned by [`parse_top_level_only`]
+/// (or by a previous `expand_container` call).
+///
+/// `parent_end` is where the box's parent ends: the file size for top-level
+/// boxes, the end of the expanded parent otherwise. A box of size 0 runs to
+/// it, and one declaring more is clamped to it, as in a full parse.
+///
+/// Nested containers are again left unexpanded. Returns an empty list for
+/// boxes that aren't containers.
+pub fn expand_container<R: Read + Seek>(
+    r: &mut R,
+    b: &BoxRef,
+    parent_end: u64,
+) -> Result<Vec<BoxRef>> {
+    if !is_container(&b.hdr) {
+        return Ok(Vec::new());
+    }
+    let end = if b.hdr.size == 0 {
+        parent_end
+    } else {
+        b.hdr.start.saturating_add(b.hdr.size).min(parent_end)
+    };
+    let start = container_children_start(r, &b.hdr)?;
+    r.seek(SeekFrom::Start(start))?;
+    let opts = ParseOptions::default();
+    parse_level(r, end, 1, &opts, &mut Anomalies::fail(), false)
+}
+
+/// Where the parse loop sends the anomalies it finds.
+struct Anomalies<'a> {
+    /// Recoverable problems (clamped sizes, nested size-0 boxes, ...); they
+    /// are handled silently when `None`
+    warnings: Option<&'a mut Vec<Warning>>,
+    /// Malformed headers that ended their parent early; they go to
+    /// `warnings` when `None`
+    errors: Option<&'a mut Vec<Warning>>,
+    /// Fail on a malformed header instead of ending its parent there
+    fatal_headers: bool,
+}
+
+impl Anomalies<'_> {
+    /// What [`parse_children`] and the other plain parsers do: clamp silently
+    /// and fail on malformed headers.
+    fn fail() -> Self {
+        Self {
+            warnings: None,
+            errors: None,
+            fatal_headers: true,
+        }
+    }
+
+    fn warn(&mut self, offset: u64, box_type: impl Into<String>, message: impl Into<String>) {
+        if let Some(warnings) = self.warnings.as_deref_mut() {
+            warnings.push(Warning::new(offset, box_type, message));
+        }
+    }
+
+    /// Record a malformed header that ends its parent at `offset`.
+    fn stop(&mut self, offset: u64, message: impl Into<String>) {
+        match self.errors.as_deref_mut() {
+            Some(errors) => errors.push(Warning::new(offset, "", message)),
+            None => self.warn(offset, "", message),
+        }
+    }
+}
+
+/// Parse the boxes up to `parent_end`, descending into containers only when
+/// `recurse` is set.
+fn parse_level<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+    depth: usize,
+    opts: &ParseOptions,
+    anomalies: &mut Anomalies,
+    recurse: bool,
+) -> Result<Vec<BoxRef>> {
     let mut kids = Vec::new();
-    while r.stream_position()? < parent_end {
-        let h = read_box_header(r)?;
-        let box_end = if h.size == 0 {
-            parent_end
-        } else {
-            h.start + h.size
+    while let Some(b) = parse_box(r, parent_end, depth, opts, anomalies, recurse)? {
+        kids.push(b);
+    }
+    Ok(kids)
+}
+
+/// Parse the box at the current position, with its children when `recurse`
+/// is set, and seek past it. `None` once the parent holds no more boxes.
+fn parse_box<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+    depth: usize,
+    opts: &ParseOptions,
+    anomalies: &mut Anomalies,
+    recurse: bool,
+) -> Result<Option<BoxRef>> {
+    let Some(next) = next_box(r, parent_end, depth, opts, anomalies)? else {
+        return Ok(None);
+    };
+    let kind = match next.body {
+        Body::Container(_) if !recurse => NodeKind::Unexpanded,
+        Body::Container(start) => {
+            r.seek(SeekFrom::Start(start))?;
+            NodeKind::Container(parse_level(r, next.end, depth + 1, opts, anomalies, true)?)
+        }
+        Body::Payload(kind) => kind,
+    };
+    r.seek(SeekFrom::Start(next.end))?;
+    Ok(Some(BoxRef {
+        hdr: next.hdr,
+        kind,
+    }))
+}
+
+/// Parse the boxes up to `size` the way the `analyze_*` functions report
+/// them: malformed headers end their parent and are recorded in `errors`,
+/// everything else recoverable in `warnings`.
+pub(crate) fn parse_children_checked<R: Read + Seek>(
+    r: &mut R,
+    size: u64,
+    opts: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+    errors: &mut Vec<Warning>,
+) -> Result<Vec<BoxRef>> {
+    let mut anomalies = Anomalies {
+        warnings: Some(warnings),
+        errors: Some(errors),
+        fatal_headers: false,
+    };
+    parse_level(r, size, 0, opts, &mut anomalies, true)
+}
+
+/// A box as read by [`next_box`].
+struct NextBox {
+    hdr: BoxHeader,
+    /// End of the box, resolved for size 0 and clamped to its parent
+    end: u64,
+    body: Body,
+}
+
+enum Body {
+    /// A container whose first child starts at this offset
+    Container(u64),
+    /// Anything else, including containers not to be descended into
+    Payload(NodeKind),
+}
+
+/// Read the next box header before `parent_end` and work out where the box
+/// ends and what follows its header.
+///
+/// Every parser in this module reads boxes through here, so box sizes,
+/// size-0 boxes, QuickTime `meta` and full-box headers are handled in one
+/// place. Returns `None` when the parent holds no more boxes, including when
+/// the rest of it can't be parsed and that isn't fatal.
+fn next_box<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+    depth: usize,
+    opts: &ParseOptions,
+    anomalies: &mut Anomalies,
+) -> Result<Option<NextBox>> {
+    let h = loop {
+        let pos = r.stream_position()?;
+        if pos >= parent_end {
+            return Ok(None);
+        }
+        if parent_end - pos < 8 {
+            let msg = format!(
+                "{} trailing bytes are too short for a box header",
+                parent_end - pos
+            );
+            if depth > 0 {
+                // Padding at the end of a box, like the 4-byte zero terminator
+                // QuickTime writes after the last `udta` child.
+                if !is_zero_terminator(r, pos, parent_end)? {
+                    anomalies.warn(pos, "", msg);
+                }
+                return Ok(None);
+            }
+            if anomalies.fatal_headers {
+                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, msg).into());
+            }
+            anomalies.stop(pos, msg);
+            return Ok(None);
+        }
+
+        let header = read_box_header(r);
+        let malformed = match &header {
+            Ok(h) => !plausible_type(&h.typ.0),
+            Err(ParseError::InvalidSize) => true,
+            Err(_) => false,
         };
+        if opts.resync && malformed {
+            match find_next_box(r, pos + 1, parent_end)? {
+                Some(next) => {
+                    anomalies.warn(
+                        pos,
+                        "",
+                        format!(
+                            "malformed box header; skipped {} bytes to resynchronize at {:#x}",
+                            next - pos,
+                            next
+                        ),
+                    );
+                    r.seek(SeekFrom::Start(next))?;
+                    continue;
+                }
+                None => {
+                    anomalies.stop(
+                        pos,
+                        "malformed box header and no box found after it; skipping the rest of the parent",
+                    );
+                    return Ok(None);
+                }
+            }
+        }
 
-        // Decide kind
-        let kind = if is_container(&h) {
-            // recurse into container
-            let content_start = h.start + h.header_size;
-            r.seek(SeekFrom::Start(content_start))?;
-            let child = parse_children(r, box_end)?;
-            NodeKind::Container(child)
-        } else if is_full_box(&h) {
-            let content_start = h.start + h.header_size;
-            r.seek(SeekFrom::Start(content_start))?;
-            let version = r.read_u8()?;
-            let mut f = [0u8; 3];
-            r.read_exact(&mut f)?;
-            let flags = ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32);
-            let data_offset = r.stream_position()?;
-            let data_len = box_end.saturating_sub(data_offset);
-            NodeKind::FullBox {
-                version,
-                flags,
-                data_offset,
-                data_len,
+        match header {
+            Ok(h) => break h,
+            Err(ParseError::InvalidSize) if !anomalies.fatal_headers => {
+                anomalies.stop(pos, "invalid box size; skipping the rest of the parent");
+                return Ok(None);
             }
-        } else {
-            let data_offset = h.start + h.header_size;
-            let data_len = box_end.saturating_sub(data_offset);
-            if &h.typ.0 == b"uuid" {
-                NodeKind::Unknown {
-                    data_offset,
-                    data_len,
+            Err(e) => return Err(e),
+        }
+    };
+    let typ = h.typ.to_string();
+
+    if h.size == 0 && depth > 0 {
+        anomalies.warn(h.start, typ.clone(), ZERO_SIZE_NESTED);
+    }
+    let mut end = if h.size == 0 {
+        parent_end
+    } else {
+        h.start.saturating_add(h.size)
+    };
+    if end > parent_end {
+        let overrun = Warning::new(
+            h.start,
+            typ.clone(),
+            format!(
+                "box declares {} bytes but only {} remain in its parent",
+                h.size,
+                parent_end - h.start
+            ),
+        );
+        if !opts.lenient_truncation {
+            return Err(ParseError::Truncated(overrun));
+        }
+        if let Some(warnings) = anomalies.warnings.as_deref_mut() {
+            warnings.push(overrun);
+        }
+        end = parent_end;
+    }
+    // Each box must move the reader forward or the caller's loop never ends.
+    if end <= h.start {
+        return Err(ParseError::InvalidSize);
+    }
+
+    let known = KnownBox::from(opts.canonical_type(h.typ));
+    let content_start = h.start + h.header_size;
+    let full_box_header = if known == KnownBox::Meta && is_quicktime_meta(r, content_start, end)? {
+        0
+    } else if known.is_full_box() {
+        4
+    } else {
+        0
+    };
+
+    let body = if known.is_container() && opts.max_depth.is_some_and(|max| depth >= max) {
+        anomalies.warn(
+            h.start,
+            typ,
+            "maximum nesting depth reached; children not parsed",
+        );
+        Body::Payload(leaf_kind(&h, content_start, end))
+    } else if content_start + full_box_header > end {
+        anomalies.warn(h.start, typ, "box is too small for its header");
+        Body::Payload(leaf_kind(&h, content_start.min(end), end))
+    } else if known.is_container() {
+        Body::Container(content_start + full_box_header)
+    } else if known.is_full_box() {
+        Body::Payload(full_box_kind(r, content_start, end)?)
+    } else {
+        Body::Payload(leaf_kind(&h, content_start, end))
+    };
+    Ok(Some(NextBox { hdr: h, end, body }))
+}
+
+/// Version, flags and payload of a full box whose content starts at
+/// `content_start`.
+fn full_box_kind<R: Read + Seek>(r: &mut R, content_start: u64, box_end: u64) -> Result<NodeKind> {
+    r.seek(SeekFrom::Start(content_start))?;
+    let version = r.read_u8()?;
+    let mut f = [0u8; 3];
+    r.read_exact(&mut f)?;
+    let flags = ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32);
+    let data_offset = content_start + 4;
+    Ok(NodeKind::FullBox {
+        version,
+        flags,
+        data_offset,
+        data_len: box_end - data_offset,
+    })
+}
+
+/// Walk every box in `r` up to `file_size` in pre-order, calling `visitor`
+/// with each header, its kind and its nesting depth (0 for top level).
+///
+/// Nothing is collected: containers are reported with an empty child list
+/// and their children follow as separate calls. Returning
+/// [`ControlFlow::Break`] from the visitor stops the walk.
+pub fn visit_boxes<R, F>(r: &mut R, file_size: u64, mut visitor: F) -> Result<()>
+where
+    R: Read + Seek,
+    F: FnMut(&BoxHeader, &NodeKind, usize) -> ControlFlow<()>,
+{
+    let opts = ParseOptions::default();
+    visit_level(r, file_size, 0, &opts, &mut visitor).map(|_| ())
+}
+
+fn visit_level<R, F>(
+    r: &mut R,
+    parent_end: u64,
+    depth: usize,
+    opts: &ParseOptions,
+    visitor: &mut F,
+) -> Result<ControlFlow<()>>
+where
+    R: Read + Seek,
+    F: FnMut(&BoxHeader, &NodeKind, usize) -> ControlFlow<()>,
+{
+    while let Some(next) = next_box(r, parent_end, depth, opts, &mut Anomalies::fail())? {
+        match next.body {
+            Body::Container(start) => {
+                if visitor(&next.hdr, &NodeKind::Container(Vec::new()), depth).is_break() {
+                    return Ok(ControlFlow::Break(()));
                 }
-            } else {
-                NodeKind::Leaf {
-                    data_offset,
-                    data_len,
+                r.seek(SeekFrom::Start(start))?;
+                if visit_level(r, next.end, depth + 1, opts, visitor)?.is_break() {
+                    return Ok(ControlFlow::Break(()));
                 }
             }
-        };
+            Body::Payload(kind) => {
+                if visitor(&next.hdr, &kind, depth).is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
+            }
+        }
+        r.seek(SeekFrom::Start(next.end))?;
+    }
+    Ok(ControlFlow::Continue(()))
+}
+
+/// Box types are printable ASCII in practice, apart from the `©` (0xA9)
+/// that starts iTunes metadata item types.
+fn plausible_type(typ: &[u8; 4]) -> bool {
+    typ.iter()
+        .all(|&c| c.is_ascii_graphic() || c == b' ' || c == 0xA9)
+}
+
+/// Warning for a box of size 0 ("extends to the end of the file") below the
+/// top level, where it can only be read as running to the end of its parent.
+pub(crate) const ZERO_SIZE_NESTED: &str =
+    "size 0 is only valid for a top-level box; treating it as running to the end of its parent";
 
-        // Skip to end of box
-        r.seek(SeekFrom::Start(box_end))?;
-        kids.push(BoxRef { hdr: h, kind });
+/// Offset of the first plausible box header at or after `from` that fits
+/// before `end`: a 32-bit size of at least 8 followed by a plausible type.
+fn find_next_box<R: Read + Seek>(r: &mut R, from: u64, end: u64) -> std::io::Result<Option<u64>> {
+    const WINDOW: u64 = 64 * 1024;
+
+    let mut start = from;
+    while start + 8 <= end {
+        let len = WINDOW.min(end - start);
+        let buf = crate::util::read_slice(r, start, len)?;
+        for (i, w) in buf.windows(8).enumerate() {
+            let at = start + i as u64;
+            let size = u32::from_be_bytes([w[0], w[1], w[2], w[3]]) as u64;
+            if size >= 8 && at + size <= end && plausible_type(&[w[4], w[5], w[6], w[7]]) {
+                return Ok(Some(at));
+            }
+        }
+        if start + len >= end {
+            break;
+        }
+        // Overlap windows so a header straddling the boundary is still seen.
+        start += len - 7;
+    }
+    Ok(None)
+}
+
+/// Like [`parse_children`], but anomalies are recorded in `warnings` instead of
+/// aborting, following `opts` (see [`ParseOptions`]).
+///
+/// `depth` is the nesting depth of the boxes being parsed (0 for top level).
+/// Errors are returned for I/O failures, and for truncated boxes when
+/// `opts.lenient_truncation` is off.
+pub fn parse_children_opts<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+    depth: usize,
+    opts: &ParseOptions,
+    warnings: &mut Vec<Warning>,
+) -> anyhow::Result<Vec<BoxRef>> {
+    let mut anomalies = Anomalies {
+        warnings: Some(warnings),
+        errors: None,
+        fatal_headers: false,
+    };
+    Ok(parse_level(
+        r,
+        parent_end,
+        depth,
+        opts,
+        &mut anomalies,
+        true,
+    )?)
+}
+
+fn leaf_kind(h: &BoxHeader, data_offset: u64, box_end: u64) -> NodeKind {
+    let data_len = box_end.saturating_sub(data_offset);
+    if &h.typ.0 == b"uuid" {
+        NodeKind::Unknown {
+            data_offset,
+            data_len,
+        }
+    } else {
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        }
+    }
+}
+
+/// Offset of the first child of a container box.
+///
+/// Full-box containers such as `meta` carry a version/flags word before their
+/// children, which must be skipped.
+pub fn container_content_start(h: &BoxHeader) -> u64 {
+    let start = h.start + h.header_size;
+    if is_full_box(h) { start + 4 } else { start }
+}
+
+/// Like [`container_content_start`], but peeks at the payload to tell a
+/// QuickTime `meta`, which has no version/flags word, from an ISO one.
+pub fn container_children_start<R: Read + Seek>(r: &mut R, h: &BoxHeader) -> std::io::Result<u64> {
+    let start = h.start + h.header_size;
+    let end = if h.size == 0 {
+        u64::MAX
+    } else {
+        h.start.saturating_add(h.size)
+    };
+    if &h.typ.0 == b"meta" && is_quicktime_meta(r, start, end)? {
+        return Ok(start);
+    }
+    Ok(container_content_start(h))
+}
+
+/// Whether the bytes from `pos` to `end` are the 4 zero bytes QuickTime uses
+/// to terminate a list of boxes.
+fn is_zero_terminator<R: Read + Seek>(r: &mut R, pos: u64, end: u64) -> std::io::Result<bool> {
+    if end - pos != 4 {
+        return Ok(false);
+    }
+    r.seek(SeekFrom::Start(pos))?;
+    let mut word = [0u8; 4];
+    r.read_exact(&mut word)?;
+    Ok(word == [0; 4])
+}
+
+/// A QuickTime `meta` starts straight with its first child (normally `hdlr`),
+/// where an ISO one has a zero version/flags word first. Treat it as
+/// QuickTime-style when that word is instead a child size that fits in the
+/// box and is followed by a plausible type.
+fn is_quicktime_meta<R: Read + Seek>(
+    r: &mut R,
+    content_start: u64,
+    box_end: u64,
+) -> std::io::Result<bool> {
+    r.seek(SeekFrom::Start(content_start))?;
+    let mut head = [0u8; 8];
+    match r.read_exact(&mut head) {
+        Ok(()) => {
+            let size = u32::from_be_bytes([head[0], head[1], head[2], head[3]]) as u64;
+            Ok(size >= 8
+                && content_start + size <= box_end
+                && plausible_type(&[head[4], head[5], head[6], head[7]]))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
     }
-    Ok(kids)
 }
 
 // Known containers from ISOBMFF / MP4
@@ -1,6 +1,6 @@
 use crate::boxes::{BoxHeader, BoxKey, FourCC};
 use byteorder::{BigEndian, ReadBytesExt};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{Cursor, Read};
 
 /// A value returned from a box decoder.
@@ -16,6 +16,8 @@ pub enum BoxValue {
 /// Structured data for sample table boxes
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum StructuredData {
+    /// File Type Box (ftyp) or Segment Type Box (styp)
+    FileType(FtypData),
     /// Sample Description Box (stsd)
     SampleDescription(StsdData),
     /// Decoding Time-to-Sample Box (stts)
@@ -36,8 +38,170 @@ pub enum StructuredData {
     MediaHeader(MdhdData),
     /// Handler Reference Box (hdlr)
     HandlerReference(HdlrData),
+    /// Video Media Header Box (vmhd)
+    VideoMediaHeader(VmhdData),
+    /// Sound Media Header Box (smhd)
+    SoundMediaHeader(SmhdData),
+    /// Null Media Header Box (nmhd)
+    NullMediaHeader(NmhdData),
+    /// Movie Header Box (mvhd)
+    MovieHeader(MvhdData),
     /// Track Header Box (tkhd)
     TrackHeader(TkhdData),
+    /// Dolby Vision Configuration Box (dvcC / dvvC / dvwC)
+    DolbyVisionConfiguration(DolbyVisionConfig),
+    /// Track Reference Type Box (vdep, hint, cdsc, chap, ...)
+    TrackReference(TrackReferenceData),
+    /// Metadata Item Keys Box (keys)
+    MetadataKeys(KeysData),
+    /// Metadata Item List Box (ilst)
+    Metadata(IlstData),
+    /// Meta Box (meta), classified by handler and parent
+    Meta(MetaData),
+    /// Data Reference Box (dref)
+    DataReference(DrefData),
+    /// Track aperture dimensions (clef / prof / enof inside QuickTime tapt)
+    TrackAperture(TrackApertureData),
+    /// Track Fragment Header Box (tfhd)
+    TrackFragmentHeader(TfhdData),
+    /// Track Fragment Run Box (trun)
+    TrackRun(TrunData),
+    /// Edit List Box (elst)
+    EditList(ElstData),
+    /// Track Fragment Base Media Decode Time Box (tfdt)
+    TrackFragmentDecodeTime(TfdtData),
+    /// Movie Extends Header Box (mehd)
+    MovieExtendsHeader(MehdData),
+    /// Track Extends Box (trex)
+    TrackExtends(TrexData),
+    /// Track Fragment Random Access Box (tfra)
+    TrackFragmentRandomAccess(TfraData),
+    /// Movie Fragment Random Access Offset Box (mfro)
+    MovieFragmentRandomAccessOffset(MfroData),
+    /// Segment Index Box (sidx)
+    SegmentIndex(SidxData),
+    /// QuickTime / CoreAudio channel layout (chan)
+    ChannelLayout(ChannelLayout),
+    /// Elementary Stream Descriptor Box (esds)
+    ElementaryStreamDescriptor(EsdsData),
+    /// AVC Decoder Configuration Record (avcC)
+    AvcConfig(AvcConfig),
+    /// HEVC Decoder Configuration Record (hvcC)
+    HevcConfig(HevcConfig),
+    /// MIME Box (mime)
+    Mime(MimeData),
+    /// Item Information Box (iinf) with its infe entries
+    ItemInfo(IinfData),
+    /// Item Location Box (iloc)
+    ItemLocation(IlocData),
+    /// Item Reference Box (iref)
+    ItemReference(IrefData),
+    /// `grid` derived-image item payload (not a box; see [`crate::heif::decode_item`])
+    ImageGrid(ImageGridData),
+    /// Sample Group Description Box (sgpd)
+    SampleGroupDescription(SgpdData),
+    /// Sample-to-Group Box (sbgp)
+    SampleToGroup(SbgpData),
+    /// Track Encryption Box (tenc)
+    TrackEncryption(TencData),
+    /// Sample Encryption Box (senc)
+    SampleEncryption(SencData),
+    /// Sample Auxiliary Information Sizes Box (saiz)
+    SampleAuxInfoSizes(SaizData),
+    /// Sample Auxiliary Information Offsets Box (saio)
+    SampleAuxInfoOffsets(SaioData),
+    /// Protection System Specific Header Box (pssh)
+    ProtectionSystemHeader(PsshData),
+}
+
+impl StructuredData {
+    /// A short human-readable summary, used as the box's `decoded` text when
+    /// the raw structure is too noisy to read at a glance.
+    pub fn summary(&self) -> Option<String> {
+        match self {
+            StructuredData::FileType(ftyp) => Some(format!(
+                "{} ({})",
+                ftyp.major_brand,
+                ftyp.compatible_brands.join(", ")
+            )),
+            StructuredData::AvcConfig(avc) => Some(avc.profile_level()),
+            StructuredData::HevcConfig(hevc) => Some(hevc.profile_level_tier()),
+            StructuredData::MovieHeader(mvhd) => {
+                let mut text = format!("timescale={} duration={}", mvhd.timescale, mvhd.duration);
+                if !mvhd.matrix.is_identity() {
+                    text.push_str(&format!(" rotation={}", mvhd.matrix.rotation()));
+                }
+                Some(text)
+            }
+            StructuredData::TrackHeader(tkhd) => Some(format!(
+                "{}x{}, layer {}",
+                tkhd.width, tkhd.height, tkhd.layer
+            )),
+            StructuredData::HandlerReference(hdlr) if hdlr.name.is_empty() => {
+                Some(hdlr.handler_type.clone())
+            }
+            StructuredData::HandlerReference(hdlr) => {
+                Some(format!("{} \"{}\"", hdlr.handler_type, hdlr.name))
+            }
+            StructuredData::DataReference(dref) => Some(
+                dref.entries
+                    .iter()
+                    .map(|e| {
+                        let typ = e.entry_type.trim_end();
+                        match (e.self_contained, &e.location) {
+                            (true, _) => format!("{} self-contained", typ),
+                            (false, Some(loc)) => format!("{} external \"{}\"", typ, loc),
+                            (false, None) => format!("{} external", typ),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            StructuredData::VideoMediaHeader(vmhd) => Some(format!(
+                "graphicsmode={} opcolor=({}, {}, {})",
+                vmhd.graphics_mode, vmhd.opcolor[0], vmhd.opcolor[1], vmhd.opcolor[2]
+            )),
+            StructuredData::SoundMediaHeader(smhd) => Some(format!("balance={}", smhd.balance)),
+            StructuredData::SegmentIndex(sidx) => Some(format!(
+                "timescale={} earliest_presentation_time={} first_offset={} references={}",
+                sidx.timescale,
+                sidx.earliest_presentation_time,
+                sidx.first_offset,
+                sidx.references.len()
+            )),
+            StructuredData::TrackExtends(trex) => Some(format!(
+                "track_id={} duration={} size={} flags={:#010x}",
+                trex.track_id,
+                trex.default_sample_duration,
+                trex.default_sample_size,
+                trex.default_sample_flags
+            )),
+            StructuredData::TrackFragmentDecodeTime(tfdt) => {
+                let mut text = format!("base_media_decode_time={}", tfdt.base_media_decode_time);
+                if let Some(seconds) = tfdt.base_media_decode_time_seconds {
+                    text.push_str(&format!(" ({:.3}s)", seconds));
+                }
+                Some(text)
+            }
+            StructuredData::ProtectionSystemHeader(pssh) => {
+                let system = pssh.system_name.as_deref().unwrap_or(&pssh.system_id);
+                let kids = match pssh.kids.len() {
+                    1 => "1 KID".to_string(),
+                    n => format!("{} KIDs", n),
+                };
+                Some(format!("{}, {}", system, kids))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// File Type / Segment Type Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FtypData {
+    pub major_brand: String,
+    pub minor_version: u32,
+    pub compatible_brands: Vec<String>,
 }
 
 /// Sample Description Box data
@@ -56,6 +220,101 @@ pub struct SampleEntry {
     pub data_reference_index: u16,
     pub width: Option<u16>,
     pub height: Option<u16>,
+    /// Dolby Vision configuration (dvcC/dvvC/dvwC) found inside the sample entry
+    pub dolby_vision: Option<DolbyVisionConfig>,
+    /// Size in bytes of the NAL unit length prefix (from avcC/hvcC lengthSizeMinusOne + 1)
+    pub nal_length_size: Option<u8>,
+    /// Channel layout (chan) found inside a sound sample entry
+    pub channel_layout: Option<ChannelLayout>,
+    /// MPEG-4 elementary stream descriptor (esds) found inside the sample entry
+    pub esds: Option<EsdsData>,
+    /// AVC decoder configuration (avcC) found inside the sample entry
+    pub avc_config: Option<AvcConfig>,
+    /// HEVC decoder configuration (hvcC) found inside the sample entry
+    pub hevc_config: Option<HevcConfig>,
+    /// Fields of the visual or audio sample entry itself
+    pub details: SampleEntryDetails,
+}
+
+/// The media-specific part of a sample entry.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SampleEntryDetails {
+    Visual(VisualSampleEntry),
+    Audio(AudioSampleEntry),
+    /// Codec not recognised as audio or video, or entry too short
+    Other,
+}
+
+/// VisualSampleEntry fields (avc1, hvc1, mp4v, ...)
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct VisualSampleEntry {
+    pub width: u16,
+    pub height: u16,
+    /// Pixels per inch from 16.16 fixed point (normally 72)
+    pub horizresolution: f64,
+    pub vertresolution: f64,
+    pub frame_count: u16,
+    /// Encoder name from the Pascal-style 32-byte field; often empty
+    pub compressorname: String,
+    /// 24 for colour without alpha
+    pub depth: u16,
+}
+
+/// AudioSampleEntry fields (mp4a, ac-3, Opus, ...)
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AudioSampleEntry {
+    /// QuickTime sound description version (0 for plain ISO entries)
+    pub qt_version: u16,
+    pub channel_count: u16,
+    /// Bits per sample
+    pub sample_size: u16,
+    /// Sample rate in Hz; from the 16.16 field, or the 64-bit float of a
+    /// QuickTime version 2 entry
+    pub sample_rate: f64,
+}
+
+impl VisualSampleEntry {
+    /// Parse from a sample entry body (after the box header).
+    fn parse(body: &[u8]) -> Option<Self> {
+        let body = body.get(..VISUAL_ENTRY_FIELDS)?;
+        let u16_at = |i: usize| u16::from_be_bytes([body[i], body[i + 1]]);
+        let fixed_16_16 =
+            |i: usize| u32::from_be_bytes(body[i..i + 4].try_into().unwrap()) as f64 / 65536.0;
+        let name_len = (body[42] as usize).min(31);
+        Some(Self {
+            width: u16_at(24),
+            height: u16_at(26),
+            horizresolution: fixed_16_16(28),
+            vertresolution: fixed_16_16(32),
+            frame_count: u16_at(40),
+            compressorname: String::from_utf8_lossy(&body[43..43 + name_len]).to_string(),
+            depth: u16_at(74),
+        })
+    }
+}
+
+impl AudioSampleEntry {
+    /// Parse from a sample entry body (after the box header).
+    fn parse(body: &[u8]) -> Option<Self> {
+        let fields = body.get(..AUDIO_ENTRY_FIELDS)?;
+        let u16_at = |i: usize| u16::from_be_bytes([fields[i], fields[i + 1]]);
+        let mut entry = Self {
+            qt_version: u16_at(8),
+            channel_count: u16_at(16),
+            sample_size: u16_at(18),
+            sample_rate: u32::from_be_bytes(fields[24..28].try_into().unwrap()) as f64 / 65536.0,
+        };
+        // Version 2 moves the rate and channel count into its extension:
+        // sizeOfStructOnly (u32), audioSampleRate (f64), numAudioChannels (u32), ...
+        if entry.qt_version == 2
+            && let Some(ext) = body.get(AUDIO_ENTRY_FIELDS..AUDIO_ENTRY_FIELDS + 16)
+        {
+            entry.sample_rate = f64::from_be_bytes(ext[4..12].try_into().unwrap());
+            entry.channel_count = u32::from_be_bytes(ext[12..16].try_into().unwrap()) as u16;
+        }
+        Some(entry)
+    }
 }
 
 /// Decoding Time-to-Sample Box data
@@ -85,7 +344,8 @@ pub struct CttsData {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CttsEntry {
     pub sample_count: u32,
-    pub sample_offset: i32, // Can be negative in version 1
+    /// Unsigned in version 0, signed (possibly negative) in version 1
+    pub sample_offset: i64,
 }
 
 /// Sample-to-Chunk Box data
@@ -141,6 +401,343 @@ pub struct Co64Data {
     pub chunk_offsets: Vec<u64>,
 }
 
+/// Edit List Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ElstData {
+    pub version: u8,
+    pub flags: u32,
+    pub entry_count: u32,
+    pub entries: Vec<ElstEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ElstEntry {
+    /// Edit duration in movie (mvhd) timescale units
+    pub segment_duration: u64,
+    /// Start of the edit in media timescale units; -1 for an empty edit
+    pub media_time: i64,
+    pub media_rate_integer: i16,
+    pub media_rate_fraction: i16,
+}
+
+/// Sample Group Description Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SgpdData {
+    pub version: u8,
+    pub flags: u32,
+    /// Grouping type shared with the matching sbgp (e.g. "roll", "rap ")
+    pub grouping_type: String,
+    /// Size of every entry, or 0 when each carries its own length (v1+)
+    pub default_length: Option<u32>,
+    /// Group applied to samples not mapped by any sbgp (v2+)
+    pub default_group_description_index: Option<u32>,
+    pub entries: Vec<SampleGroupEntry>,
+}
+
+/// One sample group description entry.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SampleGroupEntry {
+    /// Raw entry payload
+    pub payload: Vec<u8>,
+    /// Decoded `roll` / `prol` roll distance, in samples
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roll_distance: Option<i16>,
+}
+
+/// Sample-to-Group Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SbgpData {
+    pub version: u8,
+    pub flags: u32,
+    pub grouping_type: String,
+    /// Sub-type of the grouping (v1+)
+    pub grouping_type_parameter: Option<u32>,
+    pub entries: Vec<SbgpEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SbgpEntry {
+    pub sample_count: u32,
+    /// 1-based index into the matching sgpd, or 0 for no group
+    pub group_description_index: u32,
+}
+
+/// Track Encryption Box data (CENC default protection parameters)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TencData {
+    pub version: u8,
+    pub flags: u32,
+    /// Encrypted blocks per pattern (v1+, `cens`/`cbcs`)
+    pub default_crypt_byte_block: Option<u8>,
+    /// Clear blocks per pattern (v1+, `cens`/`cbcs`)
+    pub default_skip_byte_block: Option<u8>,
+    pub default_is_protected: u8,
+    /// IV size in `senc` (0, 8 or 16); 0 means a constant IV is used
+    pub default_per_sample_iv_size: u8,
+    /// Default key ID, as 32 hex digits
+    pub default_kid: String,
+    /// Hex constant IV, when protected with a per-sample IV size of 0
+    pub default_constant_iv: Option<String>,
+}
+
+/// Sample Encryption Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SencData {
+    pub version: u8,
+    pub flags: u32,
+    /// IV size used to split the entries: from the track's `tenc` when known,
+    /// otherwise inferred from the box size
+    pub per_sample_iv_size: u8,
+    pub samples: Vec<SencSample>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SencSample {
+    /// Initialization vector, as hex (empty with a constant IV)
+    pub iv: String,
+    /// Clear/protected byte ranges; present when flags has 0x2 set
+    pub subsamples: Vec<SencSubsample>,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct SencSubsample {
+    pub clear_bytes: u16,
+    pub protected_bytes: u32,
+}
+
+/// Protection System Specific Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PsshData {
+    pub version: u8,
+    pub flags: u32,
+    /// DRM system UUID, e.g. "edef8ba9-79d6-4ace-a3c8-27dcd51d21ed"
+    pub system_id: String,
+    /// Name of a well-known system ("Widevine", "PlayReady", ...)
+    pub system_name: Option<String>,
+    /// Key IDs as 32 hex digits (version 1 only)
+    pub kids: Vec<String>,
+    /// Size of the system-specific data
+    pub data_size: u32,
+    /// File offset of the system-specific data
+    pub data_offset: u64,
+}
+
+/// DRM systems by `pssh` system ID.
+const PROTECTION_SYSTEMS: &[([u8; 16], &str)] = &[
+    (
+        [
+            0xed, 0xef, 0x8b, 0xa9, 0x79, 0xd6, 0x4a, 0xce, 0xa3, 0xc8, 0x27, 0xdc, 0xd5, 0x1d,
+            0x21, 0xed,
+        ],
+        "Widevine",
+    ),
+    (
+        [
+            0x9a, 0x04, 0xf0, 0x79, 0x98, 0x40, 0x42, 0x86, 0xab, 0x92, 0xe6, 0x5b, 0xe0, 0x88,
+            0x5f, 0x95,
+        ],
+        "PlayReady",
+    ),
+    (
+        [
+            0x94, 0xce, 0x86, 0xfb, 0x07, 0xff, 0x4f, 0x43, 0xad, 0xb8, 0x93, 0xd2, 0xfa, 0x96,
+            0x8c, 0xa2,
+        ],
+        "FairPlay",
+    ),
+    (
+        [
+            0x10, 0x77, 0xef, 0xec, 0xc0, 0xb2, 0x4d, 0x02, 0xac, 0xe3, 0x3c, 0x1e, 0x52, 0xe2,
+            0xfb, 0x4b,
+        ],
+        "Common (ClearKey)",
+    ),
+    (
+        [
+            0x5e, 0x62, 0x9a, 0xf5, 0x38, 0xda, 0x40, 0x63, 0x89, 0x77, 0x97, 0xff, 0xbd, 0x99,
+            0x02, 0xd4,
+        ],
+        "Marlin",
+    ),
+    (
+        [
+            0xf2, 0x39, 0xe7, 0x69, 0xef, 0xa3, 0x48, 0x50, 0x9c, 0x16, 0xa9, 0x03, 0xc6, 0x93,
+            0x2e, 0xfb,
+        ],
+        "Adobe Primetime",
+    ),
+];
+
+/// Sample Auxiliary Information Sizes Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SaizData {
+    pub version: u8,
+    pub flags: u32,
+    /// Present when flags has 0x1 set (e.g. "cenc")
+    pub aux_info_type: Option<String>,
+    pub aux_info_type_parameter: Option<u32>,
+    /// Size of every sample's info, or 0 when listed per sample
+    pub default_sample_info_size: u8,
+    pub sample_count: u32,
+    pub sample_info_sizes: Vec<u8>,
+}
+
+/// Sample Auxiliary Information Offsets Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SaioData {
+    pub version: u8,
+    pub flags: u32,
+    pub aux_info_type: Option<String>,
+    pub aux_info_type_parameter: Option<u32>,
+    /// Offsets of the aux info (relative to the moof in fragments, else absolute)
+    pub offsets: Vec<u64>,
+}
+
+/// Track Fragment Header Box data
+///
+/// Optional fields are `None` when the corresponding tf_flags bit is clear.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TfhdData {
+    pub version: u8,
+    pub flags: u32,
+    pub track_id: u32,
+    pub base_data_offset: Option<u64>,
+    pub sample_description_index: Option<u32>,
+    pub default_sample_duration: Option<u32>,
+    pub default_sample_size: Option<u32>,
+    pub default_sample_flags: Option<u32>,
+    /// default-base-is-moof (0x020000): offsets are relative to the enclosing moof
+    pub default_base_is_moof: bool,
+}
+
+/// Track Fragment Base Media Decode Time Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TfdtData {
+    pub version: u8,
+    pub flags: u32,
+    /// Decode time of the fragment's first sample, in media timescale units
+    pub base_media_decode_time: u64,
+    /// `base_media_decode_time` in seconds, when the track's timescale was
+    /// known from an earlier `mdhd`
+    pub base_media_decode_time_seconds: Option<f64>,
+}
+
+impl TfdtData {
+    /// The fragment's start on the track timeline, in seconds, for a track
+    /// with the given media timescale (from the init segment's `mdhd`).
+    /// `None` for a zero timescale.
+    pub fn seconds(&self, timescale: u32) -> Option<f64> {
+        (timescale > 0).then(|| self.base_media_decode_time as f64 / timescale as f64)
+    }
+}
+
+/// Movie Extends Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MehdData {
+    pub version: u8,
+    pub flags: u32,
+    /// Duration of the whole fragmented movie, in the `mvhd` timescale
+    pub fragment_duration: u64,
+}
+
+/// Track Extends Box data: per-track defaults for movie fragments
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrexData {
+    pub version: u8,
+    pub flags: u32,
+    pub track_id: u32,
+    pub default_sample_description_index: u32,
+    pub default_sample_duration: u32,
+    pub default_sample_size: u32,
+    pub default_sample_flags: u32,
+}
+
+/// Track Fragment Random Access Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TfraData {
+    pub version: u8,
+    pub flags: u32,
+    pub track_id: u32,
+    /// Byte widths of traf_number, trun_number and sample_number (1 to 4),
+    /// i.e. the box's `length_size_of_*` fields plus one
+    pub traf_number_size: u8,
+    pub trun_number_size: u8,
+    pub sample_number_size: u8,
+    pub entries: Vec<TfraEntry>,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct TfraEntry {
+    /// Presentation time of the sync sample, in track timescale units
+    pub time: u64,
+    /// File offset of the moof holding the sample
+    pub moof_offset: u64,
+    /// 1-based traf, trun and sample numbers locating the sample in that moof
+    pub traf_number: u32,
+    pub trun_number: u32,
+    pub sample_number: u32,
+}
+
+/// Movie Fragment Random Access Offset Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MfroData {
+    pub version: u8,
+    pub flags: u32,
+    /// Size of the enclosing mfra box
+    pub size: u32,
+}
+
+/// Segment Index Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SidxData {
+    pub version: u8,
+    pub flags: u32,
+    /// Track (stream) the index describes
+    pub reference_id: u32,
+    pub timescale: u32,
+    /// In `timescale` units
+    pub earliest_presentation_time: u64,
+    /// Bytes from the end of this box to the first referenced byte
+    pub first_offset: u64,
+    pub references: Vec<SidxReference>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SidxReference {
+    /// 0 = media (a moof and its data), 1 = another sidx
+    pub reference_type: u8,
+    /// Size in bytes of the referenced material; references follow each
+    /// other from `first_offset`
+    pub referenced_size: u32,
+    /// In `timescale` units
+    pub subsegment_duration: u32,
+    pub starts_with_sap: bool,
+    pub sap_type: u8,
+    pub sap_delta_time: u32,
+}
+
+/// Track Fragment Run Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrunData {
+    pub version: u8,
+    pub flags: u32,
+    pub sample_count: u32,
+    /// Added to the fragment's base data offset to find the first sample
+    pub data_offset: Option<i32>,
+    pub first_sample_flags: Option<u32>,
+    pub samples: Vec<TrunSample>,
+}
+
+/// Per-sample trun fields; `None` when the run does not carry that field.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrunSample {
+    pub duration: Option<u32>,
+    pub size: Option<u32>,
+    pub flags: Option<u32>,
+    /// Unsigned in version 0, signed in version 1
+    pub composition_time_offset: Option<i64>,
+}
+
 /// Media Header Box data
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MdhdData {
@@ -158,10 +755,66 @@ pub struct MdhdData {
 pub struct HdlrData {
     pub version: u8,
     pub flags: u32,
+    /// QuickTime component type ("mhlr" media / "dhlr" data handler); ISO
+    /// files leave this pre_defined field zero
+    pub component_type: Option<String>,
     pub handler_type: String,
+    /// QuickTime component manufacturer (e.g. "appl"); zero in ISO files
+    pub component_manufacturer: Option<String>,
+    /// Handler name written by the muxer, e.g. "VideoHandler" or
+    /// "Core Media Video"
     pub name: String,
 }
 
+/// Video Media Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct VmhdData {
+    pub version: u8,
+    /// Always 1 in ISO files
+    pub flags: u32,
+    /// Composition mode for this track (0 = copy)
+    pub graphics_mode: u16,
+    /// Red, green and blue used by graphics modes that need a colour
+    pub opcolor: [u16; 3],
+}
+
+/// Sound Media Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SmhdData {
+    pub version: u8,
+    pub flags: u32,
+    /// Stereo balance from 8.8 fixed point (-1.0 full left, 1.0 full right)
+    pub balance: f32,
+}
+
+/// Null Media Header Box data (no fields besides version/flags)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct NmhdData {
+    pub version: u8,
+    pub flags: u32,
+}
+
+/// Movie Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MvhdData {
+    pub version: u8,
+    pub flags: u32,
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub timescale: u32,
+    /// Duration of the longest track, in `timescale` units
+    pub duration: u64,
+    /// `duration / timescale` (0 when the timescale is 0)
+    pub duration_seconds: f64,
+    /// Preferred playback rate from 16.16 fixed point (1.0 = normal)
+    pub rate: f64,
+    /// Preferred volume from 8.8 fixed point (1.0 = full)
+    pub volume: f32,
+    pub matrix: Matrix,
+    /// Must be greater than every track_id in the movie
+    pub next_track_id: u32,
+}
+
 /// Track Header Box data
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TkhdData {
@@ -169,345 +822,2748 @@ pub struct TkhdData {
     pub flags: u32,
     pub track_id: u32,
     pub duration: u64,
-    pub width: f32,
-    pub height: f32,
+    /// Front-to-back ordering of visual tracks; lower is closer to the viewer
+    pub layer: i16,
+    /// Tracks sharing a non-zero group are alternatives to each other
+    pub alternate_group: i16,
+    /// Audio volume from 8.8 fixed point (1.0 = full volume, 0 for non-audio tracks)
+    pub volume: f32,
+    pub matrix: Matrix,
+    /// Presentation width in pixels, from 16.16 fixed point
+    pub width: f64,
+    /// Presentation height in pixels, from 16.16 fixed point
+    pub height: f64,
+    /// track_enabled (flags & 0x1): a disabled track is ignored by players
+    pub enabled: bool,
+    /// track_in_movie (flags & 0x2): used in the presentation
+    pub in_movie: bool,
+    /// track_in_preview (flags & 0x4): used when previewing the presentation
+    pub in_preview: bool,
 }
 
-/// Trait for custom box decoders.
+/// The 3x3 transformation matrix from mvhd/tkhd.
 ///
-/// A decoder is responsible for interpreting the payload of a specific box
-/// (identified by a [`BoxKey`]) and returning a [`BoxValue`].
-pub trait BoxDecoder: Send + Sync {
-    fn decode(
-        &self,
-        r: &mut dyn Read,
-        hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
-    ) -> anyhow::Result<BoxValue>;
+/// Stored row-major as `{a, b, u, c, d, v, x, y, w}`; `u`, `v` and `w` are 2.30
+/// fixed point, everything else is 16.16. A point `(p, q)` maps to
+/// `(a*p + c*q + x, b*p + d*q + y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Matrix {
+    pub values: [i32; 9],
 }
 
-/// Registry of decoders keyed by `BoxKey` (4CC or UUID).
-///
-/// The registry is immutable once constructed; use [`Registry::with_decoder`]
-/// to build it fluently.
-pub struct Registry {
-    map: HashMap<BoxKey, BoxDecoderEntry>,
-}
+impl Matrix {
+    pub const IDENTITY: Matrix = Matrix {
+        values: [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000],
+    };
+
+    /// Parse the 36-byte big-endian matrix field.
+    pub fn from_bytes(buf: &[u8]) -> Option<Matrix> {
+        let buf = buf.get(..36)?;
+        let mut values = [0i32; 9];
+        for (v, c) in values.iter_mut().zip(buf.chunks_exact(4)) {
+            *v = i32::from_be_bytes(c.try_into().unwrap());
+        }
+        Some(Matrix { values })
+    }
 
-struct BoxDecoderEntry {
-    inner: Box<dyn BoxDecoder>,
-    _name: String,
-}
+    fn fixed_16_16(v: i32) -> f64 {
+        v as f64 / 65536.0
+    }
 
-impl Registry {
-    /// Create an empty registry.
-    pub fn new() -> Self {
-        Self {
-            map: HashMap::new(),
-        }
+    fn fixed_2_30(v: i32) -> f64 {
+        v as f64 / (1u32 << 30) as f64
     }
 
-    /// Return a new registry with the given decoder added.
-    ///
-    /// `name` is human-readable and used only for debugging / logging.
-    pub fn with_decoder(mut self, key: BoxKey, name: &str, dec: Box<dyn BoxDecoder>) -> Self {
-        self.map.insert(
-            key,
-            BoxDecoderEntry {
-                inner: dec,
-                _name: name.to_string(),
-            },
-        );
-        self
+    pub fn a(&self) -> f64 {
+        Self::fixed_16_16(self.values[0])
+    }
+    pub fn b(&self) -> f64 {
+        Self::fixed_16_16(self.values[1])
+    }
+    pub fn u(&self) -> f64 {
+        Self::fixed_2_30(self.values[2])
+    }
+    pub fn c(&self) -> f64 {
+        Self::fixed_16_16(self.values[3])
+    }
+    pub fn d(&self) -> f64 {
+        Self::fixed_16_16(self.values[4])
+    }
+    pub fn v(&self) -> f64 {
+        Self::fixed_2_30(self.values[5])
+    }
+    pub fn x(&self) -> f64 {
+        Self::fixed_16_16(self.values[6])
+    }
+    pub fn y(&self) -> f64 {
+        Self::fixed_16_16(self.values[7])
+    }
+    pub fn w(&self) -> f64 {
+        Self::fixed_2_30(self.values[8])
     }
 
-    /// Try to decode the payload of a box using a registered decoder.
-    ///
-    /// Returns `None` if no decoder exists for the given key.
-    pub fn decode(
-        &self,
-        key: &BoxKey,
-        r: &mut dyn Read,
-        hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
-    ) -> Option<anyhow::Result<BoxValue>> {
-        self.map
-            .get(key)
-            .map(|d| d.inner.decode(r, hdr, version, flags))
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
     }
-}
 
-impl Default for Registry {
-    fn default() -> Self {
-        Self::new()
+    /// Clockwise display rotation in degrees, in `[0, 360)`.
+    pub fn rotation(&self) -> f64 {
+        let deg = self.b().atan2(self.a()).to_degrees();
+        if deg < 0.0 { deg + 360.0 } else { deg }
     }
 }
 
-// ---------- Helpers ----------
-
-fn read_all(r: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
-    let mut buf = Vec::new();
-    r.read_to_end(&mut buf)?;
-    Ok(buf)
+/// AVC Decoder Configuration Record (avcC)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AvcConfig {
+    pub configuration_version: u8,
+    pub profile_indication: u8,
+    pub profile_compatibility: u8,
+    pub level_indication: u8,
+    pub length_size_minus_one: u8,
+    /// Sequence parameter set NAL units, as ranges within the avcC payload
+    pub sps: Vec<NalUnitRange>,
+    /// Picture parameter set NAL units, as ranges within the avcC payload
+    pub pps: Vec<NalUnitRange>,
+    /// High profile extension (profile_idc 100, 110, 122, 144), when present
+    pub chroma_format: Option<u8>,
+    pub bit_depth_luma_minus8: Option<u8>,
+    pub bit_depth_chroma_minus8: Option<u8>,
+    pub sps_ext: Vec<NalUnitRange>,
 }
 
-fn lang_from_u16(code: u16) -> String {
-    if code == 0 {
-        return "und".to_string();
-    }
-    let c1 = ((code >> 10) & 0x1F) as u8 + 0x60;
-    let c2 = ((code >> 5) & 0x1F) as u8 + 0x60;
-    let c3 = (code & 0x1F) as u8 + 0x60;
-    format!("{}{}{}", c1 as char, c2 as char, c3 as char,)
+/// A NAL unit stored inside a decoder configuration record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NalUnitRange {
+    /// Offset of the NAL unit from the start of the box payload
+    pub offset: u32,
+    pub size: u16,
 }
 
-// ---------- Decoders ----------
+impl AvcConfig {
+    /// Parse an `avcC` payload.
+    pub fn parse(buf: &[u8]) -> anyhow::Result<Self> {
+        if buf.len() < 6 {
+            anyhow::bail!("avcC too short ({} bytes)", buf.len());
+        }
+        let mut pos = 5;
+
+        // Each NAL unit is a u16 length followed by its bytes.
+        let nal_units = |pos: &mut usize, count: usize| -> anyhow::Result<Vec<NalUnitRange>> {
+            let mut units = Vec::with_capacity(count);
+            for _ in 0..count {
+                let len_bytes = buf
+                    .get(*pos..*pos + 2)
+                    .ok_or_else(|| anyhow::anyhow!("avcC truncated in NAL unit list"))?;
+                let size = u16::from_be_bytes([len_bytes[0], len_bytes[1]]);
+                let offset = *pos + 2;
+                if offset + size as usize > buf.len() {
+                    anyhow::bail!("avcC NAL unit runs past the end of the box");
+                }
+                units.push(NalUnitRange {
+                    offset: offset as u32,
+                    size,
+                });
+                *pos = offset + size as usize;
+            }
+            Ok(units)
+        };
 
-// ftyp: major + minor + compatible brands
-pub struct FtypDecoder;
+        // Upper 3 bits of numOfSequenceParameterSets are reserved (all ones).
+        let sps_count = (buf[pos] & 0x1F) as usize;
+        pos += 1;
+        let sps = nal_units(&mut pos, sps_count)?;
+        let pps_count = *buf
+            .get(pos)
+            .ok_or_else(|| anyhow::anyhow!("avcC truncated before PPS count"))?
+            as usize;
+        pos += 1;
+        let pps = nal_units(&mut pos, pps_count)?;
+
+        let profile = buf[1];
+        let mut config = Self {
+            configuration_version: buf[0],
+            profile_indication: profile,
+            profile_compatibility: buf[2],
+            level_indication: buf[3],
+            // Upper 6 bits are reserved (all ones).
+            length_size_minus_one: buf[4] & 0x03,
+            sps,
+            pps,
+            chroma_format: None,
+            bit_depth_luma_minus8: None,
+            bit_depth_chroma_minus8: None,
+            sps_ext: Vec::new(),
+        };
 
-impl BoxDecoder for FtypDecoder {
+        // Many muxers omit the extension even for high profiles, so only read
+        // it when the bytes are there.
+        if matches!(profile, 100 | 110 | 122 | 144) && buf.len() >= pos + 4 {
+            config.chroma_format = Some(buf[pos] & 0x03);
+            config.bit_depth_luma_minus8 = Some(buf[pos + 1] & 0x07);
+            config.bit_depth_chroma_minus8 = Some(buf[pos + 2] & 0x07);
+            let ext_count = buf[pos + 3] as usize;
+            pos += 4;
+            config.sps_ext = nal_units(&mut pos, ext_count)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Profile name from profile_idc (and constraint_set1 for Constrained Baseline).
+    pub fn profile_name(&self) -> String {
+        match self.profile_indication {
+            66 if self.profile_compatibility & 0x40 != 0 => "Constrained Baseline".to_string(),
+            66 => "Baseline".to_string(),
+            77 => "Main".to_string(),
+            88 => "Extended".to_string(),
+            100 => "High".to_string(),
+            110 => "High 10".to_string(),
+            122 => "High 4:2:2".to_string(),
+            244 => "High 4:4:4 Predictive".to_string(),
+            44 => "CAVLC 4:4:4 Intra".to_string(),
+            other => format!("profile {}", other),
+        }
+    }
+
+    /// Profile and level in the usual "High@L4.0" form.
+    pub fn profile_level(&self) -> String {
+        format!(
+            "{}@L{}.{}",
+            self.profile_name(),
+            self.level_indication / 10,
+            self.level_indication % 10
+        )
+    }
+}
+
+/// HEVC Decoder Configuration Record (hvcC)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HevcConfig {
+    pub configuration_version: u8,
+    pub general_profile_space: u8,
+    /// false = Main tier, true = High tier
+    pub general_tier_flag: bool,
+    pub general_profile_idc: u8,
+    pub general_profile_compatibility_flags: u32,
+    /// The 48-bit general_constraint_indicator_flags
+    pub general_constraint_indicator_flags: u64,
+    /// Level times 30 (e.g. 153 = level 5.1)
+    pub general_level_idc: u8,
+    pub min_spatial_segmentation_idc: u16,
+    pub parallelism_type: u8,
+    /// 0 = monochrome, 1 = 4:2:0, 2 = 4:2:2, 3 = 4:4:4
+    pub chroma_format: u8,
+    pub bit_depth_luma_minus8: u8,
+    pub bit_depth_chroma_minus8: u8,
+    pub avg_frame_rate: u16,
+    pub constant_frame_rate: u8,
+    pub num_temporal_layers: u8,
+    pub temporal_id_nested: bool,
+    pub length_size_minus_one: u8,
+    /// Parameter set arrays (VPS/SPS/PPS/SEI), in box order
+    pub arrays: Vec<HevcNalArray>,
+    /// The box ended in the middle of the NAL unit arrays; `arrays` holds
+    /// what could be read
+    pub truncated: bool,
+}
+
+/// One NAL unit array of an `hvcC` record.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HevcNalArray {
+    pub array_completeness: bool,
+    /// 32 = VPS, 33 = SPS, 34 = PPS, 39/40 = SEI
+    pub nal_unit_type: u8,
+    /// numNalus as declared, which may exceed `nal_units.len()` when truncated
+    pub count: u16,
+    pub nal_units: Vec<NalUnitRange>,
+}
+
+impl HevcConfig {
+    /// Fixed-size part of the record, up to and including numOfArrays.
+    const HEADER_LEN: usize = 23;
+
+    /// Parse an `hvcC` payload.
+    ///
+    /// A record cut off inside the NAL unit arrays is not an error: the arrays
+    /// read so far are kept and `truncated` is set.
+    pub fn parse(buf: &[u8]) -> anyhow::Result<Self> {
+        if buf.len() < Self::HEADER_LEN {
+            anyhow::bail!("hvcC too short ({} bytes)", buf.len());
+        }
+        let mut constraint = [0u8; 8];
+        constraint[2..].copy_from_slice(&buf[6..12]);
+
+        let mut config = Self {
+            configuration_version: buf[0],
+            general_profile_space: buf[1] >> 6,
+            general_tier_flag: buf[1] & 0x20 != 0,
+            general_profile_idc: buf[1] & 0x1F,
+            general_profile_compatibility_flags: u32::from_be_bytes(buf[2..6].try_into()?),
+            general_constraint_indicator_flags: u64::from_be_bytes(constraint),
+            general_level_idc: buf[12],
+            // Reserved bits above each of these fields are all ones.
+            min_spatial_segmentation_idc: u16::from_be_bytes([buf[13], buf[14]]) & 0x0FFF,
+            parallelism_type: buf[15] & 0x03,
+            chroma_format: buf[16] & 0x03,
+            bit_depth_luma_minus8: buf[17] & 0x07,
+            bit_depth_chroma_minus8: buf[18] & 0x07,
+            avg_frame_rate: u16::from_be_bytes([buf[19], buf[20]]),
+            constant_frame_rate: buf[21] >> 6,
+            num_temporal_layers: (buf[21] >> 3) & 0x07,
+            temporal_id_nested: buf[21] & 0x04 != 0,
+            length_size_minus_one: buf[21] & 0x03,
+            arrays: Vec::new(),
+            truncated: false,
+        };
+
+        let num_arrays = buf[22];
+        let mut pos = Self::HEADER_LEN;
+        'arrays: for _ in 0..num_arrays {
+            let Some(head) = buf.get(pos..pos + 3) else {
+                config.truncated = true;
+                break;
+            };
+            let mut array = HevcNalArray {
+                array_completeness: head[0] & 0x80 != 0,
+                nal_unit_type: head[0] & 0x3F,
+                count: u16::from_be_bytes([head[1], head[2]]),
+                nal_units: Vec::new(),
+            };
+            pos += 3;
+            for _ in 0..array.count {
+                let Some(len) = buf.get(pos..pos + 2) else {
+                    config.truncated = true;
+                    config.arrays.push(array);
+                    break 'arrays;
+                };
+                let size = u16::from_be_bytes([len[0], len[1]]);
+                let offset = pos + 2;
+                if offset + size as usize > buf.len() {
+                    config.truncated = true;
+                    config.arrays.push(array);
+                    break 'arrays;
+                }
+                array.nal_units.push(NalUnitRange {
+                    offset: offset as u32,
+                    size,
+                });
+                pos = offset + size as usize;
+            }
+            config.arrays.push(array);
+        }
+
+        Ok(config)
+    }
+
+    /// Profile name from general_profile_idc.
+    pub fn profile_name(&self) -> String {
+        match self.general_profile_idc {
+            1 => "Main".to_string(),
+            2 => "Main10".to_string(),
+            3 => "Main Still Picture".to_string(),
+            4 => "RExt".to_string(),
+            5 => "High Throughput".to_string(),
+            9 => "SCC".to_string(),
+            other => format!("profile {}", other),
+        }
+    }
+
+    /// Profile, level and tier in the usual "Main10@L5.1@Main" form.
+    pub fn profile_level_tier(&self) -> String {
+        let level = self.general_level_idc as u32;
+        format!(
+            "{}@L{}.{}@{}",
+            self.profile_name(),
+            level / 30,
+            level % 30 / 3,
+            if self.general_tier_flag {
+                "High"
+            } else {
+                "Main"
+            }
+        )
+    }
+}
+
+/// MPEG-4 Elementary Stream Descriptor (esds) data
+///
+/// Fields missing from a truncated descriptor are left at 0 / `None`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct EsdsData {
+    pub es_id: u16,
+    /// objectTypeIndication (0x40 = MPEG-4 Audio, 0x6B = MP3, ...)
+    pub object_type_indication: u8,
+    /// streamType (5 = audio, 4 = visual)
+    pub stream_type: u8,
+    pub buffer_size: u32,
+    pub max_bitrate: u32,
+    pub avg_bitrate: u32,
+    /// Raw DecoderSpecificInfo payload
+    pub decoder_specific_info: Vec<u8>,
+    /// DecoderSpecificInfo decoded as an AudioSpecificConfig, for MPEG-4 audio
+    pub audio_config: Option<AudioSpecificConfig>,
+}
+
+/// The leading fields of an MPEG-4 AudioSpecificConfig
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AudioSpecificConfig {
+    /// 2 = AAC-LC, 5 = SBR (HE-AAC), 29 = PS (HE-AACv2), ...
+    pub audio_object_type: u8,
+    pub sampling_frequency_index: u8,
+    /// Sampling rate in Hz, from the index table or the explicit 24-bit value
+    pub sampling_frequency: Option<u32>,
+    pub channel_configuration: u8,
+}
+
+const ES_DESCRIPTOR_TAG: u8 = 0x03;
+const DECODER_CONFIG_TAG: u8 = 0x04;
+const DECODER_SPECIFIC_INFO_TAG: u8 = 0x05;
+
+const AAC_SAMPLE_RATES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+impl EsdsData {
+    /// Parse an `esds` payload (after version/flags). Unknown descriptor tags are
+    /// skipped and truncated descriptors yield whatever fields were present.
+    pub fn parse(buf: &[u8]) -> anyhow::Result<Self> {
+        let mut data = EsdsData::default();
+        let (_, es) = descriptors(buf)
+            .find(|(tag, _)| *tag == ES_DESCRIPTOR_TAG)
+            .ok_or_else(|| anyhow::anyhow!("esds has no ES_Descriptor"))?;
+
+        if es.len() < 3 {
+            return Ok(data);
+        }
+        data.es_id = u16::from_be_bytes([es[0], es[1]]);
+        let es_flags = es[2];
+        let mut pos = 3;
+        if es_flags & 0x80 != 0 {
+            pos += 2; // dependsOn_ES_ID
+        }
+        if es_flags & 0x40 != 0 {
+            pos += 1 + es.get(pos).copied().unwrap_or(0) as usize; // URL
+        }
+        if es_flags & 0x20 != 0 {
+            pos += 2; // OCR_ES_Id
+        }
+
+        let Some((_, dc)) = descriptors(es.get(pos..).unwrap_or_default())
+            .find(|(tag, _)| *tag == DECODER_CONFIG_TAG)
+        else {
+            return Ok(data);
+        };
+        if dc.len() < 13 {
+            return Ok(data);
+        }
+        data.object_type_indication = dc[0];
+        data.stream_type = dc[1] >> 2;
+        data.buffer_size = u32::from_be_bytes([0, dc[2], dc[3], dc[4]]);
+        data.max_bitrate = u32::from_be_bytes(dc[5..9].try_into().unwrap());
+        data.avg_bitrate = u32::from_be_bytes(dc[9..13].try_into().unwrap());
+
+        if let Some((_, dsi)) =
+            descriptors(&dc[13..]).find(|(tag, _)| *tag == DECODER_SPECIFIC_INFO_TAG)
+        {
+            data.decoder_specific_info = dsi.to_vec();
+            // 0x40 = MPEG-4 Audio; 0x66..=0x68 = MPEG-2 AAC profiles
+            if matches!(data.object_type_indication, 0x40 | 0x66..=0x68) {
+                data.audio_config = AudioSpecificConfig::parse(dsi);
+            }
+        }
+        Ok(data)
+    }
+}
+
+impl AudioSpecificConfig {
+    pub fn parse(buf: &[u8]) -> Option<Self> {
+        let mut bits = BitReader::new(buf);
+        let mut audio_object_type = bits.read(5)? as u8;
+        if audio_object_type == 31 {
+            audio_object_type = 32 + bits.read(6)? as u8;
+        }
+        let sampling_frequency_index = bits.read(4)? as u8;
+        let sampling_frequency = if sampling_frequency_index == 0x0F {
+            Some(bits.read(24)?)
+        } else {
+            AAC_SAMPLE_RATES
+                .get(sampling_frequency_index as usize)
+                .copied()
+        };
+        let channel_configuration = bits.read(4)? as u8;
+        Some(Self {
+            audio_object_type,
+            sampling_frequency_index,
+            sampling_frequency,
+            channel_configuration,
+        })
+    }
+}
+
+/// MSB-first bit reader over a byte slice.
+struct BitReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn read(&mut self, n: usize) -> Option<u32> {
+        let mut v = 0u32;
+        for _ in 0..n {
+            let byte = *self.buf.get(self.pos / 8)?;
+            let bit = (byte >> (7 - self.pos % 8)) & 1;
+            v = (v << 1) | bit as u32;
+            self.pos += 1;
+        }
+        Some(v)
+    }
+}
+
+/// Iterate MPEG-4 descriptors as `(tag, body)`. Sizes use the expandable
+/// encoding (7 bits per byte, high bit = more); a body running past the end of
+/// `buf` is cut short rather than dropped.
+fn descriptors(buf: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let tag = *buf.get(pos)?;
+        pos += 1;
+        let mut size = 0usize;
+        for _ in 0..4 {
+            let b = *buf.get(pos)?;
+            pos += 1;
+            size = (size << 7) | (b & 0x7F) as usize;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        let body = &buf[pos..(pos + size).min(buf.len())];
+        pos += size;
+        Some((tag, body))
+    })
+}
+
+/// CoreAudio channel layout from a `chan` box
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChannelLayout {
+    /// AudioChannelLayoutTag: layout id in the high 16 bits, channel count in the low 16
+    pub layout_tag: u32,
+    /// Name of a well-known layout tag, e.g. "5.1 (L R C LFE Ls Rs)"
+    pub layout_name: Option<String>,
+    /// Channel bitmap, used when `layout_tag` is UseChannelBitmap (0x10000)
+    pub channel_bitmap: u32,
+    /// Per-channel descriptions, used when `layout_tag` is UseChannelDescriptions (0)
+    pub channels: Vec<ChannelDescription>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChannelDescription {
+    /// AudioChannelLabel (1 = left, 2 = right, 3 = center, 4 = LFE, ...)
+    pub label: u32,
+    /// Short speaker name for well-known labels ("L", "R", "C", "LFE", ...)
+    pub label_name: Option<String>,
+    pub flags: u32,
+    pub coordinates: [f32; 3],
+}
+
+impl ChannelLayout {
+    /// Parse a `chan` payload (after version/flags).
+    pub fn parse(buf: &[u8]) -> anyhow::Result<Self> {
+        let mut cur = Cursor::new(buf);
+        let layout_tag = cur.read_u32::<BigEndian>()?;
+        let channel_bitmap = cur.read_u32::<BigEndian>()?;
+        let count = cur.read_u32::<BigEndian>()?;
+
+        let mut channels = Vec::new();
+        for _ in 0..count {
+            let label = cur.read_u32::<BigEndian>()?;
+            let flags = cur.read_u32::<BigEndian>()?;
+            let mut coordinates = [0f32; 3];
+            for c in &mut coordinates {
+                *c = cur.read_f32::<BigEndian>()?;
+            }
+            channels.push(ChannelDescription {
+                label,
+                label_name: channel_label_name(label).map(str::to_string),
+                flags,
+                coordinates,
+            });
+        }
+
+        Ok(Self {
+            layout_tag,
+            layout_name: channel_layout_name(layout_tag).map(str::to_string),
+            channel_bitmap,
+            channels,
+        })
+    }
+
+    /// Number of channels the layout describes.
+    pub fn channel_count(&self) -> u32 {
+        match self.layout_tag {
+            0 => self.channels.len() as u32,
+            0x0001_0000 => self.channel_bitmap.count_ones(),
+            tag => tag & 0xFFFF,
+        }
+    }
+}
+
+fn channel_layout_name(tag: u32) -> Option<&'static str> {
+    Some(match tag {
+        0x0064_0001 => "Mono",
+        0x0065_0002 => "Stereo",
+        0x0066_0002 => "Stereo (headphones)",
+        0x0067_0002 => "Matrix stereo",
+        0x0068_0002 => "Mid/side",
+        0x006A_0002 => "Binaural",
+        0x006B_0004 => "Ambisonic B-format",
+        0x006C_0004 => "Quadraphonic",
+        0x0071_0003 => "3.0 (L R C)",
+        0x0074_0004 => "4.0 (L R C Cs)",
+        0x0076_0005 => "5.0 (L R C Ls Rs)",
+        0x0079_0006 => "5.1 (L R C LFE Ls Rs)",
+        0x007A_0006 => "5.1 (L R Ls Rs C LFE)",
+        0x007B_0006 => "5.1 (L C R Ls Rs LFE)",
+        0x007C_0006 => "5.1 (C L R Ls Rs LFE)",
+        0x007D_0007 => "6.1 (L R C LFE Ls Rs Cs)",
+        0x007E_0008 => "7.1 (L R C LFE Ls Rs Lc Rc)",
+        0x007F_0008 => "7.1 (C Lc Rc L R Ls Rs LFE)",
+        0x0080_0008 => "7.1 (L R C LFE Ls Rs Rls Rrs)",
+        0x00C0_000C => "Atmos 7.1.4",
+        0x00C1_0010 => "Atmos 9.1.6",
+        0x00C2_0008 => "Atmos 5.1.2",
+        0x00C3_000A => "Atmos 5.1.4",
+        0x00C4_000A => "Atmos 7.1.2",
+        _ => return None,
+    })
+}
+
+fn channel_label_name(label: u32) -> Option<&'static str> {
+    Some(match label {
+        1 => "L",
+        2 => "R",
+        3 => "C",
+        4 => "LFE",
+        5 => "Ls",
+        6 => "Rs",
+        7 => "Lc",
+        8 => "Rc",
+        9 => "Cs",
+        10 => "Lsd",
+        11 => "Rsd",
+        12 => "Ts",
+        13 => "Vhl",
+        14 => "Vhc",
+        15 => "Vhr",
+        33 => "Rls",
+        34 => "Rrs",
+        35 => "Lw",
+        36 => "Rw",
+        37 => "LFE2",
+        42 => "Mono",
+        _ => return None,
+    })
+}
+
+/// Dolby Vision Decoder Configuration Record (dvcC / dvvC / dvwC)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DolbyVisionConfig {
+    pub version_major: u8,
+    pub version_minor: u8,
+    pub profile: u8,
+    pub level: u8,
+    pub rpu_present: bool,
+    pub el_present: bool,
+    pub bl_present: bool,
+    pub bl_signal_compatibility_id: u8,
+}
+
+impl DolbyVisionConfig {
+    /// Parse the configuration record from a dvcC/dvvC/dvwC payload.
+    pub fn parse(buf: &[u8]) -> anyhow::Result<Self> {
+        if buf.len() < 5 {
+            anyhow::bail!("Dolby Vision config too short ({} bytes)", buf.len());
+        }
+        let bits = u16::from_be_bytes([buf[2], buf[3]]);
+        Ok(Self {
+            version_major: buf[0],
+            version_minor: buf[1],
+            profile: (bits >> 9) as u8,
+            level: ((bits >> 3) & 0x3F) as u8,
+            rpu_present: bits & 0x4 != 0,
+            el_present: bits & 0x2 != 0,
+            bl_present: bits & 0x1 != 0,
+            bl_signal_compatibility_id: buf[4] >> 4,
+        })
+    }
+
+    /// Whether the configuration describes a dual-layer (base + enhancement layer) stream.
+    ///
+    /// Profile 7 is always dual-layer; other profiles are dual-layer only when both
+    /// layers are flagged present in the same track.
+    pub fn is_dual_layer(&self) -> bool {
+        self.profile == 7 || (self.bl_present && self.el_present)
+    }
+}
+
+/// Track Reference Type Box data (a child of `tref`)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrackReferenceData {
+    pub reference_type: String,
+    pub track_ids: Vec<u32>,
+}
+
+/// Data Reference Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DrefData {
+    pub version: u8,
+    pub flags: u32,
+    pub entry_count: u32,
+    pub entries: Vec<DataReferenceEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DataReferenceEntry {
+    /// Entry box type ("url ", "urn ", "alis", ...)
+    pub entry_type: String,
+    pub flags: u32,
+    /// Media data is in the same file as this box (flags & 1)
+    pub self_contained: bool,
+    pub name: Option<String>,
+    pub location: Option<String>,
+}
+
+/// Track aperture dimensions from a QuickTime `tapt` child.
+///
+/// `clef` is the clean aperture (what a player should display), `prof` the
+/// production aperture (clean aperture before pixel aspect correction) and
+/// `enof` the encoded pixel dimensions.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrackApertureData {
+    /// Aperture mode box type ("clef", "prof" or "enof")
+    pub mode: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// MIME Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MimeData {
+    pub content_type: String,
+}
+
+/// Item Information Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IinfData {
+    pub version: u8,
+    pub flags: u32,
+    pub entry_count: u32,
+    pub entries: Vec<ItemInfoEntry>,
+}
+
+/// Item Info Entry (infe)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ItemInfoEntry {
+    pub version: u8,
+    pub item_id: u32,
+    pub protection_index: u16,
+    /// Item type for version 2+ entries ("hvc1", "grid", "Exif", "mime", ...)
+    pub item_type: Option<String>,
+    pub item_name: String,
+    /// MIME type of `mime` items (and of every version 0/1 entry)
+    pub content_type: Option<String>,
+    pub content_encoding: Option<String>,
+    /// URI of `uri ` items
+    pub uri_type: Option<String>,
+}
+
+/// Item Location Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IlocData {
+    pub version: u8,
+    pub flags: u32,
+    pub items: Vec<ItemLocation>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ItemLocation {
+    pub item_id: u32,
+    /// 0 = file offset, 1 = offset into `idat`, 2 = item offset
+    pub construction_method: u8,
+    pub data_reference_index: u16,
+    pub base_offset: u64,
+    pub extents: Vec<ItemExtent>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ItemExtent {
+    pub index: Option<u64>,
+    pub offset: u64,
+    /// 0 means the extent runs to the end of the source
+    pub length: u64,
+}
+
+/// Item Reference Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IrefData {
+    pub version: u8,
+    pub flags: u32,
+    pub references: Vec<ItemReference>,
+}
+
+/// One typed reference from an item to one or more other items.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ItemReference {
+    /// "thmb" (thumbnail of), "dimg" (derived from), "auxl" (auxiliary for), "cdsc" (describes), ...
+    pub reference_type: String,
+    pub from_item_id: u32,
+    pub to_item_ids: Vec<u32>,
+}
+
+/// Payload of a HEIF `grid` derived-image item.
+///
+/// The tiles are the items the grid references with `dimg`, in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ImageGridData {
+    pub version: u8,
+    /// Bit 0 set: output width/height are 32-bit
+    pub flags: u8,
+    pub rows: u16,
+    pub columns: u16,
+    /// Size of the reconstructed image; tiles overhanging it are cropped
+    pub output_width: u32,
+    pub output_height: u32,
+}
+
+impl ImageGridData {
+    /// Parse a `grid` item payload.
+    pub fn parse(buf: &[u8]) -> anyhow::Result<Self> {
+        let mut cur = Cursor::new(buf);
+        let version = cur.read_u8()?;
+        if version != 0 {
+            anyhow::bail!("unsupported grid version {}", version);
+        }
+        let flags = cur.read_u8()?;
+        let rows = cur.read_u8()? as u16 + 1;
+        let columns = cur.read_u8()? as u16 + 1;
+        let (output_width, output_height) = if flags & 1 != 0 {
+            (cur.read_u32::<BigEndian>()?, cur.read_u32::<BigEndian>()?)
+        } else {
+            (
+                cur.read_u16::<BigEndian>()? as u32,
+                cur.read_u16::<BigEndian>()? as u32,
+            )
+        };
+        Ok(Self {
+            version,
+            flags,
+            rows,
+            columns,
+            output_width,
+            output_height,
+        })
+    }
+}
+
+/// Meta Box flavours, which differ in how their children are interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum MetaKind {
+    /// iTunes-style `moov/udta/meta` with an `ilst` of atoms (handler `mdir`)
+    ITunes,
+    /// QuickTime keyed metadata; `ilst` items index into `keys` (handler `mdta`)
+    QuickTimeKeys,
+    /// HEIF/image `meta` with items and properties (handler `pict`)
+    Heif,
+    Other,
+}
+
+/// Meta Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MetaData {
+    pub handler_type: String,
+    pub kind: MetaKind,
+}
+
+/// Metadata Item Keys Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct KeysData {
+    pub version: u8,
+    pub flags: u32,
+    pub keys: Vec<MetadataKey>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MetadataKey {
+    /// Key namespace, usually "mdta"
+    pub namespace: String,
+    /// Key name, e.g. "com.apple.quicktime.title"
+    pub name: String,
+}
+
+/// Metadata Item List Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IlstData {
+    pub items: Vec<MetadataItem>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MetadataItem {
+    /// Item name: the atom type (e.g. "©nam") or the resolved `keys` entry
+    pub key: String,
+    /// 1-based index into the sibling `keys` box, for keyed (mdta) items
+    pub key_index: Option<u32>,
+    /// Well-known type indicator from the `data` box (1 = UTF-8, 13 = JPEG,
+    /// 14 = PNG, 21 = integer, ...)
+    pub data_type: u32,
+    pub locale: u32,
+    /// Text and integers as-is; images as "JPEG image, N bytes"; other
+    /// types as their byte count
+    pub value: String,
+    /// Decoded gapless info, for the freeform `com.apple.iTunes:iTunSMPB` item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gapless: Option<GaplessInfo>,
+}
+
+/// Gapless playback info from the iTunes `iTunSMPB` tag.
+///
+/// All counts are in audio samples (PCM frames), not container samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GaplessInfo {
+    /// Priming samples the encoder added before the audio
+    pub encoder_delay: u32,
+    /// Padding samples after the end of the audio
+    pub padding: u32,
+    /// Length of the original audio, excluding delay and padding
+    pub original_sample_count: u64,
+}
+
+impl GaplessInfo {
+    /// Parse an `iTunSMPB` value: space-separated hex words where the second,
+    /// third and fourth are the delay, padding and original sample count.
+    pub fn from_itunsmpb(value: &str) -> Option<Self> {
+        let words: Vec<&str> = value.split_whitespace().collect();
+        Some(Self {
+            encoder_delay: u32::from_str_radix(words.get(1)?, 16).ok()?,
+            padding: u32::from_str_radix(words.get(2)?, 16).ok()?,
+            original_sample_count: u64::from_str_radix(words.get(3)?, 16).ok()?,
+        })
+    }
+}
+
+impl IlstData {
+    /// Resolve keyed items against the entries of the sibling `keys` box.
+    ///
+    /// Indices past the end of `keys` are named `unknown key #N`.
+    pub fn resolve_keys(&mut self, keys: &KeysData) {
+        for item in &mut self.items {
+            if let Some(n) = item.key_index {
+                item.key = match n.checked_sub(1).and_then(|i| keys.keys.get(i as usize)) {
+                    Some(k) => k.name.clone(),
+                    None => format!("unknown key #{}", n),
+                };
+            }
+        }
+    }
+}
+
+/// Trait for custom box decoders.
+///
+/// A decoder is responsible for interpreting the payload of a specific box
+/// (identified by a [`BoxKey`]) and returning a [`BoxValue`].
+pub trait BoxDecoder: Send + Sync {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue>;
+
+    /// Like [`BoxDecoder::decode`], with facts gathered from other boxes of
+    /// the same track. Decoders that don't need them keep the default.
+    fn decode_with_context(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+        _ctx: &DecodeContext,
+    ) -> anyhow::Result<BoxValue> {
+        self.decode(r, hdr, version, flags)
+    }
+}
+
+/// Track-level facts some boxes can't be decoded without.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecodeContext {
+    /// `default_per_sample_iv_size` from the track's `tenc`, for `senc`
+    pub per_sample_iv_size: Option<u8>,
+    /// Media timescale from the track's `mdhd`, for `tfdt`
+    pub timescale: Option<u32>,
+    /// `handler_type` from the track's `hdlr`, so `stsd` can read the visual
+    /// fields of codecs it doesn't know
+    pub handler_type: Option<FourCC>,
+    /// Type of the enclosing box, for decoders registered with
+    /// [`Registry::with_child_decoder`]
+    pub parent: Option<FourCC>,
+}
+
+/// Registry of decoders keyed by `BoxKey` (4CC or UUID).
+///
+/// The registry is immutable once constructed; use [`Registry::with_decoder`]
+/// to build it fluently.
+pub struct Registry {
+    map: HashMap<BoxKey, BoxDecoderEntry>,
+}
+
+struct BoxDecoderEntry {
+    inner: Box<dyn BoxDecoder>,
+    _name: String,
+    /// Only decode boxes directly inside this type
+    parent: Option<FourCC>,
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+
+    /// Return a new registry with the given decoder added.
+    ///
+    /// `name` is human-readable and used only for debugging / logging.
+    pub fn with_decoder(mut self, key: BoxKey, name: &str, dec: Box<dyn BoxDecoder>) -> Self {
+        self.map.insert(
+            key,
+            BoxDecoderEntry {
+                inner: dec,
+                _name: name.to_string(),
+                parent: None,
+            },
+        );
+        self
+    }
+
+    /// Like [`Registry::with_decoder`], but the decoder is only used for
+    /// boxes whose parent is `parent`.
+    ///
+    /// Some types mean different things in different places: `tmcd` is a
+    /// track reference inside `tref` but a container inside `gmhd`.
+    pub fn with_child_decoder(
+        mut self,
+        parent: FourCC,
+        key: BoxKey,
+        name: &str,
+        dec: Box<dyn BoxDecoder>,
+    ) -> Self {
+        self.map.insert(
+            key,
+            BoxDecoderEntry {
+                inner: dec,
+                _name: name.to_string(),
+                parent: Some(parent),
+            },
+        );
+        self
+    }
+
+    /// The decoder for `key`, if it applies under `parent`.
+    fn entry(&self, key: &BoxKey, parent: Option<FourCC>) -> Option<&BoxDecoderEntry> {
+        self.map
+            .get(key)
+            .filter(|d| d.parent.is_none() || d.parent == parent)
+    }
+
+    /// Try to decode the payload of a box using a registered decoder.
+    ///
+    /// Returns `None` if no decoder exists for the given key. Decoders tied
+    /// to a parent type are skipped, as the parent isn't known here.
+    pub fn decode(
+        &self,
+        key: &BoxKey,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> Option<anyhow::Result<BoxValue>> {
+        self.entry(key, None)
+            .map(|d| d.inner.decode(r, hdr, version, flags))
+    }
+
+    /// Like [`Registry::decode`], passing `ctx` on to the decoder. Decoders
+    /// tied to a parent type only run when `ctx.parent` matches.
+    pub fn decode_with_context(
+        &self,
+        key: &BoxKey,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+        ctx: &DecodeContext,
+    ) -> Option<anyhow::Result<BoxValue>> {
+        self.entry(key, ctx.parent)
+            .map(|d| d.inner.decode_with_context(r, hdr, version, flags, ctx))
+    }
+
+    /// Whether a decoder is registered for `key`.
+    pub fn contains(&self, key: &BoxKey) -> bool {
+        self.map.contains_key(key)
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a [`Registry`] from the default decoders with some of them removed.
+///
+/// Useful to skip expensive decoders (e.g. large sample tables) or to keep
+/// only the few a tool needs. Pass the result to
+/// [`crate::analyze_reader_with_registry`].
+///
+/// ```
+/// use mp4box::{BoxKey, FourCC, RegistryBuilder};
+///
+/// let reg = RegistryBuilder::new()
+///     .without(BoxKey::FourCC(FourCC(*b"stsz")))
+///     .build();
+/// assert!(!reg.contains(&BoxKey::FourCC(FourCC(*b"stsz"))));
+/// assert!(reg.contains(&BoxKey::FourCC(FourCC(*b"stco"))));
+/// ```
+pub struct RegistryBuilder {
+    base: Registry,
+    without: HashSet<BoxKey>,
+    only: Option<HashSet<BoxKey>>,
+}
+
+impl RegistryBuilder {
+    /// Start from [`default_registry`].
+    pub fn new() -> Self {
+        Self::from_registry(default_registry())
+    }
+
+    /// Start from an existing registry, e.g. one with custom decoders added.
+    pub fn from_registry(base: Registry) -> Self {
+        Self {
+            base,
+            without: HashSet::new(),
+            only: None,
+        }
+    }
+
+    /// Drop the decoder for `key`.
+    pub fn without(mut self, key: BoxKey) -> Self {
+        self.without.insert(key);
+        self
+    }
+
+    /// Keep only the decoders for `keys`. Calling this again adds to the set;
+    /// `without` still applies on top.
+    pub fn only(mut self, keys: impl IntoIterator<Item = BoxKey>) -> Self {
+        self.only.get_or_insert_with(HashSet::new).extend(keys);
+        self
+    }
+
+    pub fn build(self) -> Registry {
+        let mut reg = self.base;
+        reg.map.retain(|key, _| {
+            !self.without.contains(key) && self.only.as_ref().is_none_or(|only| only.contains(key))
+        });
+        reg
+    }
+}
+
+impl Default for RegistryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ---------- Helpers ----------
+
+fn read_all(r: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn lang_from_u16(code: u16) -> String {
+    if code == 0 {
+        return "und".to_string();
+    }
+    let c1 = ((code >> 10) & 0x1F) as u8 + 0x60;
+    let c2 = ((code >> 5) & 0x1F) as u8 + 0x60;
+    let c3 = (code & 0x1F) as u8 + 0x60;
+    format!("{}{}{}", c1 as char, c2 as char, c3 as char,)
+}
+
+// ---------- Decoders ----------
+
+// ftyp / styp: major + minor + compatible brands
+pub struct FtypDecoder;
+
+impl BoxDecoder for FtypDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        if buf.len() < 8 {
+            anyhow::bail!("{} payload too short ({} bytes)", hdr.typ, buf.len());
+        }
+
+        let brand = |b: &[u8]| String::from_utf8_lossy(b).to_string();
+        Ok(BoxValue::Structured(StructuredData::FileType(FtypData {
+            major_brand: brand(&buf[0..4]),
+            minor_version: u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]),
+            compatible_brands: buf[8..].chunks_exact(4).map(brand).collect(),
+        })))
+    }
+}
+
+// mvhd: movie timescale, duration and next_track_id
+pub struct MvhdDecoder;
+
+impl BoxDecoder for MvhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // mvhd is a FullBox: version/flags were already consumed by the parser.
+        let version = version.unwrap_or(0);
+        let (creation_time, modification_time, timescale, duration) = if version == 1 {
+            let creation = cur.read_u64::<BigEndian>()?;
+            let modification = cur.read_u64::<BigEndian>()?;
+            let ts = cur.read_u32::<BigEndian>()?;
+            let dur = cur.read_u64::<BigEndian>()?;
+            (creation, modification, ts, dur)
+        } else {
+            let creation = cur.read_u32::<BigEndian>()? as u64;
+            let modification = cur.read_u32::<BigEndian>()? as u64;
+            let ts = cur.read_u32::<BigEndian>()?;
+            let dur = cur.read_u32::<BigEndian>()? as u64;
+            (creation, modification, ts, dur)
+        };
+
+        let rate = cur.read_i32::<BigEndian>()? as f64 / 65536.0;
+        let volume = cur.read_i16::<BigEndian>()? as f32 / 256.0;
+        // reserved (2 + 8)
+        cur.set_position(cur.position() + 10);
+        let matrix_start = cur.position() as usize;
+        let matrix = buf
+            .get(matrix_start..)
+            .and_then(Matrix::from_bytes)
+            .ok_or_else(|| anyhow::anyhow!("mvhd truncated in matrix"))?;
+        // matrix (36), pre_defined (24)
+        cur.set_position((matrix_start + 36 + 24) as u64);
+        let next_track_id = cur.read_u32::<BigEndian>()?;
+
+        Ok(BoxValue::Structured(StructuredData::MovieHeader(
+            MvhdData {
+                version,
+                flags: flags.unwrap_or(0),
+                creation_time,
+                modification_time,
+                timescale,
+                duration,
+                duration_seconds: if timescale > 0 {
+                    duration as f64 / timescale as f64
+                } else {
+                    0.0
+                },
+                rate,
+                volume,
+                matrix,
+                next_track_id,
+            },
+        )))
+    }
+}
+
+// tkhd: track id, duration, width, height
+pub struct TkhdDecoder;
+
+impl BoxDecoder for TkhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+
+        // tkhd is a FullBox: version/flags were already consumed by the parser.
+        let version = version.unwrap_or(0);
+        let flags_value = flags.unwrap_or(0);
+        let mut pos = 0usize;
+
+        let read_u32 = |pos: &mut usize| -> Option<u32> {
+            if *pos + 4 > buf.len() {
+                return None;
+            }
+            let v = u32::from_be_bytes(buf[*pos..*pos + 4].try_into().unwrap());
+            *pos += 4;
+            Some(v)
+        };
+        let read_u64 = |pos: &mut usize| -> Option<u64> {
+            if *pos + 8 > buf.len() {
+                return None;
+            }
+            let v = u64::from_be_bytes(buf[*pos..*pos + 8].try_into().unwrap());
+            *pos += 8;
+            Some(v)
+        };
+
+        let track_id;
+        let duration;
+
+        if version == 1 {
+            // creation_time (8), modification_time (8), track_id (4), reserved (4), duration (8)
+            if read_u64(&mut pos).is_none() || read_u64(&mut pos).is_none() {
+                return Ok(BoxValue::Text(
+                    "tkhd: truncated creation/modification".into(),
+                ));
+            }
+            track_id = read_u32(&mut pos).unwrap_or(0);
+            let _ = read_u32(&mut pos); // reserved
+            duration = read_u64(&mut pos).unwrap_or(0);
+        } else {
+            // creation_time (4), modification_time (4), track_id (4), reserved (4), duration (4)
+            if read_u32(&mut pos).is_none() || read_u32(&mut pos).is_none() {
+                return Ok(BoxValue::Text(
+                    "tkhd: truncated creation/modification".into(),
+                ));
+            }
+            track_id = read_u32(&mut pos).unwrap_or(0);
+            let _ = read_u32(&mut pos); // reserved
+            duration = read_u32(&mut pos).unwrap_or(0) as u64;
+        }
+
+        // reserved[2]
+        for _ in 0..2 {
+            let _ = read_u32(&mut pos);
+        }
+
+        // layer (2), alternate_group (2), volume (2), reserved (2)
+        let (layer, alternate_group, volume) = if pos + 8 <= buf.len() {
+            let field = |i: usize| i16::from_be_bytes([buf[pos + i], buf[pos + i + 1]]);
+            let fields = (field(0), field(2), field(4) as f32 / 256.0);
+            pos += 8;
+            fields
+        } else {
+            // we still have track/duration, just don't try width/height
+            return Ok(BoxValue::Text(format!(
+                "track_id={} duration={} (no width/height, short payload)",
+                track_id, duration
+            )));
+        };
+
+        // matrix (36 bytes)
+        let matrix = if let Some(m) = Matrix::from_bytes(&buf[pos..]) {
+            pos += 36;
+            m
+        } else {
+            return Ok(BoxValue::Text(format!(
+                "track_id={} duration={} (no width/height, short payload)",
+                track_id, duration
+            )));
+        };
+
+        // width / height
+        let (width, height) = if pos + 8 <= buf.len() {
+            let width = u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
+            let height = u32::from_be_bytes(buf[pos + 4..pos + 8].try_into().unwrap());
+            (width as f64 / 65536.0, height as f64 / 65536.0)
+        } else {
+            (0.0, 0.0)
+        };
+
+        let data = TkhdData {
+            version,
+            flags: flags_value,
+            track_id,
+            duration,
+            layer,
+            alternate_group,
+            volume,
+            matrix,
+            width,
+            height,
+            enabled: flags_value & 0x1 != 0,
+            in_movie: flags_value & 0x2 != 0,
+            in_preview: flags_value & 0x4 != 0,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackHeader(data)))
+    }
+}
+
+// mdhd: timescale, duration, language
+pub struct MdhdDecoder;
+
+impl BoxDecoder for MdhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let creation_time = r.read_u32::<BigEndian>()?;
+        let modification_time = r.read_u32::<BigEndian>()?;
+        let timescale = r.read_u32::<BigEndian>()?;
+        let duration = r.read_u32::<BigEndian>()?;
+        let language_code = r.read_u16::<BigEndian>()?;
+        let _pre_defined = r.read_u16::<BigEndian>()?;
+
+        let lang = lang_from_u16(language_code);
+
+        let data = MdhdData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            creation_time,
+            modification_time,
+            timescale,
+            duration,
+            language: lang,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::MediaHeader(data)))
+    }
+}
+
+// hdlr: handler type + name
+pub struct HdlrDecoder;
+
+impl BoxDecoder for HdlrDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        // pre_defined (QuickTime component type) + handler_type
+        let mut component_type = [0u8; 4];
+        r.read_exact(&mut component_type)?;
+        let mut handler_type = [0u8; 4];
+        r.read_exact(&mut handler_type)?;
+
+        // reserved (QuickTime component manufacturer, flags, flags mask)
+        let mut reserved = [0u8; 12];
+        r.read_exact(&mut reserved)?;
+        let fourcc = |b: [u8; 4]| (b != [0; 4]).then(|| FourCC(b).to_string());
+
+        // name: rest of box
+        let mut name_bytes = Vec::new();
+        r.read_to_end(&mut name_bytes)?;
+        let name = hdlr_name(&name_bytes);
+
+        let handler_str = std::str::from_utf8(&handler_type).unwrap_or("????");
+
+        let data = HdlrData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            component_type: fourcc(component_type),
+            handler_type: handler_str.to_string(),
+            component_manufacturer: fourcc([reserved[0], reserved[1], reserved[2], reserved[3]]),
+            name,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::HandlerReference(data)))
+    }
+}
+
+// vmhd: graphics mode and opcolor
+pub struct VmhdDecoder;
+
+impl BoxDecoder for VmhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let graphics_mode = r.read_u16::<BigEndian>()?;
+        let mut opcolor = [0u16; 3];
+        r.read_u16_into::<BigEndian>(&mut opcolor)?;
+
+        Ok(BoxValue::Structured(StructuredData::VideoMediaHeader(
+            VmhdData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                graphics_mode,
+                opcolor,
+            },
+        )))
+    }
+}
+
+// smhd: stereo balance
+pub struct SmhdDecoder;
+
+impl BoxDecoder for SmhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        // balance (8.8) + reserved
+        let balance = r.read_i16::<BigEndian>()? as f32 / 256.0;
+
+        Ok(BoxValue::Structured(StructuredData::SoundMediaHeader(
+            SmhdData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                balance,
+            },
+        )))
+    }
+}
+
+// nmhd: null media header, version/flags only
+pub struct NmhdDecoder;
+
+impl BoxDecoder for NmhdDecoder {
+    fn decode(
+        &self,
+        _r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        Ok(BoxValue::Structured(StructuredData::NullMediaHeader(
+            NmhdData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+            },
+        )))
+    }
+}
+
+// sidx: segment index with its subsegment references
+pub struct SidxDecoder;
+
+impl BoxDecoder for SidxDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let reference_id = r.read_u32::<BigEndian>()?;
+        let timescale = r.read_u32::<BigEndian>()?;
+
+        let (earliest_presentation_time, first_offset) = if version == 1 {
+            (r.read_u64::<BigEndian>()?, r.read_u64::<BigEndian>()?)
+        } else {
+            (
+                r.read_u32::<BigEndian>()? as u64,
+                r.read_u32::<BigEndian>()? as u64,
+            )
+        };
+
+        let _reserved = r.read_u16::<BigEndian>()?;
+        let reference_count = r.read_u16::<BigEndian>()?;
+
+        let mut references = Vec::with_capacity(reference_count as usize);
+        for i in 0..reference_count {
+            let mut entry = [0u8; 12];
+            r.read_exact(&mut entry).map_err(|_| {
+                anyhow::anyhow!("sidx reference {} runs past the end of the box", i)
+            })?;
+            let word = |at: usize| u32::from_be_bytes(entry[at..at + 4].try_into().unwrap());
+            let (size_word, duration, sap_word) = (word(0), word(4), word(8));
+            references.push(SidxReference {
+                reference_type: (size_word >> 31) as u8,
+                referenced_size: size_word & 0x7FFF_FFFF,
+                subsegment_duration: duration,
+                starts_with_sap: sap_word >> 31 != 0,
+                sap_type: ((sap_word >> 28) & 0x7) as u8,
+                sap_delta_time: sap_word & 0x0FFF_FFFF,
+            });
+        }
+
+        Ok(BoxValue::Structured(StructuredData::SegmentIndex(
+            SidxData {
+                version,
+                flags: flags.unwrap_or(0),
+                reference_id,
+                timescale,
+                earliest_presentation_time,
+                first_offset,
+                references,
+            },
+        )))
+    }
+}
+
+// stsd: list sample entry formats, maybe WxH
+// ---- stsd decoder: codec + width/height per entry -----------------------
+pub struct StsdDecoder;
+
+impl BoxDecoder for StsdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        self.decode_with_context(r, hdr, version, flags, &DecodeContext::default())
+    }
+
+    fn decode_with_context(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+        ctx: &DecodeContext,
+    ) -> anyhow::Result<BoxValue> {
+        use byteorder::{BigEndian, ReadBytesExt};
+
+        // stsd is a FullBox; our reader is already positioned at payload:
+        // u32 entry_count
+        // [ SampleEntry entries... ]
+
+        let entry_count = r.read_u32::<BigEndian>()?;
+        if entry_count == 0 {
+            return Ok(BoxValue::Text("entry_count=0".to_string()));
+        }
+
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let entry_size = r.read_u32::<BigEndian>()?;
+
+            let mut codec_bytes = [0u8; 4];
+            r.read_exact(&mut codec_bytes)?;
+            let codec = std::str::from_utf8(&codec_bytes)
+                .unwrap_or("????")
+                .to_string();
+
+            // The rest of the entry (fields + child boxes). Tolerate entries
+            // whose declared size runs past the end of the box.
+            let mut body = Vec::new();
+            (&mut *r)
+                .take((entry_size as u64).saturating_sub(8))
+                .read_to_end(&mut body)?;
+
+            let visual = VISUAL_CODECS.contains(&codec.as_str())
+                || ctx
+                    .handler_type
+                    .is_some_and(|h| VISUAL_HANDLERS.contains(&&h.0));
+            entries.push(parse_sample_entry(entry_size, codec, visual, &body));
+
+            if (body.len() as u64) < (entry_size as u64).saturating_sub(8) {
+                break;
+            }
+        }
+
+        // Create structured data
+        let data = StsdData {
+            version: _version.unwrap_or(0),
+            flags: _flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SampleDescription(
+            data,
+        )))
+    }
+}
+
+// Sample entry codecs whose fields follow the VisualSampleEntry / AudioSampleEntry layouts.
+const VISUAL_CODECS: &[&str] = &[
+    "avc1", "avc2", "avc3", "avc4", "hvc1", "hev1", "dvh1", "dvhe", "dva1", "dvav", "vp09", "av01",
+];
+// Track handlers whose sample entries are all VisualSampleEntry, whatever the codec.
+const VISUAL_HANDLERS: &[&[u8; 4]] = &[b"vide", b"pict", b"auxv"];
+const AUDIO_CODECS: &[&str] = &[
+    "mp4a", "ac-3", "ec-3", "ac-4", "Opus", "fLaC", "alac", "samr", "sawb", "enca", "lpcm", "sowt",
+    "twos",
+];
+
+// Size of the fixed fields following the 8-byte sample entry header.
+const VISUAL_ENTRY_FIELDS: usize = 78;
+const AUDIO_ENTRY_FIELDS: usize = 28;
+
+fn parse_sample_entry(size: u32, codec: String, visual: bool, body: &[u8]) -> SampleEntry {
+    // SampleEntry: 6 reserved bytes, u16 data_reference_index
+    let data_reference_index = if body.len() >= 8 {
+        u16::from_be_bytes([body[6], body[7]])
+    } else {
+        1
+    };
+
+    // VisualSampleEntry continues with 16 bytes pre_defined / reserved,
+    // then u16 width and u16 height.
+    let mut width = None;
+    let mut height = None;
+    let mut children_start = None;
+    let mut details = SampleEntryDetails::Other;
+
+    if visual {
+        if let Some(visual) = VisualSampleEntry::parse(body) {
+            details = SampleEntryDetails::Visual(visual);
+        }
+        if body.len() >= 28 {
+            width = Some(u16::from_be_bytes([body[24], body[25]]));
+            height = Some(u16::from_be_bytes([body[26], body[27]]));
+        }
+        children_start = Some(VISUAL_ENTRY_FIELDS);
+    } else if AUDIO_CODECS.contains(&codec.as_str()) {
+        // QuickTime sound sample description versions 1 and 2 carry extra fields.
+        let qt_version = if body.len() >= 10 {
+            u16::from_be_bytes([body[8], body[9]])
+        } else {
+            0
+        };
+        let extra = match qt_version {
+            1 => 16,
+            2 => 36,
+            _ => 0,
+        };
+        children_start = Some(AUDIO_ENTRY_FIELDS + extra);
+        if let Some(audio) = AudioSampleEntry::parse(body) {
+            details = SampleEntryDetails::Audio(audio);
+        }
+    }
+
+    let mut dolby_vision = None;
+    let mut nal_length_size = None;
+    let mut channel_layout = None;
+    let mut esds = None;
+    let mut avc_config = None;
+    let mut hevc_config = None;
+    if let Some(start) = children_start {
+        for (typ, payload) in child_boxes(body, start) {
+            match &typ {
+                b"dvcC" | b"dvvC" | b"dvwC" => {
+                    dolby_vision = DolbyVisionConfig::parse(payload).ok();
+                }
+                // AVCDecoderConfigurationRecord: lengthSizeMinusOne in the low bits of byte 4
+                b"avcC" if payload.len() > 4 => {
+                    nal_length_size = Some((payload[4] & 0x03) + 1);
+                    avc_config = AvcConfig::parse(payload).ok();
+                }
+                // HEVCDecoderConfigurationRecord: lengthSizeMinusOne in the low bits of byte 21
+                b"hvcC" if payload.len() > 21 => {
+                    nal_length_size = Some((payload[21] & 0x03) + 1);
+                    hevc_config = HevcConfig::parse(payload).ok();
+                }
+                // chan is a FullBox; skip its version/flags
+                b"chan" if payload.len() > 4 => {
+                    channel_layout = ChannelLayout::parse(&payload[4..]).ok();
+                }
+                b"esds" if payload.len() > 4 => {
+                    esds = EsdsData::parse(&payload[4..]).ok();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    SampleEntry {
+        size,
+        codec,
+        data_reference_index,
+        width,
+        height,
+        dolby_vision,
+        nal_length_size,
+        channel_layout,
+        esds,
+        avc_config,
+        hevc_config,
+        details,
+    }
+}
+
+/// Walk the 32-bit-sized child boxes packed in `body` from `start` onward.
+fn child_boxes(body: &[u8], start: usize) -> Vec<([u8; 4], &[u8])> {
+    let mut out = Vec::new();
+    let mut pos = start;
+    while pos + 8 <= body.len() {
+        let size = u32::from_be_bytes(body[pos..pos + 4].try_into().unwrap()) as usize;
+        let typ: [u8; 4] = body[pos + 4..pos + 8].try_into().unwrap();
+        if size < 8 || pos + size > body.len() {
+            break;
+        }
+        out.push((typ, &body[pos + 8..pos + size]));
+        pos += size;
+    }
+    out
+}
+
+// stts: time-to-sample
+pub struct SttsDecoder;
+
+impl BoxDecoder for SttsDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        // and stripped from the payload. We start directly with the box-specific data.
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entry_count {
+            let sample_count = cur.read_u32::<BigEndian>()?;
+            let sample_delta = cur.read_u32::<BigEndian>()?;
+            entries.push(SttsEntry {
+                sample_count,
+                sample_delta,
+            });
+        }
+
+        let data = SttsData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::DecodingTimeToSample(
+            data,
+        )))
+    }
+}
+
+// stss: sync sample table
+pub struct StssDecoder;
+
+impl BoxDecoder for StssDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut sample_numbers = Vec::new();
+
+        for _ in 0..entry_count {
+            sample_numbers.push(cur.read_u32::<BigEndian>()?);
+        }
+
+        let data = StssData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            sample_numbers,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SyncSample(data)))
+    }
+}
+
+// ctts: composition time to sample
+pub struct CttsDecoder;
+
+impl BoxDecoder for CttsDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entry_count {
+            let sample_count = cur.read_u32::<BigEndian>()?;
+            let sample_offset = if version == Some(1) {
+                cur.read_i32::<BigEndian>()? as i64
+            } else {
+                cur.read_u32::<BigEndian>()? as i64
+            };
+            entries.push(CttsEntry {
+                sample_count,
+                sample_offset,
+            });
+        }
+
+        let data = CttsData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(
+            StructuredData::CompositionTimeToSample(data),
+        ))
+    }
+}
+
+// stsc: sample-to-chunk
+pub struct StscDecoder;
+
+impl BoxDecoder for StscDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entry_count {
+            let first_chunk = cur.read_u32::<BigEndian>()?;
+            let samples_per_chunk = cur.read_u32::<BigEndian>()?;
+            let sample_description_index = cur.read_u32::<BigEndian>()?;
+            entries.push(StscEntry {
+                first_chunk,
+                samples_per_chunk,
+                sample_description_index,
+            });
+        }
+
+        let data = StscData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SampleToChunk(data)))
+    }
+}
+
+// stsz: sample sizes
+pub struct StszDecoder;
+
+impl BoxDecoder for StszDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let sample_size = cur.read_u32::<BigEndian>()?;
+        let sample_count = cur.read_u32::<BigEndian>()?;
+        let mut sample_sizes = Vec::new();
+
+        // If sample_size is 0, each sample has its own size
+        if sample_size == 0 {
+            let remaining = buf.len() as u64 - cur.position();
+            if sample_count as u64 * 4 > remaining {
+                anyhow::bail!(
+                    "stsz sample_count {} needs {} bytes of sizes but only {} remain",
+                    sample_count,
+                    sample_count as u64 * 4,
+                    remaining
+                );
+            }
+            sample_sizes.reserve(sample_count as usize);
+            for _ in 0..sample_count {
+                sample_sizes.push(cur.read_u32::<BigEndian>()?);
+            }
+        } else {
+            // A constant size means no table should follow. Keep whatever an
+            // encoder wrote anyway so the contradiction can be reported.
+            while let Ok(size) = cur.read_u32::<BigEndian>() {
+                sample_sizes.push(size);
+            }
+        }
+
+        let data = StszData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            sample_size,
+            sample_count,
+            sample_sizes,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SampleSize(data)))
+    }
+}
+
+// stco: 32-bit chunk offsets
+pub struct StcoDecoder;
+
+impl BoxDecoder for StcoDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut chunk_offsets = Vec::new();
+
+        for _ in 0..entry_count {
+            chunk_offsets.push(cur.read_u32::<BigEndian>()?);
+        }
+
+        let data = StcoData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            chunk_offsets,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ChunkOffset(data)))
+    }
+}
+
+// co64: 64-bit chunk offsets
+pub struct Co64Decoder;
+
+impl BoxDecoder for Co64Decoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut chunk_offsets = Vec::new();
+
+        for _ in 0..entry_count {
+            chunk_offsets.push(cur.read_u64::<BigEndian>()?);
+        }
+
+        let data = Co64Data {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            chunk_offsets,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ChunkOffset64(data)))
+    }
+}
+
+// tfhd: track fragment header
+pub struct TfhdDecoder;
+
+impl BoxDecoder for TfhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let flags = flags.unwrap_or(0);
+        let track_id = r.read_u32::<BigEndian>()?;
+
+        // Fields follow in tf_flags bit order, each present only if its bit is set.
+        let base_data_offset = if flags & 0x000001 != 0 {
+            Some(r.read_u64::<BigEndian>()?)
+        } else {
+            None
+        };
+        let mut optional = |bit: u32| -> anyhow::Result<Option<u32>> {
+            Ok(if flags & bit != 0 {
+                Some(r.read_u32::<BigEndian>()?)
+            } else {
+                None
+            })
+        };
+        let sample_description_index = optional(0x000002)?;
+        let default_sample_duration = optional(0x000008)?;
+        let default_sample_size = optional(0x000010)?;
+        let default_sample_flags = optional(0x000020)?;
+
+        Ok(BoxValue::Structured(StructuredData::TrackFragmentHeader(
+            TfhdData {
+                version: version.unwrap_or(0),
+                flags,
+                track_id,
+                base_data_offset,
+                sample_description_index,
+                default_sample_duration,
+                default_sample_size,
+                default_sample_flags,
+                default_base_is_moof: flags & 0x020000 != 0,
+            },
+        )))
+    }
+}
+
+// tfdt: base media decode time (32-bit in version 0, 64-bit in version 1)
+pub struct TfdtDecoder;
+
+impl BoxDecoder for TfdtDecoder {
     fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        self.decode_with_context(r, hdr, version, flags, &DecodeContext::default())
+    }
+
+    fn decode_with_context(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        _version: Option<u8>,
-        _flags: Option<u32>,
+        version: Option<u8>,
+        flags: Option<u32>,
+        ctx: &DecodeContext,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        if buf.len() < 8 {
-            return Ok(BoxValue::Text(format!(
-                "ftyp: payload too short ({} bytes)",
-                buf.len()
-            )));
-        }
+        let version = version.unwrap_or(0);
+        let base_media_decode_time = if version == 1 {
+            r.read_u64::<BigEndian>()?
+        } else {
+            r.read_u32::<BigEndian>()? as u64
+        };
 
-        let major = &buf[0..4];
-        let mut minor_bytes = [0u8; 4];
-        minor_bytes.copy_from_slice(&buf[4..8]);
-        let minor = u32::from_be_bytes(minor_bytes);
+        let mut tfdt = TfdtData {
+            version,
+            flags: flags.unwrap_or(0),
+            base_media_decode_time,
+            base_media_decode_time_seconds: None,
+        };
+        tfdt.base_media_decode_time_seconds = ctx.timescale.and_then(|ts| tfdt.seconds(ts));
+        Ok(BoxValue::Structured(
+            StructuredData::TrackFragmentDecodeTime(tfdt),
+        ))
+    }
+}
 
-        let mut brands = Vec::new();
-        for chunk in buf[8..].chunks(4) {
-            if chunk.len() == 4 {
-                brands.push(String::from_utf8_lossy(chunk).to_string());
-            }
-        }
+// trex: track extends (fragment sample defaults)
+pub struct TrexDecoder;
 
-        Ok(BoxValue::Text(format!(
-            "major={} minor={} compatible={:?}",
-            String::from_utf8_lossy(major),
-            minor,
-            brands
+impl BoxDecoder for TrexDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        Ok(BoxValue::Structured(StructuredData::TrackExtends(
+            TrexData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                track_id: r.read_u32::<BigEndian>()?,
+                default_sample_description_index: r.read_u32::<BigEndian>()?,
+                default_sample_duration: r.read_u32::<BigEndian>()?,
+                default_sample_size: r.read_u32::<BigEndian>()?,
+                default_sample_flags: r.read_u32::<BigEndian>()?,
+            },
         )))
     }
 }
 
-// mvhd: timescale + duration
-pub struct MvhdDecoder;
+// tfra: random access points of one track (sync sample times and moof offsets)
+pub struct TfraDecoder;
 
-impl BoxDecoder for MvhdDecoder {
+impl BoxDecoder for TfraDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        _version: Option<u8>,
-        _flags: Option<u32>,
+        version: Option<u8>,
+        flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
+        let version = version.unwrap_or(0);
+        let track_id = r.read_u32::<BigEndian>()?;
+        // 26 reserved bits, then 2 bits each of length_size_of_traf_num,
+        // length_size_of_trun_num and length_size_of_sample_num
+        let sizes = r.read_u32::<BigEndian>()?;
+        let traf_number_size = ((sizes >> 4) & 0x3) as u8 + 1;
+        let trun_number_size = ((sizes >> 2) & 0x3) as u8 + 1;
+        let sample_number_size = (sizes & 0x3) as u8 + 1;
+        let entry_count = r.read_u32::<BigEndian>()?;
 
-        let version = cur.read_u8()?;
-        let _flags = {
-            let mut f = [0u8; 3];
-            cur.read_exact(&mut f)?;
-            ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32)
-        };
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let (time, moof_offset) = if version == 1 {
+                (r.read_u64::<BigEndian>()?, r.read_u64::<BigEndian>()?)
+            } else {
+                (
+                    r.read_u32::<BigEndian>()? as u64,
+                    r.read_u32::<BigEndian>()? as u64,
+                )
+            };
+            entries.push(TfraEntry {
+                time,
+                moof_offset,
+                traf_number: r.read_uint::<BigEndian>(traf_number_size as usize)? as u32,
+                trun_number: r.read_uint::<BigEndian>(trun_number_size as usize)? as u32,
+                sample_number: r.read_uint::<BigEndian>(sample_number_size as usize)? as u32,
+            });
+        }
 
-        let (timescale, duration) = if version == 1 {
-            let _creation = cur.read_u64::<BigEndian>()?;
-            let _mod = cur.read_u64::<BigEndian>()?;
-            let ts = cur.read_u32::<BigEndian>()?;
-            let dur = cur.read_u64::<BigEndian>()?;
-            (ts, dur as u64)
+        Ok(BoxValue::Structured(
+            StructuredData::TrackFragmentRandomAccess(TfraData {
+                version,
+                flags: flags.unwrap_or(0),
+                track_id,
+                traf_number_size,
+                trun_number_size,
+                sample_number_size,
+                entries,
+            }),
+        ))
+    }
+}
+
+// mehd: overall duration of a fragmented movie
+pub struct MehdDecoder;
+
+impl BoxDecoder for MehdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let fragment_duration = if version == 1 {
+            r.read_u64::<BigEndian>()?
         } else {
-            let _creation = cur.read_u32::<BigEndian>()?;
-            let _mod = cur.read_u32::<BigEndian>()?;
-            let ts = cur.read_u32::<BigEndian>()?;
-            let dur = cur.read_u32::<BigEndian>()? as u64;
-            (ts, dur)
+            r.read_u32::<BigEndian>()? as u64
         };
 
-        Ok(BoxValue::Text(format!(
-            "timescale={} duration={}",
-            timescale, duration
+        Ok(BoxValue::Structured(StructuredData::MovieExtendsHeader(
+            MehdData {
+                version,
+                flags: flags.unwrap_or(0),
+                fragment_duration,
+            },
         )))
     }
 }
 
-// tkhd: track id, duration, width, height
-pub struct TkhdDecoder;
+// mfro: size of the enclosing mfra, for finding it from the end of the file
+pub struct MfroDecoder;
 
-impl BoxDecoder for TkhdDecoder {
+impl BoxDecoder for MfroDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        _version: Option<u8>,
-        _flags: Option<u32>,
+        version: Option<u8>,
+        flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        if buf.len() < 4 {
-            return Ok(BoxValue::Text(format!(
-                "tkhd: payload too short ({} bytes)",
-                buf.len()
-            )));
-        }
+        Ok(BoxValue::Structured(
+            StructuredData::MovieFragmentRandomAccessOffset(MfroData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                size: r.read_u32::<BigEndian>()?,
+            }),
+        ))
+    }
+}
 
-        let mut pos = 0usize;
-        let version = buf[pos];
-        pos += 1;
-        if pos + 3 > buf.len() {
-            return Ok(BoxValue::Text("tkhd: truncated flags".into()));
-        }
+// trun: track fragment run
+pub struct TrunDecoder;
 
-        // Extract flags as a 24-bit big-endian value
-        let flags_bytes = [0, buf[pos], buf[pos + 1], buf[pos + 2]];
-        let flags_value = u32::from_be_bytes(flags_bytes);
-        pos += 3;
+impl BoxDecoder for TrunDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let flags = flags.unwrap_or(0);
 
-        let read_u32 = |pos: &mut usize| -> Option<u32> {
-            if *pos + 4 > buf.len() {
-                return None;
-            }
-            let v = u32::from_be_bytes(buf[*pos..*pos + 4].try_into().unwrap());
-            *pos += 4;
-            Some(v)
+        let sample_count = r.read_u32::<BigEndian>()?;
+        let data_offset = if flags & 0x000001 != 0 {
+            Some(r.read_i32::<BigEndian>()?)
+        } else {
+            None
         };
-        let read_u64 = |pos: &mut usize| -> Option<u64> {
-            if *pos + 8 > buf.len() {
-                return None;
-            }
-            let v = u64::from_be_bytes(buf[*pos..*pos + 8].try_into().unwrap());
-            *pos += 8;
-            Some(v)
+        let first_sample_flags = if flags & 0x000004 != 0 {
+            Some(r.read_u32::<BigEndian>()?)
+        } else {
+            None
         };
 
-        let track_id;
-        let duration;
+        let mut samples = Vec::new();
+        for _ in 0..sample_count {
+            let mut field = |bit: u32| -> anyhow::Result<Option<u32>> {
+                Ok(if flags & bit != 0 {
+                    Some(r.read_u32::<BigEndian>()?)
+                } else {
+                    None
+                })
+            };
+            let duration = field(0x000100)?;
+            let size = field(0x000200)?;
+            let sample_flags = field(0x000400)?;
+            let composition_time_offset = field(0x000800)?.map(|v| {
+                if version == 0 {
+                    v as i64
+                } else {
+                    v as i32 as i64
+                }
+            });
+            samples.push(TrunSample {
+                duration,
+                size,
+                flags: sample_flags,
+                composition_time_offset,
+            });
+        }
 
-        if version == 1 {
-            // creation_time (8), modification_time (8), track_id (4), reserved (4), duration (8)
-            if read_u64(&mut pos).is_none() || read_u64(&mut pos).is_none() {
-                return Ok(BoxValue::Text(
-                    "tkhd: truncated creation/modification".into(),
-                ));
-            }
-            track_id = read_u32(&mut pos).unwrap_or(0);
-            let _ = read_u32(&mut pos); // reserved
-            duration = read_u64(&mut pos).unwrap_or(0);
-            eprintln!(
-                "DEBUG tkhd v1: track_id={}, duration={}",
-                track_id, duration
-            );
-        } else {
-            // For version 0, read two 8-byte timestamps then the fields
-            let _creation_time = read_u64(&mut pos).unwrap_or(0);
-            let _modification_time = read_u64(&mut pos).unwrap_or(0);
+        Ok(BoxValue::Structured(StructuredData::TrackRun(TrunData {
+            version,
+            flags,
+            sample_count,
+            data_offset,
+            first_sample_flags,
+            samples,
+        })))
+    }
+}
 
-            track_id = read_u32(&mut pos).unwrap_or(0);
-            let _reserved = read_u32(&mut pos).unwrap_or(0);
-            duration = read_u32(&mut pos).unwrap_or(0) as u64;
-        }
+// elst: edit list
+pub struct ElstDecoder;
 
-        // reserved[2]
-        for _ in 0..2 {
-            let _ = read_u32(&mut pos);
+impl BoxDecoder for ElstDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        // elst is a FullBox: version/flags were already consumed by the parser.
+        let version = version.unwrap_or(0);
+        let entry_count = r.read_u32::<BigEndian>()?;
+
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let (segment_duration, media_time) = if version == 1 {
+                (r.read_u64::<BigEndian>()?, r.read_i64::<BigEndian>()?)
+            } else {
+                (
+                    r.read_u32::<BigEndian>()? as u64,
+                    r.read_i32::<BigEndian>()? as i64,
+                )
+            };
+            entries.push(ElstEntry {
+                segment_duration,
+                media_time,
+                media_rate_integer: r.read_i16::<BigEndian>()?,
+                media_rate_fraction: r.read_i16::<BigEndian>()?,
+            });
         }
 
-        // layer/alt_group/volume/reserved (8 bytes)
-        if pos + 8 <= buf.len() {
-            pos += 8;
+        Ok(BoxValue::Structured(StructuredData::EditList(ElstData {
+            version,
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        })))
+    }
+}
+
+// sgpd: sample group description
+pub struct SgpdDecoder;
+
+impl BoxDecoder for SgpdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        let mut grouping_type = [0u8; 4];
+        cur.read_exact(&mut grouping_type)?;
+        let default_length = if version >= 1 {
+            Some(cur.read_u32::<BigEndian>()?)
         } else {
-            // we still have track/duration, just don't try width/height
-            return Ok(BoxValue::Text(format!(
-                "track_id={} duration={} (no width/height, short payload)",
-                track_id, duration
-            )));
+            None
+        };
+        let default_group_description_index = if version >= 2 {
+            Some(cur.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
+        let entry_count = cur.read_u32::<BigEndian>()?;
+
+        // Version 0 entries carry no length; only fixed-size group types can be split.
+        let fixed_length = match default_length {
+            Some(0) => None,
+            Some(n) => Some(n),
+            None => match &grouping_type {
+                b"roll" | b"prol" => Some(2),
+                b"rap " | b"sync" | b"tele" => Some(1),
+                _ => anyhow::bail!(
+                    "sgpd v0: unknown entry size for '{}'",
+                    FourCC(grouping_type)
+                ),
+            },
+        };
+
+        let mut entries = Vec::new();
+        for i in 0..entry_count {
+            let len = match fixed_length {
+                Some(n) => n,
+                // description_length precedes each entry when default_length is 0
+                None => cur.read_u32::<BigEndian>().map_err(|_| {
+                    anyhow::anyhow!("sgpd entry {} length runs past the end of the box", i)
+                })?,
+            } as usize;
+            let start = cur.position() as usize;
+            let payload = buf
+                .get(start..start.saturating_add(len))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "sgpd entry {} ({} bytes) runs past the end of the box",
+                        i,
+                        len
+                    )
+                })?
+                .to_vec();
+            cur.set_position((start + len) as u64);
+
+            let roll_distance = match (&grouping_type, payload.as_slice()) {
+                (b"roll" | b"prol", [hi, lo, ..]) => Some(i16::from_be_bytes([*hi, *lo])),
+                _ => None,
+            };
+            entries.push(SampleGroupEntry {
+                payload,
+                roll_distance,
+            });
         }
 
-        // matrix (36 bytes)
-        if pos + 36 <= buf.len() {
-            pos += 36;
+        Ok(BoxValue::Structured(
+            StructuredData::SampleGroupDescription(SgpdData {
+                version,
+                flags: flags.unwrap_or(0),
+                grouping_type: FourCC(grouping_type).to_string(),
+                default_length,
+                default_group_description_index,
+                entries,
+            }),
+        ))
+    }
+}
+
+// sbgp: sample-to-group runs
+pub struct SbgpDecoder;
+
+impl BoxDecoder for SbgpDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let mut grouping_type = [0u8; 4];
+        r.read_exact(&mut grouping_type)?;
+        let grouping_type_parameter = if version >= 1 {
+            Some(r.read_u32::<BigEndian>()?)
         } else {
-            return Ok(BoxValue::Text(format!(
-                "track_id={} duration={} (no width/height, short payload)",
-                track_id, duration
-            )));
+            None
+        };
+        let entry_count = r.read_u32::<BigEndian>()?;
+
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            entries.push(SbgpEntry {
+                sample_count: r.read_u32::<BigEndian>()?,
+                group_description_index: r.read_u32::<BigEndian>()?,
+            });
         }
 
-        // width / height
-        let (width, height) = if pos + 8 <= buf.len() {
-            let width = u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
-            let height = u32::from_be_bytes(buf[pos + 4..pos + 8].try_into().unwrap());
-            (width as f32 / 65536.0, height as f32 / 65536.0)
+        Ok(BoxValue::Structured(StructuredData::SampleToGroup(
+            SbgpData {
+                version,
+                flags: flags.unwrap_or(0),
+                grouping_type: FourCC(grouping_type).to_string(),
+                grouping_type_parameter,
+                entries,
+            },
+        )))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Optional `aux_info_type` / `aux_info_type_parameter` of saiz and saio.
+fn read_aux_info_type(
+    r: &mut dyn Read,
+    flags: Option<u32>,
+) -> anyhow::Result<(Option<String>, Option<u32>)> {
+    if flags.unwrap_or(0) & 1 == 0 {
+        return Ok((None, None));
+    }
+    let mut typ = [0u8; 4];
+    r.read_exact(&mut typ)?;
+    Ok((
+        Some(FourCC(typ).to_string()),
+        Some(r.read_u32::<BigEndian>()?),
+    ))
+}
+
+// tenc: CENC track encryption defaults
+pub struct TencDecoder;
+
+impl BoxDecoder for TencDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        r.read_u8()?; // reserved
+        let pattern = r.read_u8()?;
+        let (default_crypt_byte_block, default_skip_byte_block) = if version >= 1 {
+            (Some(pattern >> 4), Some(pattern & 0x0F))
         } else {
-            (0.0, 0.0)
+            (None, None)
         };
+        let default_is_protected = r.read_u8()?;
+        let default_per_sample_iv_size = r.read_u8()?;
+        let mut kid = [0u8; 16];
+        r.read_exact(&mut kid)?;
+
+        let default_constant_iv = if default_is_protected == 1 && default_per_sample_iv_size == 0 {
+            let mut iv = vec![0u8; r.read_u8()? as usize];
+            r.read_exact(&mut iv)?;
+            Some(hex(&iv))
+        } else {
+            None
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackEncryption(
+            TencData {
+                version,
+                flags: flags.unwrap_or(0),
+                default_crypt_byte_block,
+                default_skip_byte_block,
+                default_is_protected,
+                default_per_sample_iv_size,
+                default_kid: hex(&kid),
+                default_constant_iv,
+            },
+        )))
+    }
+}
+
+// senc: per-sample IVs and subsample ranges
+pub struct SencDecoder;
+
+impl SencDecoder {
+    const USE_SUBSAMPLES: u32 = 0x2;
+
+    fn parse(buf: &[u8], use_subsamples: bool, iv_size: u8) -> anyhow::Result<Vec<SencSample>> {
+        let mut cur = Cursor::new(buf);
+        let sample_count = cur.read_u32::<BigEndian>()?;
+        let mut samples = Vec::new();
+        for _ in 0..sample_count {
+            let mut iv = vec![0u8; iv_size as usize];
+            cur.read_exact(&mut iv)?;
+            let mut subsamples = Vec::new();
+            if use_subsamples {
+                for _ in 0..cur.read_u16::<BigEndian>()? {
+                    subsamples.push(SencSubsample {
+                        clear_bytes: cur.read_u16::<BigEndian>()?,
+                        protected_bytes: cur.read_u32::<BigEndian>()?,
+                    });
+                }
+            }
+            samples.push(SencSample {
+                iv: hex(&iv),
+                subsamples,
+            });
+        }
+        if cur.position() != buf.len() as u64 {
+            anyhow::bail!(
+                "senc: {} bytes left over with a {}-byte IV",
+                buf.len() as u64 - cur.position(),
+                iv_size
+            );
+        }
+        Ok(samples)
+    }
+}
+
+impl BoxDecoder for SencDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        self.decode_with_context(r, hdr, version, flags, &DecodeContext::default())
+    }
 
-        let data = TkhdData {
-            version,
-            flags: flags_value,
-            track_id,
-            duration,
-            width,
-            height,
+    fn decode_with_context(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+        ctx: &DecodeContext,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let use_subsamples = flags.unwrap_or(0) & Self::USE_SUBSAMPLES != 0;
+
+        // The IV size lives in tenc; without it, take the first size the
+        // entries parse cleanly with.
+        let (per_sample_iv_size, samples) = match ctx.per_sample_iv_size {
+            Some(size) => (size, Self::parse(&buf, use_subsamples, size)?),
+            None => [8u8, 16, 0]
+                .into_iter()
+                .find_map(|size| {
+                    Self::parse(&buf, use_subsamples, size)
+                        .ok()
+                        .map(|s| (size, s))
+                })
+                .ok_or_else(|| anyhow::anyhow!("senc: no IV size fits the entries"))?,
         };
 
-        Ok(BoxValue::Structured(StructuredData::TrackHeader(data)))
+        Ok(BoxValue::Structured(StructuredData::SampleEncryption(
+            SencData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                per_sample_iv_size,
+                samples,
+            },
+        )))
     }
 }
 
-// mdhd: timescale, duration, language
-pub struct MdhdDecoder;
+// pssh: DRM system header
+pub struct PsshDecoder;
 
-impl BoxDecoder for MdhdDecoder {
+impl BoxDecoder for PsshDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
-        _hdr: &BoxHeader,
+        hdr: &BoxHeader,
         version: Option<u8>,
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let creation_time = r.read_u32::<BigEndian>()?;
-        let modification_time = r.read_u32::<BigEndian>()?;
-        let timescale = r.read_u32::<BigEndian>()?;
-        let duration = r.read_u32::<BigEndian>()?;
-        let language_code = r.read_u16::<BigEndian>()?;
-        let _pre_defined = r.read_u16::<BigEndian>()?;
-
-        let lang = lang_from_u16(language_code);
-
-        let data = MdhdData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            creation_time,
-            modification_time,
-            timescale,
-            duration,
-            language: lang,
+        let version = version.unwrap_or(0);
+        let mut system_id = [0u8; 16];
+        r.read_exact(&mut system_id)?;
+
+        let mut kids = Vec::new();
+        if version > 0 {
+            for _ in 0..r.read_u32::<BigEndian>()? {
+                let mut kid = [0u8; 16];
+                r.read_exact(&mut kid)?;
+                kids.push(hex(&kid));
+            }
+        }
+        let data_size = r.read_u32::<BigEndian>()?;
+        // version/flags, system ID, KID list and the size field precede the data
+        let kid_list = if version > 0 {
+            4 + 16 * kids.len() as u64
+        } else {
+            0
         };
+        let data_offset = hdr.start + hdr.header_size + 4 + 16 + kid_list + 4;
 
-        Ok(BoxValue::Structured(StructuredData::MediaHeader(data)))
+        let id = hex(&system_id);
+        Ok(BoxValue::Structured(
+            StructuredData::ProtectionSystemHeader(PsshData {
+                version,
+                flags: flags.unwrap_or(0),
+                system_id: format!(
+                    "{}-{}-{}-{}-{}",
+                    &id[0..8],
+                    &id[8..12],
+                    &id[12..16],
+                    &id[16..20],
+                    &id[20..]
+                ),
+                system_name: PROTECTION_SYSTEMS
+                    .iter()
+                    .find(|(uuid, _)| *uuid == system_id)
+                    .map(|(_, name)| name.to_string()),
+                kids,
+                data_size,
+                data_offset,
+            }),
+        ))
     }
 }
 
-// hdlr: handler type + name
-pub struct HdlrDecoder;
+// saiz: sample auxiliary information sizes
+pub struct SaizDecoder;
 
-impl BoxDecoder for HdlrDecoder {
+impl BoxDecoder for SaizDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -515,43 +3571,75 @@ impl BoxDecoder for HdlrDecoder {
         version: Option<u8>,
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        use byteorder::{BigEndian, ReadBytesExt};
+        let (aux_info_type, aux_info_type_parameter) = read_aux_info_type(r, flags)?;
+        let default_sample_info_size = r.read_u8()?;
+        let sample_count = r.read_u32::<BigEndian>()?;
+        let mut sample_info_sizes = Vec::new();
+        if default_sample_info_size == 0 {
+            sample_info_sizes = read_all(r)?;
+            if sample_info_sizes.len() < sample_count as usize {
+                anyhow::bail!(
+                    "saiz lists {} of {} sample sizes",
+                    sample_info_sizes.len(),
+                    sample_count
+                );
+            }
+            sample_info_sizes.truncate(sample_count as usize);
+        }
 
-        // pre_defined (4 bytes) + handler_type (4 bytes)
-        let _pre_defined = r.read_u32::<BigEndian>()?;
-        let mut handler_type = [0u8; 4];
-        r.read_exact(&mut handler_type)?;
+        Ok(BoxValue::Structured(StructuredData::SampleAuxInfoSizes(
+            SaizData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                aux_info_type,
+                aux_info_type_parameter,
+                default_sample_info_size,
+                sample_count,
+                sample_info_sizes,
+            },
+        )))
+    }
+}
 
-        // reserved (3 * 4 bytes)
-        let mut reserved = [0u8; 12];
-        r.read_exact(&mut reserved)?;
+// saio: sample auxiliary information offsets
+pub struct SaioDecoder;
 
-        // name: null-terminated string (or just rest of box)
-        let mut name_bytes = Vec::new();
-        r.read_to_end(&mut name_bytes)?;
-        // strip trailing nulls
-        while name_bytes.last() == Some(&0) {
-            name_bytes.pop();
+impl BoxDecoder for SaioDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let (aux_info_type, aux_info_type_parameter) = read_aux_info_type(r, flags)?;
+        let entry_count = r.read_u32::<BigEndian>()?;
+        let mut offsets = Vec::new();
+        for _ in 0..entry_count {
+            offsets.push(if version == 0 {
+                r.read_u32::<BigEndian>()? as u64
+            } else {
+                r.read_u64::<BigEndian>()?
+            });
         }
-        let name = String::from_utf8_lossy(&name_bytes).to_string();
-
-        let handler_str = std::str::from_utf8(&handler_type).unwrap_or("????");
-
-        let data = HdlrData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            handler_type: handler_str.to_string(),
-            name,
-        };
 
-        Ok(BoxValue::Structured(StructuredData::HandlerReference(data)))
+        Ok(BoxValue::Structured(StructuredData::SampleAuxInfoOffsets(
+            SaioData {
+                version,
+                flags: flags.unwrap_or(0),
+                aux_info_type,
+                aux_info_type_parameter,
+                offsets,
+            },
+        )))
     }
 }
 
-// sidx: segment index summary
-pub struct SidxDecoder;
+// dvcC / dvvC / dvwC: Dolby Vision configuration
+pub struct DoviDecoder;
 
-impl BoxDecoder for SidxDecoder {
+impl BoxDecoder for DoviDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -560,43 +3648,17 @@ impl BoxDecoder for SidxDecoder {
         _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        let version = cur.read_u8()?;
-        let _flags = {
-            let mut f = [0u8; 3];
-            cur.read_exact(&mut f)?;
-            ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32)
-        };
-
-        let _ref_id = cur.read_u32::<BigEndian>()?;
-        let timescale = cur.read_u32::<BigEndian>()?;
-
-        let (earliest, first_offset) = if version == 1 {
-            let earliest = cur.read_u64::<BigEndian>()?;
-            let first = cur.read_u64::<BigEndian>()?;
-            (earliest, first)
-        } else {
-            let earliest = cur.read_u32::<BigEndian>()? as u64;
-            let first = cur.read_u32::<BigEndian>()? as u64;
-            (earliest, first)
-        };
-
-        let _reserved = cur.read_u16::<BigEndian>()?;
-        let ref_count = cur.read_u16::<BigEndian>()?;
-
-        Ok(BoxValue::Text(format!(
-            "timescale={} earliest_presentation_time={} first_offset={} references={}",
-            timescale, earliest, first_offset, ref_count
-        )))
+        let data = DolbyVisionConfig::parse(&buf)?;
+        Ok(BoxValue::Structured(
+            StructuredData::DolbyVisionConfiguration(data),
+        ))
     }
 }
 
-// stsd: list sample entry formats, maybe WxH
-// ---- stsd decoder: codec + width/height for first entry -----------------
-pub struct StsdDecoder;
+// mime: content type string
+pub struct MimeDecoder;
 
-impl BoxDecoder for StsdDecoder {
+impl BoxDecoder for MimeDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -604,87 +3666,52 @@ impl BoxDecoder for StsdDecoder {
         _version: Option<u8>,
         _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        use byteorder::{BigEndian, ReadBytesExt};
-
-        // stsd is a FullBox; our reader is already positioned at payload:
-        // u32 entry_count
-        // [ SampleEntry entries... ]
+        let buf = read_all(r)?;
+        let content_type = c_string(&buf, &mut 0);
+        Ok(BoxValue::Structured(StructuredData::Mime(MimeData {
+            content_type,
+        })))
+    }
+}
 
-        let entry_count = r.read_u32::<BigEndian>()?;
-        if entry_count == 0 {
-            return Ok(BoxValue::Text("entry_count=0".to_string()));
-        }
+// iinf: item information, a list of infe entries
+pub struct IinfDecoder;
 
-        // First sample entry only (good enough for mp4info-like summary)
-        let entry_size = r.read_u32::<BigEndian>()?;
-
-        let mut codec_bytes = [0u8; 4];
-        r.read_exact(&mut codec_bytes)?;
-        let codec = std::str::from_utf8(&codec_bytes)
-            .unwrap_or("????")
-            .to_string();
-
-        // Now we’re at SampleEntry fields.
-        // For visual sample entries (avc1/hvc1/etc.), layout is:
-        //
-        // 6 reserved bytes
-        // u16 data_reference_index
-        // 16 bytes pre_defined / reserved
-        // u16 width
-        // u16 height
-        //
-        // For audio sample entries, this layout is different, so we only
-        // try to read width/height for known video codecs.
-        let visual_codecs = ["avc1", "hvc1", "hev1", "vp09", "av01"];
-
-        let mut width: Option<u32> = None;
-        let mut height: Option<u32> = None;
-
-        if visual_codecs.contains(&codec.as_str()) {
-            // Skip reserved + data_reference_index
-            let mut skip = [0u8; 6 + 2 + 16];
-            r.read_exact(&mut skip)?;
-
-            let w = r.read_u16::<BigEndian>()?;
-            let h = r.read_u16::<BigEndian>()?;
-            width = Some(w as u32);
-            height = Some(h as u32);
-        }
+impl BoxDecoder for IinfDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let entry_count = if version == 0 {
+            r.read_u16::<BigEndian>()? as u32
+        } else {
+            r.read_u32::<BigEndian>()?
+        };
+        let buf = read_all(r)?;
 
-        let mut parts = Vec::new();
-        parts.push(format!("entry_count={}", entry_count));
-        parts.push(format!("codec={}", codec));
-        if let Some(w) = width {
-            parts.push(format!("width={}", w));
-        }
-        if let Some(h) = height {
-            parts.push(format!("height={}", h));
-        }
+        let entries = child_boxes(&buf, 0)
+            .into_iter()
+            .filter(|(typ, _)| typ == b"infe")
+            .filter_map(|(_, body)| parse_infe(body))
+            .collect();
 
-        // Create structured data
-        let data = StsdData {
-            version: _version.unwrap_or(0),
-            flags: _flags.unwrap_or(0),
+        Ok(BoxValue::Structured(StructuredData::ItemInfo(IinfData {
+            version,
+            flags: flags.unwrap_or(0),
             entry_count,
-            entries: vec![SampleEntry {
-                size: entry_size,
-                codec,
-                data_reference_index: 1, // Default value
-                width: width.map(|w| w as u16),
-                height: height.map(|h| h as u16),
-            }],
-        };
-
-        Ok(BoxValue::Structured(StructuredData::SampleDescription(
-            data,
-        )))
+            entries,
+        })))
     }
 }
 
-// stts: time-to-sample
-pub struct SttsDecoder;
+// iref: typed item references; IDs are 16-bit in version 0 and 32-bit in version 1
+pub struct IrefDecoder;
 
-impl BoxDecoder for SttsDecoder {
+impl BoxDecoder for IrefDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -692,40 +3719,88 @@ impl BoxDecoder for SttsDecoder {
         version: Option<u8>,
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        // and stripped from the payload. We start directly with the box-specific data.
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut entries = Vec::new();
 
-        for _ in 0..entry_count {
-            let sample_count = cur.read_u32::<BigEndian>()?;
-            let sample_delta = cur.read_u32::<BigEndian>()?;
-            entries.push(SttsEntry {
-                sample_count,
-                sample_delta,
+        let mut references = Vec::new();
+        for (typ, body) in child_boxes(&buf, 0) {
+            let mut cur = Cursor::new(body);
+            let read_id = |cur: &mut Cursor<&[u8]>| -> std::io::Result<u32> {
+                if version == 0 {
+                    Ok(cur.read_u16::<BigEndian>()? as u32)
+                } else {
+                    cur.read_u32::<BigEndian>()
+                }
+            };
+            let from_item_id = read_id(&mut cur)?;
+            let count = cur.read_u16::<BigEndian>()?;
+            let to_item_ids = (0..count)
+                .map(|_| read_id(&mut cur))
+                .collect::<std::io::Result<_>>()?;
+            references.push(ItemReference {
+                reference_type: FourCC(typ).to_string(),
+                from_item_id,
+                to_item_ids,
             });
         }
 
-        let data = SttsData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            entries,
-        };
-
-        Ok(BoxValue::Structured(StructuredData::DecodingTimeToSample(
-            data,
+        Ok(BoxValue::Structured(StructuredData::ItemReference(
+            IrefData {
+                version,
+                flags: flags.unwrap_or(0),
+                references,
+            },
         )))
     }
 }
 
-// stss: sync sample table
-pub struct StssDecoder;
+/// Parse an infe body, including its version/flags word.
+fn parse_infe(body: &[u8]) -> Option<ItemInfoEntry> {
+    let version = *body.first()?;
+    let mut cur = Cursor::new(body.get(4..)?);
+    let item_id = if version >= 3 {
+        cur.read_u32::<BigEndian>().ok()?
+    } else {
+        cur.read_u16::<BigEndian>().ok()? as u32
+    };
+    let protection_index = cur.read_u16::<BigEndian>().ok()?;
+    let item_type = if version >= 2 {
+        let mut t = [0u8; 4];
+        cur.read_exact(&mut t).ok()?;
+        Some(FourCC(t).to_string())
+    } else {
+        None
+    };
+
+    let rest = &cur.get_ref()[cur.position() as usize..];
+    let mut pos = 0;
+    let item_name = c_string(rest, &mut pos);
+    let mut entry = ItemInfoEntry {
+        version,
+        item_id,
+        protection_index,
+        item_type: item_type.clone(),
+        item_name,
+        content_type: None,
+        content_encoding: None,
+        uri_type: None,
+    };
+    let optional = |pos: &mut usize| Some(c_string(rest, pos)).filter(|s| !s.is_empty());
+    match item_type.as_deref() {
+        None | Some("mime") => {
+            entry.content_type = optional(&mut pos);
+            entry.content_encoding = optional(&mut pos);
+        }
+        Some("uri ") => entry.uri_type = optional(&mut pos),
+        _ => {}
+    }
+    Some(entry)
+}
 
-impl BoxDecoder for StssDecoder {
+// iloc: item locations (extents in the file or in idat)
+pub struct IlocDecoder;
+
+impl BoxDecoder for IlocDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -733,114 +3808,187 @@ impl BoxDecoder for StssDecoder {
         version: Option<u8>,
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
+        let version = version.unwrap_or(0);
+        let sizes = r.read_u16::<BigEndian>()?;
+        let offset_size = (sizes >> 12) as u8;
+        let length_size = ((sizes >> 8) & 0xF) as u8;
+        let base_offset_size = ((sizes >> 4) & 0xF) as u8;
+        let index_size = if version >= 1 { (sizes & 0xF) as u8 } else { 0 };
+
+        // Field widths are 0, 4 or 8 bytes.
+        let read_sized = |r: &mut dyn Read, size: u8| -> anyhow::Result<u64> {
+            Ok(match size {
+                0 => 0,
+                4 => r.read_u32::<BigEndian>()? as u64,
+                8 => r.read_u64::<BigEndian>()?,
+                other => anyhow::bail!("iloc: unsupported field size {}", other),
+            })
+        };
 
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut sample_numbers = Vec::new();
+        let item_count = if version < 2 {
+            r.read_u16::<BigEndian>()? as u32
+        } else {
+            r.read_u32::<BigEndian>()?
+        };
 
-        for _ in 0..entry_count {
-            sample_numbers.push(cur.read_u32::<BigEndian>()?);
-        }
+        let mut items = Vec::new();
+        for _ in 0..item_count {
+            let item_id = if version < 2 {
+                r.read_u16::<BigEndian>()? as u32
+            } else {
+                r.read_u32::<BigEndian>()?
+            };
+            let construction_method = if version >= 1 {
+                (r.read_u16::<BigEndian>()? & 0xF) as u8
+            } else {
+                0
+            };
+            let data_reference_index = r.read_u16::<BigEndian>()?;
+            let base_offset = read_sized(r, base_offset_size)?;
+            let extent_count = r.read_u16::<BigEndian>()?;
+
+            let mut extents = Vec::new();
+            for _ in 0..extent_count {
+                let index = if index_size > 0 {
+                    Some(read_sized(r, index_size)?)
+                } else {
+                    None
+                };
+                extents.push(ItemExtent {
+                    index,
+                    offset: read_sized(r, offset_size)?,
+                    length: read_sized(r, length_size)?,
+                });
+            }
 
-        let data = StssData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            sample_numbers,
-        };
+            items.push(ItemLocation {
+                item_id,
+                construction_method,
+                data_reference_index,
+                base_offset,
+                extents,
+            });
+        }
 
-        Ok(BoxValue::Structured(StructuredData::SyncSample(data)))
+        Ok(BoxValue::Structured(StructuredData::ItemLocation(
+            IlocData {
+                version,
+                flags: flags.unwrap_or(0),
+                items,
+            },
+        )))
     }
 }
 
-// ctts: composition time to sample
-pub struct CttsDecoder;
+/// Read a null-terminated UTF-8 string starting at `*pos`, advancing past the
+/// terminator. A missing terminator takes the rest of the buffer.
+fn c_string(buf: &[u8], pos: &mut usize) -> String {
+    let rest = buf.get(*pos..).unwrap_or_default();
+    let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+    *pos += (len + 1).min(rest.len());
+    String::from_utf8_lossy(&rest[..len]).to_string()
+}
 
-impl BoxDecoder for CttsDecoder {
+// avcC: AVC decoder configuration record
+pub struct AvcCDecoder;
+
+impl BoxDecoder for AvcCDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
+        let data = AvcConfig::parse(&buf)?;
+        Ok(BoxValue::Structured(StructuredData::AvcConfig(data)))
+    }
+}
 
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut entries = Vec::new();
+// hvcC: HEVC decoder configuration record
+pub struct HvcCDecoder;
 
-        for _ in 0..entry_count {
-            let sample_count = cur.read_u32::<BigEndian>()?;
-            // Note: In version 1, sample_offset can be signed, but since we don't have access
-            // to the parsed version here, we assume version 0 behavior (unsigned)
-            let sample_offset = cur.read_u32::<BigEndian>()? as i32;
-            entries.push(CttsEntry {
-                sample_count,
-                sample_offset,
-            });
-        }
+impl BoxDecoder for HvcCDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let data = HevcConfig::parse(&buf)?;
+        Ok(BoxValue::Structured(StructuredData::HevcConfig(data)))
+    }
+}
 
-        let data = CttsData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            entries,
-        };
+// esds: MPEG-4 elementary stream descriptor
+pub struct EsdsDecoder;
 
+impl BoxDecoder for EsdsDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let data = EsdsData::parse(&buf)?;
         Ok(BoxValue::Structured(
-            StructuredData::CompositionTimeToSample(data),
+            StructuredData::ElementaryStreamDescriptor(data),
         ))
     }
 }
 
-// stsc: sample-to-chunk
-pub struct StscDecoder;
+// chan: channel layout
+pub struct ChanDecoder;
+
+impl BoxDecoder for ChanDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let data = ChannelLayout::parse(&buf)?;
+        Ok(BoxValue::Structured(StructuredData::ChannelLayout(data)))
+    }
+}
+
+// tref children (vdep, hint, cdsc, ...): list of referenced track IDs
+pub struct TrefTypeDecoder;
 
-impl BoxDecoder for StscDecoder {
+impl BoxDecoder for TrefTypeDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
-        _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut entries = Vec::new();
-
-        for _ in 0..entry_count {
-            let first_chunk = cur.read_u32::<BigEndian>()?;
-            let samples_per_chunk = cur.read_u32::<BigEndian>()?;
-            let sample_description_index = cur.read_u32::<BigEndian>()?;
-            entries.push(StscEntry {
-                first_chunk,
-                samples_per_chunk,
-                sample_description_index,
-            });
-        }
-
-        let data = StscData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            entries,
-        };
-
-        Ok(BoxValue::Structured(StructuredData::SampleToChunk(data)))
+        let track_ids = buf
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+            .collect();
+
+        Ok(BoxValue::Structured(StructuredData::TrackReference(
+            TrackReferenceData {
+                reference_type: hdr.typ.to_string(),
+                track_ids,
+            },
+        )))
     }
 }
 
-// stsz: sample sizes
-pub struct StszDecoder;
+// dref: data reference entries (url / urn), telling where media data lives
+pub struct DrefDecoder;
 
-impl BoxDecoder for StszDecoder {
+impl BoxDecoder for DrefDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -848,70 +3996,121 @@ impl BoxDecoder for StszDecoder {
         version: Option<u8>,
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
+        let entry_count = r.read_u32::<BigEndian>()?;
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let sample_size = cur.read_u32::<BigEndian>()?;
-        let sample_count = cur.read_u32::<BigEndian>()?;
-        let mut sample_sizes = Vec::new();
-
-        // If sample_size is 0, each sample has its own size
-        if sample_size == 0 {
-            for _ in 0..sample_count {
-                sample_sizes.push(cur.read_u32::<BigEndian>()?);
-            }
-        }
-
-        let data = StszData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            sample_size,
-            sample_count,
-            sample_sizes,
-        };
 
-        Ok(BoxValue::Structured(StructuredData::SampleSize(data)))
+        let entries = child_boxes(&buf, 0)
+            .into_iter()
+            .map(|(typ, body)| {
+                let entry_flags = if body.len() >= 4 {
+                    u32::from_be_bytes([0, body[1], body[2], body[3]])
+                } else {
+                    0
+                };
+                let self_contained = entry_flags & 1 != 0;
+
+                // url: location; urn: name then location. Both are null-terminated
+                // and absent when the entry is self-contained.
+                let mut strings = body
+                    .get(4..)
+                    .unwrap_or_default()
+                    .split(|&b| b == 0)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| String::from_utf8_lossy(s).to_string());
+                let (name, location) = match &typ {
+                    b"urn " => (strings.next(), strings.next()),
+                    _ => (None, strings.next()),
+                };
+
+                DataReferenceEntry {
+                    entry_type: FourCC(typ).to_string(),
+                    flags: entry_flags,
+                    self_contained,
+                    name,
+                    location,
+                }
+            })
+            .collect();
+
+        Ok(BoxValue::Structured(StructuredData::DataReference(
+            DrefData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                entry_count,
+                entries,
+            },
+        )))
     }
 }
 
-// stco: 32-bit chunk offsets
-pub struct StcoDecoder;
+// clef / prof / enof: 16.16 fixed-point width and height
+pub struct TrackApertureDecoder;
 
-impl BoxDecoder for StcoDecoder {
+impl BoxDecoder for TrackApertureDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
-        _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut chunk_offsets = Vec::new();
+        let width = r.read_u32::<BigEndian>()? as f32 / 65536.0;
+        let height = r.read_u32::<BigEndian>()? as f32 / 65536.0;
+
+        Ok(BoxValue::Structured(StructuredData::TrackAperture(
+            TrackApertureData {
+                mode: hdr.typ.to_string(),
+                width,
+                height,
+            },
+        )))
+    }
+}
 
-        for _ in 0..entry_count {
-            chunk_offsets.push(cur.read_u32::<BigEndian>()?);
+/// Decode the hdlr name, which ISO files write null-terminated and QuickTime
+/// files write as a Pascal string (leading length byte, no terminator).
+fn hdlr_name(bytes: &[u8]) -> String {
+    let text = match bytes.first() {
+        Some(&len)
+            if (len as usize + 1 == bytes.len() && bytes.last() != Some(&0))
+                || (len < 0x20 && (len as usize) < bytes.len()) =>
+        {
+            &bytes[1..1 + len as usize]
         }
+        _ => bytes.split(|&b| b == 0).next().unwrap_or_default(),
+    };
+    String::from_utf8_lossy(text).to_string()
+}
 
-        let data = StcoData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            chunk_offsets,
-        };
+/// `uuid` usertype of the XMP box (be7acfcb-97a9-42e8-9c71-999491e3afac).
+pub const XMP_UUID: [u8; 16] = [
+    0xbe, 0x7a, 0xcf, 0xcb, 0x97, 0xa9, 0x42, 0xe8, 0x9c, 0x71, 0x99, 0x94, 0x91, 0xe3, 0xaf, 0xac,
+];
 
-        Ok(BoxValue::Structured(StructuredData::ChunkOffset(data)))
+// XMP_ / XMP uuid: XMP packet (UTF-8 XML)
+pub struct XmpDecoder;
+
+impl BoxDecoder for XmpDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        // Some writers NUL-terminate or NUL-pad the packet.
+        let end = buf.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        Ok(BoxValue::Text(
+            String::from_utf8_lossy(&buf[..end]).to_string(),
+        ))
     }
 }
 
-// co64: 64-bit chunk offsets
-pub struct Co64Decoder;
+// keys: key table for keyed (mdta) metadata
+pub struct KeysDecoder;
 
-impl BoxDecoder for Co64Decoder {
+impl BoxDecoder for KeysDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -919,32 +4118,33 @@ impl BoxDecoder for Co64Decoder {
         version: Option<u8>,
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
+        let entry_count = r.read_u32::<BigEndian>()?;
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut chunk_offsets = Vec::new();
 
-        for _ in 0..entry_count {
-            chunk_offsets.push(cur.read_u64::<BigEndian>()?);
-        }
-
-        let data = Co64Data {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            chunk_offsets,
-        };
-
-        Ok(BoxValue::Structured(StructuredData::ChunkOffset64(data)))
+        // Each entry is laid out like a box: size, namespace, then the key name.
+        let keys = child_boxes(&buf, 0)
+            .into_iter()
+            .take(entry_count as usize)
+            .map(|(namespace, name)| MetadataKey {
+                namespace: FourCC(namespace).to_string(),
+                name: String::from_utf8_lossy(name).to_string(),
+            })
+            .collect();
+
+        Ok(BoxValue::Structured(StructuredData::MetadataKeys(
+            KeysData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                keys,
+            },
+        )))
     }
 }
 
-// elst: edit list
-pub struct ElstDecoder;
+// ilst: metadata items, each holding one or more `data` boxes
+pub struct IlstDecoder;
 
-impl BoxDecoder for ElstDecoder {
+impl BoxDecoder for IlstDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -953,104 +4153,117 @@ impl BoxDecoder for ElstDecoder {
         _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
-        if buf.len() < 8 {
-            return Ok(BoxValue::Text(format!(
-                "elst: payload too short ({} bytes)",
-                buf.len()
-            )));
-        }
-
-        let mut pos = 0usize;
-        let version = buf[pos];
-        pos += 1;
-        if pos + 3 > buf.len() {
-            return Ok(BoxValue::Text("elst: truncated flags".into()));
-        }
-        pos += 3;
-
-        let read_u32 = |pos: &mut usize| -> Option<u32> {
-            if *pos + 4 > buf.len() {
-                return None;
-            }
-            let v = u32::from_be_bytes(buf[*pos..*pos + 4].try_into().unwrap());
-            *pos += 4;
-            Some(v)
-        };
-        let read_u64 = |pos: &mut usize| -> Option<u64> {
-            if *pos + 8 > buf.len() {
-                return None;
-            }
-            let v = u64::from_be_bytes(buf[*pos..*pos + 8].try_into().unwrap());
-            *pos += 8;
-            Some(v)
-        };
-        let read_i32 = |pos: &mut usize| -> Option<i32> {
-            if *pos + 4 > buf.len() {
-                return None;
-            }
-            let v = i32::from_be_bytes(buf[*pos..*pos + 4].try_into().unwrap());
-            *pos += 4;
-            Some(v)
-        };
-        let read_i64 = |pos: &mut usize| -> Option<i64> {
-            if *pos + 8 > buf.len() {
-                return None;
-            }
-            let v = i64::from_be_bytes(buf[*pos..*pos + 8].try_into().unwrap());
-            *pos += 8;
-            Some(v)
-        };
-        let read_i16 = |pos: &mut usize| -> Option<i16> {
-            if *pos + 2 > buf.len() {
-                return None;
+        let mut items = Vec::new();
+
+        for (typ, body) in child_boxes(&buf, 0) {
+            // Keyed items use a 1-based index into `keys` as their type; it is
+            // resolved once the sibling `keys` box is known.
+            let key_index = if looks_like_fourcc(typ) {
+                None
+            } else {
+                Some(u32::from_be_bytes(typ))
+            };
+            let mut key = match key_index {
+                Some(n) => format!("key #{}", n),
+                None => metadata_atom_name(typ),
+            };
+            // Freeform "----" items name themselves with mean/name children
+            // (both FullBoxes), e.g. "com.apple.iTunes:iTunSMPB".
+            if &typ == b"----" {
+                let text = |want: &[u8; 4]| {
+                    child_boxes(body, 0)
+                        .into_iter()
+                        .find(|(t, _)| t == want)
+                        .and_then(|(_, b)| b.get(4..))
+                        .map(|b| String::from_utf8_lossy(b).to_string())
+                };
+                if let (Some(mean), Some(name)) = (text(b"mean"), text(b"name")) {
+                    key = format!("{}:{}", mean, name);
+                }
             }
-            let v = i16::from_be_bytes(buf[*pos..*pos + 2].try_into().unwrap());
-            *pos += 2;
-            Some(v)
-        };
-
-        let entry_count = read_u32(&mut pos).unwrap_or(0);
 
-        if entry_count == 0 {
-            return Ok(BoxValue::Text(format!("version={} entries=0", version)));
+            let Some((_, data)) = child_boxes(body, 0).into_iter().find(|(t, _)| t == b"data")
+            else {
+                continue;
+            };
+            if data.len() < 8 {
+                continue;
+            }
+            let data_type = u32::from_be_bytes(data[0..4].try_into().unwrap()) & 0x00FF_FFFF;
+            let locale = u32::from_be_bytes(data[4..8].try_into().unwrap());
+            let payload = &data[8..];
+            let value = match data_type {
+                1 => String::from_utf8_lossy(payload).to_string(),
+                // Cover art: report the format rather than the image bytes
+                13 => format!("JPEG image, {} bytes", payload.len()),
+                14 => format!("PNG image, {} bytes", payload.len()),
+                27 => format!("BMP image, {} bytes", payload.len()),
+                // Big-endian signed integer of 1, 2, 4 or 8 bytes
+                21 if matches!(payload.len(), 1 | 2 | 4 | 8) => {
+                    let unsigned = payload.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+                    let shift = 64 - 8 * payload.len() as u32;
+                    (((unsigned << shift) as i64) >> shift).to_string()
+                }
+                _ => format!("{} bytes", payload.len()),
+            };
+
+            let gapless = if key == "com.apple.iTunes:iTunSMPB" {
+                GaplessInfo::from_itunsmpb(&value)
+            } else {
+                None
+            };
+            items.push(MetadataItem {
+                key,
+                key_index,
+                data_type,
+                locale,
+                value,
+                gapless,
+            });
         }
 
-        let (seg_duration, media_time) = if version == 1 {
-            let dur = read_u64(&mut pos).unwrap_or(0);
-            let mt = read_i64(&mut pos).unwrap_or(0);
-            (dur, mt)
-        } else {
-            let dur = read_u32(&mut pos).unwrap_or(0) as u64;
-            let mt = read_i32(&mut pos).unwrap_or(0) as i64;
-            (dur, mt)
-        };
+        Ok(BoxValue::Structured(StructuredData::Metadata(IlstData {
+            items,
+        })))
+    }
+}
 
-        let rate_int = read_i16(&mut pos);
-        let rate_frac = read_i16(&mut pos);
-
-        match (rate_int, rate_frac) {
-            (Some(ri), Some(rf)) => Ok(BoxValue::Text(format!(
-                "version={} entries={} first: duration={} media_time={} rate={}/{}",
-                version, entry_count, seg_duration, media_time, ri, rf
-            ))),
-            _ => Ok(BoxValue::Text(format!(
-                "version={} entries={} first: duration={} media_time={} (no rate, short payload)",
-                version, entry_count, seg_duration, media_time
-            ))),
-        }
+/// iTunes atoms are printable, except for the leading © (0xA9) byte.
+fn looks_like_fourcc(typ: [u8; 4]) -> bool {
+    typ.iter()
+        .enumerate()
+        .all(|(i, &c)| (32..=126).contains(&c) || (i == 0 && c == 0xA9))
+}
+
+fn metadata_atom_name(typ: [u8; 4]) -> String {
+    if typ[0] == 0xA9 {
+        format!("©{}", String::from_utf8_lossy(&typ[1..]))
+    } else {
+        FourCC(typ).to_string()
     }
 }
 
+/// Track reference types that may appear inside `tref`.
+pub const TRACK_REFERENCE_TYPES: &[&[u8; 4]] = &[
+    b"hint", b"cdsc", b"font", b"hind", b"vdep", b"vplx", b"subt", b"chap", b"sync", b"tmcd",
+    b"dpnd", b"ipir", b"mpod", b"sbas", b"scal", b"auxl",
+];
+
 // ---------- Default registry ----------
 pub fn default_registry() -> Registry {
     use crate::boxes::BoxKey;
 
-    Registry::new()
+    let reg = Registry::new()
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"ftyp")),
             "ftyp",
             Box::new(FtypDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"styp")),
+            "styp",
+            Box::new(FtypDecoder),
+        )
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"mvhd")),
             "mvhd",
@@ -1071,11 +4284,96 @@ pub fn default_registry() -> Registry {
             "hdlr",
             Box::new(HdlrDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"vmhd")),
+            "vmhd",
+            Box::new(VmhdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"smhd")),
+            "smhd",
+            Box::new(SmhdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"nmhd")),
+            "nmhd",
+            Box::new(NmhdDecoder),
+        )
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"sidx")),
             "sidx",
             Box::new(SidxDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"dref")),
+            "dref",
+            Box::new(DrefDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"keys")),
+            "keys",
+            Box::new(KeysDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"ilst")),
+            "ilst",
+            Box::new(IlstDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"clef")),
+            "clef",
+            Box::new(TrackApertureDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"prof")),
+            "prof",
+            Box::new(TrackApertureDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"enof")),
+            "enof",
+            Box::new(TrackApertureDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"mime")),
+            "mime",
+            Box::new(MimeDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"iinf")),
+            "iinf",
+            Box::new(IinfDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"iloc")),
+            "iloc",
+            Box::new(IlocDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"iref")),
+            "iref",
+            Box::new(IrefDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"avcC")),
+            "avcC",
+            Box::new(AvcCDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"hvcC")),
+            "hvcC",
+            Box::new(HvcCDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"esds")),
+            "esds",
+            Box::new(EsdsDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"chan")),
+            "chan",
+            Box::new(ChanDecoder),
+        )
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"stsd")),
             "stsd",
@@ -1116,9 +4414,109 @@ pub fn default_registry() -> Registry {
             "co64",
             Box::new(Co64Decoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tfhd")),
+            "tfhd",
+            Box::new(TfhdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tfdt")),
+            "tfdt",
+            Box::new(TfdtDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"mehd")),
+            "mehd",
+            Box::new(MehdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"trex")),
+            "trex",
+            Box::new(TrexDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tfra")),
+            "tfra",
+            Box::new(TfraDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"mfro")),
+            "mfro",
+            Box::new(MfroDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"trun")),
+            "trun",
+            Box::new(TrunDecoder),
+        )
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"elst")),
             "elst",
             Box::new(ElstDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"sgpd")),
+            "sgpd",
+            Box::new(SgpdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"sbgp")),
+            "sbgp",
+            Box::new(SbgpDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"XMP_")),
+            "XMP_",
+            Box::new(XmpDecoder),
+        )
+        .with_decoder(BoxKey::Uuid(XMP_UUID), "xmp", Box::new(XmpDecoder))
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tenc")),
+            "tenc",
+            Box::new(TencDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"senc")),
+            "senc",
+            Box::new(SencDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"saiz")),
+            "saiz",
+            Box::new(SaizDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"saio")),
+            "saio",
+            Box::new(SaioDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"pssh")),
+            "pssh",
+            Box::new(PsshDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"dvcC")),
+            "dvcC",
+            Box::new(DoviDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"dvvC")),
+            "dvvC",
+            Box::new(DoviDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"dvwC")),
+            "dvwC",
+            Box::new(DoviDecoder),
+        );
+
+    TRACK_REFERENCE_TYPES.iter().fold(reg, |reg, typ| {
+        reg.with_child_decoder(
+            FourCC(*b"tref"),
+            BoxKey::FourCC(FourCC(**typ)),
+            "tref",
+            Box::new(TrefTypeDecoder),
+        )
+    })
 }
@@ -1,12 +1,16 @@
+use crate::registry::ElstEntry;
+pub use crate::registry::GaplessInfo;
 use anyhow::Context;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct SampleInfo {
-    /// 0-based sample index
+    /// 0-based sample index. The sample tables (stss, stsc, sdtp, ...) number
+    /// samples from 1; use [`SampleInfo::sample_number`] to compare with them.
     pub index: u32,
 
     /// Decode time (DTS) in track timescale units
@@ -21,17 +25,73 @@ pub struct SampleInfo {
     /// Duration in track timescale units (from stts)
     pub duration: u32,
 
+    /// Duration actually played, in track timescale units. Equal to `duration`
+    /// except for the last sample, which is clamped to end at the media duration
+    pub effective_duration: u32,
+
     /// Composition/rendered offset in track timescale units (from ctts, may be 0)
     pub rendered_offset: i64,
 
-    /// Byte offset in the file (from stsc + stco/co64)
-    pub file_offset: u64,
+    /// Byte offset in the file (from stsc + stco/co64). `None` when the
+    /// sample tables do not map this sample to a chunk offset; the track's
+    /// `warnings` say why.
+    pub file_offset: Option<u64>,
 
     /// Sample size in bytes (from stsz)
     pub size: u32,
 
-    /// Whether this sample is a sync sample / keyframe (from stss)
+    /// Whether this sample is a sync sample / keyframe (from stss, or from
+    /// the sample flags in fragmented files)
     pub is_sync: bool,
+
+    /// Fragment sample flags (trun, else tfhd, else trex defaults). `None`
+    /// for samples from a regular `stbl`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<SampleFlags>,
+}
+
+/// The fields of a fragment `sample_flags` word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SampleFlags {
+    /// 0 unknown, 1 leading with a dependency before the referenced I-picture,
+    /// 2 not leading, 3 leading without such a dependency
+    pub is_leading: u8,
+    /// 0 unknown, 1 depends on other samples, 2 does not (an I-picture)
+    pub sample_depends_on: u8,
+    /// 0 unknown, 1 other samples depend on this one, 2 none do (disposable)
+    pub sample_is_depended_on: u8,
+    /// 0 unknown, 1 has redundant coding, 2 has none
+    pub sample_has_redundancy: u8,
+    pub padding_value: u8,
+    pub is_non_sync_sample: bool,
+    pub degradation_priority: u16,
+}
+
+impl SampleFlags {
+    pub fn from_bits(flags: u32) -> Self {
+        Self {
+            is_leading: ((flags >> 26) & 0x3) as u8,
+            sample_depends_on: ((flags >> 24) & 0x3) as u8,
+            sample_is_depended_on: ((flags >> 22) & 0x3) as u8,
+            sample_has_redundancy: ((flags >> 20) & 0x3) as u8,
+            padding_value: ((flags >> 17) & 0x7) as u8,
+            is_non_sync_sample: flags & 0x0001_0000 != 0,
+            degradation_priority: flags as u16,
+        }
+    }
+
+    /// A sync sample: not flagged non-sync and not declared as depending on
+    /// other samples.
+    pub fn is_sync(&self) -> bool {
+        !self.is_non_sync_sample && self.sample_depends_on != 1
+    }
+}
+
+impl SampleInfo {
+    /// The 1-based sample number used by the MP4 sample tables (`index + 1`).
+    pub fn sample_number(&self) -> u32 {
+        self.index + 1
+    }
 }
 
 /// Complete sample information and metadata for a single track in an MP4 file.
@@ -57,6 +117,9 @@ pub struct SampleInfo {
 ///   - `"subt"` - Subtitle track
 ///   - And other standardized or custom handler types
 ///
+/// * `handler_name` - Human-readable handler name from hdlr (e.g. "VideoHandler",
+///   "Core Media Video"), when the file provides one.
+///
 /// * `timescale` - Time coordinate system for this track (from mdhd box).
 ///   Defines the number of time units per second. For example:
 ///   - Video tracks often use 90000 (90kHz) or frame rate multiples
@@ -72,6 +135,21 @@ pub struct SampleInfo {
 ///   Ordered chronologically by decode time (DTS). Each `SampleInfo` contains
 ///   timing, size, sync status, and file offset information.
 ///
+/// * `sample_entry` - The first sample description from stsd (codec, dimensions,
+///   codec configuration details), if it could be decoded.
+///
+/// * `edits` - The track's edit list (`edts/elst`), if any. Omitted from JSON
+///   when empty.
+///
+/// * `gapless` - iTunes gapless playback info (`iTunSMPB`) for audio tracks,
+///   if the movie carries it.
+///
+/// * `data_self_contained` - Whether the track's `dref` declares its media as
+///   stored in this file. `false` when any entry points at external data.
+///
+/// * `warnings` - Non-fatal problems found while validating the track, such as
+///   chunk offsets that point outside any `mdat`. Omitted from JSON when empty.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -100,10 +178,659 @@ pub struct SampleInfo {
 pub struct TrackSamples {
     pub track_id: u32,
     pub handler_type: String, // "vide", "soun", etc.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handler_name: Option<String>,
     pub timescale: u32,
     pub duration: u64, // in track timescale units
     pub sample_count: u32,
     pub samples: Vec<SampleInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_entry: Option<crate::registry::SampleEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub edits: Vec<ElstEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gapless: Option<GaplessInfo>,
+    pub data_self_contained: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<crate::Warning>,
+}
+
+impl TrackSamples {
+    /// Encoder delay (priming) to skip at the start of the track, for gapless
+    /// playback.
+    ///
+    /// Taken from the edit list when it starts playback past media time 0
+    /// (in media timescale units, i.e. audio samples for AAC), otherwise from
+    /// the iTunes gapless tag. `None` when neither says there is any delay.
+    pub fn encoder_delay(&self) -> Option<u32> {
+        // Leading empty edits (media_time -1) only shift the track on the
+        // movie timeline; the first edit that plays media carries the skip.
+        if let Some(edit) = self.edits.iter().find(|e| e.media_time >= 0)
+            && edit.media_time > 0
+        {
+            return u32::try_from(edit.media_time).ok();
+        }
+        self.gapless
+            .map(|g| g.encoder_delay)
+            .filter(|&delay| delay > 0)
+    }
+
+    /// Shift `pts`/`start_time` onto the movie timeline described by `edits`.
+    ///
+    /// Leading empty edits (media_time -1) delay the track: their total
+    /// duration, given in `movie_timescale` units, becomes an initial gap. The
+    /// first edit that plays media starts at its media_time, so that much is
+    /// subtracted; samples before it are clamped to the start of the edit.
+    /// Later edits are not applied, and `dts` is left as stored.
+    pub fn apply_edits(&mut self, movie_timescale: u32) {
+        let empty: u64 = self
+            .edits
+            .iter()
+            .take_while(|e| e.media_time == -1)
+            .map(|e| e.segment_duration)
+            .sum();
+        let delay = if movie_timescale == 0 {
+            empty
+        } else {
+            (empty as u128 * self.timescale as u128 / movie_timescale as u128) as u64
+        };
+        let media_start = self
+            .edits
+            .iter()
+            .find(|e| e.media_time >= 0)
+            .map_or(0, |e| e.media_time as u64);
+
+        for s in &mut self.samples {
+            s.pts = s.pts.saturating_sub(media_start) + delay;
+            s.start_time = if self.timescale > 0 {
+                s.pts as f64 / self.timescale as f64
+            } else {
+                0.0
+            };
+        }
+    }
+
+    /// The sample with 1-based `sample_number`, as found in stss and other
+    /// sample tables. `None` for 0 or numbers past the last sample.
+    pub fn sample_by_number(&self, sample_number: u32) -> Option<&SampleInfo> {
+        let index = sample_number.checked_sub(1)?;
+        self.samples.get(index as usize)
+    }
+
+    /// The sync sample to start decoding from to reach `time_seconds`: the
+    /// latest one whose `start_time` is at or before it.
+    ///
+    /// Times before the first sync sample give the first one; times past the
+    /// end give the last. `None` only when the track has no sync samples.
+    pub fn seek(&self, time_seconds: f64) -> Option<&SampleInfo> {
+        let sync: Vec<&SampleInfo> = self.samples.iter().filter(|s| s.is_sync).collect();
+        let after = sync.partition_point(|s| s.start_time <= time_seconds);
+        sync.get(after.saturating_sub(1)).copied()
+    }
+
+    /// Samples in presentation (PTS) order.
+    ///
+    /// `samples` is stored in decode order; with B-frames the two orders differ.
+    /// This returns references into `samples` rather than copies. Samples with
+    /// equal PTS keep their decode order.
+    pub fn samples_presentation_order(&self) -> Vec<&SampleInfo> {
+        let mut ordered: Vec<&SampleInfo> = self.samples.iter().collect();
+        ordered.sort_by_key(|s| s.pts);
+        ordered
+    }
+
+    /// Whether all of this track's sample data is present in a file of
+    /// `file_len` bytes.
+    ///
+    /// Requires the `dref` to declare the media self-contained and every
+    /// sample to end at or before `file_len`. A truncated download or a
+    /// reference movie pointing at external media both return `false`.
+    pub fn is_self_contained(&self, file_len: u64) -> bool {
+        self.data_self_contained
+            && self.samples.iter().all(|s| {
+                s.file_offset
+                    .is_some_and(|o| o.saturating_add(s.size as u64) <= file_len)
+            })
+    }
+
+    /// Bitrate per window of `window_s` seconds, for plotting a bitrate graph.
+    ///
+    /// Samples are bucketed by presentation time into windows aligned to
+    /// multiples of `window_s`, from the window of the earliest sample to that
+    /// of the latest. Each entry is `(window start in seconds, bits per second)`,
+    /// with the start on the track's own timeline, so fragments with a large
+    /// `tfdt` report large times. A run of empty windows is reported once, as 0
+    /// at its start. Returns nothing for a non-positive window.
+    pub fn bitrate_over_time(&self, window_s: f64) -> Vec<(f64, u64)> {
+        if window_s <= 0.0 || self.timescale == 0 {
+            return Vec::new();
+        }
+
+        // Bucket in timescale ticks so window boundaries land exactly on sample
+        // times. Sparse, as live streams start at epoch-based times.
+        let window_ticks = window_s * self.timescale as f64;
+        let mut window_bytes: BTreeMap<u64, u64> = BTreeMap::new();
+        for s in &self.samples {
+            let window = (s.pts as f64 / window_ticks) as u64;
+            *window_bytes.entry(window).or_default() += s.size as u64;
+        }
+
+        let bps = |bytes: u64| (bytes as f64 * 8.0 / window_s).round() as u64;
+        let mut rates = Vec::with_capacity(window_bytes.len());
+        let mut next = None;
+        for (&window, &bytes) in &window_bytes {
+            if let Some(gap) = next.filter(|&n| n < window) {
+                rates.push((gap as f64 * window_s, 0));
+            }
+            rates.push((window as f64 * window_s, bps(bytes)));
+            next = Some(window + 1);
+        }
+        rates
+    }
+
+    /// Average bitrate over the whole track and the peak over any one second.
+    ///
+    /// The average uses the track duration, or the summed sample durations
+    /// when the track header says 0. The peak slides a one-second window
+    /// along the decode timeline, starting at each sample's DTS. Tracks with
+    /// no duration or no timescale report 0 rather than dividing by zero.
+    pub fn bitrate_stats(&self) -> BitrateStats {
+        let mut stats = BitrateStats::default();
+        if self.timescale == 0 {
+            return stats;
+        }
+
+        let total_bytes: u64 = self.samples.iter().map(|s| s.size as u64).sum();
+        let duration = match self.duration {
+            0 => self.samples.iter().map(|s| s.duration as u64).sum(),
+            d => d,
+        };
+        if duration > 0 {
+            let seconds = duration as f64 / self.timescale as f64;
+            stats.avg_bps = (total_bytes as f64 * 8.0 / seconds).round() as u64;
+        }
+
+        // Samples are in decode order, so DTS never decreases.
+        let window = self.timescale as u64;
+        let mut end = 0;
+        let mut bytes = 0u64;
+        for (start, s) in self.samples.iter().enumerate() {
+            while end < self.samples.len() && self.samples[end].dts < s.dts + window {
+                bytes += self.samples[end].size as u64;
+                end += 1;
+            }
+            if bytes * 8 > stats.peak_bps {
+                stats.peak_bps = bytes * 8;
+                stats.peak_window_start = s.dts as f64 / self.timescale as f64;
+            }
+            bytes -= self.samples[start].size as u64;
+        }
+        stats
+    }
+
+    /// Keyframe placement and GOP lengths, from the samples' sync flags.
+    ///
+    /// A GOP runs from one sync sample up to the next (the last one to the end
+    /// of the track); samples before the first sync sample belong to no GOP.
+    /// Durations in seconds are measured on the decode timeline. A track
+    /// without `stss` has every sample marked sync, so it reports GOPs of one
+    /// sample and `all_intra`.
+    pub fn gop_analysis(&self) -> GopStats {
+        let keyframe_indices: Vec<u32> = self
+            .samples
+            .iter()
+            .filter(|s| s.is_sync)
+            .map(|s| s.index)
+            .collect();
+        let mut stats = GopStats {
+            all_intra: !self.samples.is_empty() && keyframe_indices.len() == self.samples.len(),
+            ..Default::default()
+        };
+
+        let positions: Vec<usize> = (0..self.samples.len())
+            .filter(|&i| self.samples[i].is_sync)
+            .collect();
+        let end_dts = self.samples.last().map_or(0, |s| s.dts + s.duration as u64);
+        let timescale = self.timescale.max(1) as f64;
+        for (n, &start) in positions.iter().enumerate() {
+            let end = positions.get(n + 1).copied().unwrap_or(self.samples.len());
+            let end_time = self.samples.get(end).map_or(end_dts, |s| s.dts);
+            let samples = (end - start) as u32;
+            let seconds = end_time.saturating_sub(self.samples[start].dts) as f64 / timescale;
+            stats.max_gop_samples = stats.max_gop_samples.max(samples);
+            stats.max_gop_seconds = stats.max_gop_seconds.max(seconds);
+            stats.avg_gop_samples += samples as f64;
+            stats.avg_gop_seconds += seconds;
+        }
+        if !positions.is_empty() {
+            stats.avg_gop_samples /= positions.len() as f64;
+            stats.avg_gop_seconds /= positions.len() as f64;
+        }
+
+        stats.keyframe_indices = keyframe_indices;
+        stats
+    }
+
+    /// List the NAL units of the sample at `index` as `(nal_unit_type, size)` pairs.
+    ///
+    /// Works for AVC and HEVC tracks, using the length prefix size from avcC/hvcC.
+    /// Only the length prefixes and NAL headers are read; payloads are skipped.
+    pub fn sample_nal_units<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        index: u32,
+    ) -> anyhow::Result<Vec<(u8, u32)>> {
+        let entry = self
+            .sample_entry
+            .as_ref()
+            .context("track has no sample description")?;
+        let length_size = entry
+            .nal_length_size
+            .with_context(|| format!("'{}' track has no avcC/hvcC configuration", entry.codec))?
+            as u64;
+        let is_hevc = is_hevc_codec(&entry.codec);
+
+        let sample = self
+            .samples
+            .get(index as usize)
+            .with_context(|| format!("sample {} out of range", index))?;
+        let file_offset = sample
+            .file_offset
+            .with_context(|| format!("sample {} has no file offset", index))?;
+
+        let mut units = Vec::new();
+        let mut pos = 0u64;
+        let end = sample.size as u64;
+        // Every position read below is inside the sample, so this covers them all.
+        if file_offset.checked_add(end).is_none() {
+            anyhow::bail!(
+                "sample {} ({} bytes @ {:#x}) extends past the end of the file",
+                index,
+                sample.size,
+                file_offset
+            );
+        }
+        while pos + length_size < end {
+            reader.seek(SeekFrom::Start(file_offset + pos))?;
+            let mut len_buf = [0u8; 4];
+            reader.read_exact(&mut len_buf[4 - length_size as usize..])?;
+            let nal_size = u32::from_be_bytes(len_buf);
+            if nal_size == 0 {
+                // Empty NAL units carry nothing; skip the prefix as to_annexb does.
+                pos += length_size;
+                continue;
+            }
+            if pos + length_size + nal_size as u64 > end {
+                anyhow::bail!(
+                    "NAL unit at byte {} of sample {} overruns the sample",
+                    pos,
+                    index
+                );
+            }
+
+            let mut header = [0u8; 1];
+            reader.read_exact(&mut header)?;
+            let nal_type = if is_hevc {
+                (header[0] >> 1) & 0x3F
+            } else {
+                header[0] & 0x1F
+            };
+
+            units.push((nal_type, nal_size));
+            pos += length_size + nal_size as u64;
+        }
+
+        Ok(units)
+    }
+
+    /// Read every sample in file-offset order, passing each one's bytes to
+    /// `callback`, without holding more than `max_in_flight_bytes` in memory.
+    ///
+    /// Samples that sit back to back in the file are fetched with a single
+    /// read while they fit in the budget. Fails if a sample has no file
+    /// offset, lies past the end of the reader, or is itself larger than the
+    /// budget; errors returned by `callback` stop the walk.
+    pub fn read_all_samples_bounded<R, F>(
+        &self,
+        reader: &mut R,
+        max_in_flight_bytes: usize,
+        mut callback: F,
+    ) -> anyhow::Result<()>
+    where
+        R: Read + Seek,
+        F: FnMut(&SampleInfo, &[u8]) -> anyhow::Result<()>,
+    {
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        let mut ordered = Vec::with_capacity(self.samples.len());
+        for s in &self.samples {
+            let offset = s.file_offset.with_context(|| {
+                format!("sample {} has no known file offset", s.sample_number())
+            })?;
+            if s.size as usize > max_in_flight_bytes {
+                anyhow::bail!(
+                    "sample {} ({} bytes) exceeds the {} byte budget",
+                    s.sample_number(),
+                    s.size,
+                    max_in_flight_bytes
+                );
+            }
+            if offset
+                .checked_add(s.size as u64)
+                .is_none_or(|end| end > file_size)
+            {
+                anyhow::bail!(
+                    "sample {} ({} bytes @ {:#x}) extends past the end of the file ({} bytes)",
+                    s.sample_number(),
+                    s.size,
+                    offset,
+                    file_size
+                );
+            }
+            ordered.push((offset, s));
+        }
+        ordered.sort_by_key(|&(offset, _)| offset);
+
+        let mut buf = Vec::new();
+        let mut i = 0;
+        while i < ordered.len() {
+            // Extend the run while the next sample follows on directly.
+            let start = ordered[i].0;
+            let mut end = start + ordered[i].1.size as u64;
+            let mut j = i + 1;
+            while j < ordered.len()
+                && ordered[j].0 == end
+                && (end - start) as usize + ordered[j].1.size as usize <= max_in_flight_bytes
+            {
+                end += ordered[j].1.size as u64;
+                j += 1;
+            }
+
+            buf.resize((end - start) as usize, 0);
+            reader.seek(SeekFrom::Start(start))?;
+            reader.read_exact(&mut buf)?;
+            for &(offset, s) in &ordered[i..j] {
+                let at = (offset - start) as usize;
+                callback(s, &buf[at..at + s.size as usize])?;
+            }
+            i = j;
+        }
+        Ok(())
+    }
+
+    /// Track-level facts without the per-sample list, for callers that
+    /// only need a summary (see [`TrackSummary`]).
+    pub fn summary(&self) -> TrackSummary {
+        let duration = match self.duration {
+            0 => self.samples.iter().map(|s| s.duration as u64).sum(),
+            d => d,
+        };
+        let duration_seconds = if self.timescale > 0 {
+            duration as f64 / self.timescale as f64
+        } else {
+            0.0
+        };
+        let frame_rate = (self.handler_type == "vide" && duration_seconds > 0.0)
+            .then(|| self.samples.len() as f64 / duration_seconds);
+
+        TrackSummary {
+            track_id: self.track_id,
+            handler_type: self.handler_type.clone(),
+            timescale: self.timescale,
+            duration: self.duration,
+            duration_seconds,
+            sample_count: self.sample_count,
+            codec: self.sample_entry.as_ref().map(|e| e.codec.clone()),
+            bitrate_bps: self.bitrate_stats().avg_bps,
+            frame_rate,
+        }
+    }
+
+    /// Serialize this track to pretty-printed JSON.
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).expect("TrackSamples is always serializable")
+    }
+
+    /// One CSV row per sample, with a header line:
+    /// `index,dts,pts,duration,size,file_offset,is_sync`.
+    ///
+    /// `file_offset` is left empty for samples with no known offset.
+    pub fn to_csv(&self) -> String {
+        let mut out = format!("{}\n", CSV_COLUMNS);
+        for sample in &self.samples {
+            out.push_str(&csv_row(sample));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Like [`TrackSamples::to_csv`], with per-sample NAL unit counts
+    /// appended: `nal_slice,nal_sei,nal_vps,nal_sps,nal_pps,nal_other`.
+    ///
+    /// Only AVC and HEVC tracks can be broken down; the NAL units of each
+    /// sample are listed with [`TrackSamples::sample_nal_units`], so this
+    /// fails on the same tracks and samples that does.
+    pub fn to_csv_with_nal_counts<R: Read + Seek>(&self, reader: &mut R) -> anyhow::Result<String> {
+        let is_hevc = self
+            .sample_entry
+            .as_ref()
+            .is_some_and(|entry| is_hevc_codec(&entry.codec));
+
+        let mut out = format!(
+            "{},nal_slice,nal_sei,nal_vps,nal_sps,nal_pps,nal_other\n",
+            CSV_COLUMNS
+        );
+        for sample in &self.samples {
+            let mut counts = [0u32; 6];
+            for (nal_type, _) in self.sample_nal_units(reader, sample.index)? {
+                counts[nal_column(nal_type, is_hevc)] += 1;
+            }
+            out.push_str(&csv_row(sample));
+            for count in counts {
+                out.push_str(&format!(",{}", count));
+            }
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+const CSV_COLUMNS: &str = "index,dts,pts,duration,size,file_offset,is_sync";
+
+fn csv_row(sample: &SampleInfo) -> String {
+    format!(
+        "{},{},{},{},{},{},{}",
+        sample.index,
+        sample.dts,
+        sample.pts,
+        sample.duration,
+        sample.size,
+        sample
+            .file_offset
+            .map(|o| o.to_string())
+            .unwrap_or_default(),
+        sample.is_sync
+    )
+}
+
+fn is_hevc_codec(codec: &str) -> bool {
+    matches!(codec, "hvc1" | "hev1" | "dvh1" | "dvhe")
+}
+
+/// Column of a NAL unit type in the `to_csv_with_nal_counts` breakdown:
+/// slice, SEI, VPS, SPS, PPS or other.
+fn nal_column(nal_type: u8, is_hevc: bool) -> usize {
+    if is_hevc {
+        match nal_type {
+            0..=31 => 0,
+            39 | 40 => 1,
+            32 => 2,
+            33 => 3,
+            34 => 4,
+            _ => 5,
+        }
+    } else {
+        match nal_type {
+            1..=5 => 0,
+            6 => 1,
+            7 => 3,
+            8 => 4,
+            _ => 5,
+        }
+    }
+}
+
+/// Track-level view of a [`TrackSamples`] without the per-sample vector,
+/// from [`TrackSamples::summary`]. Much smaller to serialize for long tracks.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackSummary {
+    pub track_id: u32,
+    pub handler_type: String,
+    pub timescale: u32,
+    /// Duration in track timescale units
+    pub duration: u64,
+    pub duration_seconds: f64,
+    pub sample_count: u32,
+    /// Sample entry type of the first sample description (e.g. "avc1")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codec: Option<String>,
+    /// Average bitrate over the track
+    pub bitrate_bps: u64,
+    /// Samples per second, for video tracks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_rate: Option<f64>,
+}
+
+/// One chunk from [`extract_chunk_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ChunkEntry {
+    /// 0-based chunk index (stsc numbers chunks from 1)
+    pub index: u32,
+    /// File offset from stco/co64
+    pub offset: u64,
+    /// Number of samples in the chunk, from stsc
+    pub sample_count: u32,
+    /// 0-based index of the chunk's first sample
+    pub first_sample: u32,
+}
+
+/// Result of [`TrackSamples::gop_analysis`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GopStats {
+    /// 0-based indices of the sync samples, in decode order
+    pub keyframe_indices: Vec<u32>,
+    /// Longest GOP, in samples
+    pub max_gop_samples: u32,
+    /// Mean GOP length, in samples
+    pub avg_gop_samples: f64,
+    /// Longest GOP, in seconds
+    pub max_gop_seconds: f64,
+    /// Mean GOP length, in seconds
+    pub avg_gop_seconds: f64,
+    /// Whether every sample is a sync sample
+    pub all_intra: bool,
+}
+
+/// Result of [`TrackSamples::bitrate_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct BitrateStats {
+    /// Average bits per second over the track duration
+    pub avg_bps: u64,
+    /// Highest bits per second in any one-second window
+    pub peak_bps: u64,
+    /// Start of that window, in seconds of decode time
+    pub peak_window_start: f64,
+}
+
+/// Serialize a list of tracks (e.g. from [`track_samples_from_path`]) to pretty-printed JSON.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mp4box::{track_samples_from_path, tracks_to_json};
+///
+/// let tracks = track_samples_from_path("video.mp4").unwrap();
+/// println!("{}", tracks_to_json(&tracks));
+/// ```
+pub fn tracks_to_json(tracks: &[TrackSamples]) -> String {
+    serde_json::to_string_pretty(tracks).expect("TrackSamples is always serializable")
+}
+
+/// The timeline of `b` appended after `a`, as if the two files were concatenated.
+///
+/// `b`'s decode and presentation times are shifted by `a.duration` (or, when
+/// that is 0 as for fragmented tracks, by the end of `a`'s last sample), and its
+/// file offsets by the end of `a`'s sample data (the last byte of the
+/// furthest sample), which is the file size whenever `mdat` ends the file.
+/// Sample indices continue from `a`. Track metadata (id, handler, sample entry,
+/// edits) is taken from `a`.
+///
+/// Fails when the timescales differ, when both tracks have a sample entry
+/// and their codecs differ, or when a shifted time or offset overflows.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mp4box::{concat_tracks, track_samples_from_path};
+///
+/// let a = track_samples_from_path("part1.mp4").unwrap();
+/// let b = track_samples_from_path("part2.mp4").unwrap();
+/// let joined = concat_tracks(&a[0], &b[0]).unwrap();
+/// println!("{} samples", joined.sample_count);
+/// ```
+pub fn concat_tracks(a: &TrackSamples, b: &TrackSamples) -> anyhow::Result<TrackSamples> {
+    if a.timescale != b.timescale {
+        anyhow::bail!(
+            "cannot concatenate tracks with timescales {} and {}",
+            a.timescale,
+            b.timescale
+        );
+    }
+    if let (Some(ea), Some(eb)) = (&a.sample_entry, &b.sample_entry)
+        && ea.codec != eb.codec
+    {
+        anyhow::bail!("cannot concatenate {} and {} tracks", ea.codec, eb.codec);
+    }
+
+    let overflow = || anyhow::anyhow!("concatenated track does not fit in 64-bit times or offsets");
+    let time_shift = if a.duration > 0 {
+        a.duration
+    } else {
+        // Fragmented tracks have no mdhd duration; b starts where a's last sample ends.
+        a.samples
+            .last()
+            .map(|s| s.dts.checked_add(s.duration as u64).ok_or_else(overflow))
+            .transpose()?
+            .unwrap_or(0)
+    };
+    let offset_shift = a
+        .samples
+        .iter()
+        .filter_map(|s| s.file_offset.map(|o| o.checked_add(s.size as u64)))
+        .try_fold(0u64, |max, end| end.map(|end| max.max(end)))
+        .ok_or_else(overflow)?;
+    let first_index = a.samples.len() as u32;
+
+    let mut joined = a.clone();
+    for (i, s) in b.samples.iter().enumerate() {
+        let pts = s.pts.checked_add(time_shift).ok_or_else(overflow)?;
+        let file_offset = match s.file_offset {
+            Some(o) => Some(o.checked_add(offset_shift).ok_or_else(overflow)?),
+            None => None,
+        };
+        joined.samples.push(SampleInfo {
+            index: first_index + i as u32,
+            dts: s.dts.checked_add(time_shift).ok_or_else(overflow)?,
+            pts,
+            start_time: pts as f64 / a.timescale as f64,
+            file_offset,
+            ..s.clone()
+        });
+    }
+    joined.duration = time_shift.checked_add(b.duration).ok_or_else(overflow)?;
+    joined.sample_count = a.sample_count + b.sample_count;
+    joined.data_self_contained = a.data_self_contained && b.data_self_contained;
+    joined.warnings.extend(b.warnings.iter().cloned());
+    Ok(joined)
 }
 
 /// Extracts sample information from all tracks in an MP4 file using a generic reader.
@@ -159,11 +886,21 @@ pub fn track_samples_from_reader<R: Read + Seek>(
     let mut result = Vec::new();
 
     for moov_box in boxes.iter().filter(|b| b.typ == "moov") {
+        let gapless = find_gapless_info(moov_box);
         if let Some(children) = &moov_box.children {
             for trak_box in children.iter().filter(|b| b.typ == "trak") {
-                if let Some(track_samples) =
+                if let Some(mut track_samples) =
                     crate::samples::extract_track_samples(trak_box, &mut reader)?
                 {
+                    append_fragment_samples(&mut track_samples, moov_box, &boxes);
+                    if let Some(w) =
+                        check_samples_in_mdat(&track_samples, trak_box, &boxes, file_size)
+                    {
+                        track_samples.warnings.push(w);
+                    }
+                    if track_samples.handler_type == "soun" {
+                        track_samples.gapless = gapless;
+                    }
                     result.push(track_samples);
                 }
             }
@@ -173,6 +910,216 @@ pub fn track_samples_from_reader<R: Read + Seek>(
     Ok(result)
 }
 
+/// Append the samples of every `moof/traf` belonging to `track` (matched by
+/// the tfhd track_id), in file order.
+///
+/// Per-sample values come from the trun, then the tfhd defaults, then the
+/// track's `mvex/trex` defaults. Decode times start at the traf's `tfdt` when
+/// present and otherwise continue from the previous fragment.
+fn append_fragment_samples(track: &mut TrackSamples, moov_box: &crate::Box, boxes: &[crate::Box]) {
+    use crate::registry::{StructuredData, TrexData};
+
+    let trexes: Vec<&TrexData> = moov_box
+        .children
+        .iter()
+        .flatten()
+        .filter(|b| b.typ == "mvex")
+        .flat_map(|mvex| mvex.children.iter().flatten())
+        .filter_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackExtends(trex)) => Some(trex),
+            _ => None,
+        })
+        .collect();
+
+    let mut next_dts = track
+        .samples
+        .last()
+        .map_or(0, |s| s.dts.saturating_add(s.duration as u64));
+    let mut dts_overflow = None;
+
+    for (fragment, moof) in boxes.iter().filter(|b| b.typ == "moof").enumerate() {
+        // Without an explicit base, the first traf starts at the moof and each
+        // following traf where the previous one's data ended. Offsets come from
+        // the file, so they are `None` once they no longer fit in 64 bits.
+        let mut traf_base = Some(moof.offset);
+
+        for traf in moof.children.iter().flatten().filter(|b| b.typ == "traf") {
+            let kids = traf.children.as_deref().unwrap_or_default();
+            let Some(tfhd) = kids.iter().find_map(|b| match &b.structured_data {
+                Some(StructuredData::TrackFragmentHeader(tfhd)) => Some(tfhd),
+                _ => None,
+            }) else {
+                continue;
+            };
+            let trex = trexes.iter().find(|t| t.track_id == tfhd.track_id);
+            // Other tracks' runs are still walked: their data advances the implicit base.
+            let ours = tfhd.track_id == track.track_id;
+
+            let base = match tfhd.base_data_offset {
+                Some(offset) => Some(offset),
+                None if tfhd.default_base_is_moof => Some(moof.offset),
+                None => traf_base,
+            };
+            let mut data_end = base;
+
+            // Samples whose explicit duration disagrees with the fragment's default
+            let default_duration = match (tfhd.default_sample_duration, trex) {
+                (Some(d), _) => Some((d, "tfhd")),
+                (None, Some(t)) if t.default_sample_duration > 0 => {
+                    Some((t.default_sample_duration, "trex"))
+                }
+                _ => None,
+            };
+            let mut traf_samples = 0usize;
+            let mut deviating = 0usize;
+
+            if ours
+                && let Some(tfdt) = kids.iter().find_map(|b| match &b.structured_data {
+                    Some(StructuredData::TrackFragmentDecodeTime(tfdt)) => Some(tfdt),
+                    _ => None,
+                })
+            {
+                next_dts = tfdt.base_media_decode_time;
+            }
+
+            for (trun_box, trun) in kids.iter().filter_map(|b| match &b.structured_data {
+                Some(StructuredData::TrackRun(trun)) => Some((b, trun)),
+                _ => None,
+            }) {
+                // data_offset is relative to the base; without it the run
+                // continues right after the previous one.
+                let mut offset = match trun.data_offset {
+                    Some(d) => base.and_then(|b| b.checked_add_signed(d as i64)),
+                    None => data_end,
+                };
+                // Our samples in this run left without an offset: (first, count)
+                let mut unplaced: Option<(u32, usize)> = None;
+
+                // Per-sample value, else the tfhd default, else the trex default.
+                let mut unresolved = Vec::new();
+                for (i, s) in trun.samples.iter().enumerate() {
+                    let size = s
+                        .size
+                        .or(tfhd.default_sample_size)
+                        .or(trex.map(|t| t.default_sample_size));
+                    if ours {
+                        let duration = s
+                            .duration
+                            .or(tfhd.default_sample_duration)
+                            .or(trex.map(|t| t.default_sample_duration));
+                        for (field, value) in [("size", size), ("duration", duration)] {
+                            if value.is_none() && !unresolved.contains(&field) {
+                                unresolved.push(field);
+                            }
+                        }
+                        let duration = duration.unwrap_or(0);
+                        traf_samples += 1;
+                        if default_duration.is_some_and(|(d, _)| duration != d) {
+                            deviating += 1;
+                        }
+                        let flags = s
+                            .flags
+                            .or(if i == 0 {
+                                trun.first_sample_flags
+                            } else {
+                                None
+                            })
+                            .or(tfhd.default_sample_flags)
+                            .or(trex.map(|t| t.default_sample_flags))
+                            .map(SampleFlags::from_bits)
+                            .unwrap_or(SampleFlags::from_bits(0));
+                        let cto = s.composition_time_offset.unwrap_or(0);
+                        let pts = next_dts.saturating_add_signed(cto);
+
+                        track.samples.push(SampleInfo {
+                            index: track.samples.len() as u32,
+                            dts: next_dts,
+                            pts,
+                            start_time: if track.timescale > 0 {
+                                pts as f64 / track.timescale as f64
+                            } else {
+                                0.0
+                            },
+                            duration,
+                            effective_duration: duration,
+                            rendered_offset: cto,
+                            file_offset: offset,
+                            size: size.unwrap_or(0),
+                            is_sync: flags.is_sync(),
+                            flags: Some(flags),
+                        });
+                        if offset.is_none() {
+                            let number = track.samples.len() as u32;
+                            unplaced.get_or_insert((number, 0)).1 += 1;
+                        }
+                        next_dts = next_dts.checked_add(duration as u64).unwrap_or_else(|| {
+                            dts_overflow.get_or_insert(trun_box.offset);
+                            u64::MAX
+                        });
+                    }
+                    offset = offset.and_then(|o| o.checked_add(size.unwrap_or(0) as u64));
+                }
+                data_end = offset;
+
+                if let Some((first, count)) = unplaced {
+                    track.warnings.push(crate::Warning::new(
+                        trun_box.offset,
+                        "trun",
+                        format!(
+                            "{} samples (first: sample {}) have no file offset: \
+                             the fragment data offset does not fit in 64 bits",
+                            count, first
+                        ),
+                    ));
+                }
+
+                if !unresolved.is_empty() {
+                    track.warnings.push(crate::Warning::new(
+                        trun_box.offset,
+                        "trun",
+                        format!(
+                            "no sample {} in trun, tfhd or trex for track {}; using 0",
+                            unresolved.join(" or "),
+                            track.track_id
+                        ),
+                    ));
+                }
+            }
+            if deviating > 0
+                && let Some((default, source)) = default_duration
+            {
+                track.warnings.push(crate::Warning::new(
+                    traf.offset,
+                    "traf",
+                    format!(
+                        "{} of {} sample durations in fragment {} differ from the {} default of {} \
+                         for track {}",
+                        deviating,
+                        traf_samples,
+                        fragment + 1,
+                        source,
+                        default,
+                        track.track_id
+                    ),
+                ));
+            }
+            traf_base = data_end;
+        }
+    }
+
+    if let Some(trun_offset) = dts_overflow {
+        track.warnings.push(crate::Warning::new(
+            trun_offset,
+            "trun",
+            format!(
+                "decode times of track {} run past 64 bits; later samples are held at the maximum",
+                track.track_id
+            ),
+        ));
+    }
+    track.sample_count = track.samples.len() as u32;
+}
+
 /// Extracts sample information from all tracks in an MP4 file specified by file path.
 ///
 /// This is a convenience function that opens a file from the filesystem and delegates
@@ -223,6 +1170,159 @@ pub fn track_samples_from_path(path: impl AsRef<Path>) -> anyhow::Result<Vec<Tra
     track_samples_from_reader(file)
 }
 
+/// Total number of samples across every track of the file at `path`: the
+/// `stsz`/`stz2` sample counts plus the `trun` sample counts of any movie
+/// fragments.
+///
+/// Only the count fields are read; no sample tables are decoded and no
+/// [`SampleInfo`] is built, so this is cheap even for long files.
+pub fn total_sample_count(path: impl AsRef<Path>) -> anyhow::Result<u64> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let boxes = crate::get_boxes(&mut file, size, false)?;
+
+    // (path, offset of the sample_count field after version/flags)
+    let count_fields: [(&[&str], u64); 3] = [
+        (&["moov", "trak", "mdia", "minf", "stbl", "stsz"], 4),
+        (&["moov", "trak", "mdia", "minf", "stbl", "stz2"], 4),
+        (&["moof", "traf", "trun"], 0),
+    ];
+    let mut total = 0u64;
+    for (path, field_offset) in count_fields {
+        for b in crate::find_path(&boxes, path) {
+            let Some(offset) = b.payload_offset else {
+                continue;
+            };
+            let field = crate::util::read_slice(&mut file, offset + field_offset, 4)
+                .with_context(|| format!("reading {} sample count at {:#x}", b.typ, offset))?;
+            total += u32::from_be_bytes([field[0], field[1], field[2], field[3]]) as u64;
+        }
+    }
+    Ok(total)
+}
+
+/// Reads the payload of one sample: `size` bytes at its `file_offset`.
+///
+/// Fails if the sample's offset is unknown or the range extends past the end
+/// of the reader.
+pub fn read_sample_bytes<R: Read + Seek>(
+    reader: &mut R,
+    sample: &SampleInfo,
+) -> anyhow::Result<Vec<u8>> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    read_sample_in(reader, sample, file_size)
+}
+
+/// Reads the payload of every sample of `track`, in decode order.
+///
+/// See [`read_sample_bytes`]; the first sample that can't be read aborts the
+/// whole batch.
+pub fn read_all_samples<R: Read + Seek>(
+    reader: &mut R,
+    track: &TrackSamples,
+) -> anyhow::Result<Vec<Vec<u8>>> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    track
+        .samples
+        .iter()
+        .map(|s| read_sample_in(reader, s, file_size))
+        .collect()
+}
+
+fn read_sample_in<R: Read + Seek>(
+    reader: &mut R,
+    sample: &SampleInfo,
+    file_size: u64,
+) -> anyhow::Result<Vec<u8>> {
+    let Some(offset) = sample.file_offset else {
+        anyhow::bail!("sample {} has no known file offset", sample.sample_number());
+    };
+    if offset
+        .checked_add(sample.size as u64)
+        .is_none_or(|end| end > file_size)
+    {
+        anyhow::bail!(
+            "sample {} ({} bytes @ {:#x}) extends past the end of the file ({} bytes)",
+            sample.sample_number(),
+            sample.size,
+            offset,
+            file_size
+        );
+    }
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut buf = vec![0u8; sample.size as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Returns the edit list (`edts/elst`) of every track in the file, keyed by
+/// track ID, in `moov` order.
+///
+/// Tracks without an edit list are included with an empty list, so trimming
+/// and delays can be compared across all tracks at once.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use mp4box::edit_lists;
+///
+/// for (track_id, edits) in edit_lists("video.mp4").unwrap() {
+///     let delay: u64 = edits
+///         .iter()
+///         .filter(|e| e.media_time == -1)
+///         .map(|e| e.segment_duration)
+///         .sum();
+///     println!("Track {}: {} edits, {} empty", track_id, edits.len(), delay);
+/// }
+/// ```
+pub fn edit_lists(path: impl AsRef<Path>) -> anyhow::Result<Vec<(u32, Vec<ElstEntry>)>> {
+    let mut file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let boxes = crate::get_boxes(&mut file, file_size, /*decode=*/ true)
+        .context("getting boxes from file")?;
+
+    let mut result = Vec::new();
+    for moov_box in boxes.iter().filter(|b| b.typ == "moov") {
+        for trak_box in moov_box
+            .children
+            .iter()
+            .flatten()
+            .filter(|b| b.typ == "trak")
+        {
+            result.push((find_track_id(trak_box)?, find_edits(trak_box)));
+        }
+    }
+    Ok(result)
+}
+
+/// Lists the chunks of a track with the samples each one holds, pairing the
+/// `stco`/`co64` offsets with the `stsc` mapping.
+///
+/// Useful for visualizing how tracks are interleaved in `mdat`. Chunks that
+/// `stsc` doesn't cover report 0 samples.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use mp4box::{extract_chunk_table, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4").unwrap();
+/// let size = file.metadata().unwrap().len();
+/// let boxes = get_boxes(&mut file, size, true).unwrap();
+/// let moov = boxes.iter().find(|b| b.typ == "moov").unwrap();
+/// for trak in moov.children.iter().flatten().filter(|b| b.typ == "trak") {
+///     for chunk in extract_chunk_table(trak).unwrap() {
+///         println!("chunk {} @ {:#x}: {} samples", chunk.index, chunk.offset, chunk.sample_count);
+///     }
+/// }
+/// ```
+pub fn extract_chunk_table(trak_box: &crate::Box) -> anyhow::Result<Vec<ChunkEntry>> {
+    let stbl_box = find_stbl_box(trak_box)?;
+    let tables = extract_sample_tables(stbl_box)?;
+    Ok(chunk_table(&tables))
+}
+
 /// Extracts sample information from a single track box (trak) in an MP4 file.
 ///
 /// This function processes a specific track box from an already-parsed MP4 file structure
@@ -295,7 +1395,36 @@ pub fn extract_track_samples<R: Read + Seek>(
     let track_id = find_track_id(trak_box)?;
 
     // Find handler type from mdhd
-    let (handler_type, timescale, duration) = find_media_info(trak_box)?;
+    let MediaInfo {
+        handler_type,
+        handler_name,
+        timescale,
+        duration,
+    } = find_media_info(trak_box)?;
+
+    let mut warnings = Vec::new();
+    let decoded =
+        |typ: &str| child_at(trak_box, &["mdia", typ]).is_some_and(|b| b.structured_data.is_some());
+    if !decoded("mdhd") {
+        warnings.push(crate::Warning::new(
+            trak_box.offset,
+            "trak",
+            format!(
+                "track {} has no readable mdhd; assuming timescale 1000",
+                track_id
+            ),
+        ));
+    }
+    if !decoded("hdlr") {
+        warnings.push(crate::Warning::new(
+            trak_box.offset,
+            "trak",
+            format!(
+                "track {} has no readable hdlr; assuming handler 'vide'",
+                track_id
+            ),
+        ));
+    }
 
     // Find sample table (stbl) box
     let stbl_box = find_stbl_box(trak_box)?;
@@ -304,19 +1433,187 @@ pub fn extract_track_samples<R: Read + Seek>(
     let sample_tables = extract_sample_tables(stbl_box)?;
 
     // Build sample information from the tables
-    let samples = build_sample_info(&sample_tables, timescale, reader)?;
+    let (samples, offset_issues) = build_sample_info(&sample_tables, timescale, duration, reader)?;
     let sample_count = samples.len() as u32;
 
+    if let Some(w) = check_stsz_consistency(&sample_tables, stbl_box) {
+        warnings.push(w);
+    }
+    if sample_tables.stco.is_some() && sample_tables.co64.is_some() {
+        warnings.push(crate::Warning::new(
+            child_offset(stbl_box, "co64"),
+            "co64",
+            "both stco and co64 are present; using co64",
+        ));
+    }
+    warnings.extend(offset_warnings(&offset_issues, stbl_box));
+
     Ok(Some(TrackSamples {
         track_id,
         handler_type,
+        handler_name,
         timescale,
         duration,
         sample_count,
         samples,
+        sample_entry: sample_tables
+            .stsd
+            .and_then(|stsd| stsd.entries.first().cloned()),
+        edits: find_edits(trak_box),
+        gapless: None,
+        data_self_contained: find_data_self_contained(trak_box),
+        warnings,
     }))
 }
 
+/// Like [`extract_track_samples`], with [`ParseOptions`](crate::ParseOptions).
+///
+/// With `opts.apply_edits`, presentation times are shifted by the track's edit
+/// list (see [`TrackSamples::apply_edits`]); `movie_timescale` is the `mvhd`
+/// timescale that edit durations are expressed in.
+pub fn extract_track_samples_opts<R: Read + Seek>(
+    trak_box: &crate::Box,
+    reader: &mut R,
+    movie_timescale: u32,
+    opts: &crate::ParseOptions,
+) -> anyhow::Result<Option<TrackSamples>> {
+    let mut track = extract_track_samples(trak_box, reader)?;
+    if opts.apply_edits
+        && let Some(track) = &mut track
+    {
+        track.apply_edits(movie_timescale);
+    }
+    Ok(track)
+}
+
+/// One warning per kind of sample whose file offset could not be computed.
+fn offset_warnings(issues: &[OffsetIssue], stbl_box: &crate::Box) -> Vec<crate::Warning> {
+    issues
+        .iter()
+        .map(|issue| {
+            let (box_type, reason) = match issue.error {
+                OffsetError::NoChunkOffsets => ("stbl", "there is no stco or co64".to_string()),
+                OffsetError::NoChunkMapping => ("stbl", "stsc or stsz is missing".to_string()),
+                OffsetError::Unmapped => ("stsc", "stsc maps no chunk to them".to_string()),
+                OffsetError::ChunkOutOfRange { chunk, chunk_count } => (
+                    "stsc",
+                    format!(
+                        "stsc references chunk {} but the chunk offset table has {} entries",
+                        chunk, chunk_count
+                    ),
+                ),
+            };
+            crate::Warning::new(
+                child_offset(stbl_box, box_type),
+                box_type,
+                format!(
+                    "{} samples (first: sample {}) have no file offset: {}",
+                    issue.count, issue.first_sample, reason
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Offset of the `typ` child of `stbl`, or of `stbl` itself.
+fn child_offset(stbl_box: &crate::Box, typ: &str) -> u64 {
+    stbl_box
+        .children
+        .as_ref()
+        .and_then(|c| c.iter().find(|b| b.typ == typ))
+        .map_or(stbl_box.offset, |b| b.offset)
+}
+
+/// A non-zero `sample_size` means every sample has that size and no per-sample
+/// table follows; having both is an encoder bug. The constant size wins.
+fn check_stsz_consistency(tables: &SampleTables, stbl_box: &crate::Box) -> Option<crate::Warning> {
+    let stsz = tables.stsz?;
+    if stsz.sample_size == 0 || stsz.sample_sizes.is_empty() {
+        return None;
+    }
+
+    Some(crate::Warning::new(
+        child_offset(stbl_box, "stsz"),
+        "stsz",
+        format!(
+            "sample_size is {} but {} per-sample sizes follow; using the constant size",
+            stsz.sample_size,
+            stsz.sample_sizes.len()
+        ),
+    ))
+}
+
+/// Check that every sample's bytes lie inside a top-level `mdat` payload.
+///
+/// Offsets that land in `moov` or other metadata usually mean a stale `stco`
+/// after the file was rewritten (e.g. faststart without offset fix-up).
+/// Returns a single warning for the track, attached to its chunk offset box.
+/// Tracks whose `dref` points at another file are skipped: their offsets
+/// refer to that file.
+fn check_samples_in_mdat(
+    track: &TrackSamples,
+    trak_box: &crate::Box,
+    top_level: &[crate::Box],
+    file_size: u64,
+) -> Option<crate::Warning> {
+    if !track.data_self_contained {
+        return None;
+    }
+    let mdat_ranges: Vec<(u64, u64)> = top_level
+        .iter()
+        .filter(|b| b.typ == "mdat")
+        .map(|b| {
+            let end = if b.size == 0 {
+                file_size
+            } else {
+                b.offset.saturating_add(b.size)
+            };
+            (b.offset.saturating_add(b.header_size), end)
+        })
+        .collect();
+
+    // Samples without an offset are reported when the tables are read.
+    let outside: Vec<(&SampleInfo, u64)> = track
+        .samples
+        .iter()
+        .filter_map(|s| Some((s, s.file_offset?)))
+        .filter(|&(s, offset)| {
+            let end = offset.saturating_add(s.size as u64);
+            !mdat_ranges
+                .iter()
+                .any(|&(start, stop)| offset >= start && end <= stop)
+        })
+        .collect();
+
+    let &(first, first_offset) = outside.first()?;
+    let location = top_level
+        .iter()
+        .find(|b| first_offset >= b.offset && first_offset < b.offset.saturating_add(b.size))
+        .map(|b| format!("inside '{}'", b.typ))
+        .unwrap_or_else(|| "outside any top-level box".to_string());
+
+    let (offset, box_type) = find_stbl_box(trak_box)
+        .ok()
+        .and_then(|stbl| stbl.children.as_ref())
+        .and_then(|c| c.iter().find(|b| b.typ == "stco" || b.typ == "co64"))
+        .map(|b| (b.offset, b.typ.clone()))
+        .unwrap_or((trak_box.offset, trak_box.typ.clone()));
+
+    Some(crate::Warning::new(
+        offset,
+        box_type,
+        format!(
+            "track {}: {} of {} samples lie outside mdat (first: sample {} at {:#x}, {})",
+            track.track_id,
+            outside.len(),
+            track.samples.len(),
+            first.index,
+            first_offset,
+            location
+        ),
+    ))
+}
+
 fn find_track_id(trak_box: &crate::Box) -> anyhow::Result<u32> {
     use crate::registry::StructuredData;
 
@@ -334,7 +1631,14 @@ fn find_track_id(trak_box: &crate::Box) -> anyhow::Result<u32> {
     anyhow::bail!("No tkhd box found or track ID could not be parsed")
 }
 
-fn find_media_info(trak_box: &crate::Box) -> anyhow::Result<(String, u32, u64)> {
+struct MediaInfo {
+    handler_type: String,
+    handler_name: Option<String>,
+    timescale: u32,
+    duration: u64,
+}
+
+fn find_media_info(trak_box: &crate::Box) -> anyhow::Result<MediaInfo> {
     use crate::registry::StructuredData;
 
     // Look for mdia/mdhd and mdia/hdlr boxes
@@ -346,6 +1650,7 @@ fn find_media_info(trak_box: &crate::Box) -> anyhow::Result<(String, u32, u64)>
                 let mut timescale = 1000; // Default
                 let mut duration = 0; // Default
                 let mut handler_type = String::from("vide"); // Default
+                let mut handler_name = None;
 
                 for mdia_child in mdia_children {
                     if mdia_child.typ == "mdhd" {
@@ -363,64 +1668,90 @@ fn find_media_info(trak_box: &crate::Box) -> anyhow::Result<(String, u32, u64)>
                             &mdia_child.structured_data
                         {
                             handler_type = hdlr_data.handler_type.clone();
+                            if !hdlr_data.name.is_empty() {
+                                handler_name = Some(hdlr_data.name.clone());
+                            }
                         }
                     }
                 }
 
-                return Ok((handler_type, timescale, duration));
+                return Ok(MediaInfo {
+                    handler_type,
+                    handler_name,
+                    timescale,
+                    duration,
+                });
             }
         }
     }
-    Ok((String::from("vide"), 1000, 0))
+    Ok(MediaInfo {
+        handler_type: String::from("vide"),
+        handler_name: None,
+        timescale: 1000,
+        duration: 0,
+    })
 }
 
-fn find_stbl_box(trak_box: &crate::Box) -> anyhow::Result<&crate::Box> {
-    // Navigate to mdia/minf/stbl
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "mdia"
-                && let Some(mdia_children) = &child.children
-            {
-                for mdia_child in mdia_children {
-                    if mdia_child.typ == "minf"
-                        && let Some(minf_children) = &mdia_child.children
-                    {
-                        for minf_child in minf_children {
-                            if minf_child.typ == "stbl" {
-                                return Ok(minf_child);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+fn find_edits(trak_box: &crate::Box) -> Vec<ElstEntry> {
+    use crate::registry::StructuredData;
+
+    match child_at(trak_box, &["edts", "elst"]).and_then(|e| e.structured_data.as_ref()) {
+        Some(StructuredData::EditList(elst)) => elst.entries.clone(),
+        _ => Vec::new(),
     }
-    anyhow::bail!("stbl box not found")
 }
 
-#[derive(Debug)]
-struct SampleTables {
-    stsd: Option<crate::registry::StsdData>,
-    stts: Option<crate::registry::SttsData>,
-    ctts: Option<crate::registry::CttsData>,
-    stsc: Option<crate::registry::StscData>,
-    stsz: Option<crate::registry::StszData>,
-    stss: Option<crate::registry::StssData>,
-    stco: Option<crate::registry::StcoData>,
-    co64: Option<crate::registry::Co64Data>,
-}
+/// `iTunSMPB` lives in the movie-level iTunes metadata (`moov/udta/meta/ilst`).
+fn find_gapless_info(moov_box: &crate::Box) -> Option<GaplessInfo> {
+    use crate::registry::StructuredData;
 
-fn extract_sample_tables(stbl_box: &crate::Box) -> anyhow::Result<SampleTables> {
-    let mut tables = SampleTables {
-        stsd: None,
-        stts: None,
-        ctts: None,
-        stsc: None,
-        stsz: None,
-        stss: None,
-        stco: None,
-        co64: None,
+    let ilst = child_at(moov_box, &["udta", "meta", "ilst"])?;
+    let Some(StructuredData::Metadata(ilst)) = &ilst.structured_data else {
+        return None;
     };
+    ilst.items.iter().find_map(|item| item.gapless)
+}
+
+/// A track without a decodable `dref` is treated as self-contained, which is
+/// what every player assumes too.
+fn find_data_self_contained(trak_box: &crate::Box) -> bool {
+    use crate::registry::StructuredData;
+
+    let dref = child_at(trak_box, &["mdia", "minf", "dinf", "dref"]);
+    match dref.and_then(|d| d.structured_data.as_ref()) {
+        Some(StructuredData::DataReference(dref)) => dref.entries.iter().all(|e| e.self_contained),
+        _ => true,
+    }
+}
+
+fn find_stbl_box(trak_box: &crate::Box) -> anyhow::Result<&crate::Box> {
+    child_at(trak_box, &["mdia", "minf", "stbl"]).context("stbl box not found")
+}
+
+/// First box at `path` below `parent` (see [`crate::find_path`]).
+fn child_at<'a>(parent: &'a crate::Box, path: &[&str]) -> Option<&'a crate::Box> {
+    let children = parent.children.as_deref().unwrap_or_default();
+    crate::find_path(children, path).into_iter().next()
+}
+
+/// Sample tables borrowed from the decoded `stbl` children.
+///
+/// Tables can hold hundreds of thousands of entries, so they are referenced
+/// in place rather than copied out of the box tree.
+#[derive(Debug, Default)]
+struct SampleTables<'a> {
+    stsd: Option<&'a crate::registry::StsdData>,
+    stts: Option<&'a crate::registry::SttsData>,
+    ctts: Option<&'a crate::registry::CttsData>,
+    stsc: Option<&'a crate::registry::StscData>,
+    stsz: Option<&'a crate::registry::StszData>,
+    stss: Option<&'a crate::registry::StssData>,
+    stco: Option<&'a crate::registry::StcoData>,
+    co64: Option<&'a crate::registry::Co64Data>,
+}
+
+fn extract_sample_tables(stbl_box: &crate::Box) -> anyhow::Result<SampleTables<'_>> {
+    let mut tables = SampleTables::default();
 
     // Extract structured data directly from child boxes
     if let Some(children) = &stbl_box.children {
@@ -428,33 +1759,31 @@ fn extract_sample_tables(stbl_box: &crate::Box) -> anyhow::Result<SampleTables>
             if let Some(structured_data) = &child.structured_data {
                 match structured_data {
                     crate::registry::StructuredData::SampleDescription(data) => {
-                        tables.stsd = Some(data.clone());
+                        tables.stsd = Some(data);
                     }
                     crate::registry::StructuredData::DecodingTimeToSample(data) => {
-                        tables.stts = Some(data.clone());
+                        tables.stts = Some(data);
                     }
                     crate::registry::StructuredData::CompositionTimeToSample(data) => {
-                        tables.ctts = Some(data.clone());
+                        tables.ctts = Some(data);
                     }
                     crate::registry::StructuredData::SampleToChunk(data) => {
-                        tables.stsc = Some(data.clone());
+                        tables.stsc = Some(data);
                     }
                     crate::registry::StructuredData::SampleSize(data) => {
-                        tables.stsz = Some(data.clone());
+                        tables.stsz = Some(data);
                     }
                     crate::registry::StructuredData::SyncSample(data) => {
-                        tables.stss = Some(data.clone());
+                        tables.stss = Some(data);
                     }
                     crate::registry::StructuredData::ChunkOffset(data) => {
-                        tables.stco = Some(data.clone());
+                        tables.stco = Some(data);
                     }
                     crate::registry::StructuredData::ChunkOffset64(data) => {
-                        tables.co64 = Some(data.clone());
+                        tables.co64 = Some(data);
                     }
-                    // MediaHeader, HandlerReference, and TrackHeader are not sample table data, ignore them
-                    crate::registry::StructuredData::MediaHeader(_) => {}
-                    crate::registry::StructuredData::HandlerReference(_) => {}
-                    crate::registry::StructuredData::TrackHeader(_) => {}
+                    // Headers, codec configuration, etc. are not sample table data, ignore them
+                    _ => {}
                 }
             }
         }
@@ -466,15 +1795,17 @@ fn extract_sample_tables(stbl_box: &crate::Box) -> anyhow::Result<SampleTables>
 fn build_sample_info<R: Read + Seek>(
     tables: &SampleTables,
     timescale: u32,
+    media_duration: u64,
     _reader: &mut R,
-) -> anyhow::Result<Vec<SampleInfo>> {
+) -> anyhow::Result<(Vec<SampleInfo>, Vec<OffsetIssue>)> {
     let mut samples = Vec::new();
+    let mut issues: Vec<OffsetIssue> = Vec::new();
 
     // Get sample count from stsz
-    let sample_count = if let Some(stsz) = &tables.stsz {
+    let sample_count = if let Some(stsz) = tables.stsz {
         stsz.sample_count
     } else {
-        return Ok(samples);
+        return Ok((samples, issues));
     };
 
     // Calculate timing information from stts
@@ -484,41 +1815,70 @@ fn build_sample_info<R: Read + Seek>(
     // Build samples using the available tables
     for i in 0..sample_count {
         // Get duration from stts or use default
-        let duration = if let Some(stts) = &tables.stts {
+        let duration = if let Some(stts) = tables.stts {
             get_sample_duration_from_stts(stts, i).unwrap_or(default_duration)
         } else {
             default_duration
         };
 
         // Calculate PTS from DTS + composition offset
-        let composition_offset = if let Some(ctts) = &tables.ctts {
+        let composition_offset = if let Some(ctts) = tables.ctts {
             get_composition_offset_from_ctts(ctts, i).unwrap_or(0)
         } else {
             0
         };
 
-        let pts = current_dts.saturating_add_signed(composition_offset as i64);
+        let pts = current_dts.saturating_add_signed(composition_offset);
+
+        let file_offset = match get_sample_file_offset(tables, i) {
+            Ok(offset) => Some(offset),
+            Err(error) => {
+                let same_kind = |issue: &&mut OffsetIssue| {
+                    std::mem::discriminant(&issue.error) == std::mem::discriminant(&error)
+                };
+                match issues.iter_mut().find(same_kind) {
+                    Some(issue) => issue.count += 1,
+                    None => issues.push(OffsetIssue {
+                        error,
+                        first_sample: i,
+                        count: 1,
+                    }),
+                }
+                None
+            }
+        };
 
-        let sample = SampleInfo {
+        let mut sample = SampleInfo {
             index: i,
             dts: current_dts,
             pts,
             start_time: pts as f64 / timescale as f64,
             duration,
-            rendered_offset: composition_offset as i64,
-            file_offset: get_sample_file_offset(tables, i),
-            size: get_sample_size(&tables.stsz, i),
-            is_sync: is_sync_sample(&tables.stss, i + 1), // stss uses 1-based indexing
+            effective_duration: duration,
+            rendered_offset: composition_offset,
+            file_offset,
+            size: get_sample_size(tables.stsz, i),
+            is_sync: false,
+            flags: None,
         };
+        sample.is_sync = is_sync_sample(tables.stss, sample.sample_number());
 
         current_dts += duration as u64;
         samples.push(sample);
     }
 
-    Ok(samples)
+    // The last stts delta often overshoots; the media duration marks the real end.
+    if media_duration > 0
+        && let Some(last) = samples.last_mut()
+        && last.dts + last.duration as u64 > media_duration
+    {
+        last.effective_duration = media_duration.saturating_sub(last.dts) as u32;
+    }
+
+    Ok((samples, issues))
 }
 
-fn get_sample_size(stsz: &Option<crate::registry::StszData>, index: u32) -> u32 {
+fn get_sample_size(stsz: Option<&crate::registry::StszData>, index: u32) -> u32 {
     if let Some(stsz) = stsz {
         if stsz.sample_size > 0 {
             // All samples have the same size
@@ -533,7 +1893,7 @@ fn get_sample_size(stsz: &Option<crate::registry::StszData>, index: u32) -> u32
     }
 }
 
-fn is_sync_sample(stss: &Option<crate::registry::StssData>, sample_number: u32) -> bool {
+fn is_sync_sample(stss: Option<&crate::registry::StssData>, sample_number: u32) -> bool {
     if let Some(stss) = stss {
         stss.sample_numbers.contains(&sample_number)
     } else {
@@ -563,7 +1923,7 @@ fn get_sample_duration_from_stts(
 fn get_composition_offset_from_ctts(
     ctts: &crate::registry::CttsData,
     sample_index: u32,
-) -> Option<i32> {
+) -> Option<i64> {
     let mut current_sample = 0;
 
     for entry in &ctts.entries {
@@ -577,26 +1937,75 @@ fn get_composition_offset_from_ctts(
     Some(0)
 }
 
-fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> u64 {
-    // Calculate actual file offset using stsc + stco/co64 + stsz
+/// Why a sample's file offset could not be computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OffsetError {
+    /// Neither stco nor co64 is present
+    NoChunkOffsets,
+    /// stsc or stsz is missing
+    NoChunkMapping,
+    /// stsc runs out before reaching the sample
+    Unmapped,
+    /// stsc puts the sample in a chunk (1-based) past the end of stco/co64
+    ChunkOutOfRange { chunk: u32, chunk_count: usize },
+}
 
-    let stsc = match &tables.stsc {
-        Some(data) => data,
-        None => return 0, // No chunk mapping available
+/// Samples that share one [`OffsetError`] kind, for a single warning per kind.
+struct OffsetIssue {
+    error: OffsetError,
+    first_sample: u32,
+    count: u32,
+}
+
+fn chunk_table(tables: &SampleTables) -> Vec<ChunkEntry> {
+    let offsets: Vec<u64> = if let Some(co64) = tables.co64 {
+        co64.chunk_offsets.clone()
+    } else if let Some(stco) = tables.stco {
+        stco.chunk_offsets.iter().map(|&o| o as u64).collect()
+    } else {
+        Vec::new()
     };
+    let stsc_entries = tables.stsc.map_or(&[][..], |stsc| &stsc.entries);
+
+    let mut first_sample = 0u32;
+    let mut entry = 0usize;
+    let mut chunks = Vec::with_capacity(offsets.len());
+    for (index, &offset) in offsets.iter().enumerate() {
+        // stsc chunk numbers are 1-based.
+        let chunk_number = index as u32 + 1;
+        while entry + 1 < stsc_entries.len() && stsc_entries[entry + 1].first_chunk <= chunk_number
+        {
+            entry += 1;
+        }
+        let sample_count = match stsc_entries.get(entry) {
+            Some(e) if e.first_chunk.max(1) <= chunk_number => e.samples_per_chunk,
+            _ => 0,
+        };
+        chunks.push(ChunkEntry {
+            index: index as u32,
+            offset,
+            sample_count,
+            first_sample,
+        });
+        first_sample = first_sample.saturating_add(sample_count);
+    }
+    chunks
+}
+
+fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> Result<u64, OffsetError> {
+    // Calculate actual file offset using stsc + stco/co64 + stsz
 
-    let stsz = match &tables.stsz {
-        Some(data) => data,
-        None => return 0, // No sample sizes available
+    let (Some(stsc), Some(stsz)) = (tables.stsc, tables.stsz) else {
+        return Err(OffsetError::NoChunkMapping);
     };
 
-    // Get chunk offsets reference (prefer 64-bit if available)
-    let (chunk_offsets_64, chunk_offsets_32) = if let Some(co64) = &tables.co64 {
+    // Get chunk offsets reference; co64 wins when a file carries both.
+    let (chunk_offsets_64, chunk_offsets_32) = if let Some(co64) = tables.co64 {
         (Some(&co64.chunk_offsets), None)
-    } else if let Some(stco) = &tables.stco {
+    } else if let Some(stco) = tables.stco {
         (None, Some(&stco.chunk_offsets))
     } else {
-        return 0; // No chunk offsets available
+        return Err(OffsetError::NoChunkOffsets);
     };
 
     // Helper function to get chunk offset by index
@@ -627,7 +2036,13 @@ fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> u64 {
     let mut sample_offset_in_range = 0u32;
     let mut chunk_offset_in_range = 0u32;
 
+    let mut found = false;
+
     for (i, entry) in stsc.entries.iter().enumerate() {
+        // Chunk numbers are 1-based. The first entry normally starts at chunk 1, but
+        // some muxers start it later; the chunks before it then carry no samples.
+        let first_chunk = entry.first_chunk.max(1);
+
         // Calculate how many samples are covered by previous chunks with this entry's configuration
         let next_first_chunk = if i + 1 < stsc.entries.len() {
             stsc.entries[i + 1].first_chunk
@@ -636,7 +2051,10 @@ fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> u64 {
         };
 
         samples_per_chunk = entry.samples_per_chunk;
-        let chunks_with_this_config = next_first_chunk - entry.first_chunk;
+        if samples_per_chunk == 0 {
+            continue;
+        }
+        let chunks_with_this_config = next_first_chunk.saturating_sub(first_chunk);
         let samples_in_this_range =
             (chunks_with_this_config as u64).saturating_mul(samples_per_chunk as u64);
 
@@ -645,7 +2063,8 @@ fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> u64 {
             // First, find the chunk containing the sample within this range
             sample_offset_in_range = target_sample - current_sample;
             chunk_offset_in_range = sample_offset_in_range / samples_per_chunk;
-            chunk_index = (entry.first_chunk - 1) as usize + chunk_offset_in_range as usize;
+            chunk_index = (first_chunk - 1) as usize + chunk_offset_in_range as usize;
+            found = true;
             break;
         }
 
@@ -653,8 +2072,14 @@ fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> u64 {
             (current_sample as u64 + samples_in_this_range).min(u32::MAX as u64) as u32;
     }
 
+    if !found {
+        return Err(OffsetError::Unmapped);
+    }
     if chunk_index >= chunk_count {
-        return 0; // Chunk index out of bounds
+        return Err(OffsetError::ChunkOutOfRange {
+            chunk: chunk_index as u32 + 1,
+            chunk_count,
+        });
     }
 
     // Get the base offset of the chunk
@@ -684,13 +2109,13 @@ fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> u64 {
         }
     }
 
-    chunk_offset + offset_in_chunk
+    Ok(chunk_offset + offset_in_chunk)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::registry::{StructuredData, TkhdData};
+    use crate::registry::{Matrix, StructuredData, TkhdData};
 
     #[test]
     fn test_find_track_id_from_structured_data() {
@@ -700,8 +2125,15 @@ mod tests {
             flags: 0,
             track_id: 42,
             duration: 48000,
+            layer: 0,
+            alternate_group: 0,
+            volume: 0.0,
+            matrix: Matrix::IDENTITY,
             width: 1920.0,
             height: 1080.0,
+            enabled: true,
+            in_movie: true,
+            in_preview: false,
         };
 
         let tkhd_box = crate::Box {
@@ -752,8 +2184,15 @@ mod tests {
                 flags: 0,
                 track_id: expected_id,
                 duration: 24000,
+                layer: 0,
+                alternate_group: 0,
+                volume: 0.0,
+                matrix: Matrix::IDENTITY,
                 width: 0.0,
                 height: 0.0,
+                enabled: true,
+                in_movie: true,
+                in_preview: false,
             };
 
             let tkhd_box = crate::Box {
@@ -824,4 +2263,77 @@ mod tests {
                 .contains("No tkhd box found")
         );
     }
+
+    fn stsc_tables(
+        stsc_entries: Vec<(u32, u32)>,
+        chunk_offsets: Vec<u32>,
+    ) -> (
+        crate::registry::StscData,
+        crate::registry::StszData,
+        crate::registry::StcoData,
+    ) {
+        use crate::registry::{StcoData, StscData, StscEntry, StszData};
+
+        let stsc = StscData {
+            version: 0,
+            flags: 0,
+            entry_count: stsc_entries.len() as u32,
+            entries: stsc_entries
+                .into_iter()
+                .map(|(first_chunk, samples_per_chunk)| StscEntry {
+                    first_chunk,
+                    samples_per_chunk,
+                    sample_description_index: 1,
+                })
+                .collect(),
+        };
+        let stsz = StszData {
+            version: 0,
+            flags: 0,
+            sample_size: 10,
+            sample_count: 4,
+            sample_sizes: vec![],
+        };
+        let stco = StcoData {
+            version: 0,
+            flags: 0,
+            entry_count: chunk_offsets.len() as u32,
+            chunk_offsets,
+        };
+        (stsc, stsz, stco)
+    }
+
+    #[test]
+    fn test_stsc_first_chunk_not_one() {
+        // The first stsc entry starts at chunk 2, so chunk 1 holds no samples.
+        let (stsc, stsz, stco) = stsc_tables(vec![(2, 2)], vec![100, 200, 300]);
+        let tables = SampleTables {
+            stsc: Some(&stsc),
+            stsz: Some(&stsz),
+            stco: Some(&stco),
+            ..Default::default()
+        };
+
+        let offsets: Vec<u64> = (0..4)
+            .map(|i| get_sample_file_offset(&tables, i).unwrap())
+            .collect();
+        assert_eq!(offsets, vec![200, 210, 300, 310]);
+    }
+
+    #[test]
+    fn test_stsc_degenerate_entries_do_not_panic() {
+        // first_chunk = 0 is treated as chunk 1, and a zero samples_per_chunk entry is skipped.
+        let (stsc, stsz, stco) = stsc_tables(vec![(0, 1), (2, 0), (2, 3)], vec![100, 200]);
+        let tables = SampleTables {
+            stsc: Some(&stsc),
+            stsz: Some(&stsz),
+            stco: Some(&stco),
+            ..Default::default()
+        };
+
+        let offsets: Vec<u64> = (0..4)
+            .map(|i| get_sample_file_offset(&tables, i).unwrap())
+            .collect();
+        assert_eq!(offsets, vec![100, 200, 210, 220]);
+    }
 }
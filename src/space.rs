@@ -0,0 +1,203 @@
+//! Where the bytes of a file go: per-type usage, free-space classification
+//! and how much of `mdat` the sample tables actually reference.
+
+use serde::Serialize;
+use std::fs::File;
+use std::path::Path;
+
+/// How a top-level `free`/`skip` box is being used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FreeSpaceKind {
+    /// Directly follows `moov`; reserved so `moov` can grow in place
+    MoovReserve,
+    /// Nothing but other free space follows it up to the end of the file
+    TrailingPadding,
+    /// Anywhere else
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FreeSpace {
+    pub offset: u64,
+    pub size: u64,
+    pub box_type: String,
+    pub kind: FreeSpaceKind,
+}
+
+/// Byte usage of a file's top-level boxes.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpaceReport {
+    pub file_size: u64,
+    /// Total bytes per top-level box type, in order of first appearance
+    pub by_type: Vec<(String, u64)>,
+    /// Every top-level `free` / `skip` box
+    pub free_space: Vec<FreeSpace>,
+    /// Bytes of `free`/`skip` reserved directly after `moov`, which faststart
+    /// tooling can reuse when rewriting `moov`
+    pub moov_reserve_bytes: u64,
+}
+
+/// Build a [`SpaceReport`] from the top-level boxes returned by [`crate::get_boxes`].
+pub fn space_report(boxes: &[crate::Box], file_size: u64) -> SpaceReport {
+    let size_of = |b: &crate::Box| {
+        if b.size == 0 {
+            file_size.saturating_sub(b.offset)
+        } else {
+            b.size
+        }
+    };
+    let is_free = |b: &crate::Box| b.typ == "free" || b.typ == "skip";
+
+    let mut by_type: Vec<(String, u64)> = Vec::new();
+    for b in boxes {
+        match by_type.iter_mut().find(|(t, _)| *t == b.typ) {
+            Some((_, total)) => *total += size_of(b),
+            None => by_type.push((b.typ.clone(), size_of(b))),
+        }
+    }
+
+    let mut free_space = Vec::new();
+    for (i, b) in boxes.iter().enumerate().filter(|(_, b)| is_free(b)) {
+        let kind = if i > 0 && boxes[i - 1].typ == "moov" {
+            FreeSpaceKind::MoovReserve
+        } else if boxes[i + 1..].iter().all(is_free) {
+            FreeSpaceKind::TrailingPadding
+        } else {
+            FreeSpaceKind::Other
+        };
+        free_space.push(FreeSpace {
+            offset: b.offset,
+            size: size_of(b),
+            box_type: b.typ.clone(),
+            kind,
+        });
+    }
+
+    let moov_reserve_bytes = free_space
+        .iter()
+        .filter(|f| f.kind == FreeSpaceKind::MoovReserve)
+        .map(|f| f.size)
+        .sum();
+
+    SpaceReport {
+        file_size,
+        by_type,
+        free_space,
+        moov_reserve_bytes,
+    }
+}
+
+/// A contiguous run of bytes in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ByteRange {
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// How the tracks' sample data lines up with the file's `mdat` boxes.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageReport {
+    /// Payload of every top-level `mdat`
+    pub mdat: Vec<ByteRange>,
+    /// Total `mdat` payload bytes
+    pub mdat_bytes: u64,
+    /// `mdat` payload bytes covered by at least one sample
+    pub referenced_bytes: u64,
+    /// Stretches of `mdat` payload that no sample points into
+    pub unreferenced: Vec<ByteRange>,
+    /// Stretches of sample data lying outside every `mdat`, usually the sign
+    /// of a broken chunk offset table
+    pub outside_mdat: Vec<ByteRange>,
+}
+
+/// Compare the union of all tracks' sample byte ranges with the payloads of
+/// the top-level `mdat` boxes of the file at `path`.
+///
+/// Samples are located through [`crate::track_samples_from_reader`]; samples
+/// it could not place (no `file_offset`) are left out.
+pub fn mdat_coverage(path: impl AsRef<Path>) -> anyhow::Result<CoverageReport> {
+    let mut file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let boxes = crate::get_boxes(&mut file, file_size, false)?;
+    let tracks = crate::track_samples_from_reader(file)?;
+
+    let mdat = merge(
+        boxes
+            .iter()
+            .filter(|b| b.typ == "mdat")
+            .map(|b| {
+                // An mdat declaring more than the file holds ends with the file.
+                let end = if b.size == 0 {
+                    file_size
+                } else {
+                    b.offset.saturating_add(b.size).min(file_size)
+                };
+                (b.offset.saturating_add(b.header_size).min(end), end)
+            })
+            .collect(),
+    );
+    let samples = merge(
+        tracks
+            .iter()
+            .flat_map(|t| &t.samples)
+            .filter_map(|s| {
+                let offset = s.file_offset?;
+                Some((offset, offset.saturating_add(s.size as u64)))
+            })
+            .collect(),
+    );
+
+    let unreferenced = subtract(&mdat, &samples);
+    let mdat_bytes: u64 = mdat.iter().map(|(start, end)| end - start).sum();
+    let unreferenced_bytes: u64 = unreferenced.iter().map(|r| r.size).sum();
+
+    Ok(CoverageReport {
+        mdat: mdat
+            .iter()
+            .map(|&(start, end)| to_range(start, end))
+            .collect(),
+        mdat_bytes,
+        referenced_bytes: mdat_bytes - unreferenced_bytes,
+        unreferenced,
+        outside_mdat: subtract(&samples, &mdat),
+    })
+}
+
+fn to_range(start: u64, end: u64) -> ByteRange {
+    ByteRange {
+        offset: start,
+        size: end - start,
+    }
+}
+
+/// Sort half-open `(start, end)` ranges and join the ones that overlap or touch.
+fn merge(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.retain(|(start, end)| end > start);
+    ranges.sort_unstable();
+    let mut out: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match out.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => out.push((start, end)),
+        }
+    }
+    out
+}
+
+/// The parts of `from` not covered by `remove`; both must come from [`merge`].
+fn subtract(from: &[(u64, u64)], remove: &[(u64, u64)]) -> Vec<ByteRange> {
+    let mut out = Vec::new();
+    for &(start, end) in from {
+        let mut pos = start;
+        for &(s, e) in remove.iter().filter(|&&(s, e)| e > start && s < end) {
+            if s > pos {
+                out.push(to_range(pos, s));
+            }
+            pos = pos.max(e);
+        }
+        if pos < end {
+            out.push(to_range(pos, end));
+        }
+    }
+    out
+}
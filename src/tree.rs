@@ -0,0 +1,68 @@
+//! Plain-text rendering of a box tree, for logs, terminals and TUIs.
+
+use crate::util::human_size;
+
+/// Options for [`render_tree`].
+#[derive(Debug, Clone, Default)]
+pub struct RenderOpts {
+    /// Deepest level whose children are listed (top-level boxes are depth 0).
+    /// Containers below it are printed on one line with a count of the
+    /// children left out. `None` renders the whole tree.
+    pub max_depth: Option<usize>,
+    /// Print sizes as exact byte counts instead of `human_size` units
+    pub exact_sizes: bool,
+}
+
+/// Render `boxes` as an indented tree, one box per line:
+///
+/// ```text
+/// moov 1.2 KiB (3.1%) Movie Box
+///   mvhd 108 B (8.8%) Movie Header Box
+/// ```
+///
+/// The percentage is the box's share of its parent's size; for top-level
+/// boxes it is the share of all of `boxes` together.
+pub fn render_tree(boxes: &[crate::Box], opts: RenderOpts) -> String {
+    let mut out = String::new();
+    let total = boxes.iter().map(|b| b.size).sum();
+    render_level(boxes, total, 0, &opts, &mut out);
+    out
+}
+
+fn render_level(
+    boxes: &[crate::Box],
+    parent_size: u64,
+    depth: usize,
+    opts: &RenderOpts,
+    out: &mut String,
+) {
+    for b in boxes {
+        let size = if opts.exact_sizes {
+            format!("{} B", b.size)
+        } else {
+            human_size(b.size)
+        };
+        let percent = if parent_size == 0 {
+            0.0
+        } else {
+            b.size as f64 * 100.0 / parent_size as f64
+        };
+        out.push_str(&format!(
+            "{:indent$}{} {} ({:.1}%) {}",
+            "",
+            b.typ,
+            size,
+            percent,
+            b.full_name,
+            indent = depth * 2
+        ));
+
+        let children = b.children.as_deref().unwrap_or_default();
+        if !children.is_empty() && opts.max_depth.is_some_and(|max| depth >= max) {
+            out.push_str(&format!(" [+{} collapsed]\n", children.len()));
+            continue;
+        }
+        out.push('\n');
+        render_level(children, b.size, depth + 1, opts, out);
+    }
+}
@@ -28,3 +28,23 @@ pub fn hex_dump(bytes: &[u8], start_offset: u64) -> String {
     }
     out
 }
+
+/// Format a byte count using binary units (e.g. `"1.5 MiB"`).
+///
+/// Values below 1 KiB are printed as whole bytes (`"1023 B"`).
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    // Compare the value as printed, so 1048575 is "1.0 MiB" rather than "1024.0 KiB".
+    while (value * 10.0).round() / 10.0 >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
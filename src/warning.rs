@@ -0,0 +1,34 @@
+use serde::Serialize;
+
+/// A non-fatal problem found while parsing or analyzing a file.
+///
+/// Warnings point at the box they relate to so UIs can highlight it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    /// Absolute file offset of the related box
+    pub offset: u64,
+    /// Four-character type of the related box (e.g. "stco")
+    pub box_type: String,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(offset: u64, box_type: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            box_type: box_type.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} @ {:#x}: {}",
+            self.box_type, self.offset, self.message
+        )
+    }
+}
@@ -0,0 +1,67 @@
+use mp4box::registry::AvcConfig;
+use mp4box::{to_annexb, to_annexb_with_parameter_sets};
+
+fn prefixed(length_size: usize, nals: &[&[u8]]) -> Vec<u8> {
+    let mut v = Vec::new();
+    for nal in nals {
+        v.extend_from_slice(&(nal.len() as u32).to_be_bytes()[4 - length_size..]);
+        v.extend_from_slice(nal);
+    }
+    v
+}
+
+#[test]
+fn four_byte_prefixes() {
+    let sample = prefixed(4, &[&[0x06, 1, 2], &[0x65, 0xAA, 0xBB, 0xCC]]);
+    assert_eq!(
+        to_annexb(&sample, 4),
+        [0, 0, 0, 1, 0x06, 1, 2, 0, 0, 0, 1, 0x65, 0xAA, 0xBB, 0xCC]
+    );
+}
+
+#[test]
+fn two_byte_prefixes() {
+    let sample = prefixed(2, &[&[0x41, 9], &[0x41, 8, 7]]);
+    assert_eq!(
+        to_annexb(&sample, 2),
+        [0, 0, 0, 1, 0x41, 9, 0, 0, 0, 1, 0x41, 8, 7]
+    );
+}
+
+#[test]
+fn overrunning_length_is_truncated() {
+    let mut sample = prefixed(4, &[&[0x41, 1]]);
+    sample.extend_from_slice(&[0, 0, 0, 100, 0x41, 2, 3]);
+    assert_eq!(
+        to_annexb(&sample, 4),
+        [0, 0, 0, 1, 0x41, 1, 0, 0, 0, 1, 0x41, 2, 3]
+    );
+    // Not even a whole length prefix left: nothing more is emitted.
+    assert_eq!(
+        to_annexb(&[0, 0, 0, 2, 0x41, 1, 0, 0], 4),
+        [0, 0, 0, 1, 0x41, 1]
+    );
+}
+
+#[test]
+fn parameter_sets_precede_idr_samples() {
+    // avcC with one SPS [0x67, 0x64] and one PPS [0x68, 0xEE]
+    let avcc = [
+        1, 0x64, 0x00, 0x28, 0xFF, 0xE1, 0, 2, 0x67, 0x64, 1, 0, 2, 0x68, 0xEE,
+    ];
+    let config = AvcConfig::parse(&avcc).unwrap();
+
+    let idr = prefixed(4, &[&[0x65, 0x11]]);
+    assert_eq!(
+        to_annexb_with_parameter_sets(&idr, 4, &config, &avcc),
+        [
+            0, 0, 0, 1, 0x67, 0x64, 0, 0, 0, 1, 0x68, 0xEE, 0, 0, 0, 1, 0x65, 0x11
+        ]
+    );
+
+    let non_idr = prefixed(4, &[&[0x41, 0x22]]);
+    assert_eq!(
+        to_annexb_with_parameter_sets(&non_idr, 4, &config, &avcc),
+        to_annexb(&non_idr, 4)
+    );
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{AvcConfig, NalUnitRange, StructuredData};
+use std::io::Cursor;
+
+/// avcC from an x264 High@L4.0 1080p encode, including the high-profile extension.
+const X264_AVCC: [u8; 48] = [
+    0x01, 0x64, 0x00, 0x28, 0xFF, 0xE1, 0x00, 0x1B, 0x67, 0x64, 0x00, 0x28, 0xAC, 0xD9, 0x40, 0x78,
+    0x02, 0x27, 0xE5, 0xC0, 0x44, 0x00, 0x00, 0x03, 0x00, 0x04, 0x00, 0x00, 0x03, 0x00, 0xC8, 0x3C,
+    0x60, 0xC6, 0x58, 0x01, 0x00, 0x06, 0x68, 0xEB, 0xE3, 0xCB, 0x22, 0xC0, 0xFD, 0xF8, 0xF8, 0x00,
+];
+
+#[test]
+fn x264_high_profile_avcc() {
+    let avc = AvcConfig::parse(&X264_AVCC).unwrap();
+
+    assert_eq!(avc.configuration_version, 1);
+    assert_eq!(avc.profile_indication, 100);
+    assert_eq!(avc.level_indication, 40);
+    assert_eq!(avc.length_size_minus_one, 3);
+    assert_eq!(
+        avc.sps,
+        vec![NalUnitRange {
+            offset: 8,
+            size: 27
+        }]
+    );
+    assert_eq!(
+        avc.pps,
+        vec![NalUnitRange {
+            offset: 38,
+            size: 6
+        }]
+    );
+    assert_eq!(X264_AVCC[8] & 0x1F, 7); // SPS NAL type
+    assert_eq!(X264_AVCC[38] & 0x1F, 8); // PPS NAL type
+    assert_eq!(avc.chroma_format, Some(1));
+    assert_eq!(avc.bit_depth_luma_minus8, Some(0));
+    assert!(avc.sps_ext.is_empty());
+    assert_eq!(avc.profile_level(), "High@L4.0");
+}
+
+#[test]
+fn avcc_box_summary_and_sample_entry() {
+    let avcc = bx(b"avcC", &X264_AVCC);
+    let size = avcc.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(avcc.clone()), size, true).unwrap();
+    assert_eq!(boxes[0].decoded.as_deref(), Some("High@L4.0"));
+    assert!(matches!(
+        boxes[0].structured_data,
+        Some(StructuredData::AvcConfig(_))
+    ));
+
+    let stsd = stsd(&[visual_entry(b"avc1", &avcc)]);
+    let size = stsd.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(stsd), size, true).unwrap();
+    let Some(StructuredData::SampleDescription(stsd)) = &boxes[0].structured_data else {
+        panic!("stsd not decoded");
+    };
+    let avc = stsd.entries[0].avc_config.as_ref().unwrap();
+    assert_eq!(avc.profile_level(), "High@L4.0");
+}
+
+#[test]
+fn main_profile_without_extension() {
+    // Main@L3.1 with one SPS and one PPS, no trailing bytes.
+    let avcc = [
+        0x01, 0x4D, 0x40, 0x1F, 0xFF, 0xE1, 0x00, 0x02, 0x67, 0x4D, 0x01, 0x00, 0x01, 0x68,
+    ];
+    let avc = AvcConfig::parse(&avcc).unwrap();
+    assert_eq!(avc.profile_level(), "Main@L3.1");
+    assert_eq!(avc.chroma_format, None);
+
+    assert!(AvcConfig::parse(&avcc[..9]).is_err());
+}
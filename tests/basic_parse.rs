@@ -47,3 +47,183 @@ fn parse_children_no_children_for_leaf() {
     let children = parse_children(&mut cur, len).expect("parse_children failed");
     assert!(children.is_empty());
 }
+
+#[test]
+fn nested_size_zero_box_stops_at_parent_end() {
+    // [moov [free size=0 ...]] [mdat]
+    // The nested size-0 `free` must extend to the end of moov, not the end of the file,
+    // so the top-level mdat sibling after moov is still found.
+    let mut free = Vec::new();
+    free.extend_from_slice(&0u32.to_be_bytes());
+    free.extend_from_slice(b"free");
+    free.extend_from_slice(&[0xAA; 12]);
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&((8 + free.len()) as u32).to_be_bytes());
+    data.extend_from_slice(b"moov");
+    data.extend_from_slice(&free);
+    data.extend_from_slice(&16u32.to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&[0u8; 8]);
+
+    let len = data.len() as u64;
+    let boxes = mp4box::get_boxes(&mut Cursor::new(data), len, false).expect("get_boxes failed");
+
+    assert_eq!(boxes.len(), 2);
+    assert_eq!(boxes[0].typ, "moov");
+    assert_eq!(boxes[1].typ, "mdat");
+    assert_eq!(boxes[1].offset, 28);
+
+    let kids = boxes[0].children.as_ref().unwrap();
+    assert_eq!(kids.len(), 1);
+    assert_eq!(kids[0].typ, "free");
+    assert_eq!(kids[0].size, 0);
+    assert_eq!(kids[0].payload_offset, Some(16));
+    assert_eq!(kids[0].payload_size, Some(12));
+}
+
+#[test]
+fn box_ref_children_is_uniform_across_kinds() {
+    // [moov [free]] [ftyp]
+    let mut moov = Vec::new();
+    moov.extend_from_slice(&16u32.to_be_bytes());
+    moov.extend_from_slice(b"moov");
+    moov.extend_from_slice(&8u32.to_be_bytes());
+    moov.extend_from_slice(b"free");
+
+    let mut data = moov;
+    data.extend_from_slice(&make_minimal_file());
+    let len = data.len() as u64;
+    let mut cur = Cursor::new(data);
+
+    let boxes = parse_children(&mut cur, len).expect("parse_children failed");
+    assert_eq!(boxes.len(), 2);
+
+    let moov = &boxes[0];
+    assert_eq!(moov.children().len(), 1);
+    assert_eq!(moov.children()[0].hdr.typ, FourCC(*b"free"));
+
+    let ftyp = &boxes[1];
+    assert!(ftyp.children().is_empty());
+}
+
+#[test]
+fn visitor_sees_boxes_in_preorder_with_depth() {
+    use mp4box::parser::visit_boxes;
+    use std::ops::ControlFlow;
+
+    fn bx(typ: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        [&(8 + payload.len() as u32).to_be_bytes()[..], typ, payload].concat()
+    }
+
+    // ftyp, then moov > trak > tkhd
+    let mut data = bx(b"ftyp", b"isom\0\0\0\0isom");
+    let tkhd = bx(b"tkhd", &[0u8; 4]);
+    data.extend_from_slice(&bx(b"moov", &bx(b"trak", &tkhd)));
+    let len = data.len() as u64;
+
+    let mut seen = Vec::new();
+    visit_boxes(&mut Cursor::new(&data), len, |h, _, depth| {
+        seen.push((h.typ.to_string(), depth));
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    let expected = [("ftyp", 0), ("moov", 0), ("trak", 1), ("tkhd", 2)];
+    let expected: Vec<(String, usize)> =
+        expected.iter().map(|(t, d)| (t.to_string(), *d)).collect();
+    assert_eq!(seen, expected);
+
+    // Breaking stops the walk before any children of moov are seen.
+    let mut seen = Vec::new();
+    visit_boxes(&mut Cursor::new(&data), len, |h, _, _| {
+        seen.push(h.typ.to_string());
+        if h.typ == FourCC(*b"moov") {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+    .unwrap();
+    assert_eq!(seen, ["ftyp", "moov"]);
+}
+
+#[test]
+fn box_header_new_matches_parsed_header() {
+    use mp4box::boxes::BoxHeader;
+
+    let uuid = [0x5a; 16];
+    for hdr in [
+        BoxHeader::new(FourCC(*b"free"), 12),
+        BoxHeader::new_full(FourCC(*b"mvhd"), 96),
+        BoxHeader::new_uuid(uuid, 3),
+        BoxHeader::new(FourCC(*b"mdat"), u32::MAX as u64),
+    ] {
+        let parsed = read_box_header(&mut Cursor::new(hdr.to_bytes())).unwrap();
+        assert_eq!(parsed, hdr);
+    }
+
+    let full = BoxHeader::new_full(FourCC(*b"mvhd"), 96);
+    assert_eq!((full.size, full.header_size), (108, 8));
+    let large = BoxHeader::new(FourCC(*b"mdat"), u32::MAX as u64);
+    assert_eq!(large.header_size, 16);
+    assert_eq!(large.size, u32::MAX as u64 + 16);
+    let uuid_box = BoxHeader::new_uuid(uuid, 3);
+    assert_eq!((uuid_box.size, uuid_box.uuid), (27, Some(uuid)));
+}
+
+/// ftyp (isom, minor 0x200, compatible [isom]) followed by
+/// moov { mvhd v0 (timescale 1000, duration 5000) }
+const FTYP_MOOV: &[u8] = &[
+    0x00, 0x00, 0x00, 0x14, 0x66, 0x74, 0x79, 0x70, 0x69, 0x73, 0x6f, 0x6d, 0x00, 0x00, 0x02, 0x00,
+    0x69, 0x73, 0x6f, 0x6d, 0x00, 0x00, 0x00, 0x74, 0x6d, 0x6f, 0x6f, 0x76, 0x00, 0x00, 0x00, 0x6c,
+    0x6d, 0x76, 0x68, 0x64, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x03, 0xe8, 0x00, 0x00, 0x13, 0x88, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+];
+
+#[test]
+fn analyze_bytes_parses_in_memory_data() {
+    let boxes = mp4box::analyze_bytes(FTYP_MOOV, true).unwrap();
+    assert_eq!(boxes.len(), 2);
+    assert_eq!(boxes[0].decoded.as_deref(), Some("isom (isom)"));
+    assert_eq!(boxes[1].typ, "moov");
+    assert_eq!(boxes[1].size, 116);
+
+    let mvhd = &boxes[1].children.as_ref().unwrap()[0];
+    assert_eq!(mvhd.typ, "mvhd");
+    assert_eq!(
+        mvhd.decoded.as_deref(),
+        Some("timescale=1000 duration=5000")
+    );
+
+    let undecoded = mp4box::analyze_bytes(FTYP_MOOV, false).unwrap();
+    assert!(undecoded[0].decoded.is_none());
+}
+
+#[test]
+fn largesize_header_is_16_bytes() {
+    let mut data = 1u32.to_be_bytes().to_vec();
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&20u64.to_be_bytes());
+    data.extend_from_slice(&[1, 2, 3, 4]);
+    data.extend_from_slice(&8u32.to_be_bytes());
+    data.extend_from_slice(b"free");
+
+    let h = read_box_header(&mut Cursor::new(&data)).unwrap();
+    assert!(h.is_extended_size());
+    assert_eq!((h.size, h.header_size), (20, 16));
+    assert_eq!(h.to_bytes(), data[..16]);
+
+    let boxes = mp4box::analyze_bytes(&data, false).unwrap();
+    assert_eq!(boxes[0].header_size, 16);
+    assert_eq!(boxes[0].payload_offset, Some(16));
+    assert_eq!(boxes[0].payload_size, Some(4));
+    assert_eq!((boxes[1].typ.as_str(), boxes[1].offset), ("free", 20));
+
+    let plain = read_box_header(&mut Cursor::new(&data[20..])).unwrap();
+    assert!(!plain.is_extended_size());
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use std::io::Cursor;
+
+const KID: [u8; 16] = [
+    0x10, 0x77, 0xef, 0xec, 0xc0, 0xb2, 0x4d, 0x02, 0xac, 0xe3, 0x3c, 0x1e, 0x52, 0xe2, 0xfb, 0x4b,
+];
+
+fn tenc(version: u8, pattern: u8, iv_size: u8, constant_iv: &[u8]) -> Vec<u8> {
+    let mut p = vec![0, pattern, 1, iv_size];
+    p.extend_from_slice(&KID);
+    if iv_size == 0 {
+        p.push(constant_iv.len() as u8);
+        p.extend_from_slice(constant_iv);
+    }
+    full_box(b"tenc", version, 0, &p)
+}
+
+/// IV and (clear, protected) subsample ranges of one encrypted sample.
+type EncryptedSample<'a> = (&'a [u8], &'a [(u16, u32)]);
+
+/// senc with subsamples.
+fn senc(samples: &[EncryptedSample]) -> Vec<u8> {
+    let mut p = (samples.len() as u32).to_be_bytes().to_vec();
+    for (iv, subsamples) in samples {
+        p.extend_from_slice(iv);
+        p.extend_from_slice(&(subsamples.len() as u16).to_be_bytes());
+        for &(clear, protected) in *subsamples {
+            p.extend_from_slice(&clear.to_be_bytes());
+            p.extend_from_slice(&protected.to_be_bytes());
+        }
+    }
+    full_box(b"senc", 0, 0x2, &p)
+}
+
+fn boxes(data: Vec<u8>) -> Vec<mp4box::Box> {
+    let size = data.len() as u64;
+    get_boxes(&mut Cursor::new(data), size, true).unwrap()
+}
+
+fn child<'a>(b: &'a mp4box::Box, path: &[&str]) -> &'a mp4box::Box {
+    path.iter().fold(b, |b, typ| {
+        b.children
+            .iter()
+            .flatten()
+            .find(|c| c.typ == *typ)
+            .unwrap_or_else(|| panic!("no {}", typ))
+    })
+}
+
+#[test]
+fn tenc_v1_pattern_and_constant_iv() {
+    let b = boxes(tenc(1, 0x19, 0, &[0xAB; 16])).remove(0);
+    let Some(StructuredData::TrackEncryption(tenc)) = b.structured_data else {
+        panic!("tenc not decoded");
+    };
+    assert_eq!(tenc.default_crypt_byte_block, Some(1));
+    assert_eq!(tenc.default_skip_byte_block, Some(9));
+    assert_eq!(tenc.default_is_protected, 1);
+    assert_eq!(tenc.default_per_sample_iv_size, 0);
+    assert_eq!(tenc.default_kid, "1077efecc0b24d02ace33c1e52e2fb4b");
+    assert_eq!(tenc.default_constant_iv.as_deref(), Some(&*"ab".repeat(16)));
+}
+
+#[test]
+fn senc_uses_iv_size_from_track_tenc() {
+    let mut trak = tkhd(1);
+    trak.extend_from_slice(&bx(b"sinf", &bx(b"schi", &tenc(0, 0, 16, &[]))));
+    let mut data = bx(b"moov", &bx(b"trak", &trak));
+
+    let mut saiz = vec![0u8]; // default_sample_info_size: per sample
+    saiz.extend_from_slice(&2u32.to_be_bytes());
+    saiz.extend_from_slice(&[24, 30]);
+    let mut traf = full_box(b"tfhd", 0, 0x020000, &1u32.to_be_bytes());
+    traf.extend_from_slice(&full_box(b"saiz", 0, 0, &saiz));
+    traf.extend_from_slice(&full_box(b"saio", 0, 0, &[0, 0, 0, 1, 0, 0, 0, 0x50]));
+    traf.extend_from_slice(&senc(&[
+        (&[1; 16], &[(5, 100)]),
+        (&[2; 16], &[(5, 60), (3, 40)]),
+    ]));
+    data.extend_from_slice(&bx(b"moof", &bx(b"traf", &traf)));
+
+    let top = boxes(data);
+    let traf = child(&top[1], &["traf"]);
+
+    let Some(StructuredData::SampleEncryption(senc)) = &child(traf, &["senc"]).structured_data
+    else {
+        panic!("senc not decoded");
+    };
+    assert_eq!(senc.per_sample_iv_size, 16);
+    assert_eq!(senc.samples.len(), 2);
+    assert_eq!(senc.samples[1].iv, "02".repeat(16));
+    let ranges: Vec<(u16, u32)> = senc.samples[1]
+        .subsamples
+        .iter()
+        .map(|s| (s.clear_bytes, s.protected_bytes))
+        .collect();
+    assert_eq!(ranges, [(5, 60), (3, 40)]);
+
+    let Some(StructuredData::SampleAuxInfoSizes(saiz)) = &child(traf, &["saiz"]).structured_data
+    else {
+        panic!("saiz not decoded");
+    };
+    assert_eq!(saiz.aux_info_type, None);
+    assert_eq!(saiz.sample_count, 2);
+    assert_eq!(saiz.sample_info_sizes, [24, 30]);
+
+    let Some(StructuredData::SampleAuxInfoOffsets(saio)) = &child(traf, &["saio"]).structured_data
+    else {
+        panic!("saio not decoded");
+    };
+    assert_eq!(saio.offsets, [0x50]);
+}
+
+#[test]
+fn senc_without_tenc_infers_iv_size() {
+    let b = boxes(senc(&[(&[7; 8], &[(10, 20)])])).remove(0);
+    let Some(StructuredData::SampleEncryption(senc)) = b.structured_data else {
+        panic!("senc not decoded");
+    };
+    assert_eq!(senc.per_sample_iv_size, 8);
+    assert_eq!(senc.samples[0].iv, "07".repeat(8));
+}
+
+const WIDEVINE: [u8; 16] = [
+    0xed, 0xef, 0x8b, 0xa9, 0x79, 0xd6, 0x4a, 0xce, 0xa3, 0xc8, 0x27, 0xdc, 0xd5, 0x1d, 0x21, 0xed,
+];
+
+#[test]
+fn pssh_v1_widevine() {
+    let mut p = WIDEVINE.to_vec();
+    p.extend_from_slice(&1u32.to_be_bytes());
+    p.extend_from_slice(&KID);
+    p.extend_from_slice(&5u32.to_be_bytes());
+    p.extend_from_slice(b"\x08\x01\x12\x01\x00");
+    let mut data = bx(b"free", &[0; 4]);
+    data.extend_from_slice(&full_box(b"pssh", 1, 0, &p));
+    let data_offset = (data.len() - 5) as u64;
+
+    let b = boxes(data).remove(1);
+    assert_eq!(b.decoded.as_deref(), Some("Widevine, 1 KID"));
+    let Some(StructuredData::ProtectionSystemHeader(pssh)) = b.structured_data else {
+        panic!("pssh not decoded");
+    };
+    assert_eq!(pssh.system_id, "edef8ba9-79d6-4ace-a3c8-27dcd51d21ed");
+    assert_eq!(pssh.system_name.as_deref(), Some("Widevine"));
+    assert_eq!(pssh.kids, ["1077efecc0b24d02ace33c1e52e2fb4b"]);
+    assert_eq!((pssh.data_size, pssh.data_offset), (5, data_offset));
+}
+
+#[test]
+fn pssh_v0_unknown_system() {
+    let mut p = [0x42; 16].to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes());
+    let b = boxes(full_box(b"pssh", 0, 0, &p)).remove(0);
+    assert_eq!(
+        b.decoded.as_deref(),
+        Some("42424242-4242-4242-4242-424242424242, 0 KIDs")
+    );
+}
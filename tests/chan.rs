@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use mp4box::track_samples_from_reader;
+use std::io::Cursor;
+
+fn chan(layout_tag: u32, labels: &[u32]) -> Vec<u8> {
+    let mut p = layout_tag.to_be_bytes().to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes()); // bitmap
+    p.extend_from_slice(&(labels.len() as u32).to_be_bytes());
+    for label in labels {
+        p.extend_from_slice(&label.to_be_bytes());
+        p.extend_from_slice(&0u32.to_be_bytes()); // flags
+        p.extend_from_slice(&[0u8; 12]); // coordinates
+    }
+    full_box(b"chan", 0, 0, &p)
+}
+
+#[test]
+fn five_one_layout_in_sound_sample_entry() {
+    let mut stbl = stsd(&[audio_entry(b"mp4a", 6, 48000, &chan(0x0079_0006, &[]))]);
+    stbl.extend_from_slice(&stts(&[]));
+    stbl.extend_from_slice(&stsc(&[]));
+    stbl.extend_from_slice(&stsz(&[]));
+    stbl.extend_from_slice(&stco(&[]));
+    let moov = bx(b"moov", &trak(1, b"soun", 48000, &stbl));
+
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let layout = tracks[0]
+        .sample_entry
+        .as_ref()
+        .and_then(|e| e.channel_layout.as_ref())
+        .expect("chan parsed from mp4a");
+
+    assert_eq!(layout.layout_tag, 0x0079_0006);
+    assert_eq!(layout.layout_name.as_deref(), Some("5.1 (L R C LFE Ls Rs)"));
+    assert_eq!(layout.channel_count(), 6);
+}
+
+#[test]
+fn channel_descriptions_are_named() {
+    let data = chan(0, &[1, 2, 3, 4, 5, 6]);
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+    let Some(StructuredData::ChannelLayout(layout)) = &boxes[0].structured_data else {
+        panic!("chan not decoded: {:?}", boxes[0].decoded);
+    };
+    assert_eq!(layout.layout_name, None);
+    assert_eq!(layout.channel_count(), 6);
+    let names: Vec<&str> = layout
+        .channels
+        .iter()
+        .filter_map(|c| c.label_name.as_deref())
+        .collect();
+    assert_eq!(names, vec!["L", "R", "C", "LFE", "Ls", "Rs"]);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::*;
+use mp4box::cmaf::cmaf_report;
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn styp(brands: &[&[u8; 4]]) -> Vec<u8> {
+    let mut p = b"msdh".to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes());
+    for b in brands {
+        p.extend_from_slice(*b);
+    }
+    bx(b"styp", &p)
+}
+
+fn moof(traf_count: usize) -> Vec<u8> {
+    let mut p = bx(b"mfhd", &[0, 0, 0, 0, 0, 0, 0, 1]);
+    for i in 0..traf_count {
+        let mut tfhd = vec![0, 0, 0, 0];
+        tfhd.extend_from_slice(&(i as u32 + 1).to_be_bytes());
+        p.extend_from_slice(&bx(b"traf", &bx(b"tfhd", &tfhd)));
+    }
+    bx(b"moof", &p)
+}
+
+fn analyze(data: Vec<u8>) -> mp4box::cmaf::CmafReport {
+    let size = data.len() as u64;
+    let mut cur = Cursor::new(data);
+    let boxes = get_boxes(&mut cur, size, false).unwrap();
+    cmaf_report(&mut cur, &boxes).unwrap()
+}
+
+#[test]
+fn cmaf_segment_is_compliant() {
+    let mut data = styp(&[b"msdh", b"msix", b"cmfs", b"cmfc"]);
+    data.extend_from_slice(&moof(1));
+    data.extend_from_slice(&bx(b"mdat", &[0u8; 16]));
+
+    let report = analyze(data);
+    assert!(report.is_cmaf);
+    assert_eq!(report.brands, vec!["cmfs", "cmfc"]);
+    assert!(report.findings.is_empty(), "{:?}", report.findings);
+    assert!(report.compliant);
+}
+
+#[test]
+fn cmaf_segment_violations_are_reported() {
+    // No leading styp, two tracks in one fragment, and moof without mdat.
+    let mut data = moof(2);
+    data.extend_from_slice(&styp(&[b"cmfc"]));
+
+    let report = analyze(data);
+    assert!(report.is_cmaf);
+    assert!(!report.compliant);
+    assert_eq!(report.findings.len(), 3, "{:?}", report.findings);
+    assert!(report.findings[0].contains("does not start with styp"));
+    assert!(report.findings[1].contains("2 traf boxes"));
+    assert!(report.findings[2].contains("not immediately followed by mdat"));
+}
+
+#[test]
+fn non_cmaf_file_is_not_compliant() {
+    let mut data = styp(&[b"msdh"]);
+    data.extend_from_slice(&moof(1));
+    data.extend_from_slice(&bx(b"mdat", &[0u8; 4]));
+
+    let report = analyze(data);
+    assert!(!report.is_cmaf);
+    assert!(!report.compliant);
+}
@@ -0,0 +1,215 @@
+//! Helpers for building small synthetic MP4 files in integration tests.
+#![allow(dead_code)]
+
+pub fn bx(typ: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&((payload.len() + 8) as u32).to_be_bytes());
+    v.extend_from_slice(typ);
+    v.extend_from_slice(payload);
+    v
+}
+
+pub fn full_box(typ: &[u8; 4], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut p = vec![version];
+    p.extend_from_slice(&flags.to_be_bytes()[1..]);
+    p.extend_from_slice(payload);
+    bx(typ, &p)
+}
+
+pub fn ftyp(major: &[u8; 4], compatible: &[&[u8; 4]]) -> Vec<u8> {
+    let mut p = major.to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes());
+    for b in compatible {
+        p.extend_from_slice(*b);
+    }
+    bx(b"ftyp", &p)
+}
+
+pub fn tkhd(track_id: u32) -> Vec<u8> {
+    let mut p = Vec::new();
+    p.extend_from_slice(&0u32.to_be_bytes()); // creation_time
+    p.extend_from_slice(&0u32.to_be_bytes()); // modification_time
+    p.extend_from_slice(&track_id.to_be_bytes());
+    p.extend_from_slice(&0u32.to_be_bytes()); // reserved
+    p.extend_from_slice(&0u32.to_be_bytes()); // duration
+    p.extend_from_slice(&[0u8; 8]); // reserved[2]
+    p.extend_from_slice(&[0u8; 8]); // layer, alternate_group, volume, reserved
+    p.extend_from_slice(&[0u8; 36]); // matrix
+    p.extend_from_slice(&(1920u32 << 16).to_be_bytes());
+    p.extend_from_slice(&(1080u32 << 16).to_be_bytes());
+    full_box(b"tkhd", 0, 3, &p)
+}
+
+pub fn mdhd(timescale: u32, duration: u32) -> Vec<u8> {
+    let mut p = Vec::new();
+    p.extend_from_slice(&0u32.to_be_bytes()); // creation_time
+    p.extend_from_slice(&0u32.to_be_bytes()); // modification_time
+    p.extend_from_slice(&timescale.to_be_bytes());
+    p.extend_from_slice(&duration.to_be_bytes());
+    p.extend_from_slice(&0x55c4u16.to_be_bytes()); // "und"
+    p.extend_from_slice(&0u16.to_be_bytes());
+    full_box(b"mdhd", 0, 0, &p)
+}
+
+pub fn hdlr(handler: &[u8; 4]) -> Vec<u8> {
+    let mut p = 0u32.to_be_bytes().to_vec();
+    p.extend_from_slice(handler);
+    p.extend_from_slice(&[0u8; 12]);
+    p.extend_from_slice(b"Handler\0");
+    full_box(b"hdlr", 0, 0, &p)
+}
+
+pub fn visual_entry(codec: &[u8; 4], children: &[u8]) -> Vec<u8> {
+    let mut p = vec![0u8; 6]; // reserved
+    p.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+    p.extend_from_slice(&[0u8; 16]); // pre_defined / reserved
+    p.extend_from_slice(&1920u16.to_be_bytes());
+    p.extend_from_slice(&1080u16.to_be_bytes());
+    p.extend_from_slice(&0x0048_0000u32.to_be_bytes()); // horizresolution
+    p.extend_from_slice(&0x0048_0000u32.to_be_bytes()); // vertresolution
+    p.extend_from_slice(&0u32.to_be_bytes()); // reserved
+    p.extend_from_slice(&1u16.to_be_bytes()); // frame_count
+    p.extend_from_slice(&[0u8; 32]); // compressorname
+    p.extend_from_slice(&24u16.to_be_bytes()); // depth
+    p.extend_from_slice(&0xFFFFu16.to_be_bytes()); // pre_defined
+    p.extend_from_slice(children);
+    bx(codec, &p)
+}
+
+pub fn audio_entry(codec: &[u8; 4], channels: u16, sample_rate: u16, children: &[u8]) -> Vec<u8> {
+    let mut p = vec![0u8; 6]; // reserved
+    p.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+    p.extend_from_slice(&[0u8; 8]); // version, revision, vendor
+    p.extend_from_slice(&channels.to_be_bytes());
+    p.extend_from_slice(&16u16.to_be_bytes()); // sample size
+    p.extend_from_slice(&[0u8; 4]); // pre_defined, reserved
+    p.extend_from_slice(&((sample_rate as u32) << 16).to_be_bytes());
+    p.extend_from_slice(children);
+    bx(codec, &p)
+}
+
+pub fn stsd(entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut p = (entries.len() as u32).to_be_bytes().to_vec();
+    for e in entries {
+        p.extend_from_slice(e);
+    }
+    full_box(b"stsd", 0, 0, &p)
+}
+
+/// `stts` from (sample_count, sample_delta) runs.
+pub fn stts(entries: &[(u32, u32)]) -> Vec<u8> {
+    let mut p = (entries.len() as u32).to_be_bytes().to_vec();
+    for &(count, delta) in entries {
+        p.extend_from_slice(&count.to_be_bytes());
+        p.extend_from_slice(&delta.to_be_bytes());
+    }
+    full_box(b"stts", 0, 0, &p)
+}
+
+/// `ctts` (version 0) from (sample_count, sample_offset) runs.
+pub fn ctts(entries: &[(u32, u32)]) -> Vec<u8> {
+    let mut p = (entries.len() as u32).to_be_bytes().to_vec();
+    for &(count, offset) in entries {
+        p.extend_from_slice(&count.to_be_bytes());
+        p.extend_from_slice(&offset.to_be_bytes());
+    }
+    full_box(b"ctts", 0, 0, &p)
+}
+
+/// `stsc` from (first_chunk, samples_per_chunk, sample_description_index) entries.
+pub fn stsc(entries: &[(u32, u32, u32)]) -> Vec<u8> {
+    let mut p = (entries.len() as u32).to_be_bytes().to_vec();
+    for &(first, per_chunk, desc) in entries {
+        p.extend_from_slice(&first.to_be_bytes());
+        p.extend_from_slice(&per_chunk.to_be_bytes());
+        p.extend_from_slice(&desc.to_be_bytes());
+    }
+    full_box(b"stsc", 0, 0, &p)
+}
+
+pub fn stsz(sizes: &[u32]) -> Vec<u8> {
+    let mut p = 0u32.to_be_bytes().to_vec(); // sample_size (0 = per-sample table)
+    p.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+    for s in sizes {
+        p.extend_from_slice(&s.to_be_bytes());
+    }
+    full_box(b"stsz", 0, 0, &p)
+}
+
+pub fn stco(offsets: &[u32]) -> Vec<u8> {
+    let mut p = (offsets.len() as u32).to_be_bytes().to_vec();
+    for o in offsets {
+        p.extend_from_slice(&o.to_be_bytes());
+    }
+    full_box(b"stco", 0, 0, &p)
+}
+
+pub fn stss(sample_numbers: &[u32]) -> Vec<u8> {
+    let mut p = (sample_numbers.len() as u32).to_be_bytes().to_vec();
+    for n in sample_numbers {
+        p.extend_from_slice(&n.to_be_bytes());
+    }
+    full_box(b"stss", 0, 0, &p)
+}
+
+/// A `trak` with the standard tkhd/mdia/minf/stbl nesting around `stbl_children`.
+pub fn trak(track_id: u32, handler: &[u8; 4], timescale: u32, stbl_children: &[u8]) -> Vec<u8> {
+    trak_with(track_id, handler, timescale, &[], &[], stbl_children)
+}
+
+/// Like [`trak`], with extra boxes appended to `trak` (e.g. `edts`, `tref`)
+/// and to `minf` (e.g. `dinf`).
+pub fn trak_with(
+    track_id: u32,
+    handler: &[u8; 4],
+    timescale: u32,
+    trak_extra: &[u8],
+    minf_extra: &[u8],
+    stbl_children: &[u8],
+) -> Vec<u8> {
+    let mut minf = minf_extra.to_vec();
+    minf.extend_from_slice(&bx(b"stbl", stbl_children));
+
+    let mut mdia = mdhd(timescale, 0);
+    mdia.extend_from_slice(&hdlr(handler));
+    mdia.extend_from_slice(&bx(b"minf", &minf));
+
+    let mut p = tkhd(track_id);
+    p.extend_from_slice(trak_extra);
+    p.extend_from_slice(&bx(b"mdia", &mdia));
+    bx(b"trak", &p)
+}
+
+/// `edts` with a version 0 `elst` of (segment_duration, media_time) entries at rate 1.
+pub fn edts(edits: &[(u32, i32)]) -> Vec<u8> {
+    let mut p = (edits.len() as u32).to_be_bytes().to_vec();
+    for &(duration, media_time) in edits {
+        p.extend_from_slice(&duration.to_be_bytes());
+        p.extend_from_slice(&media_time.to_be_bytes());
+        p.extend_from_slice(&1i16.to_be_bytes());
+        p.extend_from_slice(&0i16.to_be_bytes());
+    }
+    bx(b"edts", &full_box(b"elst", 0, 0, &p))
+}
+
+/// `ftyp` + `moov` + `mdat`, where `moov_for` receives the absolute offset of
+/// the first `mdat` payload byte so chunk offsets can point into it.
+///
+/// `moov_for` must produce a `moov` whose size does not depend on that offset.
+pub fn movie(moov_for: impl Fn(u32) -> Vec<u8>, mdat_payload: &[u8]) -> Vec<u8> {
+    let ftyp = ftyp(b"isom", &[b"isom", b"mp41"]);
+    let probe = moov_for(0);
+    let mdat_start = (ftyp.len() + probe.len() + 8) as u32;
+
+    let mut file = ftyp;
+    file.extend_from_slice(&moov_for(mdat_start));
+    file.extend_from_slice(&bx(b"mdat", mdat_payload));
+    file
+}
+
+/// A fresh per-process scratch directory; callers remove it when done.
+pub fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("mp4box-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
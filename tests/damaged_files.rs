@@ -0,0 +1,296 @@
+mod common;
+
+use common::*;
+use mp4box::{analyze_file, analyze_file_with_warnings, analyze_reader, get_boxes};
+use std::io::Cursor;
+
+fn ftyp_then_garbage() -> Vec<u8> {
+    let mut data = ftyp(b"isom", &[b"isom", b"mp41"]);
+    // A header whose size (3) is smaller than the header itself, then noise.
+    data.extend_from_slice(&[0x00, 0x00, 0x00, 0x03, 0xDE, 0xAD, 0xBE, 0xEF, 0x42, 0x42]);
+    data
+}
+
+#[test]
+fn garbage_after_ftyp_keeps_parsed_boxes() {
+    let dir = temp_dir("damaged_garbage");
+    let path = dir.join("garbage.mp4");
+    let data = ftyp_then_garbage();
+    let ftyp_len = ftyp(b"isom", &[b"isom", b"mp41"]).len() as u64;
+    std::fs::write(&path, &data).unwrap();
+
+    let analysis = analyze_file(&path, true).unwrap();
+    assert_eq!(analysis.boxes.len(), 1);
+    assert_eq!(analysis.boxes[0].typ, "ftyp");
+    assert!(analysis.boxes[0].decoded.is_some());
+
+    assert_eq!(analysis.errors.len(), 1);
+    assert_eq!(analysis.errors[0].offset, ftyp_len);
+    assert!(analysis.errors[0].message.contains("invalid box size"));
+
+    // The lower-level reader still reports the failure as an error.
+    let size = data.len() as u64;
+    assert!(get_boxes(&mut Cursor::new(data), size, true).is_err());
+}
+
+#[test]
+fn reader_analysis_matches_file_analysis() {
+    let data = ftyp_then_garbage();
+    let size = data.len() as u64;
+    let mut cursor = Cursor::new(data);
+    // Left at the end by an earlier read; analysis starts from the beginning.
+    cursor.set_position(size);
+
+    let analysis = analyze_reader(&mut cursor, size, true).unwrap();
+    assert_eq!(analysis.boxes.len(), 1);
+    assert_eq!(
+        analysis.boxes[0].decoded.as_deref(),
+        Some("isom (isom, mp41)")
+    );
+    assert_eq!(analysis.errors.len(), 1);
+    assert!(analysis.errors[0].message.contains("invalid box size"));
+}
+
+#[test]
+fn short_trailing_bytes_are_reported() {
+    let dir = temp_dir("damaged_trailing");
+    let path = dir.join("trailing.mp4");
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend_from_slice(&[0x00, 0x00, 0x01]);
+    std::fs::write(&path, &data).unwrap();
+
+    let analysis = analyze_file(&path, false).unwrap();
+    assert_eq!(analysis.boxes.len(), 1);
+    assert_eq!(analysis.errors.len(), 1);
+}
+
+#[test]
+fn intact_file_has_no_errors() {
+    let dir = temp_dir("damaged_intact");
+    let path = dir.join("intact.mp4");
+    std::fs::write(&path, movie(|_| bx(b"moov", &[]), &[0u8; 16])).unwrap();
+
+    let analysis = analyze_file(&path, false).unwrap();
+    assert!(analysis.errors.is_empty());
+    assert!(analysis.boxes.iter().any(|b| b.typ == "mdat"));
+}
+
+#[test]
+fn warnings_from_boxes_and_sample_tables_are_collected() {
+    let moov = |mdat_start: u32| {
+        // dref declares 2 entries but holds 1
+        let mut dref = 2u32.to_be_bytes().to_vec();
+        dref.extend_from_slice(&full_box(b"url ", 0, 1, &[]));
+        let dinf = bx(b"dinf", &full_box(b"dref", 0, 0, &dref));
+
+        // 3 samples at 2 per chunk, but only one chunk to hold them
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(3, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 2, 1)]));
+        stbl.extend_from_slice(&stsz(&[4, 4, 4]));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+        let mut p = trak_with(1, b"vide", 12800, &[], &dinf, &stbl);
+
+        // A second track without mdhd
+        let mut mdia = hdlr(b"soun");
+        mdia.extend_from_slice(&bx(b"minf", &bx(b"stbl", &[])));
+        let mut trak2 = tkhd(2);
+        trak2.extend_from_slice(&bx(b"mdia", &mdia));
+        p.extend_from_slice(&bx(b"trak", &trak2));
+        bx(b"moov", &p)
+    };
+    let dir = temp_dir("collected-warnings");
+    let path = dir.join("buggy.mp4");
+    std::fs::write(&path, movie(moov, &[0u8; 12])).unwrap();
+
+    let (boxes, warnings) = analyze_file_with_warnings(&path, true).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(boxes.len(), 3);
+    let messages: Vec<(&str, &str)> = warnings
+        .iter()
+        .map(|w| (w.box_type.as_str(), w.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            ("dref", "declares 2 entries but contains 1"),
+            (
+                "stsc",
+                "1 samples (first: sample 2) have no file offset: stsc maps no chunk to them"
+            ),
+            (
+                "trak",
+                "track 2 has no readable mdhd; assuming timescale 1000"
+            ),
+        ]
+    );
+    assert!(warnings.windows(2).all(|w| w[0].offset <= w[1].offset));
+}
+
+/// `moov` holding a `udta` (with one `free` child) whose declared size is
+/// replaced by `udta_size`, followed by a top-level `mdat`.
+fn moov_with_udta_size(udta_size: u32) -> Vec<u8> {
+    let mut udta = bx(b"udta", &bx(b"free", &[0u8; 4]));
+    udta[..4].copy_from_slice(&udta_size.to_be_bytes());
+    let mut data = bx(b"moov", &udta);
+    data.extend_from_slice(&bx(b"mdat", &[0u8; 8]));
+    data
+}
+
+fn analysis_of(data: Vec<u8>) -> (Vec<String>, Vec<String>) {
+    let size = data.len() as u64;
+    let analysis = analyze_reader(&mut Cursor::new(data), size, false).unwrap();
+    let types = analysis.boxes.iter().map(|b| b.typ.clone()).collect();
+    let moov_kids = analysis.boxes[0].children.as_ref().unwrap();
+    assert_eq!(moov_kids[0].children.as_ref().unwrap()[0].typ, "free");
+    let messages = analysis
+        .warnings
+        .iter()
+        .map(|w| w.message.clone())
+        .collect();
+    (types, messages)
+}
+
+#[test]
+fn oversized_child_is_clamped_to_its_parent() {
+    let (types, warnings) = analysis_of(moov_with_udta_size(1000));
+    assert_eq!(types, ["moov", "mdat"]);
+    assert_eq!(
+        warnings,
+        ["box declares 1000 bytes but only 20 remain in its parent"]
+    );
+}
+
+#[test]
+fn nested_zero_size_box_runs_to_its_parent_end() {
+    let (types, warnings) = analysis_of(moov_with_udta_size(0));
+    assert_eq!(types, ["moov", "mdat"]);
+    assert_eq!(
+        warnings,
+        [
+            "size 0 is only valid for a top-level box; treating it as running to the end of its parent"
+        ]
+    );
+
+    let data = moov_with_udta_size(0);
+    let size = data.len() as u64;
+    let opts = mp4box::ParseOptions::default();
+    let analysis = mp4box::analyze_reader_opts(&mut Cursor::new(data), size, &opts).unwrap();
+    assert_eq!(analysis.warnings.len(), 1);
+    assert_eq!(analysis.warnings[0].box_type, "udta");
+}
+
+/// A `free` header declaring 4 bytes, less than its own 8-byte header.
+const SIZE_4_BOX: [u8; 12] = [0, 0, 0, 4, b'f', b'r', b'e', b'e', 0, 0, 0, 0];
+
+#[test]
+fn declared_size_smaller_than_header_stops_every_parser() {
+    let mut top = ftyp(b"isom", &[b"isom"]);
+    top.extend_from_slice(&SIZE_4_BOX);
+    let mut nested = ftyp(b"isom", &[b"isom"]);
+    nested.extend_from_slice(&bx(b"moov", &SIZE_4_BOX));
+
+    for data in [top, nested] {
+        let size = data.len() as u64;
+        assert!(get_boxes(&mut Cursor::new(data.clone()), size, false).is_err());
+        assert!(mp4box::parse_children(&mut Cursor::new(data.clone()), size).is_err());
+        assert!(
+            mp4box::visit_boxes(&mut Cursor::new(data.clone()), size, |_, _, _| {
+                std::ops::ControlFlow::Continue(())
+            })
+            .is_err()
+        );
+
+        let analysis = analyze_reader(&mut Cursor::new(data.clone()), size, false).unwrap();
+        assert_eq!(analysis.boxes[0].typ, "ftyp");
+        assert_eq!(analysis.errors.len(), 1);
+        assert!(analysis.errors[0].message.contains("invalid box size"));
+        assert!(analysis.warnings.is_empty());
+
+        // Both analysis entry points report damage the same way.
+        let opts = mp4box::ParseOptions::default();
+        let with_opts = mp4box::analyze_reader_opts(&mut Cursor::new(data), size, &opts).unwrap();
+        let types =
+            |a: &mp4box::Analysis| a.boxes.iter().map(|b| b.typ.clone()).collect::<Vec<_>>();
+        assert_eq!(types(&with_opts), types(&analysis));
+        assert_eq!(with_opts.errors, analysis.errors);
+    }
+}
+
+#[test]
+fn largesize_near_u64_max_does_not_overflow() {
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&u64::MAX.to_be_bytes());
+    data.extend_from_slice(&[0u8; 8]);
+    let size = data.len() as u64;
+
+    let analysis = analyze_reader(&mut Cursor::new(data.clone()), size, false).unwrap();
+    assert_eq!(analysis.boxes.len(), 2);
+    let opts = mp4box::ParseOptions::default();
+    let analysis =
+        mp4box::analyze_reader_opts(&mut Cursor::new(data.clone()), size, &opts).unwrap();
+    assert_eq!(analysis.boxes.len(), 2);
+    assert!(
+        mp4box::visit_boxes(&mut Cursor::new(data), size, |_, _, _| {
+            std::ops::ControlFlow::Continue(())
+        })
+        .is_ok()
+    );
+}
+
+/// `ftyp`, then `moov/udta` holding one `©nam` followed by `tail`, then `mdat`.
+fn udta_with_tail(tail: &[u8]) -> Vec<u8> {
+    let mut udta = bx(b"\xA9nam", b"\x00\x05\x00\x00title");
+    udta.extend_from_slice(tail);
+    let mut data = ftyp(b"qt  ", &[b"qt  "]);
+    data.extend_from_slice(&bx(b"moov", &bx(b"udta", &udta)));
+    data.extend_from_slice(&bx(b"mdat", &[0u8; 8]));
+    data
+}
+
+#[test]
+fn quicktime_udta_terminator_is_accepted() {
+    let data = udta_with_tail(&[0, 0, 0, 0]);
+    let size = data.len() as u64;
+
+    let boxes = get_boxes(&mut Cursor::new(data.clone()), size, false).unwrap();
+    let types: Vec<&str> = boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(types, ["ftyp", "moov", "mdat"]);
+    let udta = &boxes[1].children.as_ref().unwrap()[0];
+    assert_eq!(udta.children.as_ref().unwrap().len(), 1);
+
+    let analysis = analyze_reader(&mut Cursor::new(data.clone()), size, false).unwrap();
+    assert_eq!(analysis.boxes.len(), 3);
+    assert!(analysis.errors.is_empty(), "{:?}", analysis.errors);
+    assert!(analysis.warnings.is_empty(), "{:?}", analysis.warnings);
+
+    assert!(mp4box::track_samples_from_reader(Cursor::new(data)).is_ok());
+}
+
+#[test]
+fn short_padding_inside_a_box_only_warns() {
+    let data = udta_with_tail(&[1, 2, 3]);
+    let size = data.len() as u64;
+
+    assert_eq!(
+        get_boxes(&mut Cursor::new(data.clone()), size, false)
+            .unwrap()
+            .len(),
+        3
+    );
+    let analysis = analyze_reader(&mut Cursor::new(data), size, false).unwrap();
+    assert_eq!(analysis.boxes.len(), 3);
+    assert!(analysis.errors.is_empty());
+    let messages: Vec<&str> = analysis
+        .warnings
+        .iter()
+        .map(|w| w.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        ["3 trailing bytes are too short for a box header"]
+    );
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::*;
+use mp4box::registry::StructuredData;
+use mp4box::{
+    ParseOptions, analyze_file_with_refs, analyze_reader_opts, track_samples_from_reader,
+};
+use std::io::Cursor;
+
+fn dinf_with_url(location: &str) -> Vec<u8> {
+    let mut url = location.as_bytes().to_vec();
+    url.push(0);
+    let mut dref = 1u32.to_be_bytes().to_vec();
+    dref.extend_from_slice(&full_box(b"url ", 0, 0, &url)); // flags 0 = external
+    bx(b"dinf", &full_box(b"dref", 0, 0, &dref))
+}
+
+fn reference_movie(location: &str) -> Vec<u8> {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(1, 512)]));
+    stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+    stbl.extend_from_slice(&stsz(&[4]));
+    stbl.extend_from_slice(&stco(&[8]));
+    let trak = trak_with(1, b"vide", 12800, &[], &dinf_with_url(location), &stbl);
+
+    let mut file = ftyp(b"qt  ", &[b"qt  "]);
+    file.extend_from_slice(&bx(b"moov", &trak));
+    file
+}
+
+#[test]
+fn inlines_sibling_media_file() {
+    let dir = temp_dir("dref-sibling");
+    std::fs::write(dir.join("movie.mov"), reference_movie("media.mdat")).unwrap();
+    std::fs::write(dir.join("media.mdat"), bx(b"mdat", &[1, 2, 3, 4])).unwrap();
+
+    let analysis = analyze_file_with_refs(dir.join("movie.mov"), &dir).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(analysis.warnings.is_empty());
+    assert_eq!(analysis.references.len(), 1);
+    let r = &analysis.references[0];
+    assert_eq!(r.location, "media.mdat");
+    assert_eq!(r.boxes.len(), 1);
+    assert_eq!(r.boxes[0].typ, "mdat");
+}
+
+#[test]
+fn missing_target_is_a_warning() {
+    let dir = temp_dir("dref-missing");
+    std::fs::write(dir.join("movie.mov"), reference_movie("gone.mdat")).unwrap();
+
+    let analysis = analyze_file_with_refs(dir.join("movie.mov"), &dir).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(analysis.references.is_empty());
+    assert_eq!(analysis.warnings.len(), 1);
+    assert_eq!(analysis.warnings[0].box_type, "dref");
+    assert!(analysis.warnings[0].message.contains("gone.mdat"));
+}
+
+#[test]
+fn locations_outside_base_dir_are_not_read() {
+    let dir = temp_dir("dref-escape");
+    let base = dir.join("base");
+    std::fs::create_dir_all(&base).unwrap();
+    // A real file one level up, so a read would succeed if attempted.
+    std::fs::write(dir.join("x.mp4"), bx(b"mdat", &[1, 2, 3, 4])).unwrap();
+
+    for location in ["/etc/passwd", "../x.mp4", "file://../x.mp4"] {
+        std::fs::write(base.join("movie.mov"), reference_movie(location)).unwrap();
+        let analysis = analyze_file_with_refs(base.join("movie.mov"), &base).unwrap();
+
+        assert!(analysis.references.is_empty(), "{location}");
+        assert_eq!(analysis.warnings.len(), 1, "{location}");
+        let w = &analysis.warnings[0];
+        assert_eq!(w.box_type, "dref");
+        assert!(w.message.contains("not reading"), "{}", w.message);
+        assert!(w.message.contains(location), "{}", w.message);
+    }
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn external_dref_is_not_self_contained() {
+    let file = reference_movie("media.mov");
+    let tracks = track_samples_from_reader(Cursor::new(file.clone())).unwrap();
+
+    assert!(!tracks[0].data_self_contained);
+    assert!(!tracks[0].is_self_contained(file.len() as u64));
+}
+
+#[test]
+fn external_samples_are_not_reported_outside_mdat() {
+    // The chunk offset points into moov here, but it belongs to media.mov.
+    let tracks = track_samples_from_reader(Cursor::new(reference_movie("media.mov"))).unwrap();
+
+    assert!(
+        !tracks[0]
+            .warnings
+            .iter()
+            .any(|w| w.message.contains("outside mdat")),
+        "{:?}",
+        tracks[0].warnings
+    );
+}
+
+#[test]
+fn entry_count_above_actual_entries_is_a_warning() {
+    let mut dref = 3u32.to_be_bytes().to_vec();
+    dref.extend_from_slice(&full_box(b"url ", 0, 1, &[]));
+    let minf = bx(b"dinf", &full_box(b"dref", 0, 0, &dref));
+    let trak = trak_with(1, b"vide", 600, &[], &minf, &[]);
+    let file = bx(b"moov", &trak);
+
+    let size = file.len() as u64;
+    let analysis =
+        analyze_reader_opts(&mut Cursor::new(file), size, &ParseOptions::default()).unwrap();
+
+    assert_eq!(analysis.warnings.len(), 1, "{:?}", analysis.warnings);
+    let w = &analysis.warnings[0];
+    assert_eq!(w.box_type, "dref");
+    assert_eq!(w.message, "declares 3 entries but contains 1");
+}
+
+#[test]
+fn self_contained_url_entry() {
+    let mut dref = 1u32.to_be_bytes().to_vec();
+    dref.extend_from_slice(&full_box(b"url ", 0, 1, &[]));
+    let data = full_box(b"dref", 0, 0, &dref);
+    let size = data.len() as u64;
+    let boxes = mp4box::get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+    let Some(StructuredData::DataReference(d)) = &boxes[0].structured_data else {
+        panic!("dref not decoded");
+    };
+    assert_eq!(d.entry_count, 1);
+    let entry = &d.entries[0];
+    assert_eq!(entry.entry_type, "url ");
+    assert_eq!(entry.flags, 1);
+    assert!(entry.self_contained);
+    assert_eq!(entry.location, None);
+    assert_eq!(boxes[0].decoded.as_deref(), Some("url self-contained"));
+}
+
+#[test]
+fn external_entry_summary_names_the_location() {
+    let size = dinf_with_url("media.mov").len() as u64;
+    let boxes =
+        mp4box::get_boxes(&mut Cursor::new(dinf_with_url("media.mov")), size, true).unwrap();
+    let dref = &boxes[0].children.as_ref().unwrap()[0];
+    assert_eq!(dref.decoded.as_deref(), Some("url external \"media.mov\""));
+}
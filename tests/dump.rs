@@ -0,0 +1,53 @@
+mod common;
+
+use common::*;
+use mp4box::{DumpFormat, dump};
+
+fn sample_file() -> std::path::PathBuf {
+    let mut data = ftyp(b"isom", &[b"isom", b"mp41"]);
+    data.extend_from_slice(&bx(b"moov", &bx(b"udta", &bx(b"free", &[0u8; 4]))));
+    data.extend_from_slice(&bx(b"mdat", &[0u8; 16]));
+
+    let dir = temp_dir("dump");
+    let path = dir.join("sample.mp4");
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+#[test]
+fn every_format_is_parseable() {
+    let path = sample_file();
+    let json = dump(&path, DumpFormat::Json, true).unwrap();
+    let pretty = dump(&path, DumpFormat::PrettyJson, true).unwrap();
+    let ndjson = dump(&path, DumpFormat::Ndjson, true).unwrap();
+    let text = dump(&path, DumpFormat::Text, true).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).ok();
+
+    let tree: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(tree.as_array().unwrap().len(), 3);
+    assert_eq!(tree[1]["children"][0]["typ"], "udta");
+    assert!(!json.contains('\n'));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&pretty).unwrap(),
+        tree
+    );
+
+    let lines: Vec<serde_json::Value> = ndjson
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let paths: Vec<&str> = lines.iter().map(|l| l["path"].as_str().unwrap()).collect();
+    assert_eq!(
+        paths,
+        ["ftyp", "moov", "moov/udta", "moov/udta/free", "mdat"]
+    );
+    assert!(lines.iter().all(|l| l.get("children").is_none()));
+    assert_eq!(lines[0]["decoded"], "isom (isom, mp41)");
+
+    let types: Vec<&str> = text
+        .lines()
+        .map(|l| l.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(types, ["ftyp", "moov", "udta", "free", "mdat"]);
+    assert!(text.lines().nth(2).unwrap().starts_with("  udta "));
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::*;
+use mp4box::registry::StructuredData;
+use mp4box::samples::{extract_track_samples, extract_track_samples_opts};
+use mp4box::{ParseOptions, edit_lists, get_boxes};
+use std::io::Cursor;
+
+/// Four 10-unit samples at timescale 1000, with `edts` added to the trak.
+fn video_trak(trak_extra: &[u8]) -> Vec<u8> {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(4, 10)]));
+    stbl.extend_from_slice(&stsc(&[(1, 4, 1)]));
+    stbl.extend_from_slice(&stsz(&[1, 1, 1, 1]));
+    stbl.extend_from_slice(&stco(&[0]));
+    trak_with(1, b"vide", 1000, trak_extra, &[], &stbl)
+}
+
+fn trak_box(data: Vec<u8>) -> (mp4box::Box, Cursor<Vec<u8>>) {
+    let size = data.len() as u64;
+    let mut cur = Cursor::new(data);
+    let mut boxes = get_boxes(&mut cur, size, true).unwrap();
+    (boxes.remove(0), cur)
+}
+
+#[test]
+fn elst_v1_entries() {
+    let mut p = 2u32.to_be_bytes().to_vec();
+    for (duration, media_time) in [(100u64, -1i64), (5_000_000_000, 20)] {
+        p.extend_from_slice(&duration.to_be_bytes());
+        p.extend_from_slice(&media_time.to_be_bytes());
+        p.extend_from_slice(&1i16.to_be_bytes());
+        p.extend_from_slice(&0i16.to_be_bytes());
+    }
+    let data = full_box(b"elst", 1, 0, &p);
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let Some(StructuredData::EditList(elst)) = &boxes[0].structured_data else {
+        panic!("elst not decoded");
+    };
+    assert_eq!(elst.entries.len(), 2);
+    assert_eq!(elst.entries[0].media_time, -1);
+    assert_eq!(elst.entries[1].segment_duration, 5_000_000_000);
+    assert_eq!(elst.entries[1].media_time, 20);
+    assert_eq!(elst.entries[1].media_rate_integer, 1);
+}
+
+#[test]
+fn empty_edit_delays_presentation() {
+    let (trak, mut cur) = trak_box(video_trak(&edts(&[(100, -1), (40, 0)])));
+
+    let raw = extract_track_samples(&trak, &mut cur).unwrap().unwrap();
+    assert_eq!(raw.samples[0].pts, 0);
+    assert_eq!(raw.edits.len(), 2);
+
+    let opts = ParseOptions {
+        apply_edits: true,
+        ..Default::default()
+    };
+    let track = extract_track_samples_opts(&trak, &mut cur, 1000, &opts)
+        .unwrap()
+        .unwrap();
+    let pts: Vec<u64> = track.samples.iter().map(|s| s.pts).collect();
+    assert_eq!(pts, vec![100, 110, 120, 130]);
+    assert_eq!(track.samples[0].start_time, 0.1);
+    // Decode times stay on the media timeline.
+    assert_eq!(track.samples[0].dts, 0);
+}
+
+#[test]
+fn empty_edit_is_scaled_from_movie_timescale() {
+    let (trak, mut cur) = trak_box(video_trak(&edts(&[(60, -1), (40, 0)])));
+    let opts = ParseOptions {
+        apply_edits: true,
+        ..Default::default()
+    };
+    // 60 units at a 600 Hz movie timescale is 100 ms, i.e. 100 media units.
+    let track = extract_track_samples_opts(&trak, &mut cur, 600, &opts)
+        .unwrap()
+        .unwrap();
+    assert_eq!(track.samples[0].pts, 100);
+}
+
+#[test]
+fn media_time_trims_the_start() {
+    let (trak, mut cur) = trak_box(video_trak(&edts(&[(20, 20)])));
+    let opts = ParseOptions {
+        apply_edits: true,
+        ..Default::default()
+    };
+    let track = extract_track_samples_opts(&trak, &mut cur, 1000, &opts)
+        .unwrap()
+        .unwrap();
+    let pts: Vec<u64> = track.samples.iter().map(|s| s.pts).collect();
+    assert_eq!(pts, vec![0, 0, 0, 10]);
+
+    // Without the option the edit list is only reported.
+    let untouched = extract_track_samples_opts(&trak, &mut cur, 1000, &ParseOptions::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!(untouched.samples[3].pts, 30);
+}
+
+#[test]
+fn edit_lists_per_track() {
+    let mut stbl = stsd(&[audio_entry(b"mp4a", 2, 48000, &[])]);
+    stbl.extend_from_slice(&stts(&[]));
+    let audio = trak_with(2, b"soun", 48000, &edts(&[(1024, 0)]), &[], &stbl);
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[]));
+    let subs = trak(3, b"vide", 1000, &stbl);
+
+    let mut moov = video_trak(&edts(&[(100, -1), (40, 0)]));
+    moov.extend_from_slice(&audio);
+    moov.extend_from_slice(&subs);
+    let path = temp_dir("edit-lists").join("av.mp4");
+    std::fs::write(&path, bx(b"moov", &moov)).unwrap();
+
+    let lists = edit_lists(&path).unwrap();
+    let ids: Vec<u32> = lists.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, [1, 2, 3]);
+
+    let video = &lists[0].1;
+    assert_eq!(video.len(), 2);
+    assert_eq!((video[0].segment_duration, video[0].media_time), (100, -1));
+    let audio = &lists[1].1;
+    assert_eq!(audio.len(), 1);
+    assert_eq!((audio[0].segment_duration, audio[0].media_time), (1024, 0));
+    assert!(lists[2].1.is_empty());
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::*;
+use mp4box::registry::StructuredData;
+use mp4box::{GaplessInfo, get_boxes, track_samples_from_reader};
+use std::io::Cursor;
+
+fn aac_track(trak_extra: &[u8]) -> Vec<u8> {
+    let mut stbl = stsd(&[audio_entry(b"mp4a", 2, 44100, &[])]);
+    stbl.extend_from_slice(&stts(&[(3, 1024)]));
+    stbl.extend_from_slice(&stsc(&[(1, 3, 1)]));
+    stbl.extend_from_slice(&stsz(&[10, 10, 10]));
+    stbl.extend_from_slice(&stco(&[0]));
+    trak_with(1, b"soun", 44100, trak_extra, &[], &stbl)
+}
+
+fn itunsmpb(value: &str) -> Vec<u8> {
+    let mut item = full_box(b"mean", 0, 0, b"com.apple.iTunes");
+    item.extend_from_slice(&full_box(b"name", 0, 0, b"iTunSMPB"));
+    let mut data = 1u32.to_be_bytes().to_vec();
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(value.as_bytes());
+    item.extend_from_slice(&bx(b"data", &data));
+
+    let mut meta = hdlr(b"mdir");
+    meta.extend_from_slice(&bx(b"ilst", &bx(b"----", &item)));
+    bx(b"udta", &full_box(b"meta", 0, 0, &meta))
+}
+
+#[test]
+fn priming_from_edit_list() {
+    // A leading empty edit followed by an edit that skips 2112 priming samples.
+    let trak = aac_track(&edts(&[(100, -1), (1000, 2112)]));
+    let tracks = track_samples_from_reader(Cursor::new(bx(b"moov", &trak))).unwrap();
+
+    assert_eq!(tracks[0].edits.len(), 2);
+    assert_eq!(tracks[0].edits[1].media_time, 2112);
+    assert_eq!(tracks[0].encoder_delay(), Some(2112));
+}
+
+#[test]
+fn priming_from_itunes_gapless_tag() {
+    let mut moov = aac_track(&[]);
+    moov.extend_from_slice(&itunsmpb(
+        " 00000000 00000840 000001CA 00000000003F31F6 00000000 00000000",
+    ));
+    let tracks = track_samples_from_reader(Cursor::new(bx(b"moov", &moov))).unwrap();
+
+    assert_eq!(
+        tracks[0].gapless,
+        Some(GaplessInfo {
+            encoder_delay: 0x840,
+            padding: 0x1ca,
+            original_sample_count: 0x3f31f6,
+        })
+    );
+    assert_eq!(tracks[0].encoder_delay(), Some(0x840));
+}
+
+#[test]
+fn no_delay_without_edits_or_tag() {
+    let tracks = track_samples_from_reader(Cursor::new(bx(b"moov", &aac_track(&[])))).unwrap();
+    assert_eq!(tracks[0].encoder_delay(), None);
+}
+
+#[test]
+fn ilst_decodes_itunsmpb_item() {
+    // iTunSMPB as written by iTunes for a 3:05 AAC track.
+    let value = " 00000000 00000840 00000374 00000000007A7D4C 00000000 00000000 \
+                 00000000 00000000 00000000 00000000 00000000 00000000";
+    let udta = itunsmpb(value);
+    let size = udta.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(udta), size, true).unwrap();
+
+    let ilst = &boxes[0].children.as_ref().unwrap()[0]
+        .children
+        .as_ref()
+        .unwrap()[1];
+    let Some(StructuredData::Metadata(ilst)) = &ilst.structured_data else {
+        panic!("ilst not decoded");
+    };
+    assert_eq!(ilst.items[0].key, "com.apple.iTunes:iTunSMPB");
+    assert_eq!(ilst.items[0].value, value);
+    assert_eq!(
+        ilst.items[0].gapless,
+        Some(GaplessInfo {
+            encoder_delay: 2112,
+            padding: 884,
+            original_sample_count: 8_027_468,
+        })
+    );
+}
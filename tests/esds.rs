@@ -0,0 +1,82 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{AudioSpecificConfig, EsdsData, StructuredData};
+use mp4box::track_samples_from_reader;
+use std::io::Cursor;
+
+/// AAC-LC, 44.1 kHz stereo, 128 kb/s, as written by common muxers.
+const AAC_LC_ESDS: [u8; 27] = [
+    0x03, 0x19, 0x00, 0x01, 0x00, // ES_Descriptor, ES_ID 1
+    0x04, 0x11, 0x40, 0x15, 0x00, 0x00, 0x00, // DecoderConfig: MPEG-4 audio
+    0x00, 0x01, 0xF4, 0x00, 0x00, 0x01, 0xF4, 0x00, // max/avg bitrate
+    0x05, 0x02, 0x12, 0x10, // AudioSpecificConfig
+    0x06, 0x01, 0x02, // SLConfig
+];
+
+fn assert_aac_lc(esds: &EsdsData) {
+    assert_eq!(esds.es_id, 1);
+    assert_eq!(esds.object_type_indication, 0x40);
+    assert_eq!(esds.stream_type, 5);
+    assert_eq!(esds.max_bitrate, 128_000);
+    assert_eq!(esds.avg_bitrate, 128_000);
+    assert_eq!(
+        esds.audio_config,
+        Some(AudioSpecificConfig {
+            audio_object_type: 2,
+            sampling_frequency_index: 4,
+            sampling_frequency: Some(44100),
+            channel_configuration: 2,
+        })
+    );
+}
+
+#[test]
+fn aac_lc_esds_payload() {
+    assert_aac_lc(&EsdsData::parse(&AAC_LC_ESDS).unwrap());
+}
+
+#[test]
+fn esds_box_and_mp4a_entry() {
+    let esds = full_box(b"esds", 0, 0, &AAC_LC_ESDS);
+    let size = esds.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(esds.clone()), size, true).unwrap();
+    let Some(StructuredData::ElementaryStreamDescriptor(decoded)) = &boxes[0].structured_data
+    else {
+        panic!("esds not decoded: {:?}", boxes[0].decoded);
+    };
+    assert_aac_lc(decoded);
+
+    let mut stbl = stsd(&[audio_entry(b"mp4a", 2, 44100, &esds)]);
+    stbl.extend_from_slice(&stts(&[]));
+    stbl.extend_from_slice(&stsc(&[]));
+    stbl.extend_from_slice(&stsz(&[]));
+    stbl.extend_from_slice(&stco(&[]));
+    let moov = bx(b"moov", &trak(1, b"soun", 44100, &stbl));
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let entry = tracks[0].sample_entry.as_ref().unwrap();
+    assert_aac_lc(entry.esds.as_ref().expect("esds parsed from mp4a"));
+}
+
+#[test]
+fn long_size_encoding_and_unknown_tags() {
+    // Four-byte expandable sizes, plus an unknown descriptor before DecoderConfig.
+    let mut p = vec![0x03, 0x80, 0x80, 0x80, 0x19, 0x00, 0x02, 0x00];
+    p.extend_from_slice(&[0x0A, 0x01, 0xFF]);
+    p.extend_from_slice(&AAC_LC_ESDS[5..24]);
+    let esds = EsdsData::parse(&p).unwrap();
+    assert_eq!(esds.es_id, 2);
+    assert_eq!(esds.audio_config.unwrap().audio_object_type, 2);
+}
+
+#[test]
+fn truncated_descriptor_keeps_what_is_present() {
+    // Cut off in the middle of the DecoderConfigDescriptor.
+    let esds = EsdsData::parse(&AAC_LC_ESDS[..12]).unwrap();
+    assert_eq!(esds.es_id, 1);
+    assert_eq!(esds.object_type_indication, 0);
+    assert!(esds.audio_config.is_none());
+
+    assert!(EsdsData::parse(&[0x06, 0x01, 0x02]).is_err());
+}
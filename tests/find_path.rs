@@ -0,0 +1,41 @@
+mod common;
+
+use common::*;
+use mp4box::{find_path, get_boxes};
+use std::io::Cursor;
+
+fn two_track_movie() -> Vec<mp4box::Box> {
+    let mut tracks = trak(1, b"vide", 12800, &stsd(&[]));
+    tracks.extend_from_slice(&trak(2, b"soun", 44100, &stsd(&[])));
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend_from_slice(&bx(b"moov", &tracks));
+    let size = data.len() as u64;
+    get_boxes(&mut Cursor::new(data), size, true).unwrap()
+}
+
+#[test]
+fn exact_path_finds_every_match() {
+    let boxes = two_track_movie();
+
+    let stsds = find_path(&boxes, &["moov", "trak", "mdia", "minf", "stbl", "stsd"]);
+    assert_eq!(stsds.len(), 2);
+    assert!(stsds.iter().all(|b| b.typ == "stsd"));
+    assert!(stsds[0].offset < stsds[1].offset);
+
+    assert_eq!(find_path(&boxes, &["ftyp"]).len(), 1);
+    assert!(find_path(&boxes, &["moov", "mvex"]).is_empty());
+    assert!(find_path(&boxes, &[]).is_empty());
+}
+
+#[test]
+fn wildcard_segment_matches_any_type() {
+    let boxes = two_track_movie();
+
+    let tkhds = find_path(&boxes, &["moov", "*", "tkhd"]);
+    assert_eq!(tkhds.len(), 2);
+    assert!(tkhds.iter().all(|b| b.typ == "tkhd"));
+
+    let hdlrs = find_path(&boxes, &["*", "trak", "*", "hdlr"]);
+    assert_eq!(hdlrs.len(), 2);
+    assert_eq!(find_path(&boxes, &["*"]).len(), 2);
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+use mp4box::{FileKind, ParseOptions, analyze_full, detect_kind};
+
+fn write(name: &str, data: &[u8]) -> std::path::PathBuf {
+    let path = temp_dir("format-info").join(name);
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+#[test]
+fn standard_mp4_brands() {
+    let mut file = ftyp(b"isom", &[b"isom", b"iso2", b"avc1", b"mp41"]);
+    file.extend_from_slice(&bx(b"moov", &[]));
+    let path = write("plain.mp4", &file);
+
+    let full = analyze_full(&path, &ParseOptions::default()).unwrap();
+    let format = &full.format;
+    assert_eq!(format.major_brand.as_deref(), Some("isom"));
+    assert_eq!(format.minor_version, Some(0));
+    assert_eq!(format.compatible_brands, ["isom", "iso2", "avc1", "mp41"]);
+    assert!(!format.is_fragmented);
+    assert_eq!(format.detected_format, "MP4");
+    assert_eq!(full.analysis.boxes.len(), 2);
+}
+
+#[test]
+fn fragmented_cmaf_and_brandless_quicktime() {
+    let mut file = ftyp(b"cmfc", &[b"iso6", b"cmfc"]);
+    file.extend_from_slice(&bx(b"moov", &bx(b"mvex", &[])));
+    let format = analyze_full(write("frag.mp4", &file), &ParseOptions::default())
+        .unwrap()
+        .format;
+    assert!(format.is_fragmented);
+    assert_eq!(format.detected_format, "CMAF");
+
+    let format = analyze_full(
+        write("old.mov", &bx(b"moov", &[])),
+        &ParseOptions::default(),
+    )
+    .unwrap()
+    .format;
+    assert_eq!(format.major_brand, None);
+    assert!(format.compatible_brands.is_empty());
+    assert_eq!(format.detected_format, "QuickTime");
+}
+
+#[test]
+fn detect_kind_ignores_extension() {
+    // Generic brands, but only a sound track: an .m4a saved as .mp4
+    let mut file = ftyp(b"isom", &[b"isom", b"mp42"]);
+    file.extend_from_slice(&bx(b"moov", &trak(1, b"soun", 44100, &[])));
+    assert_eq!(
+        detect_kind(write("audio.mp4", &file)).unwrap(),
+        FileKind::M4a
+    );
+
+    let mut file = ftyp(b"isom", &[b"isom", b"mp42"]);
+    let mut tracks = trak(1, b"vide", 12800, &[]);
+    tracks.extend_from_slice(&trak(2, b"soun", 44100, &[]));
+    file.extend_from_slice(&bx(b"moov", &tracks));
+    assert_eq!(
+        detect_kind(write("video.m4a", &file)).unwrap(),
+        FileKind::Mp4
+    );
+
+    let mut file = ftyp(b"3gp4", &[b"isom", b"3gp4"]);
+    file.extend_from_slice(&bx(b"moov", &trak(1, b"vide", 12800, &[])));
+    assert_eq!(
+        detect_kind(write("phone.mp4", &file)).unwrap(),
+        FileKind::ThreeGp
+    );
+}
@@ -0,0 +1,468 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use mp4box::track_samples_from_reader;
+use std::io::Cursor;
+
+/// moof/traf with a tfhd carrying an explicit 64-bit base_data_offset and a
+/// trun with a data_offset and per-sample sizes.
+fn moof(base_data_offset: u64, data_offset: i32, sizes: &[u32]) -> Vec<u8> {
+    let mut tfhd = 1u32.to_be_bytes().to_vec();
+    tfhd.extend_from_slice(&base_data_offset.to_be_bytes());
+
+    let mut trun = (sizes.len() as u32).to_be_bytes().to_vec();
+    trun.extend_from_slice(&data_offset.to_be_bytes());
+    for size in sizes {
+        trun.extend_from_slice(&size.to_be_bytes());
+    }
+
+    let mut traf = full_box(b"tfhd", 0, 0x000001, &tfhd);
+    traf.extend_from_slice(&full_box(b"trun", 0, 0x000201, &trun));
+    bx(b"moof", &bx(b"traf", &traf))
+}
+
+#[test]
+fn base_data_offset_above_4gib() {
+    let base = 0x1_2345_6780u64;
+    let data = moof(base, 16, &[1000, 2000, 3000]);
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+    let traf = &boxes[0].children.as_ref().unwrap()[0];
+    let kids = traf.children.as_ref().unwrap();
+    let Some(StructuredData::TrackFragmentHeader(tfhd)) = &kids[0].structured_data else {
+        panic!("tfhd not decoded: {:?}", kids[0].decoded);
+    };
+    let Some(StructuredData::TrackRun(trun)) = &kids[1].structured_data else {
+        panic!("trun not decoded: {:?}", kids[1].decoded);
+    };
+    assert_eq!(tfhd.base_data_offset, Some(base));
+    assert_eq!(trun.data_offset, Some(16));
+
+    let mut file = init_segment();
+    file.extend_from_slice(&moof(base, 16, &[1000, 2000, 3000]));
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let offsets: Vec<u64> = tracks[0]
+        .samples
+        .iter()
+        .map(|s| s.file_offset.unwrap())
+        .collect();
+    assert_eq!(offsets, vec![base + 16, base + 1016, base + 3016]);
+}
+
+fn init_segment() -> Vec<u8> {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[]));
+    stbl.extend_from_slice(&stsc(&[]));
+    stbl.extend_from_slice(&stsz(&[]));
+    stbl.extend_from_slice(&stco(&[]));
+
+    // trex: track 1, description 1, duration 512, size 300, flags non-sync
+    let mut trex = Vec::new();
+    for v in [1u32, 1, 512, 300, 0x0001_0000] {
+        trex.extend_from_slice(&v.to_be_bytes());
+    }
+
+    let mut moov = trak(1, b"vide", 12800, &stbl);
+    moov.extend_from_slice(&bx(b"mvex", &full_box(b"trex", 0, 0, &trex)));
+    let mut file = ftyp(b"iso6", &[b"iso6", b"dash"]);
+    file.extend_from_slice(&bx(b"moov", &moov));
+    file
+}
+
+/// A trun as `(data_offset, per-sample (duration, size, flags))`.
+type Run = (Option<i32>, Vec<(u32, u32, u32)>);
+
+/// moof with a default-base-is-moof tfhd, a tfdt and the given truns.
+fn fragment(decode_time: u64, truns: &[Run]) -> Vec<u8> {
+    let mut traf = full_box(b"tfhd", 0, 0x020000, &1u32.to_be_bytes());
+    traf.extend_from_slice(&full_box(b"tfdt", 1, 0, &decode_time.to_be_bytes()));
+    for (data_offset, samples) in truns {
+        let mut p = (samples.len() as u32).to_be_bytes().to_vec();
+        let mut flags = 0x000700;
+        if let Some(d) = data_offset {
+            p.extend_from_slice(&d.to_be_bytes());
+            flags |= 0x000001;
+        }
+        for (duration, size, sample_flags) in samples {
+            p.extend_from_slice(&duration.to_be_bytes());
+            p.extend_from_slice(&size.to_be_bytes());
+            p.extend_from_slice(&sample_flags.to_be_bytes());
+        }
+        traf.extend_from_slice(&full_box(b"trun", 0, flags, &p));
+    }
+    let mut moof = full_box(b"mfhd", 0, 0, &1u32.to_be_bytes());
+    moof.extend_from_slice(&bx(b"traf", &traf));
+    bx(b"moof", &moof)
+}
+
+#[test]
+fn fragment_samples_from_moof_truns() {
+    let mut file = init_segment();
+    let moof_offset = file.len() as u64;
+
+    // Two runs in one traf: the second has no data_offset and follows the first.
+    let runs = |data_offset| {
+        vec![
+            (
+                Some(data_offset),
+                vec![(512, 100, 0), (512, 50, 0x0001_0000)],
+            ),
+            (None, vec![(1024, 25, 0x0001_0000)]),
+        ]
+    };
+    let moof_len = fragment(0, &runs(0)).len() as i32;
+    // data_offset is relative to the moof start and points past the mdat header.
+    file.extend_from_slice(&fragment(0, &runs(moof_len + 8)));
+    file.extend_from_slice(&bx(b"mdat", &[0u8; 175]));
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let samples = &tracks[0].samples;
+    assert_eq!(tracks[0].sample_count, 3);
+
+    let mdat_payload = moof_offset + moof_len as u64 + 8;
+    let offsets: Vec<u64> = samples.iter().map(|s| s.file_offset.unwrap()).collect();
+    assert_eq!(
+        offsets,
+        vec![mdat_payload, mdat_payload + 100, mdat_payload + 150]
+    );
+    let dts: Vec<u64> = samples.iter().map(|s| s.dts).collect();
+    assert_eq!(dts, vec![0, 512, 1024]);
+    assert_eq!(samples[2].size, 25);
+    assert!(samples[0].is_sync);
+    assert!(!samples[1].is_sync);
+    // The 1024 duration is the only thing flagged.
+    let messages: Vec<&str> = tracks[0]
+        .warnings
+        .iter()
+        .map(|w| w.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        ["1 of 3 sample durations in fragment 1 differ from the trex default of 512 for track 1"]
+    );
+}
+
+#[test]
+fn tfdt_places_each_fragment_and_trex_fills_defaults() {
+    let mut file = init_segment();
+
+    // A trun carrying no per-sample fields: sizes/durations come from trex.
+    let bare_run = |decode_time: u64, count: u32, data_offset: i32| {
+        let mut traf = full_box(b"tfhd", 0, 0x020000, &1u32.to_be_bytes());
+        traf.extend_from_slice(&full_box(b"tfdt", 1, 0, &decode_time.to_be_bytes()));
+        let mut p = count.to_be_bytes().to_vec();
+        p.extend_from_slice(&data_offset.to_be_bytes());
+        traf.extend_from_slice(&full_box(b"trun", 0, 0x000001, &p));
+        bx(b"moof", &bx(b"traf", &traf))
+    };
+    let moof_len = bare_run(0, 2, 0).len() as i32;
+    for decode_time in [0, 90_000] {
+        file.extend_from_slice(&bare_run(decode_time, 2, moof_len + 8));
+        file.extend_from_slice(&bx(b"mdat", &[0u8; 600]));
+    }
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let samples = &tracks[0].samples;
+    let dts: Vec<u64> = samples.iter().map(|s| s.dts).collect();
+    assert_eq!(dts, vec![0, 512, 90_000, 90_512]);
+    assert!(samples.iter().all(|s| s.size == 300 && s.duration == 512));
+    assert!(samples.iter().all(|s| !s.is_sync));
+}
+
+/// moof whose tfhd has `default_sample_size` and whose trun carries only
+/// per-sample durations.
+fn sized_by_tfhd(default_size: u32, durations: &[u32], data_offset: i32) -> Vec<u8> {
+    let mut tfhd = 1u32.to_be_bytes().to_vec();
+    tfhd.extend_from_slice(&default_size.to_be_bytes());
+    let mut traf = full_box(b"tfhd", 0, 0x020010, &tfhd);
+
+    let mut p = (durations.len() as u32).to_be_bytes().to_vec();
+    p.extend_from_slice(&data_offset.to_be_bytes());
+    for d in durations {
+        p.extend_from_slice(&d.to_be_bytes());
+    }
+    traf.extend_from_slice(&full_box(b"trun", 0, 0x000101, &p));
+    bx(b"moof", &bx(b"traf", &traf))
+}
+
+#[test]
+fn tfhd_default_size_overrides_trex() {
+    let mut file = init_segment();
+    let moof_offset = file.len() as u64;
+    let moof_len = sized_by_tfhd(200, &[256, 768], 0).len() as i32;
+    file.extend_from_slice(&sized_by_tfhd(200, &[256, 768], moof_len + 8));
+    file.extend_from_slice(&bx(b"mdat", &[0u8; 400]));
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let samples = &tracks[0].samples;
+    let fields: Vec<(u32, u32)> = samples.iter().map(|s| (s.duration, s.size)).collect();
+    // Sizes from tfhd (not trex's 300), durations from the trun.
+    assert_eq!(fields, vec![(256, 200), (768, 200)]);
+    let payload = moof_offset + moof_len as u64 + 8;
+    assert_eq!(samples[1].file_offset, Some(payload + 200));
+    // Flags still fall back to trex (non-sync).
+    assert!(!samples[0].is_sync);
+    let messages: Vec<&str> = tracks[0]
+        .warnings
+        .iter()
+        .map(|w| w.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        ["2 of 2 sample durations in fragment 1 differ from the trex default of 512 for track 1"]
+    );
+}
+
+#[test]
+fn missing_defaults_are_reported() {
+    // No mvex/trex, and a trun without sizes or durations.
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[]));
+    stbl.extend_from_slice(&stsc(&[]));
+    stbl.extend_from_slice(&stsz(&[]));
+    stbl.extend_from_slice(&stco(&[]));
+    let mut file = bx(b"moov", &trak(1, b"vide", 12800, &stbl));
+
+    let traf = {
+        let mut traf = full_box(b"tfhd", 0, 0x020000, &1u32.to_be_bytes());
+        traf.extend_from_slice(&full_box(b"trun", 0, 0, &2u32.to_be_bytes()));
+        traf
+    };
+    file.extend_from_slice(&bx(b"moof", &bx(b"traf", &traf)));
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    assert_eq!(tracks[0].samples.len(), 2);
+    let w = tracks[0]
+        .warnings
+        .iter()
+        .find(|w| w.box_type == "trun")
+        .expect("trun warning");
+    assert_eq!(
+        w.message,
+        "no sample size or duration in trun, tfhd or trex for track 1; using 0"
+    );
+}
+
+#[test]
+fn fragment_offsets_past_64_bits_are_left_unknown() {
+    let mut file = init_segment();
+    file.extend_from_slice(&moof(u64::MAX - 1500, 16, &[1000, 2000, 3000]));
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let offsets: Vec<Option<u64>> = tracks[0].samples.iter().map(|s| s.file_offset).collect();
+    assert_eq!(offsets, [Some(u64::MAX - 1484), Some(u64::MAX - 484), None]);
+    let w = tracks[0]
+        .warnings
+        .iter()
+        .find(|w| w.box_type == "trun")
+        .expect("trun warning");
+    assert_eq!(
+        w.message,
+        "1 samples (first: sample 3) have no file offset: \
+         the fragment data offset does not fit in 64 bits"
+    );
+}
+
+#[test]
+fn mfra_random_access_index() {
+    let mut tfra = 2u32.to_be_bytes().to_vec(); // track_ID
+    // traf_num 1 byte, trun_num 2 bytes, sample_num 4 bytes
+    tfra.extend_from_slice(&0b00_01_11u32.to_be_bytes());
+    tfra.extend_from_slice(&1u32.to_be_bytes());
+    tfra.extend_from_slice(&90_000u64.to_be_bytes()); // time
+    tfra.extend_from_slice(&0x1_0000_0000u64.to_be_bytes()); // moof_offset
+    tfra.push(1);
+    tfra.extend_from_slice(&3u16.to_be_bytes());
+    tfra.extend_from_slice(&70_000u32.to_be_bytes());
+
+    let mut mfra = full_box(b"tfra", 1, 0, &tfra);
+    let mfra_size = (mfra.len() + 8 + 16) as u32;
+    mfra.extend_from_slice(&full_box(b"mfro", 0, 0, &mfra_size.to_be_bytes()));
+    let data = bx(b"mfra", &mfra);
+    let size = data.len() as u64;
+
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let kids = boxes[0].children.as_ref().unwrap();
+
+    let Some(StructuredData::TrackFragmentRandomAccess(tfra)) = &kids[0].structured_data else {
+        panic!("expected tfra data, got {:?}", kids[0].decoded);
+    };
+    assert_eq!(tfra.track_id, 2);
+    assert_eq!(
+        (
+            tfra.traf_number_size,
+            tfra.trun_number_size,
+            tfra.sample_number_size
+        ),
+        (1, 2, 4)
+    );
+    assert_eq!(tfra.entries.len(), 1);
+    let entry = tfra.entries[0];
+    assert_eq!(entry.time, 90_000);
+    assert_eq!(entry.moof_offset, 0x1_0000_0000);
+    assert_eq!(
+        (entry.traf_number, entry.trun_number, entry.sample_number),
+        (1, 3, 70_000)
+    );
+
+    let Some(StructuredData::MovieFragmentRandomAccessOffset(mfro)) = &kids[1].structured_data
+    else {
+        panic!("expected mfro data");
+    };
+    assert_eq!(mfro.size as u64, size);
+}
+
+#[test]
+fn tfdt_seconds_use_the_track_timescale() {
+    let mut traf = full_box(b"tfhd", 0, 0x020000, &2u32.to_be_bytes());
+    traf.extend_from_slice(&full_box(b"tfdt", 1, 0, &180_000u64.to_be_bytes()));
+    let mut data = bx(b"moov", &trak(2, b"vide", 90_000, &[]));
+    data.extend_from_slice(&bx(b"moof", &bx(b"traf", &traf)));
+    let size = data.len() as u64;
+
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let tfdt = &mp4box::find_path(&boxes, &["moof", "traf", "tfdt"])[0];
+    assert_eq!(
+        tfdt.decoded.as_deref(),
+        Some("base_media_decode_time=180000 (2.000s)")
+    );
+    let Some(StructuredData::TrackFragmentDecodeTime(data)) = &tfdt.structured_data else {
+        panic!("expected tfdt data");
+    };
+    assert_eq!(data.base_media_decode_time_seconds, Some(2.0));
+    assert_eq!(data.seconds(48_000), Some(3.75));
+    assert_eq!(data.seconds(0), None);
+
+    // A lone fragment has no init segment to take the timescale from.
+    let data = bx(b"moof", &bx(b"traf", &traf));
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let tfdt = &mp4box::find_path(&boxes, &["moof", "traf", "tfdt"])[0];
+    assert_eq!(
+        tfdt.decoded.as_deref(),
+        Some("base_media_decode_time=180000")
+    );
+}
+
+#[test]
+fn durations_off_the_trex_default_are_flagged_per_fragment() {
+    let mut file = init_segment();
+    let runs = |data_offset, durations: [u32; 2]| {
+        vec![(
+            Some(data_offset),
+            durations.iter().map(|&d| (d, 10, 0)).collect(),
+        )]
+    };
+    let moof_len = fragment(0, &runs(0, [512, 512])).len() as i32;
+    for (decode_time, durations) in [(0, [512, 512]), (1024, [512, 500]), (2036, [512, 512])] {
+        file.extend_from_slice(&fragment(decode_time, &runs(moof_len + 8, durations)));
+        file.extend_from_slice(&bx(b"mdat", &[0u8; 20]));
+    }
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    assert_eq!(tracks[0].samples.len(), 6);
+    let warnings = &tracks[0].warnings;
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert_eq!(warnings[0].box_type, "traf");
+    assert_eq!(
+        warnings[0].message,
+        "1 of 2 sample durations in fragment 2 differ from the trex default of 512 for track 1"
+    );
+}
+
+#[test]
+fn first_sample_flags_mark_the_fragment_keyframe() {
+    let mut file = init_segment();
+
+    // tfhd default_sample_flags: depends on others, non-sync, priority 7.
+    // trun first_sample_flags: depends on nothing, leading unknown.
+    let moof = |data_offset: i32| {
+        let mut tfhd = 1u32.to_be_bytes().to_vec();
+        tfhd.extend_from_slice(&0x0101_0007u32.to_be_bytes());
+        let mut traf = full_box(b"tfhd", 0, 0x020020, &tfhd);
+        let mut p = 3u32.to_be_bytes().to_vec();
+        p.extend_from_slice(&data_offset.to_be_bytes());
+        p.extend_from_slice(&0x0200_0000u32.to_be_bytes());
+        traf.extend_from_slice(&full_box(b"trun", 0, 0x000005, &p));
+        bx(b"moof", &bx(b"traf", &traf))
+    };
+    let moof_len = moof(0).len() as i32;
+    file.extend_from_slice(&moof(moof_len + 8));
+    file.extend_from_slice(&bx(b"mdat", &[0u8; 900]));
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let samples = &tracks[0].samples;
+    let sync: Vec<bool> = samples.iter().map(|s| s.is_sync).collect();
+    assert_eq!(sync, [true, false, false]);
+
+    let first = samples[0].flags.unwrap();
+    assert_eq!(first.sample_depends_on, 2);
+    assert!(!first.is_non_sync_sample);
+    let rest = samples[1].flags.unwrap();
+    assert_eq!(rest.sample_depends_on, 1);
+    assert!(rest.is_non_sync_sample);
+    assert_eq!(rest.degradation_priority, 7);
+    assert_eq!(rest.is_leading, 0);
+}
+
+#[test]
+fn each_track_takes_sizes_from_its_own_trex() {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[]));
+    stbl.extend_from_slice(&stsc(&[]));
+    stbl.extend_from_slice(&stsz(&[]));
+    stbl.extend_from_slice(&stco(&[]));
+
+    let trex = |track_id: u32, size: u32| {
+        let mut p = Vec::new();
+        for v in [track_id, 1, 1000, size, 0] {
+            p.extend_from_slice(&v.to_be_bytes());
+        }
+        full_box(b"trex", 0, 0, &p)
+    };
+    let mut mvex = trex(1, 100);
+    mvex.extend_from_slice(&trex(2, 40));
+    let mut moov = trak(1, b"vide", 1000, &stbl);
+    moov.extend_from_slice(&trak(2, b"soun", 1000, &stbl));
+    moov.extend_from_slice(&bx(b"mvex", &mvex));
+    let mut file = bx(b"moov", &moov);
+
+    // One traf per track; both truns carry only a sample count and data_offset.
+    let traf = |track_id: u32, count: u32, data_offset: i32| {
+        let mut traf = full_box(b"tfhd", 0, 0x020000, &track_id.to_be_bytes());
+        let mut p = count.to_be_bytes().to_vec();
+        p.extend_from_slice(&data_offset.to_be_bytes());
+        traf.extend_from_slice(&full_box(b"trun", 0, 0x000001, &p));
+        bx(b"traf", &traf)
+    };
+    let moof_len = (8 + 2 * traf(0, 0, 0).len()) as i32;
+    let mut trafs = traf(1, 2, moof_len + 8);
+    trafs.extend_from_slice(&traf(2, 3, moof_len + 8 + 200));
+    let moof_start = file.len() as u64;
+    file.extend_from_slice(&bx(b"moof", &trafs));
+    file.extend_from_slice(&bx(b"mdat", &[0u8; 320]));
+
+    let size = file.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(file.clone()), size, true).unwrap();
+    let trexes = mp4box::find_path(&boxes, &["moov", "mvex", "trex"]);
+    assert_eq!(
+        trexes[1].decoded.as_deref(),
+        Some("track_id=2 duration=1000 size=40 flags=0x00000000")
+    );
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let data_start = moof_start + moof_len as u64 + 8;
+    let layout = |i: usize| -> Vec<(u32, u64)> {
+        tracks[i]
+            .samples
+            .iter()
+            .map(|s| (s.size, s.file_offset.unwrap() - data_start))
+            .collect()
+    };
+    assert_eq!(layout(0), [(100, 0), (100, 100)]);
+    assert_eq!(layout(1), [(40, 200), (40, 240), (40, 280)]);
+    assert!(tracks.iter().all(|t| t.warnings.is_empty()));
+}
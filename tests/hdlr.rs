@@ -0,0 +1,90 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use mp4box::track_samples_from_reader;
+use std::io::Cursor;
+
+fn hdlr_with_name(handler: &[u8; 4], name: &[u8]) -> Vec<u8> {
+    let mut p = 0u32.to_be_bytes().to_vec();
+    p.extend_from_slice(handler);
+    p.extend_from_slice(&[0u8; 12]);
+    p.extend_from_slice(name);
+    full_box(b"hdlr", 0, 0, &p)
+}
+
+fn decoded_name(data: Vec<u8>) -> String {
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::HandlerReference(h)) => h.name.clone(),
+        other => panic!("unexpected hdlr data: {:?}", other),
+    }
+}
+
+#[test]
+fn iso_null_terminated_name() {
+    assert_eq!(
+        decoded_name(hdlr_with_name(b"vide", b"VideoHandler\0")),
+        "VideoHandler"
+    );
+}
+
+#[test]
+fn quicktime_pascal_name() {
+    let mut name = vec![16u8];
+    name.extend_from_slice(b"Core Media Video");
+    assert_eq!(
+        decoded_name(hdlr_with_name(b"vide", &name)),
+        "Core Media Video"
+    );
+}
+
+#[test]
+fn sound_handler_name_and_summary() {
+    let data = hdlr_with_name(b"soun", b"SoundHandler\0");
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    assert_eq!(boxes[0].decoded.as_deref(), Some("soun \"SoundHandler\""));
+    let Some(StructuredData::HandlerReference(hdlr)) = &boxes[0].structured_data else {
+        panic!("expected hdlr data");
+    };
+    assert_eq!(hdlr.handler_type, "soun");
+    assert_eq!(hdlr.name, "SoundHandler");
+    assert_eq!(hdlr.component_type, None);
+    assert_eq!(hdlr.component_manufacturer, None);
+}
+
+#[test]
+fn quicktime_component_fields() {
+    let mut p = b"mhlr".to_vec();
+    p.extend_from_slice(b"soun");
+    p.extend_from_slice(b"appl");
+    p.extend_from_slice(&[0u8; 8]);
+    p.push(0); // empty Pascal name
+    let data = full_box(b"hdlr", 0, 0, &p);
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    assert_eq!(boxes[0].decoded.as_deref(), Some("soun"));
+    let Some(StructuredData::HandlerReference(hdlr)) = &boxes[0].structured_data else {
+        panic!("expected hdlr data");
+    };
+    assert_eq!(hdlr.component_type.as_deref(), Some("mhlr"));
+    assert_eq!(hdlr.component_manufacturer.as_deref(), Some("appl"));
+    assert_eq!(hdlr.name, "");
+}
+
+#[test]
+fn track_samples_carry_handler_name() {
+    let moov_for = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(1, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+        stbl.extend_from_slice(&stsz(&[4]));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let tracks = track_samples_from_reader(Cursor::new(movie(moov_for, &[0u8; 4]))).unwrap();
+    assert_eq!(tracks[0].handler_name.as_deref(), Some("Handler"));
+}
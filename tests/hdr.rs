@@ -0,0 +1,87 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::hdr::hdr_summary;
+use std::io::Cursor;
+
+fn dvcc(profile: u8, level: u8, rpu: bool, el: bool, bl: bool) -> Vec<u8> {
+    let bits: u16 = (profile as u16) << 9
+        | (level as u16) << 3
+        | (rpu as u16) << 2
+        | (el as u16) << 1
+        | bl as u16;
+    let mut p = vec![1, 0];
+    p.extend_from_slice(&bits.to_be_bytes());
+    p.push(6 << 4); // bl_signal_compatibility_id = 6
+    p.extend_from_slice(&[0u8; 19]);
+    bx(b"dvcC", &p)
+}
+
+fn dv_trak(track_id: u32, tref: Option<Vec<u8>>, entry: Vec<u8>) -> Vec<u8> {
+    let mut stsd_payload = 1u32.to_be_bytes().to_vec();
+    stsd_payload.extend_from_slice(&entry);
+    let stbl = bx(b"stbl", &full_box(b"stsd", 0, 0, &stsd_payload));
+    let mdia = bx(b"mdia", &bx(b"minf", &stbl));
+
+    let mut p = tkhd(track_id);
+    if let Some(tref) = tref {
+        p.extend_from_slice(&tref);
+    }
+    p.extend_from_slice(&mdia);
+    bx(b"trak", &p)
+}
+
+#[test]
+fn detects_profile7_dual_track_with_vdep() {
+    // Track 1: HEVC base layer. Track 2: Dolby Vision enhancement layer referencing track 1.
+    let bl = dv_trak(1, None, visual_entry(b"hvc1", &[]));
+    let el = dv_trak(
+        2,
+        Some(bx(b"tref", &bx(b"vdep", &1u32.to_be_bytes()))),
+        visual_entry(b"dvhe", &dvcc(7, 6, true, true, false)),
+    );
+
+    let mut moov_payload = bl;
+    moov_payload.extend_from_slice(&el);
+    let data = bx(b"moov", &moov_payload);
+    let size = data.len() as u64;
+
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let summary = hdr_summary(&boxes);
+    assert_eq!(summary.len(), 2);
+
+    let base = summary.iter().find(|s| s.track_id == 1).unwrap();
+    assert!(base.is_dual_layer);
+    assert!(base.dolby_vision.is_none());
+    assert_eq!(base.enhancement_layer_track_id, Some(2));
+
+    let enh = summary.iter().find(|s| s.track_id == 2).unwrap();
+    let dv = enh.dolby_vision.as_ref().unwrap();
+    assert_eq!(dv.profile, 7);
+    assert_eq!(dv.level, 6);
+    assert!(dv.rpu_present && dv.el_present && !dv.bl_present);
+    assert_eq!(dv.bl_signal_compatibility_id, 6);
+    assert!(enh.is_dual_layer);
+    assert_eq!(enh.base_layer_track_id, Some(1));
+}
+
+#[test]
+fn profile8_is_single_layer() {
+    let data = bx(
+        b"moov",
+        &dv_trak(
+            1,
+            None,
+            visual_entry(b"dvh1", &dvcc(8, 4, true, false, true)),
+        ),
+    );
+    let size = data.len() as u64;
+
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let summary = hdr_summary(&boxes);
+    assert_eq!(summary.len(), 1);
+    assert_eq!(summary[0].dolby_vision.as_ref().unwrap().profile, 8);
+    assert!(!summary[0].is_dual_layer);
+    assert_eq!(summary[0].base_layer_track_id, None);
+}
@@ -0,0 +1,271 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::heif::{decode_item, item_data, metadata_items, referenced_items};
+use mp4box::registry::{ImageGridData, IrefData, ItemReference, StructuredData};
+use std::io::Cursor;
+
+const XMP: &str = "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"></x:xmpmeta>";
+
+fn infe(item_id: u16, item_type: &[u8; 4], content_type: Option<&str>) -> Vec<u8> {
+    let mut p = item_id.to_be_bytes().to_vec();
+    p.extend_from_slice(&0u16.to_be_bytes()); // protection index
+    p.extend_from_slice(item_type);
+    p.push(0); // empty item name
+    if let Some(ct) = content_type {
+        p.extend_from_slice(ct.as_bytes());
+        p.push(0);
+    }
+    full_box(b"infe", 2, 0, &p)
+}
+
+/// iloc v1 with 4-byte offsets/lengths, no base offset; one extent per item.
+fn iloc(items: &[(u16, u16, u32, u32)]) -> Vec<u8> {
+    let mut p = vec![0x44, 0x00];
+    p.extend_from_slice(&(items.len() as u16).to_be_bytes());
+    for &(id, method, offset, length) in items {
+        p.extend_from_slice(&id.to_be_bytes());
+        p.extend_from_slice(&method.to_be_bytes());
+        p.extend_from_slice(&0u16.to_be_bytes()); // data reference index
+        p.extend_from_slice(&1u16.to_be_bytes()); // extent count
+        p.extend_from_slice(&offset.to_be_bytes());
+        p.extend_from_slice(&length.to_be_bytes());
+    }
+    full_box(b"iloc", 1, 0, &p)
+}
+
+/// HEIC with an XMP item stored in idat and an Exif item stored in mdat.
+fn heic() -> Vec<u8> {
+    let mut exif = 6u32.to_be_bytes().to_vec();
+    exif.extend_from_slice(b"Exif\0\0MM\0*");
+
+    let mut iinf = 2u16.to_be_bytes().to_vec();
+    iinf.extend_from_slice(&infe(1, b"mime", Some("application/rdf+xml")));
+    iinf.extend_from_slice(&infe(2, b"Exif", None));
+
+    let mut file = ftyp(b"heic", &[b"mif1", b"heic"]);
+    let build_meta = |mdat_payload_offset: u32| {
+        let mut meta = hdlr(b"pict");
+        meta.extend_from_slice(&full_box(b"iinf", 0, 0, &iinf));
+        meta.extend_from_slice(&iloc(&[
+            (1, 1, 0, XMP.len() as u32),
+            (2, 0, mdat_payload_offset, exif.len() as u32),
+        ]));
+        meta.extend_from_slice(&bx(b"idat", XMP.as_bytes()));
+        full_box(b"meta", 0, 0, &meta)
+    };
+    let meta_len = build_meta(0).len() as u32;
+    file.extend_from_slice(&build_meta(file.len() as u32 + meta_len + 8));
+    file.extend_from_slice(&bx(b"mdat", &exif));
+    file
+}
+
+#[test]
+fn mime_box_exposes_content_type() {
+    let mut data = full_box(b"mime", 0, 0, b"application/rdf+xml\0");
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(&mut data), size, true).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::Mime(m)) => assert_eq!(m.content_type, "application/rdf+xml"),
+        other => panic!("unexpected mime data: {:?}", other),
+    }
+}
+
+#[test]
+fn iinf_lists_mime_item_content_type() {
+    let mut data = heic();
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(&mut data), size, true).unwrap();
+    let meta = boxes.iter().find(|b| b.typ == "meta").unwrap();
+    let iinf = meta
+        .children
+        .as_ref()
+        .unwrap()
+        .iter()
+        .find(|b| b.typ == "iinf")
+        .unwrap();
+    match &iinf.structured_data {
+        Some(StructuredData::ItemInfo(d)) => {
+            assert_eq!(d.entry_count, 2);
+            assert_eq!(d.entries[0].item_type.as_deref(), Some("mime"));
+            assert_eq!(
+                d.entries[0].content_type.as_deref(),
+                Some("application/rdf+xml")
+            );
+            assert_eq!(d.entries[1].item_type.as_deref(), Some("Exif"));
+        }
+        other => panic!("unexpected iinf data: {:?}", other),
+    }
+}
+
+#[test]
+fn metadata_items_read_xmp_from_idat_and_exif_from_mdat() {
+    let data = heic();
+    let size = data.len() as u64;
+    let mut cur = Cursor::new(data);
+    let boxes = get_boxes(&mut cur, size, true).unwrap();
+
+    assert_eq!(item_data(&mut cur, &boxes, 1).unwrap(), XMP.as_bytes());
+
+    let items = metadata_items(&mut cur, &boxes).unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].item_type, "mime");
+    assert_eq!(
+        items[0].content_type.as_deref(),
+        Some("application/rdf+xml")
+    );
+    assert_eq!(items[0].text.as_deref(), Some(XMP));
+    assert_eq!(items[1].item_type, "Exif");
+    assert_eq!(items[1].data, b"MM\0*");
+    assert!(items[1].text.is_none());
+}
+
+#[test]
+fn unknown_item_is_an_error() {
+    let data = heic();
+    let size = data.len() as u64;
+    let mut cur = Cursor::new(data);
+    let boxes = get_boxes(&mut cur, size, true).unwrap();
+    assert!(item_data(&mut cur, &boxes, 9).is_err());
+}
+
+#[test]
+fn extent_offset_past_64_bits_is_an_error() {
+    // iloc v0 with 4-byte offsets/lengths and an 8-byte base offset near u64::MAX.
+    let mut p = vec![0x44, 0x80];
+    p.extend_from_slice(&1u16.to_be_bytes()); // item count
+    p.extend_from_slice(&1u16.to_be_bytes()); // item ID
+    p.extend_from_slice(&0u16.to_be_bytes()); // data reference index
+    p.extend_from_slice(&0xFFFF_FFFF_FFFF_FFF0u64.to_be_bytes());
+    p.extend_from_slice(&1u16.to_be_bytes()); // extent count
+    p.extend_from_slice(&0x20u32.to_be_bytes());
+    p.extend_from_slice(&4u32.to_be_bytes());
+    let mut meta = hdlr(b"pict");
+    meta.extend_from_slice(&full_box(b"iloc", 0, 0, &p));
+    let mut data = ftyp(b"heic", &[b"mif1", b"heic"]);
+    data.extend_from_slice(&full_box(b"meta", 0, 0, &meta));
+
+    let size = data.len() as u64;
+    let mut cur = Cursor::new(data);
+    let boxes = get_boxes(&mut cur, size, true).unwrap();
+    let err = item_data(&mut cur, &boxes, 1).unwrap_err();
+    assert!(err.to_string().contains("runs past the end"), "{err}");
+}
+
+fn iref(version: u8, refs: &[(&[u8; 4], u32, &[u32])]) -> Vec<u8> {
+    let id = |p: &mut Vec<u8>, v: u32| {
+        if version == 0 {
+            p.extend_from_slice(&(v as u16).to_be_bytes());
+        } else {
+            p.extend_from_slice(&v.to_be_bytes());
+        }
+    };
+    let mut p = Vec::new();
+    for (typ, from, to) in refs {
+        let mut r = Vec::new();
+        id(&mut r, *from);
+        r.extend_from_slice(&(to.len() as u16).to_be_bytes());
+        for t in *to {
+            id(&mut r, *t);
+        }
+        p.extend_from_slice(&bx(typ, &r));
+    }
+    full_box(b"iref", version, 0, &p)
+}
+
+fn decode_iref(data: Vec<u8>) -> IrefData {
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::ItemReference(d)) => d.clone(),
+        other => panic!("unexpected iref data: {:?}", other),
+    }
+}
+
+#[test]
+fn iref_grid_derived_from_four_tiles() {
+    let iref = decode_iref(iref(
+        0,
+        &[(b"dimg", 10, &[1, 2, 3, 4]), (b"thmb", 5, &[10])],
+    ));
+    assert_eq!(iref.version, 0);
+    assert_eq!(
+        iref.references,
+        vec![
+            ItemReference {
+                reference_type: "dimg".into(),
+                from_item_id: 10,
+                to_item_ids: vec![1, 2, 3, 4],
+            },
+            ItemReference {
+                reference_type: "thmb".into(),
+                from_item_id: 5,
+                to_item_ids: vec![10],
+            },
+        ]
+    );
+}
+
+#[test]
+fn iref_v1_uses_32_bit_ids() {
+    let iref = decode_iref(iref(1, &[(b"auxl", 70_000, &[70_001])]));
+    assert_eq!(iref.references[0].reference_type, "auxl");
+    assert_eq!(iref.references[0].from_item_id, 70_000);
+    assert_eq!(iref.references[0].to_item_ids, vec![70_001]);
+}
+
+/// A 2x2 grid (item 10) of four 512x512 tiles cropped to 1000x800, with the
+/// grid payload in idat.
+fn tiled_heic() -> Vec<u8> {
+    let grid = [0u8, 0, 1, 1, 0x03, 0xE8, 0x03, 0x20];
+
+    let mut iinf = 5u16.to_be_bytes().to_vec();
+    for tile in 1..=4 {
+        iinf.extend_from_slice(&infe(tile, b"hvc1", None));
+    }
+    iinf.extend_from_slice(&infe(10, b"grid", None));
+
+    let mut meta = hdlr(b"pict");
+    meta.extend_from_slice(&full_box(b"iinf", 0, 0, &iinf));
+    meta.extend_from_slice(&iref(0, &[(b"dimg", 10, &[1, 2, 3, 4])]));
+    meta.extend_from_slice(&iloc(&[(10, 1, 0, grid.len() as u32)]));
+    meta.extend_from_slice(&bx(b"idat", &grid));
+
+    let mut file = ftyp(b"heic", &[b"mif1", b"heic"]);
+    file.extend_from_slice(&full_box(b"meta", 0, 0, &meta));
+    file
+}
+
+#[test]
+fn grid_item_decodes_layout_and_tiles() {
+    let data = tiled_heic();
+    let size = data.len() as u64;
+    let mut cur = Cursor::new(data);
+    let boxes = get_boxes(&mut cur, size, true).unwrap();
+
+    let Some(StructuredData::ImageGrid(grid)) = decode_item(&mut cur, &boxes, 10).unwrap() else {
+        panic!("grid not decoded");
+    };
+    assert_eq!(
+        grid,
+        ImageGridData {
+            version: 0,
+            flags: 0,
+            rows: 2,
+            columns: 2,
+            output_width: 1000,
+            output_height: 800,
+        }
+    );
+    assert_eq!(referenced_items(&boxes, 10, "dimg"), vec![1, 2, 3, 4]);
+    assert!(referenced_items(&boxes, 10, "thmb").is_empty());
+}
+
+#[test]
+fn grid_with_32_bit_output_size() {
+    let grid = ImageGridData::parse(&[0, 1, 3, 7, 0, 1, 0, 0, 0, 0, 0x80, 0]).unwrap();
+    assert_eq!((grid.rows, grid.columns), (4, 8));
+    assert_eq!((grid.output_width, grid.output_height), (65536, 32768));
+    assert!(ImageGridData::parse(&[0, 1, 3, 7, 0, 1]).is_err());
+}
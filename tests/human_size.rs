@@ -0,0 +1,13 @@
+use mp4box::util::human_size;
+
+#[test]
+fn human_size_boundaries() {
+    assert_eq!(human_size(0), "0 B");
+    assert_eq!(human_size(1023), "1023 B");
+    assert_eq!(human_size(1024), "1.0 KiB");
+    assert_eq!(human_size(1536), "1.5 KiB");
+    assert_eq!(human_size(1048575), "1.0 MiB");
+    assert_eq!(human_size(1048576), "1.0 MiB");
+    assert_eq!(human_size(1073741823), "1.0 GiB");
+    assert_eq!(human_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GiB");
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{HevcConfig, NalUnitRange, StructuredData};
+use std::io::Cursor;
+
+/// hvcC for Main10@L5.1, Main tier, 4:2:0 10-bit, with the given NAL arrays.
+fn hvcc_record(arrays: &[(u8, &[&[u8]])]) -> Vec<u8> {
+    let mut p = vec![
+        0x01, // configurationVersion
+        0x02, // profile_space 0, tier 0, profile_idc 2
+        0x20,
+        0x00,
+        0x00,
+        0x00, // profile compatibility: Main10
+        0x90,
+        0x00,
+        0x00,
+        0x00,
+        0x00,
+        0x00, // constraint flags
+        153,  // level_idc (5.1)
+        0xF0,
+        0x00, // min_spatial_segmentation_idc
+        0xFC, // parallelismType
+        0xFD, // chromaFormat 1
+        0xFA, // bitDepthLumaMinus8 2
+        0xFA, // bitDepthChromaMinus8 2
+        0x00,
+        0x00, // avgFrameRate
+        0x0F, // numTemporalLayers 1, temporalIdNested 1, lengthSizeMinusOne 3
+        arrays.len() as u8,
+    ];
+    for (nal_type, units) in arrays {
+        p.push(0x80 | nal_type);
+        p.extend_from_slice(&(units.len() as u16).to_be_bytes());
+        for unit in *units {
+            p.extend_from_slice(&(unit.len() as u16).to_be_bytes());
+            p.extend_from_slice(unit);
+        }
+    }
+    p
+}
+
+fn parameter_sets() -> Vec<u8> {
+    hvcc_record(&[
+        (32, &[&[0x40, 0x01, 0x0C]]),
+        (33, &[&[0x42, 0x01, 0x01, 0x02]]),
+        (34, &[&[0x44, 0x01]]),
+    ])
+}
+
+#[test]
+fn main10_hvcc() {
+    let hevc = HevcConfig::parse(&parameter_sets()).unwrap();
+
+    assert_eq!(hevc.general_profile_space, 0);
+    assert!(!hevc.general_tier_flag);
+    assert_eq!(hevc.general_profile_idc, 2);
+    assert_eq!(hevc.general_profile_compatibility_flags, 0x2000_0000);
+    assert_eq!(hevc.general_constraint_indicator_flags, 0x9000_0000_0000);
+    assert_eq!(hevc.general_level_idc, 153);
+    assert_eq!(hevc.chroma_format, 1);
+    assert_eq!(hevc.bit_depth_luma_minus8, 2);
+    assert_eq!(hevc.bit_depth_chroma_minus8, 2);
+    assert_eq!(hevc.length_size_minus_one, 3);
+    assert!(hevc.temporal_id_nested);
+    assert!(!hevc.truncated);
+
+    let types: Vec<u8> = hevc.arrays.iter().map(|a| a.nal_unit_type).collect();
+    assert_eq!(types, vec![32, 33, 34]);
+    assert!(hevc.arrays.iter().all(|a| a.array_completeness));
+    assert_eq!(
+        hevc.arrays[0].nal_units,
+        vec![NalUnitRange {
+            offset: 28,
+            size: 3
+        }]
+    );
+    assert_eq!(
+        hevc.arrays[1].nal_units,
+        vec![NalUnitRange {
+            offset: 36,
+            size: 4
+        }]
+    );
+    assert_eq!(hevc.profile_level_tier(), "Main10@L5.1@Main");
+}
+
+#[test]
+fn hvcc_without_arrays() {
+    let hevc = HevcConfig::parse(&hvcc_record(&[])).unwrap();
+    assert!(hevc.arrays.is_empty());
+    assert!(!hevc.truncated);
+}
+
+#[test]
+fn hvcc_truncated_mid_array_keeps_what_was_read() {
+    let mut record = parameter_sets();
+    record.truncate(record.len() - 10); // cut into the SPS payload
+    let hevc = HevcConfig::parse(&record).unwrap();
+
+    assert!(hevc.truncated);
+    assert_eq!(hevc.arrays.len(), 2);
+    assert_eq!(hevc.arrays[0].nal_units.len(), 1);
+    assert_eq!(hevc.arrays[1].count, 1);
+    assert!(hevc.arrays[1].nal_units.is_empty());
+
+    assert!(HevcConfig::parse(&record[..20]).is_err());
+}
+
+#[test]
+fn hvcc_box_summary_and_sample_entry() {
+    let hvcc = bx(b"hvcC", &parameter_sets());
+    let size = hvcc.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(hvcc.clone()), size, true).unwrap();
+    assert_eq!(boxes[0].decoded.as_deref(), Some("Main10@L5.1@Main"));
+
+    let stsd = stsd(&[visual_entry(b"hvc1", &hvcc)]);
+    let size = stsd.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(stsd), size, true).unwrap();
+    let Some(StructuredData::SampleDescription(stsd)) = &boxes[0].structured_data else {
+        panic!("stsd not decoded");
+    };
+    let hevc = stsd.entries[0].hevc_config.as_ref().unwrap();
+    assert_eq!(hevc.arrays.len(), 3);
+    assert_eq!(stsd.entries[0].nal_length_size, Some(4));
+}
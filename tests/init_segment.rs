@@ -0,0 +1,75 @@
+mod common;
+
+use common::*;
+use mp4box::registry::StructuredData;
+use mp4box::{extract_init_segment, find_path, get_boxes};
+use std::io::Cursor;
+
+fn fragmented_moov() -> Vec<u8> {
+    let mut moov = trak(1, b"vide", 12800, &stsd(&[visual_entry(b"avc1", &[])]));
+    moov.extend_from_slice(&bx(b"mvex", &full_box(b"trex", 0, 0, &[0; 20])));
+    bx(b"moov", &moov)
+}
+
+fn assert_init_segment(init: &[u8], moov: &[u8]) {
+    let size = init.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(init.to_vec()), size, true).unwrap();
+    let types: Vec<&str> = boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(types, ["ftyp", "moov"]);
+    assert!(init.ends_with(moov));
+
+    let stsd = find_path(&boxes, &["moov", "trak", "mdia", "minf", "stbl", "stsd"]);
+    let Some(StructuredData::SampleDescription(stsd)) = &stsd[0].structured_data else {
+        panic!("expected stsd data");
+    };
+    assert_eq!(stsd.entries[0].codec, "avc1");
+}
+
+#[test]
+fn splits_init_segment_from_combined_file() {
+    let dir = temp_dir("init-segment");
+    let head = ftyp(b"iso6", &[b"iso6", b"cmfc"]);
+    let moov = fragmented_moov();
+    let mut media = bx(b"moof", &bx(b"mfhd", &[0; 8]));
+    media.extend_from_slice(&bx(b"mdat", &[0; 32]));
+
+    let mut file = head.clone();
+    file.extend_from_slice(&moov);
+    file.extend_from_slice(&media);
+    let path = dir.join("combined.mp4");
+    std::fs::write(&path, &file).unwrap();
+    let init = extract_init_segment(&path).unwrap();
+    assert_eq!(init.len(), head.len() + moov.len());
+    assert_init_segment(&init, &moov);
+
+    // moov after the media data is moved up next to ftyp
+    let mut file = head.clone();
+    file.extend_from_slice(&bx(b"free", &[0; 4]));
+    file.extend_from_slice(&media);
+    file.extend_from_slice(&moov);
+    let path = dir.join("moov-last.mp4");
+    std::fs::write(&path, &file).unwrap();
+    assert_init_segment(&extract_init_segment(&path).unwrap(), &moov);
+
+    let path = dir.join("no-moov.mp4");
+    std::fs::write(&path, &head).unwrap();
+    let err = extract_init_segment(&path).unwrap_err();
+    assert!(err.to_string().contains("no moov"), "{}", err);
+}
+
+#[test]
+fn huge_box_before_moov_means_no_moov() {
+    // A free box with a 64-bit size of u64::MAX swallows everything after it.
+    let mut file = ftyp(b"iso6", &[b"iso6"]);
+    file.extend_from_slice(&1u32.to_be_bytes());
+    file.extend_from_slice(b"free");
+    file.extend_from_slice(&u64::MAX.to_be_bytes());
+    file.extend_from_slice(&fragmented_moov());
+
+    let dir = temp_dir("init-segment-huge-box");
+    let path = dir.join("huge.mp4");
+    std::fs::write(&path, file).unwrap();
+    let err = extract_init_segment(&path).unwrap_err();
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(err.to_string().contains("no moov"), "{}", err);
+}
@@ -0,0 +1,79 @@
+mod common;
+use common::*;
+
+use mp4box::{NodeKind, expand_container, parse_children, parse_top_level_only};
+use std::fs::File;
+use std::io::Cursor;
+
+fn sample_file() -> Vec<u8> {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(2, 512)]));
+    let mut file = ftyp(b"isom", &[b"isom"]);
+    file.extend_from_slice(&bx(b"moov", &trak(1, b"vide", 12800, &stbl)));
+    file.extend_from_slice(&bx(b"mdat", &[0u8; 64]));
+    file
+}
+
+#[test]
+fn top_level_only_leaves_containers_unexpanded() {
+    let path = temp_dir("lazy").join("top.mp4");
+    std::fs::write(&path, sample_file()).unwrap();
+
+    let top = parse_top_level_only(&path).unwrap();
+    let types: Vec<String> = top.iter().map(|b| b.hdr.typ.to_string()).collect();
+    assert_eq!(types, ["ftyp", "moov", "mdat"]);
+    assert!(matches!(top[1].kind, NodeKind::Unexpanded));
+
+    let mut f = File::open(&path).unwrap();
+    let file_len = f.metadata().unwrap().len();
+    let moov = expand_container(&mut f, &top[1], file_len).unwrap();
+    assert_eq!(moov.len(), 1);
+    assert_eq!(moov[0].hdr.typ.to_string(), "trak");
+    assert!(matches!(moov[0].kind, NodeKind::Unexpanded));
+
+    let moov_end = top[1].hdr.start + top[1].hdr.size;
+    let trak = expand_container(&mut f, &moov[0], moov_end).unwrap();
+    let types: Vec<String> = trak.iter().map(|b| b.hdr.typ.to_string()).collect();
+    assert_eq!(types, ["tkhd", "mdia"]);
+
+    // Leaves have nothing to expand.
+    assert!(
+        expand_container(&mut f, &top[2], file_len)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn nested_zero_size_container_stops_at_its_parent() {
+    // moov/udta with size 0, followed by a top-level mdat.
+    let mut udta = bx(b"udta", &bx(b"free", &[0u8; 4]));
+    udta[..4].copy_from_slice(&0u32.to_be_bytes());
+    let mut file = bx(b"moov", &udta);
+    let moov_end = file.len() as u64;
+    file.extend_from_slice(&bx(b"mdat", &[0u8; 8]));
+    let path = temp_dir("lazy").join("zero.mp4");
+    std::fs::write(&path, &file).unwrap();
+
+    let top = parse_top_level_only(&path).unwrap();
+    let mut f = File::open(&path).unwrap();
+    let moov = expand_container(&mut f, &top[0], file.len() as u64).unwrap();
+    let udta = expand_container(&mut f, &moov[0], moov_end).unwrap();
+    let types: Vec<String> = udta.iter().map(|b| b.hdr.typ.to_string()).collect();
+    assert_eq!(types, ["free"]);
+}
+
+#[test]
+fn lazy_offsets_match_eager_parse() {
+    let data = sample_file();
+    let len = data.len() as u64;
+    let path = temp_dir("lazy").join("eager.mp4");
+    std::fs::write(&path, &data).unwrap();
+
+    let eager = parse_children(&mut Cursor::new(data), len).unwrap();
+    let lazy = parse_top_level_only(&path).unwrap();
+    let moov = expand_container(&mut File::open(&path).unwrap(), &lazy[1], len).unwrap();
+
+    assert_eq!(eager[1].children()[0].hdr.start, moov[0].hdr.start);
+    assert_eq!(eager[1].children()[0].hdr.size, moov[0].hdr.size);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::*;
+use mp4box::Matrix;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use std::io::Cursor;
+
+fn matrix_bytes(values: [i32; 9]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+#[test]
+fn identity_matrix() {
+    let m = Matrix::from_bytes(&matrix_bytes(Matrix::IDENTITY.values)).unwrap();
+    assert!(m.is_identity());
+    assert_eq!((m.a(), m.b(), m.u()), (1.0, 0.0, 0.0));
+    assert_eq!((m.c(), m.d(), m.v()), (0.0, 1.0, 0.0));
+    assert_eq!((m.x(), m.y(), m.w()), (0.0, 0.0, 1.0));
+    assert_eq!(m.rotation(), 0.0);
+}
+
+#[test]
+fn tkhd_with_90_degree_rotation() {
+    // a=0 b=1 c=-1 d=0, translated by the (portrait) width
+    let rotated = [
+        0,
+        0x0001_0000,
+        0,
+        -0x0001_0000,
+        0,
+        0,
+        1080 << 16,
+        0,
+        0x4000_0000,
+    ];
+
+    let mut p = Vec::new();
+    p.extend_from_slice(&[0u8; 20]); // times, track_id, reserved, duration
+    p.extend_from_slice(&[0u8; 16]); // reserved, layer, alternate_group, volume
+    p.extend_from_slice(&matrix_bytes(rotated));
+    p.extend_from_slice(&(1920u32 << 16).to_be_bytes());
+    p.extend_from_slice(&(1080u32 << 16).to_be_bytes());
+    let data = full_box(b"tkhd", 0, 3, &p);
+
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let Some(StructuredData::TrackHeader(tkhd)) = &boxes[0].structured_data else {
+        panic!("tkhd not decoded");
+    };
+
+    assert!(!tkhd.matrix.is_identity());
+    assert_eq!(tkhd.matrix.rotation(), 90.0);
+    assert_eq!(tkhd.matrix.x(), 1080.0);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use std::io::Cursor;
+
+fn decode(data: Vec<u8>) -> mp4box::Box {
+    let size = data.len() as u64;
+    get_boxes(&mut Cursor::new(data), size, true)
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn vmhd_graphics_mode_and_opcolor() {
+    let mut p = 0x40u16.to_be_bytes().to_vec();
+    for c in [0x8000u16, 0x4000, 0xFFFF] {
+        p.extend_from_slice(&c.to_be_bytes());
+    }
+    let vmhd = decode(full_box(b"vmhd", 0, 1, &p));
+
+    let Some(StructuredData::VideoMediaHeader(data)) = &vmhd.structured_data else {
+        panic!("unexpected vmhd data: {:?}", vmhd.structured_data);
+    };
+    assert_eq!(data.flags, 1);
+    assert_eq!(data.graphics_mode, 0x40);
+    assert_eq!(data.opcolor, [0x8000, 0x4000, 0xFFFF]);
+    assert_eq!(
+        vmhd.decoded.as_deref(),
+        Some("graphicsmode=64 opcolor=(32768, 16384, 65535)")
+    );
+}
+
+#[test]
+fn smhd_balance_and_nmhd() {
+    let mut p = (-128i16).to_be_bytes().to_vec();
+    p.extend_from_slice(&[0, 0]);
+    let smhd = decode(full_box(b"smhd", 0, 0, &p));
+    let Some(StructuredData::SoundMediaHeader(data)) = &smhd.structured_data else {
+        panic!("unexpected smhd data: {:?}", smhd.structured_data);
+    };
+    assert_eq!(data.balance, -0.5);
+    assert_eq!(smhd.decoded.as_deref(), Some("balance=-0.5"));
+
+    let nmhd = decode(full_box(b"nmhd", 0, 0, &[]));
+    assert!(matches!(
+        nmhd.structured_data,
+        Some(StructuredData::NullMediaHeader(_))
+    ));
+}
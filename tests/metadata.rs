@@ -0,0 +1,269 @@
+mod common;
+
+use common::*;
+use mp4box::registry::{MetaKind, StructuredData};
+use mp4box::{
+    BoxRef, ParseOptions, analyze_reader, analyze_reader_opts, expand_container, get_boxes,
+    parse_children, parse_top_level_only, visit_boxes,
+};
+use std::io::Cursor;
+use std::ops::ControlFlow;
+
+fn keys(names: &[&str]) -> Vec<u8> {
+    let mut p = (names.len() as u32).to_be_bytes().to_vec();
+    for name in names {
+        p.extend_from_slice(&((name.len() + 8) as u32).to_be_bytes());
+        p.extend_from_slice(b"mdta");
+        p.extend_from_slice(name.as_bytes());
+    }
+    full_box(b"keys", 0, 0, &p)
+}
+
+fn text_item(typ: [u8; 4], text: &str) -> Vec<u8> {
+    let mut data = 1u32.to_be_bytes().to_vec(); // UTF-8
+    data.extend_from_slice(&0u32.to_be_bytes()); // locale
+    data.extend_from_slice(text.as_bytes());
+    bx(&typ, &bx(b"data", &data))
+}
+
+fn ilst_items(meta: &mp4box::Box) -> Vec<(String, String)> {
+    let ilst = meta
+        .children
+        .as_ref()
+        .unwrap()
+        .iter()
+        .find(|c| c.typ == "ilst")
+        .expect("ilst");
+    match &ilst.structured_data {
+        Some(StructuredData::Metadata(d)) => d
+            .items
+            .iter()
+            .map(|i| (i.key.clone(), i.value.clone()))
+            .collect(),
+        other => panic!("unexpected ilst data: {:?}", other),
+    }
+}
+
+#[test]
+fn keyed_items_resolve_and_out_of_range_index_is_reported() {
+    let mut ilst = text_item(1u32.to_be_bytes(), "My Title");
+    ilst.extend_from_slice(&text_item(5u32.to_be_bytes(), "Orphan"));
+
+    let mut meta = hdlr(b"mdta");
+    meta.extend_from_slice(&keys(&[
+        "com.apple.quicktime.title",
+        "com.apple.quicktime.artist",
+    ]));
+    meta.extend_from_slice(&bx(b"ilst", &ilst));
+    let data = full_box(b"meta", 0, 0, &meta);
+
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+    let items = ilst_items(&boxes[0]);
+    assert_eq!(
+        items,
+        vec![
+            (
+                "com.apple.quicktime.title".to_string(),
+                "My Title".to_string()
+            ),
+            ("unknown key #5".to_string(), "Orphan".to_string()),
+        ]
+    );
+
+    // The text is rebuilt from the resolved data the same way as for any box.
+    let ilst = boxes[0].children.as_ref().unwrap().last().unwrap();
+    let data = ilst.structured_data.as_ref().unwrap();
+    let expected = data
+        .summary()
+        .unwrap_or_else(|| format!("structured: {:?}", data));
+    assert_eq!(ilst.decoded.as_deref(), Some(expected.as_str()));
+}
+
+fn meta_kind(meta: &mp4box::Box) -> MetaKind {
+    match &meta.structured_data {
+        Some(StructuredData::Meta(m)) => m.kind,
+        other => panic!("unexpected meta data: {:?}", other),
+    }
+}
+
+#[test]
+fn udta_meta_is_itunes() {
+    let mut meta = hdlr(b"mdir");
+    meta.extend_from_slice(&bx(b"ilst", &text_item(*b"\xA9nam", "Song")));
+    let udta = bx(b"udta", &full_box(b"meta", 0, 0, &meta));
+    let data = bx(b"moov", &udta);
+
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+    let udta = &boxes[0].children.as_ref().unwrap()[0];
+    let meta = &udta.children.as_ref().unwrap()[0];
+    assert_eq!(meta_kind(meta), MetaKind::ITunes);
+    assert_eq!(
+        ilst_items(meta),
+        vec![("\u{a9}nam".to_string(), "Song".to_string())]
+    );
+}
+
+#[test]
+fn file_level_meta_is_heif() {
+    let mut meta = hdlr(b"pict");
+    meta.extend_from_slice(&full_box(b"pitm", 0, 0, &1u16.to_be_bytes()));
+    let mut data = ftyp(b"heic", &[b"mif1", b"heic"]);
+    data.extend_from_slice(&full_box(b"meta", 0, 0, &meta));
+
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+    let meta = &boxes[1];
+    assert_eq!(meta_kind(meta), MetaKind::Heif);
+    let kids: Vec<&str> = meta
+        .children
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|c| c.typ.as_str())
+        .collect();
+    assert_eq!(kids, vec!["hdlr", "pitm"]);
+}
+
+fn typed_item(typ: [u8; 4], data_type: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = data_type.to_be_bytes().to_vec();
+    data.extend_from_slice(&0u32.to_be_bytes()); // locale
+    data.extend_from_slice(payload);
+    bx(&typ, &bx(b"data", &data))
+}
+
+#[test]
+fn itunes_title_artist_and_cover() {
+    let mut ilst = text_item(*b"\xA9nam", "Title Track");
+    ilst.extend_from_slice(&text_item(*b"\xA9ART", "Some Artist"));
+    ilst.extend_from_slice(&typed_item(*b"covr", 13, &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0]));
+    ilst.extend_from_slice(&typed_item(*b"tmpo", 21, &(-120i16).to_be_bytes()));
+    let mut meta = hdlr(b"mdir");
+    meta.extend_from_slice(&bx(b"ilst", &ilst));
+
+    let expected = vec![
+        ("\u{a9}nam".to_string(), "Title Track".to_string()),
+        ("\u{a9}ART".to_string(), "Some Artist".to_string()),
+        ("covr".to_string(), "JPEG image, 6 bytes".to_string()),
+        ("tmpo".to_string(), "-120".to_string()),
+    ];
+
+    // ISO meta is a FullBox; QuickTime writes it without version/flags.
+    for meta in [full_box(b"meta", 0, 0, &meta), bx(b"meta", &meta)] {
+        let data = bx(b"moov", &bx(b"udta", &meta));
+        let size = data.len() as u64;
+        let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+        let udta = &boxes[0].children.as_ref().unwrap()[0];
+        let meta = &udta.children.as_ref().unwrap()[0];
+        assert_eq!(meta_kind(meta), MetaKind::ITunes);
+        assert_eq!(ilst_items(meta), expected);
+    }
+}
+
+#[test]
+fn quicktime_meta_children_are_not_shifted() {
+    // First child is not always hdlr; any child header that fits counts.
+    let mut children = bx(b"free", &[0u8; 4]);
+    children.extend_from_slice(&hdlr(b"mdta"));
+    children.extend_from_slice(&keys(&["com.apple.quicktime.title"]));
+    let data = bx(b"moov", &bx(b"meta", &children));
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+    let meta = &boxes[0].children.as_ref().unwrap()[0];
+    let types: Vec<&str> = meta
+        .children
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|b| b.typ.as_str())
+        .collect();
+    assert_eq!(types, ["free", "hdlr", "keys"]);
+    assert_eq!(meta_kind(meta), MetaKind::QuickTimeKeys);
+}
+
+#[test]
+fn quicktime_meta_is_read_the_same_by_every_parser() {
+    let mut children = hdlr(b"mdta");
+    children.extend_from_slice(&keys(&["com.apple.quicktime.title"]));
+    let data = bx(b"moov", &bx(b"meta", &children));
+    let size = data.len() as u64;
+    let expected = ["moov", "meta", "hdlr", "keys"];
+
+    fn types(boxes: &[BoxRef], out: &mut Vec<String>) {
+        for b in boxes {
+            out.push(b.hdr.typ.to_string());
+            types(b.children(), out);
+        }
+    }
+    fn json_types(boxes: &[mp4box::Box], out: &mut Vec<String>) {
+        for b in boxes {
+            out.push(b.typ.clone());
+            json_types(b.children.as_deref().unwrap_or_default(), out);
+        }
+    }
+
+    let mut seen = Vec::new();
+    types(
+        &parse_children(&mut Cursor::new(&data), size).unwrap(),
+        &mut seen,
+    );
+    assert_eq!(seen, expected, "parse_children");
+
+    let mut warnings = Vec::new();
+    let boxes = mp4box::parser::parse_children_opts(
+        &mut Cursor::new(&data),
+        size,
+        0,
+        &ParseOptions::default(),
+        &mut warnings,
+    )
+    .unwrap();
+    let mut seen = Vec::new();
+    types(&boxes, &mut seen);
+    assert_eq!(seen, expected, "parse_children_opts");
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    let mut seen = Vec::new();
+    visit_boxes(&mut Cursor::new(&data), size, |h, _, _| {
+        seen.push(h.typ.to_string());
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(seen, expected, "visit_boxes");
+
+    let path = temp_dir("qt-meta").join("qt-meta.mov");
+    std::fs::write(&path, &data).unwrap();
+    let top = parse_top_level_only(&path).unwrap();
+    let mut f = std::fs::File::open(&path).unwrap();
+    let moov = expand_container(&mut f, &top[0], size).unwrap();
+    let meta_end = moov[0].hdr.start + moov[0].hdr.size;
+    let meta = expand_container(&mut f, &moov[0], meta_end).unwrap();
+    let mut seen = vec![top[0].hdr.typ.to_string(), moov[0].hdr.typ.to_string()];
+    types(&meta, &mut seen);
+    assert_eq!(seen, expected, "expand_container");
+
+    let mut seen = Vec::new();
+    json_types(
+        &get_boxes(&mut Cursor::new(&data), size, true).unwrap(),
+        &mut seen,
+    );
+    assert_eq!(seen, expected, "get_boxes");
+
+    let analysis = analyze_reader(&mut Cursor::new(&data), size, true).unwrap();
+    let mut seen = Vec::new();
+    json_types(&analysis.boxes, &mut seen);
+    assert_eq!(seen, expected, "analyze_reader");
+    assert!(analysis.warnings.is_empty(), "{:?}", analysis.warnings);
+
+    let analysis =
+        analyze_reader_opts(&mut Cursor::new(&data), size, &ParseOptions::default()).unwrap();
+    let mut seen = Vec::new();
+    json_types(&analysis.boxes, &mut seen);
+    assert_eq!(seen, expected, "analyze_reader_opts");
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::*;
+use mp4box::has_complete_moov;
+
+fn recording(moov_size_delta: u32, with_moov: bool) -> Vec<u8> {
+    let mut file = ftyp(b"isom", &[b"isom"]);
+    file.extend_from_slice(&bx(b"mdat", &[0u8; 64]));
+    if with_moov {
+        let mut moov = bx(b"moov", &trak(1, b"vide", 600, &[]));
+        let size = moov.len() as u32 + moov_size_delta;
+        moov[..4].copy_from_slice(&size.to_be_bytes());
+        file.extend_from_slice(&moov);
+    }
+    file
+}
+
+#[test]
+fn finalized_recording_has_complete_moov() {
+    let dir = temp_dir("moov-complete");
+    let path = dir.join("done.mp4");
+    std::fs::write(&path, recording(0, true)).unwrap();
+
+    let complete = has_complete_moov(&path);
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(complete);
+}
+
+#[test]
+fn moov_larger_than_file_is_incomplete() {
+    let dir = temp_dir("moov-truncated");
+    let path = dir.join("growing.mp4");
+    std::fs::write(&path, recording(100, true)).unwrap();
+    let missing = dir.join("no-moov.mp4");
+    std::fs::write(&missing, recording(0, false)).unwrap();
+
+    let (truncated, absent) = (has_complete_moov(&path), has_complete_moov(&missing));
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(!truncated);
+    assert!(!absent);
+}
+
+#[test]
+fn huge_box_before_moov_is_incomplete() {
+    // A free box with a 64-bit size of u64::MAX swallows everything after it.
+    let mut file = ftyp(b"isom", &[b"isom"]);
+    file.extend_from_slice(&1u32.to_be_bytes());
+    file.extend_from_slice(b"free");
+    file.extend_from_slice(&u64::MAX.to_be_bytes());
+    file.extend_from_slice(&bx(b"moov", &[]));
+
+    let dir = temp_dir("moov-huge-box");
+    let path = dir.join("huge.mp4");
+    std::fs::write(&path, file).unwrap();
+
+    let complete = has_complete_moov(&path);
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(!complete);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{MvhdData, StructuredData};
+use std::io::Cursor;
+
+fn mvhd(version: u8, timescale: u32, duration: u64, next_track_id: u32) -> Vec<u8> {
+    let mut p = Vec::new();
+    if version == 1 {
+        p.extend_from_slice(&1u64.to_be_bytes()); // creation_time
+        p.extend_from_slice(&2u64.to_be_bytes()); // modification_time
+        p.extend_from_slice(&timescale.to_be_bytes());
+        p.extend_from_slice(&duration.to_be_bytes());
+    } else {
+        p.extend_from_slice(&1u32.to_be_bytes());
+        p.extend_from_slice(&2u32.to_be_bytes());
+        p.extend_from_slice(&timescale.to_be_bytes());
+        p.extend_from_slice(&(duration as u32).to_be_bytes());
+    }
+    p.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // rate 1.0
+    p.extend_from_slice(&0x0100u16.to_be_bytes()); // volume 1.0
+    p.extend_from_slice(&[0u8; 10]); // reserved
+    for v in [0x0001_0000u32, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000] {
+        p.extend_from_slice(&v.to_be_bytes());
+    }
+    p.extend_from_slice(&[0u8; 24]); // pre_defined
+    p.extend_from_slice(&next_track_id.to_be_bytes());
+    full_box(b"mvhd", version, 0, &p)
+}
+
+fn decode_mvhd(data: Vec<u8>) -> (MvhdData, Option<String>) {
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::MovieHeader(mvhd)) => (mvhd.clone(), boxes[0].decoded.clone()),
+        other => panic!("mvhd not decoded: {other:?}"),
+    }
+}
+
+#[test]
+fn mvhd_v0_fields() {
+    let (mvhd, decoded) = decode_mvhd(mvhd(0, 600, 9000, 3));
+    assert_eq!(mvhd.version, 0);
+    assert_eq!(mvhd.creation_time, 1);
+    assert_eq!(mvhd.modification_time, 2);
+    assert_eq!(mvhd.timescale, 600);
+    assert_eq!(mvhd.duration, 9000);
+    assert_eq!(mvhd.duration_seconds, 15.0);
+    assert_eq!(mvhd.rate, 1.0);
+    assert_eq!(mvhd.volume, 1.0);
+    assert!(mvhd.matrix.is_identity());
+    assert_eq!(mvhd.next_track_id, 3);
+    assert_eq!(decoded.as_deref(), Some("timescale=600 duration=9000"));
+}
+
+#[test]
+fn mvhd_v1_uses_64_bit_duration() {
+    let (mvhd, _) = decode_mvhd(mvhd(1, 1000, 5_000_000_000, 2));
+    assert_eq!(mvhd.version, 1);
+    assert_eq!(mvhd.duration, 5_000_000_000);
+    assert_eq!(mvhd.duration_seconds, 5_000_000.0);
+    assert_eq!(mvhd.next_track_id, 2);
+}
+
+#[test]
+fn next_track_id_exceeds_track_ids() {
+    let mut moov = mvhd(0, 1000, 0, 3);
+    moov.extend_from_slice(&trak(1, b"vide", 1000, &[]));
+    moov.extend_from_slice(&trak(2, b"soun", 1000, &[]));
+    let data = bx(b"moov", &moov);
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let children = boxes[0].children.as_ref().unwrap();
+
+    let Some(StructuredData::MovieHeader(mvhd)) = &children[0].structured_data else {
+        panic!("mvhd not decoded");
+    };
+    let max_track_id = children
+        .iter()
+        .filter(|c| c.typ == "trak")
+        .filter_map(|t| match &t.children.as_ref()?[0].structured_data {
+            Some(StructuredData::TrackHeader(tkhd)) => Some(tkhd.track_id),
+            _ => None,
+        })
+        .max()
+        .unwrap();
+    assert_eq!(max_track_id, 2);
+    assert!(mvhd.next_track_id > max_track_id);
+}
+
+#[test]
+fn truncated_mvhd_is_a_decode_error() {
+    let mut data = mvhd(0, 600, 9000, 3);
+    data.truncate(40);
+    data[3] = 40;
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    assert!(boxes[0].structured_data.is_none());
+    assert!(
+        boxes[0]
+            .decoded
+            .as_deref()
+            .unwrap()
+            .starts_with("[decode error")
+    );
+}
+
+fn mehd(version: u8, fragment_duration: u64) -> Vec<u8> {
+    let p = if version == 1 {
+        fragment_duration.to_be_bytes().to_vec()
+    } else {
+        (fragment_duration as u32).to_be_bytes().to_vec()
+    };
+    full_box(b"mehd", version, 0, &p)
+}
+
+#[test]
+fn mehd_gives_fragmented_movie_duration() {
+    for (version, fragment_duration) in [(0u8, 54_000u64), (1, 5_000_000_000)] {
+        let mut moov = mvhd(0, 1000, 0, 2);
+        moov.extend_from_slice(&bx(b"mvex", &mehd(version, fragment_duration)));
+        let data = bx(b"moov", &moov);
+        let size = data.len() as u64;
+        let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+
+        let found = mp4box::find_path(&boxes, &["moov", "mvex", "mehd"]);
+        let Some(StructuredData::MovieExtendsHeader(mehd)) = &found[0].structured_data else {
+            panic!("mehd not decoded: {:?}", found[0].decoded);
+        };
+        assert_eq!(mehd.version, version);
+        assert_eq!(mehd.fragment_duration, fragment_duration);
+        assert_eq!(
+            mp4box::movie_duration(&boxes),
+            Some(fragment_duration as f64 / 1000.0)
+        );
+    }
+}
+
+#[test]
+fn movie_duration_falls_back_to_mvhd() {
+    let data = bx(b"moov", &mvhd(0, 600, 9000, 3));
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    assert_eq!(mp4box::movie_duration(&boxes), Some(15.0));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::*;
+use mp4box::track_samples_from_reader;
+use std::io::Cursor;
+
+fn avcc() -> Vec<u8> {
+    // version 1, High profile, level 4.0, lengthSizeMinusOne = 3, no SPS/PPS
+    bx(b"avcC", &[1, 0x64, 0x00, 0x28, 0xFF, 0xE0, 0x00])
+}
+
+fn nal(header: u8, len: usize) -> Vec<u8> {
+    let mut v = (len as u32).to_be_bytes().to_vec();
+    v.push(header);
+    v.extend(std::iter::repeat_n(0xAB, len - 1));
+    v
+}
+
+#[test]
+fn lists_sei_and_idr_nal_units() {
+    let mut sample = nal(0x06, 5); // SEI
+    sample.extend_from_slice(&nal(0x65, 12)); // IDR slice
+    let sample_len = sample.len() as u32;
+
+    let moov_for = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &avcc())]);
+        stbl.extend_from_slice(&stts(&[(1, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+        stbl.extend_from_slice(&stsz(&[sample_len]));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let mut reader = Cursor::new(movie(moov_for, &sample));
+
+    let tracks = track_samples_from_reader(&mut reader).unwrap();
+    let units = tracks[0].sample_nal_units(&mut reader, 0).unwrap();
+    assert_eq!(units, vec![(6, 5), (5, 12)]);
+
+    assert!(tracks[0].sample_nal_units(&mut reader, 1).is_err());
+}
+
+#[test]
+fn empty_nal_units_are_skipped_and_huge_offsets_rejected() {
+    let mut sample = 0u32.to_be_bytes().to_vec(); // empty NAL unit
+    sample.extend_from_slice(&nal(0x65, 12)); // IDR slice
+    let sample_len = sample.len() as u32;
+
+    let moov_for = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &avcc())]);
+        stbl.extend_from_slice(&stts(&[(1, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+        stbl.extend_from_slice(&stsz(&[sample_len]));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let mut reader = Cursor::new(movie(moov_for, &sample));
+
+    let mut tracks = track_samples_from_reader(&mut reader).unwrap();
+    let units = tracks[0].sample_nal_units(&mut reader, 0).unwrap();
+    assert_eq!(units, vec![(5, 12)]);
+
+    tracks[0].samples[0].file_offset = Some(u64::MAX - 2);
+    let err = tracks[0].sample_nal_units(&mut reader, 0).unwrap_err();
+    assert!(
+        err.to_string().contains("past the end of the file"),
+        "{err}"
+    );
+}
+
+#[test]
+fn csv_counts_nal_units_per_sample() {
+    let mut first = nal(0x67, 8); // SPS
+    first.extend_from_slice(&nal(0x68, 4)); // PPS
+    first.extend_from_slice(&nal(0x06, 5)); // SEI
+    first.extend_from_slice(&nal(0x65, 12)); // IDR slice
+    let mut second = nal(0x41, 6); // non-IDR slice
+    second.extend_from_slice(&nal(0x41, 6));
+    second.extend_from_slice(&nal(0x09, 2)); // access unit delimiter
+    let sizes = [first.len() as u32, second.len() as u32];
+    let mut mdat = first;
+    mdat.extend_from_slice(&second);
+
+    let moov_for = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &avcc())]);
+        stbl.extend_from_slice(&stts(&[(2, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 2, 1)]));
+        stbl.extend_from_slice(&stsz(&sizes));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+        stbl.extend_from_slice(&stss(&[1]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let mut reader = Cursor::new(movie(moov_for, &mdat));
+
+    let tracks = track_samples_from_reader(&mut reader).unwrap();
+    let csv = tracks[0].to_csv_with_nal_counts(&mut reader).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with(",is_sync,nal_slice,nal_sei,nal_vps,nal_sps,nal_pps,nal_other"));
+    assert!(lines[1].starts_with("0,0,0,512,"), "{}", lines[1]);
+    assert!(lines[1].ends_with(",true,1,1,0,1,1,0"), "{}", lines[1]);
+    assert!(lines[2].starts_with("1,512,512,512,"), "{}", lines[2]);
+    assert!(lines[2].ends_with(",false,2,0,0,0,0,1"), "{}", lines[2]);
+
+    let plain = tracks[0].to_csv();
+    assert_eq!(
+        plain.lines().next(),
+        Some("index,dts,pts,duration,size,file_offset,is_sync")
+    );
+    assert_eq!(plain.lines().count(), 3);
+}
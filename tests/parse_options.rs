@@ -0,0 +1,228 @@
+mod common;
+
+use common::*;
+use mp4box::{ParseOptions, analyze_reader_opts};
+use std::io::Cursor;
+
+/// ftyp, then a moov whose only child claims far more bytes than moov holds, then mdat.
+fn corrupt_file() -> Vec<u8> {
+    let mut trak = 200u32.to_be_bytes().to_vec();
+    trak.extend_from_slice(b"trak");
+    trak.extend_from_slice(&[0u8; 8]);
+
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend_from_slice(&bx(b"moov", &trak));
+    data.extend_from_slice(&bx(b"mdat", &[0u8; 4]));
+    data
+}
+
+fn analyze(data: Vec<u8>, opts: &ParseOptions) -> anyhow::Result<mp4box::Analysis> {
+    let size = data.len() as u64;
+    analyze_reader_opts(&mut Cursor::new(data), size, opts)
+}
+
+#[test]
+fn lenient_mode_warns_and_keeps_going() {
+    let analysis = analyze(corrupt_file(), &ParseOptions::default()).unwrap();
+
+    let types: Vec<&str> = analysis.boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(types, vec!["ftyp", "moov", "mdat"]);
+    let warnings: Vec<(&str, &str)> = analysis
+        .warnings
+        .iter()
+        .map(|w| (w.box_type.as_str(), w.message.as_str()))
+        .collect();
+    // The zeroed trak payload also reads as a nested box of size 0.
+    assert_eq!(
+        warnings,
+        [
+            (
+                "trak",
+                "box declares 200 bytes but only 16 remain in its parent"
+            ),
+            (
+                "....",
+                "size 0 is only valid for a top-level box; treating it as running to the end of its parent"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn strict_mode_fails_on_the_same_file() {
+    let opts = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let err = analyze(corrupt_file(), &opts)
+        .err()
+        .expect("strict parse must fail");
+    assert!(err.to_string().contains("trak"), "{}", err);
+}
+
+#[test]
+fn truncation_can_be_made_fatal_on_its_own() {
+    let opts = ParseOptions {
+        lenient_truncation: false,
+        ..Default::default()
+    };
+    assert!(analyze(corrupt_file(), &opts).is_err());
+}
+
+#[test]
+fn max_depth_stops_descending() {
+    let data = bx(b"moov", &bx(b"trak", &bx(b"mdia", &[])));
+    let opts = ParseOptions {
+        max_depth: Some(1),
+        ..Default::default()
+    };
+    let analysis = analyze(data, &opts).unwrap();
+
+    let trak = &analysis.boxes[0].children.as_ref().unwrap()[0];
+    assert_eq!(trak.typ, "trak");
+    assert!(trak.children.is_none());
+    assert_eq!(analysis.warnings.len(), 1);
+}
+
+#[test]
+fn case_insensitive_box_types() {
+    let data = bx(b"MOOV", &bx(b"udta", &[]));
+
+    let plain = analyze(data.clone(), &ParseOptions::default()).unwrap();
+    assert!(plain.boxes[0].children.is_none());
+
+    let opts = ParseOptions {
+        case_insensitive: true,
+        ..Default::default()
+    };
+    let analysis = analyze(data, &opts).unwrap();
+    assert_eq!(analysis.boxes[0].typ, "MOOV");
+    assert_eq!(analysis.boxes[0].children.as_ref().unwrap()[0].typ, "udta");
+}
+
+#[test]
+fn max_decode_bytes_skips_large_payloads() {
+    let data = stsz(&[1; 100]);
+    let opts = ParseOptions {
+        max_decode_bytes: Some(64),
+        ..Default::default()
+    };
+    let analysis = analyze(data, &opts).unwrap();
+    assert!(analysis.boxes[0].structured_data.is_none());
+}
+
+#[test]
+fn uuid_falls_back_to_fourcc_decoder_when_enabled() {
+    // A vendor wrote an ftyp-shaped payload in a uuid box whose usertype starts with "ftyp".
+    let mut payload = b"ftyp".to_vec();
+    payload.extend_from_slice(&[0x11; 12]);
+    payload.extend_from_slice(b"isom");
+    payload.extend_from_slice(&0u32.to_be_bytes());
+    payload.extend_from_slice(b"mp41");
+    let data = bx(b"uuid", &payload);
+
+    let plain = analyze(data.clone(), &ParseOptions::default()).unwrap();
+    assert!(plain.boxes[0].decoded.is_none());
+
+    let opts = ParseOptions {
+        uuid_fourcc_fallback: true,
+        ..Default::default()
+    };
+    let analysis = analyze(data, &opts).unwrap();
+    let decoded = analysis.boxes[0].decoded.as_deref().unwrap();
+    assert_eq!(decoded, "isom (mp41)");
+}
+
+#[test]
+fn hexdump_preview_for_undecoded_boxes() {
+    let payload: Vec<u8> = (0u8..40).collect();
+    let data = bx(b"xyz1", &payload);
+
+    let plain = analyze(data.clone(), &ParseOptions::default()).unwrap();
+    assert!(plain.boxes[0].decoded.is_none());
+
+    let opts = ParseOptions {
+        hexdump_preview: 16,
+        ..Default::default()
+    };
+    let analysis = analyze(data, &opts).unwrap();
+    let decoded = analysis.boxes[0].decoded.as_deref().unwrap();
+    assert_eq!(decoded.lines().count(), 1, "{}", decoded);
+    assert!(decoded.starts_with("00000008  00 01 02"), "{}", decoded);
+    assert!(decoded.contains(" 0e 0f "), "{}", decoded);
+    assert!(!decoded.contains(" 10 "), "{}", decoded);
+}
+
+fn ctts_v1_track(with_cslg: bool) -> Vec<u8> {
+    let mut p = 2u32.to_be_bytes().to_vec();
+    for (count, offset) in [(1u32, 512i32), (1, -256)] {
+        p.extend_from_slice(&count.to_be_bytes());
+        p.extend_from_slice(&offset.to_be_bytes());
+    }
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(2, 512)]));
+    stbl.extend_from_slice(&full_box(b"ctts", 1, 0, &p));
+    if with_cslg {
+        stbl.extend_from_slice(&full_box(b"cslg", 0, 0, &[0; 20]));
+    }
+    bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+}
+
+#[test]
+fn negative_ctts_without_cslg_is_reported() {
+    let analysis = analyze(ctts_v1_track(false), &ParseOptions::default()).unwrap();
+    let messages: Vec<&str> = analysis
+        .warnings
+        .iter()
+        .filter(|w| w.box_type == "ctts")
+        .map(|w| w.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        ["version 1 ctts has negative offsets but the track has no cslg"]
+    );
+
+    let strict = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let err = analyze(ctts_v1_track(false), &strict)
+        .err()
+        .expect("strict mode should fail");
+    assert!(err.to_string().contains("no cslg"), "{}", err);
+
+    let analysis = analyze(ctts_v1_track(true), &ParseOptions::default()).unwrap();
+    assert!(analysis.warnings.is_empty(), "{:?}", analysis.warnings);
+}
+
+#[test]
+fn resync_recovers_boxes_after_corrupt_region() {
+    let head = ftyp(b"isom", &[b"isom"]);
+    let mut data = head.clone();
+    data.extend_from_slice(&[0xFF; 13]);
+    data.extend_from_slice(&bx(b"moov", &bx(b"udta", &[])));
+    data.extend_from_slice(&bx(b"free", &[0; 4]));
+
+    // Without resync the garbage is taken as a box running to the end of the file.
+    let plain = analyze(data.clone(), &ParseOptions::default()).unwrap();
+    assert_eq!(plain.boxes.len(), 2);
+    assert!(plain.boxes.iter().all(|b| b.typ != "moov"));
+
+    let opts = ParseOptions {
+        resync: true,
+        ..Default::default()
+    };
+    let analysis = analyze(data, &opts).unwrap();
+    let types: Vec<&str> = analysis.boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(types, ["ftyp", "moov", "free"]);
+    assert_eq!(analysis.boxes[1].offset, head.len() as u64 + 13);
+    assert_eq!(analysis.boxes[1].children.as_ref().unwrap()[0].typ, "udta");
+
+    assert_eq!(analysis.warnings.len(), 1);
+    assert_eq!(analysis.warnings[0].offset, head.len() as u64);
+    assert!(
+        analysis.warnings[0].message.contains("skipped 13 bytes"),
+        "{}",
+        analysis.warnings[0].message
+    );
+}
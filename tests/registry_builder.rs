@@ -0,0 +1,58 @@
+mod common;
+
+use common::*;
+use mp4box::{BoxKey, FourCC, ParseOptions, RegistryBuilder, analyze_reader_with_registry};
+use std::io::Cursor;
+
+fn key(typ: &[u8; 4]) -> BoxKey {
+    BoxKey::FourCC(FourCC(*typ))
+}
+
+fn stbl() -> Vec<u8> {
+    let mut stbl = stsz(&[10, 20, 30]);
+    stbl.extend_from_slice(&stco(&[100]));
+    bx(b"stbl", &stbl)
+}
+
+fn analyze(reg: &mp4box::Registry) -> Vec<mp4box::Box> {
+    let data = stbl();
+    let size = data.len() as u64;
+    let analysis =
+        analyze_reader_with_registry(&mut Cursor::new(data), size, &ParseOptions::default(), reg)
+            .unwrap();
+    analysis.boxes.into_iter().next().unwrap().children.unwrap()
+}
+
+#[test]
+fn without_stsz_leaves_it_undecoded() {
+    let reg = RegistryBuilder::new().without(key(b"stsz")).build();
+    let children = analyze(&reg);
+
+    assert_eq!(children[0].typ, "stsz");
+    assert!(children[0].decoded.is_none());
+    assert!(children[0].structured_data.is_none());
+    assert_eq!(children[1].typ, "stco");
+    assert!(children[1].structured_data.is_some());
+}
+
+#[test]
+fn only_keeps_listed_decoders() {
+    let reg = RegistryBuilder::new().only([key(b"stsz")]).build();
+    assert!(reg.contains(&key(b"stsz")));
+    assert!(!reg.contains(&key(b"stco")));
+    assert!(!reg.contains(&key(b"ftyp")));
+
+    let children = analyze(&reg);
+    assert!(children[0].structured_data.is_some());
+    assert!(children[1].decoded.is_none());
+}
+
+#[test]
+fn without_applies_on_top_of_only() {
+    let reg = RegistryBuilder::new()
+        .only([key(b"stsz"), key(b"stco")])
+        .without(key(b"stco"))
+        .build();
+    assert!(reg.contains(&key(b"stsz")));
+    assert!(!reg.contains(&key(b"stco")));
+}
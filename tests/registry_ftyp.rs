@@ -51,3 +51,33 @@ fn registry_invokes_decoder() {
         _ => panic!("expected bytes"),
     }
 }
+
+#[test]
+fn ftyp_decodes_brands() {
+    use mp4box::registry::{StructuredData, default_registry};
+
+    let mut payload = b"isom".to_vec();
+    payload.extend_from_slice(&512u32.to_be_bytes());
+    payload.extend_from_slice(b"mp42avc1dash");
+
+    let reg = default_registry();
+    for typ in [b"ftyp", b"styp"] {
+        let hdr = BoxHeader::new(FourCC(*typ), payload.len() as u64);
+        let mut cursor = std::io::Cursor::new(payload.clone());
+        let value = reg
+            .decode(&BoxKey::FourCC(FourCC(*typ)), &mut cursor, &hdr, None, None)
+            .unwrap()
+            .unwrap();
+
+        let BoxValue::Structured(data) = value else {
+            panic!("expected structured data");
+        };
+        assert_eq!(data.summary().as_deref(), Some("isom (mp42, avc1, dash)"));
+        let StructuredData::FileType(ftyp) = data else {
+            panic!("expected FileType");
+        };
+        assert_eq!(ftyp.major_brand, "isom");
+        assert_eq!(ftyp.minor_version, 512);
+        assert_eq!(ftyp.compatible_brands, ["mp42", "avc1", "dash"]);
+    }
+}
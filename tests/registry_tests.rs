@@ -101,6 +101,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_stsz_rejects_oversized_sample_count() {
+        // sample_count claims ~4 billion sizes, but only two follow.
+        let mock_data = vec![
+            0, 0, 0, 0, // sample_size = 0 (individual sizes)
+            0xFF, 0xFF, 0xFF, 0xF0, // sample_count = 4294967280
+            0, 0, 3, 232, // size = 1000
+            0, 0, 7, 208, // size = 2000
+        ];
+
+        let mut cursor = Cursor::new(mock_data);
+        let header = BoxHeader {
+            typ: FourCC(*b"stsz"),
+            uuid: None,
+            size: 24,
+            header_size: 8,
+            start: 0,
+        };
+
+        let registry = default_registry();
+        let err = registry
+            .decode(
+                &BoxKey::FourCC(FourCC(*b"stsz")),
+                &mut cursor,
+                &header,
+                Some(0),
+                Some(0),
+            )
+            .unwrap()
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "stsz sample_count 4294967280 needs 17179869120 bytes of sizes but only 8 remain"
+        );
+    }
+
     #[test]
     fn test_stsc_structured_decoding() {
         // Create mock STSC box data (without version/flags)
@@ -156,7 +193,7 @@ mod tests {
 
     #[test]
     fn test_ctts_structured_decoding() {
-        // Create mock CTTS box data (without version/flags)
+        // Create mock version 1 CTTS box data (without version/flags)
         let mock_data = vec![
             0, 0, 0, 3, // entry_count = 3
             0, 0, 0, 5, // sample_count = 5
@@ -176,6 +213,60 @@ mod tests {
             start: 0,
         };
 
+        let registry = default_registry();
+        let result = registry
+            .decode(
+                &BoxKey::FourCC(FourCC(*b"ctts")),
+                &mut cursor,
+                &header,
+                Some(1),
+                Some(0),
+            )
+            .unwrap()
+            .unwrap();
+
+        match result {
+            BoxValue::Structured(StructuredData::CompositionTimeToSample(ctts_data)) => {
+                assert_eq!(ctts_data.version, 1);
+                assert_eq!(ctts_data.flags, 0);
+                assert_eq!(ctts_data.entry_count, 3);
+                assert_eq!(ctts_data.entries.len(), 3);
+
+                assert_eq!(ctts_data.entries[0].sample_count, 5);
+                assert_eq!(ctts_data.entries[0].sample_offset, 256);
+
+                assert_eq!(ctts_data.entries[1].sample_count, 2);
+                assert_eq!(ctts_data.entries[1].sample_offset, -256);
+
+                assert_eq!(ctts_data.entries[2].sample_count, 1);
+                assert_eq!(ctts_data.entries[2].sample_offset, 512);
+            }
+            _ => panic!("Expected structured CTTS data"),
+        }
+    }
+
+    #[test]
+    fn test_ctts_version0_offsets_are_unsigned() {
+        // Same entries in a version 0 box: offsets are unsigned
+        let mock_data = vec![
+            0, 0, 0, 3, // entry_count = 3
+            0, 0, 0, 5, // sample_count = 5
+            0, 0, 1, 0, // sample_offset = 256
+            0, 0, 0, 2, // sample_count = 2
+            255, 255, 255, 0, // sample_offset = 4294967040 (unsigned)
+            0, 0, 0, 1, // sample_count = 1
+            0, 0, 2, 0, // sample_offset = 512
+        ];
+
+        let mut cursor = Cursor::new(mock_data);
+        let header = BoxHeader {
+            typ: FourCC(*b"ctts"),
+            uuid: None,
+            size: 40,
+            header_size: 8,
+            start: 0,
+        };
+
         let registry = default_registry();
         let result = registry
             .decode(
@@ -199,7 +290,7 @@ mod tests {
                 assert_eq!(ctts_data.entries[0].sample_offset, 256);
 
                 assert_eq!(ctts_data.entries[1].sample_count, 2);
-                assert_eq!(ctts_data.entries[1].sample_offset, -256);
+                assert_eq!(ctts_data.entries[1].sample_offset, 4294967040);
 
                 assert_eq!(ctts_data.entries[2].sample_count, 1);
                 assert_eq!(ctts_data.entries[2].sample_offset, 512);
@@ -466,4 +557,45 @@ mod tests {
             _ => panic!("Expected structured STSD data"),
         }
     }
+
+    #[test]
+    fn track_reference_types_only_decode_inside_tref() {
+        use mp4box::registry::DecodeContext;
+
+        let payload = 1u32.to_be_bytes().to_vec();
+        let header = BoxHeader {
+            typ: FourCC(*b"tmcd"),
+            uuid: None,
+            size: 12,
+            header_size: 8,
+            start: 0,
+        };
+        let key = BoxKey::FourCC(FourCC(*b"tmcd"));
+        let registry = default_registry();
+        let decode_in = |parent: &[u8; 4]| {
+            let ctx = DecodeContext {
+                parent: Some(FourCC(*parent)),
+                ..DecodeContext::default()
+            };
+            registry.decode_with_context(
+                &key,
+                &mut Cursor::new(payload.clone()),
+                &header,
+                None,
+                None,
+                &ctx,
+            )
+        };
+
+        // QuickTime's gmhd/tmcd is a timecode media header, not a track list.
+        assert!(decode_in(b"gmhd").is_none());
+
+        match decode_in(b"tref").unwrap().unwrap() {
+            BoxValue::Structured(StructuredData::TrackReference(tref)) => {
+                assert_eq!(tref.reference_type, "tmcd");
+                assert_eq!(tref.track_ids, vec![1]);
+            }
+            _ => panic!("Expected structured track reference"),
+        }
+    }
 }
@@ -0,0 +1,57 @@
+mod common;
+
+use common::*;
+use mp4box::{RenderOpts, get_boxes, render_tree};
+use std::io::Cursor;
+
+fn sample_tree() -> Vec<mp4box::Box> {
+    let mut moov = bx(b"free", &[0; 8]);
+    moov.extend_from_slice(&bx(b"udta", &bx(b"free", &[])));
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend_from_slice(&bx(b"moov", &moov));
+    let size = data.len() as u64;
+    get_boxes(&mut Cursor::new(data), size, false).unwrap()
+}
+
+#[test]
+fn renders_full_tree_with_percentages() {
+    let text = render_tree(&sample_tree(), RenderOpts::default());
+    assert_eq!(
+        text,
+        "\
+ftyp 20 B (33.3%) File Type Box
+moov 40 B (66.7%) Movie Box
+  free 16 B (40.0%) Free Space Box
+  udta 16 B (40.0%) User Data Box
+    free 8 B (50.0%) Free Space Box
+"
+    );
+}
+
+#[test]
+fn collapses_containers_past_max_depth() {
+    let opts = RenderOpts {
+        max_depth: Some(1),
+        ..Default::default()
+    };
+    let text = render_tree(&sample_tree(), opts);
+    assert_eq!(
+        text,
+        "\
+ftyp 20 B (33.3%) File Type Box
+moov 40 B (66.7%) Movie Box
+  free 16 B (40.0%) Free Space Box
+  udta 16 B (40.0%) User Data Box [+1 collapsed]
+"
+    );
+
+    let opts = RenderOpts {
+        max_depth: Some(0),
+        exact_sizes: true,
+    };
+    let text = render_tree(&sample_tree(), opts);
+    assert_eq!(
+        text.lines().nth(1),
+        Some("moov 40 B (66.7%) Movie Box [+2 collapsed]")
+    );
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{
+    AudioSampleEntry, SampleEntry, SampleEntryDetails, StructuredData, VisualSampleEntry,
+};
+use std::io::Cursor;
+
+fn entries(stsd_box: Vec<u8>) -> Vec<SampleEntry> {
+    let size = stsd_box.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(stsd_box), size, true).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::SampleDescription(stsd)) => stsd.entries.clone(),
+        other => panic!("stsd not decoded: {:?}", other),
+    }
+}
+
+#[test]
+fn visual_entry_fields() {
+    let mut avc1 = visual_entry(b"avc1", &bx(b"avcC", &[1, 0x64, 0, 0x28, 0xFF, 0xE0, 0]));
+    // compressorname: Pascal string at byte 42 of the entry body
+    let name = b"x264";
+    avc1[8 + 42] = name.len() as u8;
+    avc1[8 + 43..8 + 43 + name.len()].copy_from_slice(name);
+
+    let entry = &entries(stsd(&[avc1]))[0];
+    assert_eq!(
+        entry.details,
+        SampleEntryDetails::Visual(VisualSampleEntry {
+            width: 1920,
+            height: 1080,
+            horizresolution: 72.0,
+            vertresolution: 72.0,
+            frame_count: 1,
+            compressorname: "x264".to_string(),
+            depth: 24,
+        })
+    );
+    // Child configuration boxes are still decoded.
+    assert_eq!(entry.avc_config.as_ref().unwrap().profile_indication, 100);
+}
+
+#[test]
+fn audio_entry_fields() {
+    let entry = &entries(stsd(&[audio_entry(b"mp4a", 2, 48000, &[])]))[0];
+    assert_eq!(
+        entry.details,
+        SampleEntryDetails::Audio(AudioSampleEntry {
+            qt_version: 0,
+            channel_count: 2,
+            sample_size: 16,
+            sample_rate: 48000.0,
+        })
+    );
+    assert!(entry.width.is_none());
+}
+
+#[test]
+fn quicktime_v2_sound_entry_uses_extended_rate() {
+    let mut lpcm = audio_entry(b"lpcm", 3, 1, &[]);
+    lpcm[8 + 8..8 + 10].copy_from_slice(&2u16.to_be_bytes()); // version 2
+    let mut ext = 72u32.to_be_bytes().to_vec(); // sizeOfStructOnly
+    ext.extend_from_slice(&96000f64.to_be_bytes());
+    ext.extend_from_slice(&6u32.to_be_bytes()); // numAudioChannels
+    ext.extend_from_slice(&[0u8; 20]); // remaining v2 fields
+    lpcm.extend_from_slice(&ext);
+    let len = lpcm.len() as u32;
+    lpcm[0..4].copy_from_slice(&len.to_be_bytes());
+
+    let entry = &entries(stsd(&[lpcm]))[0];
+    let SampleEntryDetails::Audio(audio) = &entry.details else {
+        panic!("not an audio entry: {:?}", entry.details);
+    };
+    assert_eq!(audio.qt_version, 2);
+    assert_eq!(audio.sample_rate, 96000.0);
+    assert_eq!(audio.channel_count, 6);
+}
+
+#[test]
+fn unknown_codec_has_no_details() {
+    let entry = &entries(stsd(&[bx(b"zzzz", &[0, 0, 0, 0, 0, 0, 0, 1])]))[0];
+    assert_eq!(entry.details, SampleEntryDetails::Other);
+}
+
+#[test]
+fn unknown_codec_in_video_track_reports_resolution() {
+    let mut xyz1 = visual_entry(b"xyz1", &[]);
+    xyz1[8 + 24..8 + 26].copy_from_slice(&1280u16.to_be_bytes());
+    xyz1[8 + 26..8 + 28].copy_from_slice(&720u16.to_be_bytes());
+
+    let data = bx(b"moov", &trak(1, b"vide", 1000, &stsd(&[xyz1.clone()])));
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let stsd_box = mp4box::find_path(&boxes, &["moov", "trak", "mdia", "minf", "stbl", "stsd"]);
+    let Some(StructuredData::SampleDescription(desc)) = &stsd_box[0].structured_data else {
+        panic!("stsd not decoded");
+    };
+    let entry = &desc.entries[0];
+    assert_eq!((entry.width, entry.height), (Some(1280), Some(720)));
+    let SampleEntryDetails::Visual(visual) = &entry.details else {
+        panic!("expected visual details, got {:?}", entry.details);
+    };
+    assert_eq!((visual.width, visual.height, visual.depth), (1280, 720, 24));
+
+    // Without a track handler an unknown codec stays opaque.
+    assert_eq!(entries(stsd(&[xyz1]))[0].details, SampleEntryDetails::Other);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use std::io::Cursor;
+
+fn decode(data: Vec<u8>) -> StructuredData {
+    let size = data.len() as u64;
+    let mut boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    boxes.remove(0).structured_data.expect("not decoded")
+}
+
+#[test]
+fn sgpd_v1_roll_entries() {
+    let mut p = b"roll".to_vec();
+    p.extend_from_slice(&2u32.to_be_bytes()); // default_length
+    p.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    p.extend_from_slice(&(-1i16).to_be_bytes());
+    p.extend_from_slice(&3i16.to_be_bytes());
+
+    let StructuredData::SampleGroupDescription(sgpd) = decode(full_box(b"sgpd", 1, 0, &p)) else {
+        panic!("expected sgpd");
+    };
+    assert_eq!(sgpd.grouping_type, "roll");
+    assert_eq!(sgpd.default_length, Some(2));
+    assert_eq!(sgpd.default_group_description_index, None);
+    let rolls: Vec<Option<i16>> = sgpd.entries.iter().map(|e| e.roll_distance).collect();
+    assert_eq!(rolls, [Some(-1), Some(3)]);
+    assert_eq!(sgpd.entries[0].payload, [0xFF, 0xFF]);
+}
+
+#[test]
+fn sgpd_v2_variable_length_entries() {
+    let mut p = b"abcd".to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes()); // default_length: per-entry lengths
+    p.extend_from_slice(&1u32.to_be_bytes()); // default_group_description_index
+    p.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    p.extend_from_slice(&1u32.to_be_bytes());
+    p.push(7);
+    p.extend_from_slice(&3u32.to_be_bytes());
+    p.extend_from_slice(&[1, 2, 3]);
+
+    let StructuredData::SampleGroupDescription(sgpd) = decode(full_box(b"sgpd", 2, 0, &p)) else {
+        panic!("expected sgpd");
+    };
+    assert_eq!(sgpd.default_length, Some(0));
+    assert_eq!(sgpd.default_group_description_index, Some(1));
+    let payloads: Vec<&[u8]> = sgpd.entries.iter().map(|e| e.payload.as_slice()).collect();
+    assert_eq!(payloads, [&[7][..], &[1, 2, 3][..]]);
+    assert_eq!(sgpd.entries[0].roll_distance, None);
+}
+
+#[test]
+fn sgpd_v1_variable_length_entries() {
+    // Version 1 with default_length 0 also prefixes every entry with its length.
+    let mut p = b"abcd".to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes()); // default_length
+    p.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    p.extend_from_slice(&2u32.to_be_bytes());
+    p.extend_from_slice(&[9, 9]);
+    p.extend_from_slice(&0u32.to_be_bytes());
+
+    let StructuredData::SampleGroupDescription(sgpd) = decode(full_box(b"sgpd", 1, 0, &p)) else {
+        panic!("expected sgpd");
+    };
+    assert_eq!(sgpd.default_group_description_index, None);
+    let payloads: Vec<&[u8]> = sgpd.entries.iter().map(|e| e.payload.as_slice()).collect();
+    assert_eq!(payloads, [&[9, 9][..], &[][..]]);
+}
+
+#[test]
+fn sgpd_truncated_entry_is_reported() {
+    let mut p = b"abcd".to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes()); // default_length
+    p.extend_from_slice(&1u32.to_be_bytes()); // default_group_description_index
+    p.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+    p.extend_from_slice(&4u32.to_be_bytes());
+    p.extend_from_slice(&[1, 2]); // two of the four bytes
+
+    let data = full_box(b"sgpd", 2, 0, &p);
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    assert!(boxes[0].structured_data.is_none());
+    let decoded = boxes[0].decoded.as_deref().unwrap_or_default();
+    assert!(decoded.contains("sgpd entry 0 (4 bytes)"), "{}", decoded);
+}
+
+#[test]
+fn sbgp_v0_and_v1() {
+    let mut p = b"roll".to_vec();
+    p.extend_from_slice(&2u32.to_be_bytes());
+    for (count, index) in [(10u32, 1u32), (5, 0)] {
+        p.extend_from_slice(&count.to_be_bytes());
+        p.extend_from_slice(&index.to_be_bytes());
+    }
+    let StructuredData::SampleToGroup(sbgp) = decode(full_box(b"sbgp", 0, 0, &p)) else {
+        panic!("expected sbgp");
+    };
+    assert_eq!(sbgp.grouping_type, "roll");
+    assert_eq!(sbgp.grouping_type_parameter, None);
+    let runs: Vec<(u32, u32)> = sbgp
+        .entries
+        .iter()
+        .map(|e| (e.sample_count, e.group_description_index))
+        .collect();
+    assert_eq!(runs, [(10, 1), (5, 0)]);
+
+    let mut p = b"roll".to_vec();
+    p.extend_from_slice(&42u32.to_be_bytes()); // grouping_type_parameter
+    p.extend_from_slice(&0u32.to_be_bytes());
+    let StructuredData::SampleToGroup(sbgp) = decode(full_box(b"sbgp", 1, 0, &p)) else {
+        panic!("expected sbgp");
+    };
+    assert_eq!(sbgp.grouping_type_parameter, Some(42));
+    assert!(sbgp.entries.is_empty());
+}
@@ -0,0 +1,389 @@
+mod common;
+
+use common::*;
+use mp4box::samples::extract_track_samples;
+use mp4box::{
+    extract_chunk_table, get_boxes, read_all_samples, read_sample_bytes, track_samples_from_reader,
+};
+use std::io::Cursor;
+
+fn video_moov(chunk_offset: u32) -> Vec<u8> {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(2, 512)]));
+    stbl.extend_from_slice(&stsc(&[(1, 2, 1)]));
+    stbl.extend_from_slice(&stsz(&[10, 20]));
+    stbl.extend_from_slice(&stco(&[chunk_offset]));
+    bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+}
+
+#[test]
+fn offsets_inside_mdat_have_no_warnings() {
+    let file = movie(video_moov, &[0u8; 30]);
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+
+    assert_eq!(tracks.len(), 1);
+    assert!(tracks[0].warnings.is_empty(), "{:?}", tracks[0].warnings);
+}
+
+#[test]
+fn chunk_offset_into_moov_is_reported() {
+    // Point the chunk at the start of moov instead of the mdat payload.
+    let ftyp_len = ftyp(b"isom", &[b"isom", b"mp41"]).len() as u32;
+    let file = movie(|_| video_moov(ftyp_len), &[0u8; 30]);
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+
+    let warnings = &tracks[0].warnings;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].box_type, "stco");
+    assert!(warnings[0].message.contains("2 of 2 samples"));
+    assert!(warnings[0].message.contains("inside 'moov'"));
+}
+
+#[test]
+fn large_sample_table_offsets_and_sizes() {
+    const SAMPLES: u32 = 100_000;
+    const PER_CHUNK: u32 = 10;
+
+    let sizes: Vec<u32> = (0..SAMPLES).map(|i| 1 + i % 7).collect();
+    let total: u32 = sizes.iter().sum();
+
+    let moov_for = |mdat_start: u32| {
+        let mut offsets = Vec::new();
+        let mut pos = mdat_start;
+        for chunk in sizes.chunks(PER_CHUNK as usize) {
+            offsets.push(pos);
+            pos += chunk.iter().sum::<u32>();
+        }
+
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(SAMPLES, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, PER_CHUNK, 1)]));
+        stbl.extend_from_slice(&stsz(&sizes));
+        stbl.extend_from_slice(&stco(&offsets));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let file = movie(moov_for, &vec![0u8; total as usize]);
+    let mdat_start = (file.len() - total as usize) as u64;
+
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+    let track = &tracks[0];
+    assert_eq!(track.sample_count, SAMPLES);
+    assert!(track.warnings.is_empty());
+
+    // Samples are laid out back to back, so each offset is the running total.
+    let mut expected = mdat_start;
+    for (sample, &size) in track.samples.iter().zip(&sizes) {
+        assert_eq!(sample.size, size);
+        assert_eq!(
+            sample.file_offset,
+            Some(expected),
+            "sample {}",
+            sample.index
+        );
+        assert_eq!(sample.dts, sample.index as u64 * 512);
+        expected += size as u64;
+    }
+}
+
+#[test]
+fn constant_stsz_with_size_table_is_reported() {
+    let moov_for = |mdat_start: u32| {
+        // sample_size = 10, sample_count = 2, yet a per-sample table follows.
+        let mut stsz_payload = Vec::new();
+        for v in [10u32, 2, 10, 10] {
+            stsz_payload.extend_from_slice(&v.to_be_bytes());
+        }
+
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(2, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 2, 1)]));
+        stbl.extend_from_slice(&full_box(b"stsz", 0, 0, &stsz_payload));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let file = movie(moov_for, &[0u8; 20]);
+    let tracks = track_samples_from_reader(Cursor::new(file)).unwrap();
+
+    let warnings = &tracks[0].warnings;
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert_eq!(warnings[0].box_type, "stsz");
+    assert!(warnings[0].message.contains("sample_size is 10"));
+    assert_eq!(tracks[0].samples[1].size, 10);
+}
+
+#[test]
+fn self_contained_until_last_sample_runs_past_eof() {
+    let file = movie(video_moov, &[0u8; 30]);
+    let tracks = track_samples_from_reader(Cursor::new(file.clone())).unwrap();
+    assert!(tracks[0].data_self_contained);
+    assert!(tracks[0].is_self_contained(file.len() as u64));
+
+    // Cut the file short so the second sample's last bytes are missing.
+    assert!(!tracks[0].is_self_contained(file.len() as u64 - 1));
+}
+
+#[test]
+fn last_sample_is_clamped_to_media_duration() {
+    let moov_for = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(3, 1000)]));
+        stbl.extend_from_slice(&stsc(&[(1, 3, 1)]));
+        stbl.extend_from_slice(&stsz(&[1, 1, 1]));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+
+        // mdhd says the media ends at 2400, but stts runs to 3000.
+        let minf = bx(b"minf", &bx(b"stbl", &stbl));
+        let mut mdia = mdhd(1000, 2400);
+        mdia.extend_from_slice(&hdlr(b"vide"));
+        mdia.extend_from_slice(&minf);
+        let mut trak = tkhd(1);
+        trak.extend_from_slice(&bx(b"mdia", &mdia));
+        bx(b"moov", &bx(b"trak", &trak))
+    };
+    let tracks = track_samples_from_reader(Cursor::new(movie(moov_for, &[0u8; 3]))).unwrap();
+    let samples = &tracks[0].samples;
+
+    assert_eq!(samples[1].duration, 1000);
+    assert_eq!(samples[1].effective_duration, 1000);
+    assert_eq!(samples[2].duration, 1000);
+    assert_eq!(samples[2].effective_duration, 400);
+}
+
+fn offset_moov(stsc_entries: &[(u32, u32, u32)], chunk_tables: &[u8]) -> Vec<u8> {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(4, 512)]));
+    stbl.extend_from_slice(&stsc(stsc_entries));
+    stbl.extend_from_slice(&stsz(&[10, 10, 10, 10]));
+    stbl.extend_from_slice(chunk_tables);
+    bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+}
+
+fn co64(offsets: &[u64]) -> Vec<u8> {
+    let mut p = (offsets.len() as u32).to_be_bytes().to_vec();
+    for o in offsets {
+        p.extend_from_slice(&o.to_be_bytes());
+    }
+    full_box(b"co64", 0, 0, &p)
+}
+
+#[test]
+fn healthy_tables_give_every_sample_an_offset() {
+    let moov = offset_moov(&[(1, 2, 1)], &stco(&[1000, 2000]));
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let offsets: Vec<Option<u64>> = tracks[0].samples.iter().map(|s| s.file_offset).collect();
+    assert_eq!(
+        offsets,
+        vec![Some(1000), Some(1010), Some(2000), Some(2010)]
+    );
+    assert!(
+        !tracks[0]
+            .warnings
+            .iter()
+            .any(|w| w.message.contains("no file offset"))
+    );
+}
+
+#[test]
+fn missing_chunk_offset_table_leaves_offsets_unknown() {
+    let moov = offset_moov(&[(1, 2, 1)], &[]);
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let track = &tracks[0];
+
+    assert!(track.samples.iter().all(|s| s.file_offset.is_none()));
+    let w = track
+        .warnings
+        .iter()
+        .find(|w| w.message.contains("no file offset"))
+        .expect("missing-table warning");
+    assert_eq!(w.box_type, "stbl");
+    assert!(w.message.starts_with("4 samples (first: sample 0)"));
+    // Unknown offsets are not also reported as lying outside mdat.
+    assert!(
+        !track
+            .warnings
+            .iter()
+            .any(|w| w.message.contains("outside mdat"))
+    );
+    assert!(!track.is_self_contained(u64::MAX));
+}
+
+#[test]
+fn stsc_chunk_past_offset_table_is_reported() {
+    // stsc says chunks 1-2 hold one sample and chunk 3 onwards two, but stco
+    // only has two chunks: samples 2 and 3 would need chunk 3.
+    let moov = offset_moov(&[(1, 1, 1), (3, 2, 1)], &stco(&[1000, 2000]));
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let track = &tracks[0];
+
+    let offsets: Vec<Option<u64>> = track.samples.iter().map(|s| s.file_offset).collect();
+    assert_eq!(offsets, vec![Some(1000), Some(2000), None, None]);
+    let w = track
+        .warnings
+        .iter()
+        .find(|w| w.box_type == "stsc")
+        .expect("stsc warning");
+    assert_eq!(
+        w.message,
+        "2 samples (first: sample 2) have no file offset: stsc maps no chunk to them"
+    );
+}
+
+#[test]
+fn stsc_range_beyond_chunk_table_names_the_chunk() {
+    // The first entry claims chunks 1-4 but stco has only two.
+    let moov = offset_moov(&[(1, 1, 1), (5, 1, 1)], &stco(&[1000, 2000]));
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let w = tracks[0]
+        .warnings
+        .iter()
+        .find(|w| w.box_type == "stsc")
+        .expect("stsc warning");
+    assert!(
+        w.message
+            .ends_with("stsc references chunk 3 but the chunk offset table has 2 entries"),
+        "{}",
+        w.message
+    );
+}
+
+#[test]
+fn co64_takes_precedence_over_stco() {
+    let mut tables = stco(&[1000]);
+    tables.extend_from_slice(&co64(&[5_000_000_000]));
+    let moov = offset_moov(&[(1, 4, 1)], &tables);
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let track = &tracks[0];
+
+    assert_eq!(track.samples[0].file_offset, Some(5_000_000_000));
+    assert!(
+        track
+            .warnings
+            .iter()
+            .any(|w| w.box_type == "co64" && w.message.contains("using co64"))
+    );
+}
+
+#[test]
+fn sample_bytes_round_trip() {
+    let payload: Vec<u8> = (0..30).collect();
+    let mut cur = Cursor::new(movie(video_moov, &payload));
+    let tracks = track_samples_from_reader(&mut cur).unwrap();
+
+    let samples = read_all_samples(&mut cur, &tracks[0]).unwrap();
+    assert_eq!(samples, [payload[..10].to_vec(), payload[10..].to_vec()]);
+    assert_eq!(
+        read_sample_bytes(&mut cur, &tracks[0].samples[1]).unwrap(),
+        &payload[10..]
+    );
+}
+
+#[test]
+fn sample_bytes_past_eof_are_rejected() {
+    // mdat is 5 bytes short of the 30 the samples need.
+    let mut cur = Cursor::new(movie(video_moov, &[0u8; 25]));
+    let tracks = track_samples_from_reader(&mut cur).unwrap();
+
+    assert!(read_sample_bytes(&mut cur, &tracks[0].samples[0]).is_ok());
+    let err = read_sample_bytes(&mut cur, &tracks[0].samples[1]).unwrap_err();
+    assert!(
+        err.to_string().contains("sample 2 (20 bytes @ ")
+            && err.to_string().contains("past the end"),
+        "{err}"
+    );
+    assert!(read_all_samples(&mut cur, &tracks[0]).is_err());
+
+    // An offset whose end doesn't fit in 64 bits is past the end, not a panic.
+    let mut sample = tracks[0].samples[1].clone();
+    sample.file_offset = Some(u64::MAX - 2);
+    let err = read_sample_bytes(&mut cur, &sample).unwrap_err();
+    assert!(err.to_string().contains("past the end"), "{err}");
+}
+
+#[test]
+fn bounded_reads_follow_file_order() {
+    // Three samples across two chunks; stco lists the second chunk first in
+    // the file, so decode order and file order differ.
+    let payload: Vec<u8> = (0..60).collect();
+    let moov_for = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(3, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 1, 1), (2, 2, 1)]));
+        stbl.extend_from_slice(&stsz(&[10, 20, 30]));
+        stbl.extend_from_slice(&stco(&[mdat_start + 50, mdat_start]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let mut cur = Cursor::new(movie(moov_for, &payload));
+    let tracks = track_samples_from_reader(&mut cur).unwrap();
+
+    let mut seen = Vec::new();
+    tracks[0]
+        .read_all_samples_bounded(&mut cur, 32, |s, bytes| {
+            seen.push((s.index, bytes.to_vec()));
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(
+        seen,
+        [
+            (1, payload[0..20].to_vec()),
+            (2, payload[20..50].to_vec()),
+            (0, payload[50..60].to_vec()),
+        ]
+    );
+
+    // A budget smaller than the largest sample is refused up front.
+    let err = tracks[0]
+        .read_all_samples_bounded(&mut cur, 16, |_, _| Ok(()))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("exceeds the 16 byte budget"),
+        "{err}"
+    );
+
+    // An offset whose end doesn't fit in 64 bits is past the end, not a panic.
+    let mut track = tracks[0].clone();
+    track.samples[0].file_offset = Some(u64::MAX - 4);
+    let err = track
+        .read_all_samples_bounded(&mut cur, 32, |_, _| Ok(()))
+        .unwrap_err();
+    assert!(err.to_string().contains("past the end"), "{err}");
+}
+
+#[test]
+fn chunk_table_pairs_offsets_with_stsc() {
+    // Chunks 1-2 hold three samples, chunks 3-4 one each.
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(8, 512)]));
+    stbl.extend_from_slice(&stsc(&[(1, 3, 1), (3, 1, 1)]));
+    stbl.extend_from_slice(&stsz(&[10; 8]));
+    stbl.extend_from_slice(&stco(&[1000, 2000, 3000, 4000]));
+    let data = bx(b"moov", &trak(1, b"vide", 12800, &stbl));
+    let size = data.len() as u64;
+    let mut cur = Cursor::new(data);
+    let boxes = get_boxes(&mut cur, size, true).unwrap();
+    let trak = &boxes[0].children.as_ref().unwrap()[0];
+
+    let chunks = extract_chunk_table(trak).unwrap();
+    let rows: Vec<(u32, u64, u32, u32)> = chunks
+        .iter()
+        .map(|c| (c.index, c.offset, c.sample_count, c.first_sample))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            (0, 1000, 3, 0),
+            (1, 2000, 3, 3),
+            (2, 3000, 1, 6),
+            (3, 4000, 1, 7)
+        ]
+    );
+
+    // Each chunk's first sample starts exactly at the chunk offset.
+    let track = extract_track_samples(trak, &mut cur).unwrap().unwrap();
+    for c in &chunks {
+        assert_eq!(
+            track.samples[c.first_sample as usize].file_offset,
+            Some(c.offset)
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use mp4box::{SampleInfo, TrackSamples, tracks_to_json};
+use serde_json::Value;
+
+fn make_track(track_id: u32) -> TrackSamples {
+    TrackSamples {
+        track_id,
+        handler_type: "vide".to_string(),
+        handler_name: None,
+        timescale: 1000,
+        duration: 80,
+        sample_count: 2,
+        samples: (0..2)
+            .map(|i| SampleInfo {
+                index: i,
+                dts: i as u64 * 40,
+                pts: i as u64 * 40,
+                start_time: i as f64 * 0.04,
+                duration: 40,
+                effective_duration: 40,
+                rendered_offset: 0,
+                file_offset: Some(100 + i as u64 * 10),
+                size: 10,
+                is_sync: i == 0,
+                flags: None,
+            })
+            .collect(),
+        sample_entry: None,
+        edits: Vec::new(),
+        gapless: None,
+        data_self_contained: true,
+        warnings: Vec::new(),
+    }
+}
+
+#[test]
+fn track_to_json_pretty_has_expected_keys() {
+    let json = make_track(1).to_json_pretty();
+    assert!(json.contains('\n'), "expected pretty-printed output");
+
+    let v: Value = serde_json::from_str(&json).expect("valid JSON");
+    assert_eq!(v["track_id"], 1);
+    assert_eq!(v["handler_type"], "vide");
+    assert_eq!(v["timescale"], 1000);
+    assert_eq!(v["sample_count"], 2);
+    assert_eq!(v["samples"].as_array().unwrap().len(), 2);
+    assert_eq!(v["samples"][1]["file_offset"], 110);
+}
+
+#[test]
+fn tracks_to_json_is_an_array() {
+    let json = tracks_to_json(&[make_track(1), make_track(2)]);
+
+    let v: Value = serde_json::from_str(&json).expect("valid JSON");
+    let arr = v.as_array().unwrap();
+    assert_eq!(arr.len(), 2);
+    assert_eq!(arr[1]["track_id"], 2);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{SidxData, SidxReference, StructuredData};
+use std::io::Cursor;
+
+/// `sidx` for reference_ID 1 at timescale 90000 with (reference_type,
+/// referenced_size, subsegment_duration, SAP word) references.
+fn sidx(version: u8, earliest: u64, first_offset: u64, refs: &[(u32, u32, u32, u32)]) -> Vec<u8> {
+    let mut p = 1u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&90_000u32.to_be_bytes());
+    if version == 1 {
+        p.extend_from_slice(&earliest.to_be_bytes());
+        p.extend_from_slice(&first_offset.to_be_bytes());
+    } else {
+        p.extend_from_slice(&(earliest as u32).to_be_bytes());
+        p.extend_from_slice(&(first_offset as u32).to_be_bytes());
+    }
+    p.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    p.extend_from_slice(&(refs.len() as u16).to_be_bytes());
+    for &(typ, size, duration, sap) in refs {
+        p.extend_from_slice(&((typ << 31) | size).to_be_bytes());
+        p.extend_from_slice(&duration.to_be_bytes());
+        p.extend_from_slice(&sap.to_be_bytes());
+    }
+    full_box(b"sidx", version, 0, &p)
+}
+
+fn decode(data: Vec<u8>) -> (SidxData, Option<String>) {
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::SegmentIndex(sidx)) => (sidx.clone(), boxes[0].decoded.clone()),
+        other => panic!("sidx not decoded: {:?}", other),
+    }
+}
+
+#[test]
+fn media_and_nested_sidx_references() {
+    // A media subsegment starting with a type 1 SAP, then a nested sidx.
+    let (sidx, decoded) = decode(sidx(
+        0,
+        180_000,
+        0,
+        &[
+            (0, 1000, 90_000, 0x9000_0000),
+            (1, 500, 180_000, 0x2000_0010),
+        ],
+    ));
+
+    assert_eq!(
+        (sidx.version, sidx.reference_id, sidx.timescale),
+        (0, 1, 90_000)
+    );
+    assert_eq!(sidx.earliest_presentation_time, 180_000);
+    assert_eq!(
+        sidx.references,
+        [
+            SidxReference {
+                reference_type: 0,
+                referenced_size: 1000,
+                subsegment_duration: 90_000,
+                starts_with_sap: true,
+                sap_type: 1,
+                sap_delta_time: 0,
+            },
+            SidxReference {
+                reference_type: 1,
+                referenced_size: 500,
+                subsegment_duration: 180_000,
+                starts_with_sap: false,
+                sap_type: 2,
+                sap_delta_time: 16,
+            },
+        ]
+    );
+    assert_eq!(
+        decoded.as_deref(),
+        Some("timescale=90000 earliest_presentation_time=180000 first_offset=0 references=2")
+    );
+}
+
+#[test]
+fn version_1_has_64_bit_times() {
+    let (sidx, _) = decode(sidx(1, 1 << 33, 1 << 32, &[(0, 42, 3000, 0x9000_0000)]));
+    assert_eq!(sidx.earliest_presentation_time, 1 << 33);
+    assert_eq!(sidx.first_offset, 1 << 32);
+    assert_eq!(sidx.references[0].referenced_size, 42);
+}
+
+#[test]
+fn truncated_reference_is_a_decode_error() {
+    let mut data = sidx(0, 0, 0, &[(0, 42, 3000, 0)]);
+    data.truncate(data.len() - 4);
+    let size = (data.len() as u32).to_be_bytes();
+    data[..4].copy_from_slice(&size);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    assert!(boxes[0].structured_data.is_none());
+    assert_eq!(
+        boxes[0].decoded.as_deref(),
+        Some("[decode error: sidx reference 0 runs past the end of the box]")
+    );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::space::{ByteRange, FreeSpaceKind, mdat_coverage, space_report};
+use std::io::Cursor;
+
+#[test]
+fn free_after_moov_is_reserve_space() {
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend_from_slice(&bx(b"moov", &bx(b"udta", &[])));
+    data.extend_from_slice(&bx(b"free", &[0u8; 1016]));
+    data.extend_from_slice(&bx(b"mdat", &[0u8; 64]));
+    data.extend_from_slice(&bx(b"free", &[0u8; 8]));
+
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, false).unwrap();
+    let report = space_report(&boxes, size);
+
+    assert_eq!(report.free_space.len(), 2);
+    assert_eq!(report.free_space[0].kind, FreeSpaceKind::MoovReserve);
+    assert_eq!(report.free_space[0].size, 1024);
+    assert_eq!(report.free_space[1].kind, FreeSpaceKind::TrailingPadding);
+    assert_eq!(report.moov_reserve_bytes, 1024);
+    assert!(report.by_type.contains(&("free".to_string(), 1040)));
+}
+
+#[test]
+fn padding_inside_mdat_is_unreferenced() {
+    // Two 4-byte samples with 16 bytes of padding between them, and a third
+    // whose chunk offset points past the end of the file.
+    let moov = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(3, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+        stbl.extend_from_slice(&stsz(&[4, 4, 4]));
+        stbl.extend_from_slice(&stco(&[mdat_start, mdat_start + 20, 0x10_0000]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let file = movie(moov, &[0u8; 24]);
+    let mdat_start = (file.len() - 24) as u64;
+
+    let dir = temp_dir("mdat-coverage");
+    let path = dir.join("padded.mp4");
+    std::fs::write(&path, &file).unwrap();
+    let report = mdat_coverage(&path).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(
+        report.mdat,
+        [ByteRange {
+            offset: mdat_start,
+            size: 24
+        }]
+    );
+    assert_eq!(report.referenced_bytes, 8);
+    assert_eq!(
+        report.unreferenced,
+        [ByteRange {
+            offset: mdat_start + 4,
+            size: 16
+        }]
+    );
+    assert_eq!(
+        report.outside_mdat,
+        [ByteRange {
+            offset: 0x10_0000,
+            size: 4
+        }]
+    );
+}
+
+#[test]
+fn truncated_mdat_ends_at_the_file_end() {
+    let moov = |mdat_start: u32| {
+        let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+        stbl.extend_from_slice(&stts(&[(1, 512)]));
+        stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+        stbl.extend_from_slice(&stsz(&[4]));
+        stbl.extend_from_slice(&stco(&[mdat_start]));
+        bx(b"moov", &trak(1, b"vide", 12800, &stbl))
+    };
+    let mut file = movie(moov, &[0u8; 8]);
+    let mdat_start = (file.len() - 8) as u64;
+    // The mdat claims far more bytes than the download holds.
+    let size_at = mdat_start as usize - 8;
+    file[size_at..size_at + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+
+    let dir = temp_dir("mdat-coverage-truncated");
+    let path = dir.join("truncated.mp4");
+    std::fs::write(&path, &file).unwrap();
+    let report = mdat_coverage(&path).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(
+        report.mdat,
+        [ByteRange {
+            offset: mdat_start,
+            size: 8
+        }]
+    );
+    assert_eq!(report.referenced_bytes, 4);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{StructuredData, TrackApertureData};
+use std::io::Cursor;
+
+fn aperture(typ: &[u8; 4], width: f32, height: f32) -> Vec<u8> {
+    let mut p = (((width * 65536.0) as u32).to_be_bytes()).to_vec();
+    p.extend_from_slice(&((height * 65536.0) as u32).to_be_bytes());
+    full_box(typ, 0, 0, &p)
+}
+
+fn find<'a>(b: &'a mp4box::Box, typ: &str) -> &'a mp4box::Box {
+    b.children
+        .as_ref()
+        .and_then(|kids| kids.iter().find(|c| c.typ == typ))
+        .unwrap_or_else(|| panic!("no {typ} under {}", b.typ))
+}
+
+fn aperture_data(b: &mp4box::Box) -> &TrackApertureData {
+    match &b.structured_data {
+        Some(StructuredData::TrackAperture(ap)) => ap,
+        other => panic!("expected TrackAperture, got {other:?}"),
+    }
+}
+
+#[test]
+fn tapt_with_distinct_clean_and_encoded_sizes() {
+    // Anamorphic DV: 720x480 encoded, shown as 853.33x480 after aspect correction.
+    let mut tapt = aperture(b"clef", 853.333, 480.0);
+    tapt.extend_from_slice(&aperture(b"prof", 853.333, 480.0));
+    tapt.extend_from_slice(&aperture(b"enof", 720.0, 480.0));
+    let trak = trak_with(1, b"vide", 600, &bx(b"tapt", &tapt), &[], &[]);
+
+    let mut file = ftyp(b"qt  ", &[b"qt  "]);
+    file.extend_from_slice(&bx(b"moov", &trak));
+    let size = file.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(file), size, true).unwrap();
+
+    let tapt = find(find(&boxes[1], "trak"), "tapt");
+    let clef = aperture_data(find(tapt, "clef"));
+    let enof = aperture_data(find(tapt, "enof"));
+
+    assert_eq!(clef.mode, "clef");
+    assert!((clef.width - 853.333).abs() < 0.01, "{}", clef.width);
+    assert_eq!(clef.height, 480.0);
+    assert_eq!(enof.mode, "enof");
+    assert_eq!((enof.width, enof.height), (720.0, 480.0));
+    assert_eq!(aperture_data(find(tapt, "prof")).mode, "prof");
+}
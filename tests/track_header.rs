@@ -0,0 +1,126 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{StructuredData, TkhdData};
+use mp4box::track_samples_from_reader;
+use std::io::Cursor;
+
+fn video_trak(track_id: u32, tkhd_flags: u8) -> Vec<u8> {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(1, 512)]));
+    stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+    stbl.extend_from_slice(&stsz(&[4]));
+    stbl.extend_from_slice(&stco(&[0]));
+    let mut trak = trak(track_id, b"vide", 12800, &stbl);
+    // trak header (8) + tkhd header (8) + version (1) + flags (3)
+    trak[19] = tkhd_flags;
+    trak
+}
+
+fn tkhd_of(trak: &mp4box::Box) -> &TkhdData {
+    match &trak.children.as_ref().unwrap()[0].structured_data {
+        Some(StructuredData::TrackHeader(tkhd)) => tkhd,
+        other => panic!("tkhd not decoded: {other:?}"),
+    }
+}
+
+#[test]
+fn two_traks_report_their_own_ids() {
+    let mut moov = video_trak(1, 0x3);
+    moov.extend_from_slice(&video_trak(7, 0x3));
+    let tracks = track_samples_from_reader(Cursor::new(bx(b"moov", &moov))).unwrap();
+
+    let ids: Vec<u32> = tracks.iter().map(|t| t.track_id).collect();
+    assert_eq!(ids, vec![1, 7]);
+}
+
+#[test]
+fn tkhd_flags_distinguish_disabled_tracks() {
+    let mut moov = video_trak(1, 0x7);
+    moov.extend_from_slice(&video_trak(2, 0x2));
+    let data = bx(b"moov", &moov);
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    let traks = boxes[0].children.as_ref().unwrap();
+
+    let main = tkhd_of(&traks[0]);
+    assert!(main.enabled && main.in_movie && main.in_preview);
+
+    let disabled = tkhd_of(&traks[1]);
+    assert_eq!(disabled.track_id, 2);
+    assert!(!disabled.enabled);
+    assert!(disabled.in_movie);
+    assert!(!disabled.in_preview);
+}
+
+/// tkhd with the given layer/alternate_group/volume and a 16.16 size.
+fn tkhd_fields(
+    version: u8,
+    layer: i16,
+    alternate_group: i16,
+    volume: u16,
+    size: (u32, u32),
+) -> Vec<u8> {
+    let mut p = Vec::new();
+    if version == 1 {
+        p.extend_from_slice(&0u64.to_be_bytes()); // creation_time
+        p.extend_from_slice(&0u64.to_be_bytes()); // modification_time
+        p.extend_from_slice(&3u32.to_be_bytes()); // track_id
+        p.extend_from_slice(&0u32.to_be_bytes()); // reserved
+        p.extend_from_slice(&(1u64 << 33).to_be_bytes()); // duration
+    } else {
+        p.extend_from_slice(&0u32.to_be_bytes());
+        p.extend_from_slice(&0u32.to_be_bytes());
+        p.extend_from_slice(&3u32.to_be_bytes());
+        p.extend_from_slice(&0u32.to_be_bytes());
+        p.extend_from_slice(&90000u32.to_be_bytes());
+    }
+    p.extend_from_slice(&[0u8; 8]); // reserved[2]
+    p.extend_from_slice(&layer.to_be_bytes());
+    p.extend_from_slice(&alternate_group.to_be_bytes());
+    p.extend_from_slice(&volume.to_be_bytes());
+    p.extend_from_slice(&[0u8; 2]); // reserved
+    p.extend_from_slice(&[0u8; 36]); // matrix
+    p.extend_from_slice(&size.0.to_be_bytes());
+    p.extend_from_slice(&size.1.to_be_bytes());
+    full_box(b"tkhd", version, 3, &p)
+}
+
+fn decode_tkhd(data: Vec<u8>) -> (TkhdData, Option<String>) {
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, true).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::TrackHeader(tkhd)) => (tkhd.clone(), boxes[0].decoded.clone()),
+        other => panic!("tkhd not decoded: {other:?}"),
+    }
+}
+
+#[test]
+fn tkhd_v0_presentation_fields() {
+    let (tkhd, decoded) = decode_tkhd(tkhd_fields(0, -1, 1, 0, (720 << 16, 1280 << 16)));
+    assert_eq!(tkhd.duration, 90000);
+    assert_eq!(tkhd.width, 720.0);
+    assert_eq!(tkhd.height, 1280.0);
+    assert_eq!(tkhd.layer, -1);
+    assert_eq!(tkhd.alternate_group, 1);
+    assert_eq!(tkhd.volume, 0.0);
+    assert_eq!(decoded.as_deref(), Some("720x1280, layer -1"));
+}
+
+#[test]
+fn tkhd_v1_presentation_fields() {
+    // 0x0100 is full volume, 0x0080 half; width 640.5 in 16.16
+    let (tkhd, decoded) = decode_tkhd(tkhd_fields(1, 0, 2, 0x0080, ((640 << 16) | 0x8000, 0)));
+    assert_eq!(tkhd.track_id, 3);
+    assert_eq!(tkhd.duration, 1 << 33);
+    assert_eq!(tkhd.width, 640.5);
+    assert_eq!(tkhd.height, 0.0);
+    assert_eq!(tkhd.layer, 0);
+    assert_eq!(tkhd.alternate_group, 2);
+    assert_eq!(tkhd.volume, 0.5);
+    assert_eq!(decoded.as_deref(), Some("640.5x0, layer 0"));
+
+    let (full, _) = decode_tkhd(tkhd_fields(1, 0, 1, 0x0100, (0, 0)));
+    assert_eq!(full.volume, 1.0);
+}
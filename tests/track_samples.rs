@@ -0,0 +1,394 @@
+mod common;
+
+use common::*;
+use mp4box::{
+    BitrateStats, SampleInfo, TrackSamples, concat_tracks, total_sample_count,
+    track_samples_from_reader,
+};
+use std::io::Cursor;
+
+/// Build a track from (dts, pts, size, is_sync) tuples with a 1000 Hz timescale.
+fn make_track(samples: &[(u64, u64, u32, bool)]) -> TrackSamples {
+    let samples: Vec<SampleInfo> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, &(dts, pts, size, is_sync))| SampleInfo {
+            index: i as u32,
+            dts,
+            pts,
+            start_time: pts as f64 / 1000.0,
+            duration: 40,
+            effective_duration: 40,
+            rendered_offset: pts as i64 - dts as i64,
+            file_offset: None,
+            size,
+            is_sync,
+            flags: None,
+        })
+        .collect();
+
+    TrackSamples {
+        track_id: 1,
+        handler_type: "vide".to_string(),
+        handler_name: None,
+        timescale: 1000,
+        duration: samples.len() as u64 * 40,
+        sample_count: samples.len() as u32,
+        samples,
+        sample_entry: None,
+        edits: Vec::new(),
+        gapless: None,
+        data_self_contained: true,
+        warnings: Vec::new(),
+    }
+}
+
+#[test]
+fn presentation_order_reorders_b_frames() {
+    // Decode order: I P B B
+    let track = make_track(&[
+        (0, 40, 100, true),
+        (40, 160, 50, false),
+        (80, 80, 20, false),
+        (120, 120, 20, false),
+    ]);
+
+    let order: Vec<u32> = track
+        .samples_presentation_order()
+        .iter()
+        .map(|s| s.index)
+        .collect();
+    assert_eq!(order, vec![0, 2, 3, 1]);
+
+    // Decode order is untouched.
+    let decode: Vec<u32> = track.samples.iter().map(|s| s.index).collect();
+    assert_eq!(decode, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn bitrate_window_captures_spike() {
+    // 25 fps for one second: 1000-byte frames, with a 20 kB keyframe burst at 0.4-0.56s.
+    let samples: Vec<(u64, u64, u32, bool)> = (0..25)
+        .map(|i| {
+            let t = i * 40;
+            let size = if (400..600).contains(&t) {
+                20_000
+            } else {
+                1000
+            };
+            (t, t, size, t == 400)
+        })
+        .collect();
+    let track = make_track(&samples);
+
+    let rates = track.bitrate_over_time(0.2);
+    assert_eq!(rates.len(), 5);
+    assert_eq!(rates[0], (0.0, 5 * 1000 * 8 * 5));
+    assert_eq!(rates[1].1, 5 * 1000 * 8 * 5);
+    // The 0.4-0.6s window holds five 20 kB frames.
+    assert!((rates[2].0 - 0.4).abs() < 1e-9);
+    assert_eq!(rates[2].1, 5 * 20_000 * 8 * 5);
+    assert_eq!(rates[4].1, 5 * 1000 * 8 * 5);
+
+    assert!(track.bitrate_over_time(0.0).is_empty());
+}
+
+#[test]
+fn bitrate_windows_start_at_the_first_sample() {
+    // Live DASH: tfdt counts from the epoch, ~1.7e9 s in. Two seconds of
+    // 1000-byte frames with an empty second between them.
+    let epoch_ms = 1_700_000_000_000u64;
+    let samples: Vec<(u64, u64, u32, bool)> = (0..25)
+        .chain(50..75)
+        .map(|i| (epoch_ms + i * 40, epoch_ms + i * 40, 1000, i == 0))
+        .collect();
+    let track = make_track(&samples);
+
+    let rates = track.bitrate_over_time(1.0);
+    assert_eq!(
+        rates,
+        [
+            (1_700_000_000.0, 25 * 1000 * 8),
+            (1_700_000_001.0, 0),
+            (1_700_000_002.0, 25 * 1000 * 8),
+        ]
+    );
+}
+
+#[test]
+fn bitrate_stats_even_samples() {
+    // Two seconds of 1000-byte frames at 25 fps.
+    let samples: Vec<(u64, u64, u32, bool)> =
+        (0..50).map(|i| (i * 40, i * 40, 1000, i == 0)).collect();
+    let mut track = make_track(&samples);
+
+    let stats = track.bitrate_stats();
+    assert_eq!(stats.avg_bps, 200_000);
+    assert_eq!(stats.peak_bps, 200_000);
+    assert_eq!(stats.peak_window_start, 0.0);
+
+    // One big frame at 1.2s moves the peak window onto it.
+    track.samples[30].size = 11_000;
+    let stats = track.bitrate_stats();
+    assert_eq!(stats.avg_bps, 240_000);
+    assert_eq!(stats.peak_bps, 280_000);
+    assert!((stats.peak_window_start - 0.24).abs() < 1e-9);
+
+    // No duration anywhere, or no timescale: nothing to divide by.
+    track.duration = 0;
+    track.samples.iter_mut().for_each(|s| s.duration = 0);
+    assert_eq!(track.bitrate_stats().avg_bps, 0);
+    track.timescale = 0;
+    assert_eq!(track.bitrate_stats(), BitrateStats::default());
+}
+
+fn gop_track(stss_entries: Option<&[u32]>) -> TrackSamples {
+    // 75 frames of 40 ms at timescale 1000.
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(75, 40)]));
+    if let Some(entries) = stss_entries {
+        stbl.extend_from_slice(&stss(entries));
+    }
+    stbl.extend_from_slice(&stsc(&[(1, 75, 1)]));
+    stbl.extend_from_slice(&stsz(&[1; 75]));
+    stbl.extend_from_slice(&stco(&[0]));
+    let data = bx(b"moov", &trak(1, b"vide", 1000, &stbl));
+    track_samples_from_reader(Cursor::new(data))
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn gop_analysis_from_stss() {
+    let gops = gop_track(Some(&[1, 31, 61])).gop_analysis();
+    assert_eq!(gops.keyframe_indices, [0, 30, 60]);
+    // GOPs of 30, 30 and 15 frames.
+    assert_eq!(gops.max_gop_samples, 30);
+    assert_eq!(gops.avg_gop_samples, 25.0);
+    assert!((gops.max_gop_seconds - 1.2).abs() < 1e-9);
+    assert!((gops.avg_gop_seconds - 1.0).abs() < 1e-9);
+    assert!(!gops.all_intra);
+}
+
+#[test]
+fn gop_analysis_without_stss_is_all_intra() {
+    let gops = gop_track(None).gop_analysis();
+    assert_eq!(gops.keyframe_indices.len(), 75);
+    assert_eq!(gops.max_gop_samples, 1);
+    assert_eq!(gops.avg_gop_samples, 1.0);
+    assert!(gops.all_intra);
+}
+
+/// Lay samples out back to back in an mdat whose payload starts at `mdat_start`.
+fn with_offsets(mut track: TrackSamples, mdat_start: u64) -> TrackSamples {
+    let mut offset = mdat_start;
+    for s in &mut track.samples {
+        s.file_offset = Some(offset);
+        offset += s.size as u64;
+    }
+    track
+}
+
+#[test]
+fn concat_appends_second_timeline() {
+    let a = with_offsets(make_track(&[(0, 0, 100, true), (40, 40, 50, false)]), 48);
+    let b = with_offsets(make_track(&[(0, 80, 200, true), (40, 40, 60, false)]), 32);
+
+    let joined = concat_tracks(&a, &b).unwrap();
+    assert_eq!(joined.sample_count, 4);
+    assert_eq!(joined.duration, 160);
+
+    let s: Vec<(u32, u64, u64, u64)> = joined
+        .samples
+        .iter()
+        .map(|s| (s.index, s.dts, s.pts, s.file_offset.unwrap()))
+        .collect();
+    // a's data ends at 48 + 150 = 198
+    assert_eq!(
+        s,
+        vec![
+            (0, 0, 0, 48),
+            (1, 40, 40, 148),
+            (2, 80, 160, 198 + 32),
+            (3, 120, 120, 198 + 232),
+        ]
+    );
+    assert_eq!(joined.samples[2].start_time, 0.16);
+    assert_eq!(joined.samples[2].rendered_offset, 80);
+    assert!(joined.samples[2].is_sync);
+}
+
+#[test]
+fn concat_rejects_timescale_mismatch() {
+    let a = make_track(&[(0, 0, 100, true)]);
+    let mut b = make_track(&[(0, 0, 100, true)]);
+    b.timescale = 90000;
+
+    let err = concat_tracks(&a, &b).unwrap_err();
+    assert!(err.to_string().contains("timescales 1000 and 90000"));
+}
+
+#[test]
+fn concat_after_fragmented_track_starts_at_its_last_sample_end() {
+    // Fragmented tracks report no mdhd duration.
+    let mut a = make_track(&[(0, 0, 10, true), (40, 40, 10, false)]);
+    a.duration = 0;
+    let b = make_track(&[(0, 0, 10, true)]);
+
+    let joined = concat_tracks(&a, &b).unwrap();
+    assert_eq!(joined.samples[2].dts, 80);
+    assert_eq!(joined.duration, 120);
+}
+
+#[test]
+fn concat_rejects_times_and_offsets_past_64_bits() {
+    let a = make_track(&[(0, 0, 10, true)]);
+    let mut b = make_track(&[(0, 0, 10, true)]);
+    b.samples[0].pts = u64::MAX - 10;
+    assert!(concat_tracks(&a, &b).is_err());
+
+    let a = with_offsets(make_track(&[(0, 0, 10, true)]), 48);
+    let b = with_offsets(make_track(&[(0, 0, 10, true)]), u64::MAX - 20);
+    let err = concat_tracks(&a, &b).unwrap_err();
+    assert!(err.to_string().contains("does not fit"), "{err}");
+}
+
+#[test]
+fn sample_numbers_are_one_based() {
+    let track = make_track(&[(0, 0, 10, true), (40, 40, 10, false)]);
+    assert_eq!(track.samples[0].sample_number(), 1);
+    assert_eq!(track.samples[1].sample_number(), 2);
+
+    assert_eq!(track.sample_by_number(1).unwrap().index, 0);
+    assert_eq!(track.sample_by_number(2).unwrap().index, 1);
+    assert!(track.sample_by_number(0).is_none());
+    assert!(track.sample_by_number(3).is_none());
+}
+
+#[test]
+fn sync_lookup_uses_sample_numbers() {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(4, 512)]));
+    stbl.extend_from_slice(&stsc(&[(1, 4, 1)]));
+    stbl.extend_from_slice(&stsz(&[1, 1, 1, 1]));
+    stbl.extend_from_slice(&stco(&[0]));
+    stbl.extend_from_slice(&stss(&[1, 3]));
+    let moov = bx(b"moov", &trak(1, b"vide", 12800, &stbl));
+    let tracks = track_samples_from_reader(Cursor::new(moov)).unwrap();
+    let track = &tracks[0];
+
+    let sync: Vec<u32> = track
+        .samples
+        .iter()
+        .filter(|s| s.is_sync)
+        .map(|s| s.sample_number())
+        .collect();
+    assert_eq!(sync, vec![1, 3]);
+    assert!(track.sample_by_number(1).unwrap().is_sync);
+    assert!(!track.sample_by_number(2).unwrap().is_sync);
+    assert_eq!(track.sample_by_number(3).unwrap().index, 2);
+}
+
+#[test]
+fn ctts_v1_negative_offsets_put_pts_before_dts() {
+    // Two 512-tick samples; the second is presented 256 ticks before it's decoded.
+    let mut p = 2u32.to_be_bytes().to_vec();
+    for (count, offset) in [(1u32, 512i32), (1, -256)] {
+        p.extend_from_slice(&count.to_be_bytes());
+        p.extend_from_slice(&offset.to_be_bytes());
+    }
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(2, 512)]));
+    stbl.extend_from_slice(&full_box(b"ctts", 1, 0, &p));
+    stbl.extend_from_slice(&stsc(&[(1, 2, 1)]));
+    stbl.extend_from_slice(&stsz(&[1, 1]));
+    stbl.extend_from_slice(&stco(&[0]));
+    let data = bx(b"moov", &trak(1, b"vide", 12800, &stbl));
+
+    let tracks = track_samples_from_reader(Cursor::new(data)).unwrap();
+    let s = &tracks[0].samples;
+    assert_eq!((s[0].dts, s[0].pts), (0, 512));
+    assert_eq!((s[1].dts, s[1].pts), (512, 256));
+    assert!(s[1].pts < s[1].dts);
+    assert_eq!(s[1].rendered_offset, -256);
+}
+
+#[test]
+fn ctts_v0_offsets_above_i32_max_stay_positive() {
+    let mut stbl = stsd(&[visual_entry(b"avc1", &[])]);
+    stbl.extend_from_slice(&stts(&[(1, 512)]));
+    stbl.extend_from_slice(&ctts(&[(1, 0x8000_0000)]));
+    stbl.extend_from_slice(&stsc(&[(1, 1, 1)]));
+    stbl.extend_from_slice(&stsz(&[1]));
+    stbl.extend_from_slice(&stco(&[0]));
+    let data = bx(b"moov", &trak(1, b"vide", 12800, &stbl));
+
+    let tracks = track_samples_from_reader(Cursor::new(data)).unwrap();
+    assert_eq!(tracks[0].samples[0].pts, 0x8000_0000);
+}
+
+#[test]
+fn total_sample_count_sums_tracks_and_fragments() {
+    let mut video = stts(&[(3, 512)]);
+    video.extend_from_slice(&stsz(&[10, 20, 30]));
+    let mut audio = stts(&[(2, 1024)]);
+    audio.extend_from_slice(&stsz(&[5, 5]));
+    let mut tracks = trak(1, b"vide", 12800, &video);
+    tracks.extend_from_slice(&trak(2, b"soun", 44100, &audio));
+
+    let mut file = ftyp(b"isom", &[b"isom"]);
+    file.extend_from_slice(&bx(b"moov", &tracks));
+    let path = temp_dir("total-samples").join("two-tracks.mp4");
+    std::fs::write(&path, &file).unwrap();
+    assert_eq!(total_sample_count(&path).unwrap(), 5);
+
+    // A fragment with a four-sample trun adds to the count.
+    let mut traf = full_box(b"tfhd", 0, 0x020000, &1u32.to_be_bytes());
+    traf.extend_from_slice(&full_box(b"trun", 0, 0, &4u32.to_be_bytes()));
+    file.extend_from_slice(&bx(b"moof", &bx(b"traf", &traf)));
+    std::fs::write(&path, &file).unwrap();
+    assert_eq!(total_sample_count(&path).unwrap(), 9);
+}
+
+#[test]
+fn summary_json_has_no_samples() {
+    let samples: Vec<(u64, u64, u32, bool)> =
+        (0..25).map(|i| (i * 40, i * 40, 500, i == 0)).collect();
+    let track = make_track(&samples);
+
+    let summary = track.summary();
+    assert_eq!(summary.sample_count, 25);
+    assert_eq!(summary.duration_seconds, 1.0);
+    assert_eq!(summary.bitrate_bps, 100_000);
+    assert_eq!(summary.frame_rate, Some(25.0));
+    assert_eq!(summary.codec, None);
+
+    let json = serde_json::to_value(&summary).unwrap();
+    let obj = json.as_object().unwrap();
+    assert!(!obj.contains_key("samples"));
+    assert_eq!(obj["track_id"], 1);
+    assert_eq!(obj["handler_type"], "vide");
+    assert_eq!(obj["frame_rate"], 25.0);
+}
+
+#[test]
+fn seek_returns_keyframe_at_or_before_time() {
+    // A sample every 0.5s, keyframes at 0s, 2s and 4s.
+    let samples: Vec<(u64, u64, u32, bool)> = (0..12)
+        .map(|i| (i * 500, i * 500, 100, i % 4 == 0))
+        .collect();
+    let track = make_track(&samples);
+    let seek = |t: f64| track.seek(t).map(|s| s.start_time);
+
+    assert_eq!(seek(0.0), Some(0.0));
+    assert_eq!(seek(1.99), Some(0.0));
+    assert_eq!(seek(2.0), Some(2.0));
+    assert_eq!(seek(3.9), Some(2.0));
+    assert_eq!(seek(4.0), Some(4.0));
+    // Before the first sample / past the end
+    assert_eq!(seek(-1.0), Some(0.0));
+    assert_eq!(seek(100.0), Some(4.0));
+
+    let no_sync = make_track(&[(0, 0, 100, false)]);
+    assert!(no_sync.seek(0.0).is_none());
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+use mp4box::{box_type_counts, get_boxes};
+use std::io::Cursor;
+
+#[test]
+fn type_counts_are_sorted_and_stable() {
+    let mut moov = trak(1, b"vide", 600, &[]);
+    moov.extend_from_slice(&trak(2, b"soun", 48000, &[]));
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend_from_slice(&bx(b"moov", &moov));
+    data.extend_from_slice(&bx(b"free", &[0u8; 8]));
+    data.extend_from_slice(&bx(b"mdat", &[]));
+
+    let size = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), size, false).unwrap();
+
+    let counts = box_type_counts(&boxes);
+    assert_eq!(counts["trak"], 2);
+    assert_eq!(counts["tkhd"], 2);
+    assert_eq!(counts["moov"], 1);
+
+    let keys: Vec<&String> = counts.keys().collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted);
+
+    for _ in 0..10 {
+        let again: Vec<(String, usize)> = box_type_counts(&boxes).into_iter().collect();
+        assert_eq!(again, counts.clone().into_iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::XMP_UUID;
+use std::io::Cursor;
+
+const PACKET: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Rating="4"/>
+</rdf:RDF></x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+fn decoded(data: Vec<u8>) -> mp4box::Box {
+    let size = data.len() as u64;
+    get_boxes(&mut Cursor::new(data), size, true)
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn xmp_fourcc_box_in_udta() {
+    let mut payload = PACKET.as_bytes().to_vec();
+    payload.push(0);
+    let udta = decoded(bx(b"udta", &bx(b"XMP_", &payload)));
+
+    let xmp = &udta.children.as_ref().unwrap()[0];
+    assert_eq!(xmp.full_name, "XMP Metadata Box");
+    assert_eq!(xmp.decoded.as_deref(), Some(PACKET));
+}
+
+#[test]
+fn xmp_uuid_box() {
+    let mut payload = XMP_UUID.to_vec();
+    payload.extend_from_slice(PACKET.as_bytes());
+    let xmp = decoded(bx(b"uuid", &payload));
+
+    assert_eq!(
+        xmp.uuid.as_deref(),
+        Some("be7acfcb97a942e89c71999491e3afac")
+    );
+    assert!(
+        xmp.decoded
+            .as_deref()
+            .unwrap()
+            .contains(r#"xmp:Rating="4""#)
+    );
+}